@@ -0,0 +1,23 @@
+#![warn(clippy::all, clippy::pedantic, clippy::nursery)]
+
+use std::{path::Path, process::Command};
+
+fn main() {
+	for path in ["../../.git/HEAD", "../../.git/refs/heads"] {
+		if Path::new(path).exists() {
+			println!("cargo:rerun-if-changed={path}");
+		}
+	}
+	// Builds outside a git checkout (e.g. from crates.io) simply report no commit.
+	let commit = Command::new("git")
+		.args(["rev-parse", "--short=12", "HEAD"])
+		.output()
+		.ok()
+		.filter(|output| output.status.success())
+		.and_then(|output| String::from_utf8(output.stdout).ok())
+		.map(|stdout| stdout.trim().to_owned())
+		.filter(|commit| !commit.is_empty());
+	if let Some(commit) = commit {
+		println!("cargo:rustc-env=CODESTATS_GIT_COMMIT={commit}");
+	}
+}
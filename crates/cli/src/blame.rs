@@ -0,0 +1,236 @@
+use std::{
+	collections::BTreeMap,
+	io::Write,
+	num::NonZero,
+	path::{Path, PathBuf},
+	process::Command,
+	sync::atomic::{AtomicUsize, Ordering},
+	thread,
+};
+
+use anyhow::{Context as _, Result, ensure};
+use codestats::{
+	analysis::{AnalysisResults, CommentState, Docstrings, LineType, classify_line},
+	display::{FormatterContext, pluralize},
+	langs::Language,
+};
+
+/// Lines one author wrote in one language, split by line type.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct AuthorLines {
+	lines: u64,
+	code: u64,
+	comment: u64,
+	blank: u64,
+}
+
+impl AuthorLines {
+	const fn add(&mut self, line_type: LineType) {
+		self.lines += 1;
+		match line_type {
+			LineType::Code | LineType::Mixed => self.code += 1,
+			LineType::Comment => self.comment += 1,
+			LineType::Blank => self.blank += 1,
+			// Shebangs, and any kind of line added later, count only towards the total.
+			_ => {}
+		}
+	}
+
+	const fn merge(&mut self, other: Self) {
+		self.lines += other.lines;
+		self.code += other.code;
+		self.comment += other.comment;
+		self.blank += other.blank;
+	}
+}
+
+/// One author's name, lines written and per-language lines, as listed in the breakdown.
+type AuthorRow<'a> = (&'a str, u64, Vec<(&'a str, AuthorLines)>);
+
+/// Lines by author and language, for `--blame`.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct BlameMatrix {
+	authors: BTreeMap<String, BTreeMap<&'static str, AuthorLines>>,
+}
+
+impl BlameMatrix {
+	/// Classify the blamed lines of one file, in order, and credit each to its author.
+	fn add_file(&mut self, language: &'static Language, lines: &[(String, String)], docstrings: Docstrings) {
+		let mut comment_state = CommentState::new().with_docstrings(docstrings);
+		for (idx, (author, text)) in lines.iter().enumerate() {
+			let (line_type, _) = classify_line(text, Some(language), &mut comment_state, idx == 0);
+			self.authors.entry(author.clone()).or_default().entry(language.name).or_default().add(line_type);
+		}
+	}
+
+	fn merge(&mut self, other: Self) {
+		for (author, languages) in other.authors {
+			let entry = self.authors.entry(author).or_default();
+			for (language, lines) in languages {
+				entry.entry(language).or_default().merge(lines);
+			}
+		}
+	}
+
+	/// Write the matrix as an "Author breakdown" section: authors by lines written, then their languages.
+	pub fn write(&self, writer: &mut dyn Write, ctx: &FormatterContext) -> Result<()> {
+		let total: u64 = self.authors.values().flat_map(BTreeMap::values).map(|lines| lines.lines).sum();
+		let mut authors: Vec<AuthorRow> = self
+			.authors
+			.iter()
+			.map(|(author, languages)| {
+				let mut languages: Vec<_> = languages.iter().map(|(name, lines)| (*name, *lines)).collect();
+				languages.sort_by(|a, b| b.1.lines.cmp(&a.1.lines).then_with(|| a.0.cmp(b.0)));
+				(author.as_str(), languages.iter().map(|(_, lines)| lines.lines).sum(), languages)
+			})
+			.collect();
+		authors.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+		let (i1, i2) = (ctx.indent(1), ctx.indent(2));
+		writeln!(writer, "Author breakdown:")?;
+		for (author, lines, languages) in authors {
+			writeln!(writer, "{author}:")?;
+			#[expect(clippy::cast_precision_loss, reason = "line counts are far below 2^52")]
+			let share = if total == 0 { 0.0 } else { lines as f64 * 100.0 / total as f64 };
+			writeln!(
+				writer,
+				"{i1}Lines: {} {} ({}% of total).",
+				ctx.number(lines),
+				pluralize(lines, "line", "lines"),
+				ctx.percent(share)
+			)?;
+			for (language, lines) in languages {
+				writeln!(
+					writer,
+					"{i2}{language}: {} {}: {} code, {} {}, {} {}.",
+					ctx.number(lines.lines),
+					pluralize(lines.lines, "line", "lines"),
+					ctx.number(lines.code),
+					ctx.number(lines.comment),
+					pluralize(lines.comment, "comment", "comments"),
+					ctx.number(lines.blank),
+					pluralize(lines.blank, "blank", "blanks"),
+				)?;
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Run `git blame` on every analyzed file under `root` and build the author-by-language matrix.
+///
+/// Files are blamed on one worker per available core, each running its own `git` process. Files git does not track
+/// are left out. Languages merged by display aliases must not have been applied yet, as each file is reclassified
+/// with its own language.
+pub fn blame(results: &AnalysisResults, root: &Path, docstrings: Docstrings) -> Result<BlameMatrix> {
+	let dir = if root.is_dir() {
+		root
+	} else {
+		root.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or_else(|| Path::new("."))
+	};
+	let output = Command::new("git")
+		.arg("-C")
+		.arg(dir)
+		.args(["rev-parse", "--is-inside-work-tree"])
+		.output()
+		.context("Failed to run git")?;
+	ensure!(output.status.success(), "--blame needs `{}` to be inside a git work tree", root.display());
+	let files: Vec<(&'static Language, PathBuf)> = results
+		.languages()
+		.flat_map(|(language, stats)| {
+			stats.files_list().iter().map(move |file| (language, blame_path(root, file.path())))
+		})
+		.collect();
+	let next = AtomicUsize::new(0);
+	let workers = thread::available_parallelism().map_or(1, NonZero::get).min(files.len()).max(1);
+	let matrix = thread::scope(|scope| {
+		let handles: Vec<_> = (0..workers)
+			.map(|_| {
+				scope.spawn(|| {
+					let mut matrix = BlameMatrix::default();
+					while let Some((language, path)) = files.get(next.fetch_add(1, Ordering::Relaxed)) {
+						if let Some(lines) = blame_file(dir, path) {
+							matrix.add_file(language, &lines, docstrings);
+						}
+					}
+					matrix
+				})
+			})
+			.collect();
+		handles.into_iter().fold(BlameMatrix::default(), |mut matrix, handle| {
+			matrix.merge(handle.join().expect("blame worker panicked"));
+			matrix
+		})
+	});
+	Ok(matrix)
+}
+
+/// Path of an analyzed file relative to the directory git runs in; a single analyzed file is reported with an empty
+/// path.
+fn blame_path(root: &Path, path: &str) -> PathBuf {
+	if path.is_empty() {
+		root.file_name().map_or_else(|| root.to_path_buf(), PathBuf::from)
+	} else {
+		PathBuf::from(path)
+	}
+}
+
+/// The `(author, line)` pairs of `path`, or `None` when git cannot blame it, e.g. because it is untracked.
+fn blame_file(dir: &Path, path: &Path) -> Option<Vec<(String, String)>> {
+	let output =
+		Command::new("git").arg("-C").arg(dir).args(["blame", "--line-porcelain", "--"]).arg(path).output().ok()?;
+	output.status.success().then(|| parse_porcelain(&output.stdout))
+}
+
+/// Pair each line of `git blame --line-porcelain` output with its author.
+///
+/// Every line comes with a full header, so the `author` entry just before a tab-prefixed line is that line's author.
+fn parse_porcelain(output: &[u8]) -> Vec<(String, String)> {
+	let mut author = String::new();
+	let mut lines = Vec::new();
+	for line in output.split(|&byte| byte == b'\n') {
+		if let Some(text) = line.strip_prefix(b"\t") {
+			lines.push((author.clone(), String::from_utf8_lossy(text).into_owned()));
+		} else if let Some(name) = line.strip_prefix(b"author ") {
+			author = String::from_utf8_lossy(name).into_owned();
+		}
+	}
+	lines
+}
+
+#[cfg(test)]
+mod tests {
+	use codestats::{display::ViewOptions, langs::LANGUAGES};
+
+	use super::*;
+
+	const PORCELAIN: &[u8] = b"1111 1 1 2\nauthor Ada\nauthor-mail <ada@example.com>\nfilename main.rs\n\
+		\t// entry point\n1111 2 2\nauthor Ada\nfilename main.rs\n\tfn main() {}\n\
+		2222 3 3 1\nauthor Grace Hopper\nauthor-mail <grace@example.com>\nfilename main.rs\n\t\n";
+
+	#[test]
+	fn porcelain_lines_are_paired_with_their_author() {
+		assert_eq!(
+			parse_porcelain(PORCELAIN),
+			[
+				("Ada".to_owned(), "// entry point".to_owned()),
+				("Ada".to_owned(), "fn main() {}".to_owned()),
+				("Grace Hopper".to_owned(), String::new()),
+			]
+		);
+	}
+
+	#[test]
+	fn matrix_credits_each_line_type_to_its_author() {
+		let rust = LANGUAGES.iter().find(|lang| lang.name == "Rust").unwrap();
+		let mut matrix = BlameMatrix::default();
+		matrix.add_file(rust, &parse_porcelain(PORCELAIN), Docstrings::default());
+		let mut out = Vec::new();
+		matrix.write(&mut out, &FormatterContext::new(ViewOptions::default())).unwrap();
+		assert_eq!(
+			String::from_utf8(out).unwrap(),
+			"Author breakdown:\nAda:\n\tLines: 2 lines (66.7% of total).\n\t\tRust: 2 lines: 1 code, 1 comment, 0 \
+			 blanks.\nGrace Hopper:\n\tLines: 1 line (33.3% of total).\n\t\tRust: 1 line: 0 code, 0 comments, 1 \
+			 blank.\n"
+		);
+	}
+}
@@ -1,25 +1,33 @@
 use std::path::PathBuf;
 
-use anyhow::{Result, ensure};
+use anyhow::{Result, bail, ensure};
 use clap::{
 	ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand,
 	builder::{PossibleValuesParser, TypedValueParser as _},
 	parser::ValueSource,
 };
 use codestats::{
-	config::Config,
-	display::{IndentStyle, LanguageSortKey, NumberStyle, OutputFormat, SizeStyle, SortDirection, Verbosity},
+	analysis::{Docstrings, Shebangs, cache::DEFAULT_CACHE_FILE},
+	config::{Age, Config, InputEncoding},
+	display::{
+		ColorChoice, IndentStyle, LanguageSortKey, NumberStyle, OutputFormat, RoundingMode, SizeStyle, SortDirection,
+		SummaryFormat, Verbosity,
+	},
+	langs::{self, LANGUAGES},
 };
 
 use crate::completions::Shell;
 
-fn output_format_values() -> Vec<&'static str> {
+pub fn output_format_values() -> Vec<&'static str> {
 	#[allow(unused_mut)]
 	let mut values = vec!["human", "json", "json-compact", "csv", "tsv"];
 	#[cfg(feature = "markdown")]
 	values.push("markdown");
 	#[cfg(feature = "html")]
 	values.push("html");
+	values.push("paths");
+	#[cfg(feature = "sqlite")]
+	values.push("sqlite");
 	values
 }
 
@@ -43,7 +51,14 @@ pub enum Commands {
 		shell: Shell,
 	},
 	/// List all supported programming languages
-	Langs,
+	Langs {
+		/// Emit a JSON array of each language's internal index and name, for tooling that reads raw results
+		#[arg(long)]
+		json: bool,
+		/// Wrap the listing to N columns instead of the terminal width (80 when not a terminal); 0 lists one per line
+		#[arg(long, value_name = "N", conflicts_with = "json")]
+		wrap: Option<usize>,
+	},
 	/// Generate a default configuration file
 	Init {
 		/// Output path for the configuration file [default: .codestats.toml]
@@ -53,6 +68,38 @@ pub enum Commands {
 		#[arg(short, long)]
 		force: bool,
 	},
+	/// Print the version and capabilities of this build
+	Version {
+		/// Emit machine-readable JSON with the version, git commit, language count and output formats
+		#[arg(long)]
+		json: bool,
+	},
+	/// Compare two JSON reports (`-o json`, verbose for per-file deltas) and print how the line counts changed
+	Diff {
+		/// Report from the earlier run
+		old: PathBuf,
+		/// Report from the later run
+		new: PathBuf,
+		/// Pair files git detects as renamed in this revision range (e.g. `main..HEAD`) instead of showing them as
+		/// removed and added
+		#[arg(long, value_name = "RANGE")]
+		detect_renames: Option<String>,
+		/// Directory to run git in for --detect-renames, normally the analyzed path; git's paths are taken relative to it
+		#[arg(long, value_name = "DIR", default_value = ".", requires = "detect_renames")]
+		repo: PathBuf,
+	},
+	/// Check a languages.json5 file against the same rules used to build codestats
+	ValidateLanguages {
+		/// Path to the languages.json5 file to check
+		file: PathBuf,
+	},
+	/// Debug: score each language `file`'s name could be and list which of its keywords occur, for tuning
+	/// languages.json5
+	#[command(hide = true)]
+	KeywordHits {
+		/// File whose content is scored
+		file: PathBuf,
+	},
 }
 
 /// Arguments for the main code analysis functionality
@@ -63,10 +110,16 @@ pub enum Commands {
 	reason = "CLI flags are inherently boolean; grouping them into an enum would add complexity without clarity"
 )]
 pub struct AnalyzeArgs {
-	/// Path to configuration file (TOML format)
-	#[arg(short = 'c', long = "config")]
-	pub config: Option<PathBuf>,
-	/// The path to analyze
+	/// Path to configuration file (TOML format); repeat to layer files, later ones overriding earlier ones
+	#[arg(short = 'c', long = "config", value_name = "PATH", conflicts_with = "no_config")]
+	pub config: Vec<PathBuf>,
+	/// Ignore discovered config files and use only built-in defaults and CLI arguments
+	#[arg(long)]
+	pub no_config: bool,
+	/// Print the path of the config file that would be loaded (the first found in the search order) and exit
+	#[arg(long, conflicts_with_all = ["config", "no_config"])]
+	pub print_config_path: bool,
+	/// The path to analyze: a directory, a single file, or a .zip/.tar/.tar.gz archive
 	#[arg(value_name = "PATH", default_value = ".")]
 	pub path: PathBuf,
 	/// Show totals only, no language breakdown
@@ -78,12 +131,86 @@ pub struct AnalyzeArgs {
 	/// Do not respect .gitignore files
 	#[arg(long)]
 	pub no_gitignore: bool,
+	/// Respect only .gitignore files, ignoring global gitignore, .git/info/exclude and .ignore files
+	#[arg(long, conflicts_with = "no_gitignore")]
+	pub follow_gitignore_only: bool,
+	/// Do not read ignore files from directories above the analysis root
+	#[arg(long)]
+	pub no_ignore_parent: bool,
 	/// Count generated files (lockfiles, minified assets) which are excluded by default
 	#[arg(long)]
 	pub include_generated: bool,
+	/// Count dependency lockfiles (`Cargo.lock`, `package-lock.json`, ...) while still skipping minified assets
+	#[arg(long)]
+	pub include_lockfiles: bool,
+	/// Skip files whose name or content marks them as generated (e.g. `*.pb.go`, `@generated`, `DO NOT EDIT`)
+	#[arg(long)]
+	pub exclude_generated: bool,
 	/// Limit directory traversal to N levels deep
 	#[arg(long, value_name = "N")]
 	pub max_depth: Option<usize>,
+	/// Only count files modified within this long ago, e.g. 30d or 12h (units: s, m, h, d, w)
+	#[arg(long, value_name = "DURATION")]
+	pub modified_within: Option<Age>,
+	/// Columns a tab advances to when measuring line length (1-16)
+	#[arg(long, value_name = "N", default_value_t = 8, value_parser = clap::value_parser!(u8).range(1..=16))]
+	pub tab_width: u8,
+	/// Count lines holding both code and a comment (e.g. a trailing `// note`) as mixed instead of code
+	#[arg(long)]
+	pub split_mixed: bool,
+	/// Count Python docstrings as comments or as code; triple-quoted strings after code are always code
+	#[arg(
+		long, value_name = "MODE", default_value = "comment",
+		value_parser = PossibleValuesParser::new(["comment", "code"])
+			.map(|s| s.parse::<Docstrings>().expect("value already validated by PossibleValuesParser")),
+	)]
+	pub docstrings: Docstrings,
+	/// Count shebang lines in their own category, as code, as comments, or leave them out of every count
+	#[arg(
+		long, value_name = "MODE", default_value = "separate",
+		value_parser = PossibleValuesParser::new(["separate", "code", "comment", "ignore"])
+			.map(|s| s.parse::<Shebangs>().expect("value already validated by PossibleValuesParser")),
+	)]
+	pub shebang: Shebangs,
+	/// Also end lines at the Unicode line separators U+2028, U+2029 and NEL (U+0085)
+	#[arg(long)]
+	pub unicode_line_breaks: bool,
+	/// Add a hash of each file's content to verbose JSON and template output, to spot changed files between runs
+	#[arg(long)]
+	pub hash: bool,
+	/// Decode every file with this encoding (e.g. `shift_jis`, `gbk`, `koi8-r`) instead of detecting UTF-8 or UTF-16
+	#[arg(long, value_name = "LABEL")]
+	pub input_encoding: Option<InputEncoding>,
+	/// Attribute Markdown fenced code blocks and HTML `<script>`/`<style>` bodies to their own language
+	#[arg(long)]
+	pub embedded: bool,
+	/// Report test and non-test totals separately, classifying files by the `test_patterns` config globs
+	#[arg(long)]
+	pub split_tests: bool,
+	/// Report totals per file category (generated, test, docs, config, source), set by `[[category]]` config tables
+	#[arg(long)]
+	pub split_categories: bool,
+	/// Report files in vendored directories as one "Vendored" bucket, matched by the `vendored_patterns` config globs
+	#[arg(long)]
+	pub collapse_vendored: bool,
+	/// Assign languages from `codestats_language` keys in the `.editorconfig` at PATH (or beside it when PATH is a file)
+	#[arg(long)]
+	pub editorconfig: bool,
+	/// Count binary files (images, archives, ...) by size in a separate assets bucket instead of skipping them
+	#[arg(long)]
+	pub include_assets: bool,
+	/// Print each candidate language's score to stderr for files whose language is chosen by content
+	#[arg(long)]
+	pub explain_detection: bool,
+	/// Minimum content score needed to pick among languages sharing an extension (comment match 50, keyword 10)
+	#[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+	pub detection_threshold: u32,
+	/// Skip a file as binary when more than PCT percent of its sampled bytes are control characters (default: 20)
+	#[arg(long, value_name = "PCT", value_parser = clap::value_parser!(u8).range(0..=100))]
+	pub binary_threshold: Option<u8>,
+	/// Read at most N files at once, for network filesystems where parallel reads thrash (default: unbounded)
+	#[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..).map(|n| usize::try_from(n).unwrap_or(usize::MAX)))]
+	pub io_concurrency: Option<usize>,
 	/// Search hidden files and directories
 	#[arg(short = 'H', long = "hidden")]
 	pub hidden: bool,
@@ -91,10 +218,13 @@ pub struct AnalyzeArgs {
 	/// Use with caution as this can lead to infinite loops with circular symlinks
 	#[arg(long)]
 	pub symlinks: bool,
-	/// Output number formatting style
+	/// Don't descend into directories on a different filesystem than PATH (e.g. mount points like /proc or NFS shares)
+	#[arg(long)]
+	pub one_file_system: bool,
+	/// Output number formatting style; `indian` groups digits as lakh and crore (12,34,567)
 	#[arg(
 		short, long, default_value = "plain",
-		value_parser = PossibleValuesParser::new(["plain", "comma", "underscore", "space"])
+		value_parser = PossibleValuesParser::new(["plain", "comma", "underscore", "space", "indian"])
 			.map(|s| s.parse::<NumberStyle>().expect("value already validated by PossibleValuesParser")),
 	)]
 	pub number_style: NumberStyle,
@@ -105,13 +235,31 @@ pub struct AnalyzeArgs {
 			.map(|s| s.parse::<SizeStyle>().expect("value already validated by PossibleValuesParser")),
 	)]
 	pub size_style: SizeStyle,
+	/// Write sizes in bytes instead of scaling them to KiB, MiB, ...
+	#[arg(long)]
+	pub size_raw: bool,
+	/// Decimals in scaled sizes (0-6); by default 2, 1 or 0 depending on magnitude
+	#[arg(long, value_name = "N", value_parser = clap::value_parser!(u8).range(0..=6))]
+	pub size_precision: Option<u8>,
 	/// Percentage precision (0-6)
 	#[arg(short = 'p', long = "precision", default_value_t = 1, value_parser = clap::value_parser!(u8).range(0..=6))]
 	pub percent_precision: u8,
-	/// Sorting key for languages (and per-file details when verbose)
+	/// Percentage rounding: nearest (float default), up (half-up), or down (truncate)
+	#[arg(
+		long, default_value = "nearest",
+		value_parser = PossibleValuesParser::new(["nearest", "up", "down"])
+			.map(|s| s.parse::<RoundingMode>().expect("value already validated by PossibleValuesParser")),
+	)]
+	pub rounding: RoundingMode,
+	/// Round each breakdown's line-type percentages together so they sum to exactly 100 (largest remainder)
+	#[arg(long)]
+	pub balanced_percentages: bool,
+	/// Sorting key for languages (and per-file details when verbose, unless --file-sort-by is given)
 	#[arg(
 		short = 's', long = "sort-by", default_value = "lines",
-		value_parser = PossibleValuesParser::new(["lines", "code", "comments", "blanks", "files", "size", "name"])
+		value_parser = PossibleValuesParser::new([
+			"lines", "code", "comments", "blanks", "files", "size", "name", "comment-pct", "code-pct", "ratio",
+		])
 			.map(|s| s.parse::<LanguageSortKey>().expect("value already validated by PossibleValuesParser")),
 	)]
 	pub language_sort: LanguageSortKey,
@@ -122,6 +270,22 @@ pub struct AnalyzeArgs {
 			.map(|s| s.parse::<SortDirection>().expect("value already validated by PossibleValuesParser")),
 	)]
 	pub sort_direction: SortDirection,
+	/// Sorting key for per-file details when verbose [default: same as --sort-by]
+	#[arg(
+		long, value_name = "KEY",
+		value_parser = PossibleValuesParser::new([
+			"lines", "code", "comments", "blanks", "files", "size", "name", "comment-pct", "code-pct", "ratio",
+		])
+			.map(|s| s.parse::<LanguageSortKey>().expect("value already validated by PossibleValuesParser")),
+	)]
+	pub file_sort_by: Option<LanguageSortKey>,
+	/// Sorting direction for per-file details when verbose [default: same as --sort-direction]
+	#[arg(
+		long, alias = "file-sort-dir", value_name = "DIR",
+		value_parser = PossibleValuesParser::new(["asc", "desc"])
+			.map(|s| s.parse::<SortDirection>().expect("value already validated by PossibleValuesParser")),
+	)]
+	pub file_sort_direction: Option<SortDirection>,
 	/// Indentation style: "tab" or a number 1-8 for spaces
 	#[arg(long, default_value = "tab")]
 	pub indent: IndentStyle,
@@ -132,9 +296,58 @@ pub struct AnalyzeArgs {
 			.map(|s| s.parse::<OutputFormat>().expect("value already validated by PossibleValuesParser")),
 	)]
 	pub output: OutputFormat,
+	/// Comma-separated output formats to write into --output-dir, one `report.<ext>` file each
+	#[arg(
+		long, value_delimiter = ',', requires = "output_dir",
+		value_parser = PossibleValuesParser::new(output_format_values())
+			.map(|s| s.parse::<OutputFormat>().expect("value already validated by PossibleValuesParser")),
+	)]
+	pub formats: Vec<OutputFormat>,
+	/// Directory to write the reports selected by --formats into, created if missing
+	#[arg(long, value_name = "DIR", requires = "formats", conflicts_with_all = ["output", "template"])]
+	pub output_dir: Option<PathBuf>,
+	/// Write the report to FILE instead of stdout (required for sqlite output)
+	#[arg(long, value_name = "FILE", conflicts_with = "output_dir")]
+	pub output_file: Option<PathBuf>,
+	/// With --verbose CSV or TSV output, write one row per file as soon as it is counted instead of the full report
+	#[arg(long, conflicts_with_all = ["output_dir", "template", "blame", "track"])]
+	pub stream: bool,
+	/// Print the analysis time, throughput and peak memory use (Linux only) to stderr
+	#[arg(long)]
+	pub stats: bool,
+	/// Attribute every line to its author with `git blame` and add an author-by-language breakdown to human output
+	#[arg(long, conflicts_with_all = ["output_dir", "template"])]
+	pub blame: bool,
+	/// Save a JSON report into DIR on every run and print to stderr how the lines moved since the previous one
+	#[arg(long, value_name = "DIR")]
+	pub track: Option<PathBuf>,
+	/// Write a JSON list of the files skipped as binary, unrecognized or unreadable, with the reason for each, to FILE
+	#[arg(long, value_name = "FILE")]
+	pub errors_json: Option<PathBuf>,
+	/// Field separator for CSV output (e.g. `;` for spreadsheets in locales that use a decimal comma)
+	#[arg(long, value_name = "CHAR")]
+	pub delimiter: Option<char>,
+	/// End each record of `paths` output with a NUL byte instead of a newline, for `xargs -0`
+	#[arg(long, alias = "null-output")]
+	pub null: bool,
+	/// Prefix each record of `paths` output with the file's language and a tab
+	#[arg(long)]
+	pub path_languages: bool,
+	/// Render the report through a Tera template file instead of a built-in output format
+	#[arg(long, value_name = "FILE")]
+	pub template: Option<PathBuf>,
+	/// Show this name in reports instead of the analyzed path, e.g. to hide a CI checkout directory
+	#[arg(long, value_name = "NAME")]
+	pub root_label: Option<String>,
+	/// Write the human overview line from a template with {files}, {lines}, {code}, {size} and {path} placeholders
+	#[arg(long, value_name = "TEMPLATE")]
+	pub summary_format: Option<SummaryFormat>,
 	/// Exclude files or directories matching the given glob patterns. Can be specified more than once.
 	#[arg(short, long)]
 	pub exclude: Vec<String>,
+	/// Skip every directory with this name (or name glob), e.g. `target`, without descending into it. Can be specified more than once.
+	#[arg(long = "exclude-dir", value_name = "NAME")]
+	pub exclude_dir: Vec<String>,
 	/// Only analyze files of the specified language(s). Can be specified multiple times, and cannot be used together with --exclude-lang.
 	#[arg(short = 'L', long = "lang", conflicts_with = "exclude_lang")]
 	pub include_lang: Vec<String>,
@@ -150,9 +363,67 @@ pub struct AnalyzeArgs {
 	/// Show a breakdown by directory instead of by language
 	#[arg(short = 'D', long)]
 	pub by_dir: bool,
+	/// Show a breakdown by the first N directory levels (e.g. `services/a` with N=2); implies --by-dir
+	#[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+	pub group_by_dir: Option<u16>,
+	/// In verbose human output, show only the last N components of each file path
+	#[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+	pub path_depth: Option<u16>,
+	/// Leave out the summary overview and print only the language breakdown (and file details with --verbose)
+	#[arg(long, conflicts_with = "quiet")]
+	pub no_summary: bool,
+	/// List languages and display alias groups that have no files (e.g. a --lang with no matches) with zero counts
+	#[arg(long)]
+	pub show_empty: bool,
+	/// Report tab-indented vs space-indented lines for each language
+	#[arg(long)]
+	pub show_indentation: bool,
+	/// Report line-comment vs block-comment lines for each language
+	#[arg(long)]
+	pub show_comment_styles: bool,
+	/// Report how many files start with a byte-order mark
+	#[arg(long)]
+	pub show_encodings: bool,
+	/// Also report sizes with CRLF line endings counted as LF, for comparing sizes across platforms
+	#[arg(long)]
+	pub normalized_size: bool,
+	/// Draw a bar of each language's share of lines in human output (terminal only)
+	#[arg(long)]
+	pub bars: bool,
+	/// Draw a sparkline of each language's file-size distribution in verbose human output
+	#[arg(long)]
+	pub sparkline: bool,
+	/// Embed the effective configuration in JSON reports as a `config` object, to record how a report was produced
+	#[arg(long)]
+	pub embed_config: bool,
+	/// Merge the languages of each `[[rollup]]` config table into one entry (default: C, C++, Objective-C and
+	/// Objective-C++ as "C/C++")
+	#[arg(long)]
+	pub aggregate_extensions: bool,
+	/// Report languages grouped by paradigm (systems, general, scripting, markup, config, data, other) instead of display
+	/// aliases and rollups
+	#[arg(long)]
+	pub by_paradigm: bool,
+	/// Color human output: auto (terminal only, unless `NO_COLOR` is set), always, or never
+	#[arg(
+		long, value_name = "WHEN", default_value = "auto",
+		value_parser = PossibleValuesParser::new(["auto", "always", "never"])
+			.map(|s| s.parse::<ColorChoice>().expect("value already validated by PossibleValuesParser")),
+	)]
+	pub color: ColorChoice,
+	/// Leave the generation time out of JSON, HTML and template output, so identical trees give identical reports
+	#[arg(long)]
+	pub no_timestamp: bool,
 	/// Exit with a non-zero status code if any files are skipped due to errors.
 	#[arg(long = "fail-on-error")]
 	pub fail_on_error: bool,
+	/// Stop at the first file or directory that cannot be read and exit with an error, without printing a report
+	#[arg(long)]
+	pub strict: bool,
+	/// Reuse counts for files unchanged since the last run, stored in FILE (default: `.codestats-cache` in PATH, or beside it when PATH is a file)
+	#[arg(long, value_name = "FILE", num_args = 0..=1, require_equals = true)]
+	#[expect(clippy::option_option, reason = "distinguishes a bare `--cache` from the flag being absent")]
+	pub cache: Option<Option<PathBuf>>,
 }
 
 impl Cli {
@@ -164,70 +435,224 @@ impl Cli {
 	}
 }
 
+/// Run `$body` only when the argument `$id` was given on the command line, so config file values survive otherwise.
+macro_rules! apply {
+	($matches:expr, $id:literal, $body:expr) => {
+		if cli_overrode($matches, $id) {
+			$body
+		}
+	};
+}
+
 /// Merge CLI arguments into `config`, with CLI taking precedence over file-based settings.
 ///
 /// # Errors
 ///
 /// Returns an error when the merged config sets both include and exclude languages.
 pub fn merge_config(mut config: Config, args: &AnalyzeArgs, matches: &ArgMatches) -> Result<Config> {
-	let path_overridden = cli_overrode(matches, "path");
-	if path_overridden {
+	if cli_overrode(matches, "path") {
 		config.path.clone_from(&args.path);
 	}
-	macro_rules! apply {
-		($id:literal, $body:expr) => {
-			if cli_overrode(matches, $id) {
-				$body
-			}
-		};
-	}
 	if cli_overrode(matches, "quiet") && args.quiet {
 		config.analysis.verbosity = Verbosity::Summary;
 	}
 	if cli_overrode(matches, "verbose") && args.verbose {
 		config.analysis.verbosity = Verbosity::Verbose;
 	}
-	apply!("no_gitignore", config.analysis.respect_gitignore = !args.no_gitignore);
-	apply!("hidden", config.analysis.include_hidden = args.hidden);
-	apply!("include_generated", config.analysis.include_generated = args.include_generated);
-	apply!("max_depth", config.analysis.max_depth = args.max_depth);
-	apply!("symlinks", config.analysis.follow_symlinks = args.symlinks);
-	apply!("fail_on_error", config.analysis.fail_on_error = args.fail_on_error);
-	apply!("number_style", config.display.number_style = args.number_style);
-	apply!("size_style", config.display.size_units = args.size_style);
-	apply!("percent_precision", config.display.precision = args.percent_precision);
-	apply!("language_sort", config.display.sort_by = args.language_sort);
-	apply!("sort_direction", config.display.sort_direction = args.sort_direction);
-	apply!("output", config.display.output = args.output);
-	apply!("indent", config.display.indent = args.indent);
-	apply!("top_languages", config.display.top_languages = args.top_languages);
-	apply!("min_lines", config.display.min_lines = args.min_lines);
-	apply!("by_dir", config.display.by_dir = args.by_dir);
+	apply!(matches, "no_gitignore", config.analysis.respect_gitignore = !args.no_gitignore);
+	if cli_overrode(matches, "follow_gitignore_only") && args.follow_gitignore_only {
+		config.analysis.respect_gitignore = true;
+		config.analysis.git_global = false;
+		config.analysis.git_exclude = false;
+		config.analysis.dot_ignore = false;
+	}
+	apply!(matches, "no_ignore_parent", config.analysis.ignore_parents = !args.no_ignore_parent);
+	apply!(matches, "hidden", config.analysis.include_hidden = args.hidden);
+	apply!(matches, "include_generated", config.analysis.include_generated = args.include_generated);
+	apply!(matches, "include_lockfiles", config.analysis.include_lockfiles = args.include_lockfiles);
+	apply!(matches, "exclude_generated", config.analysis.exclude_generated = args.exclude_generated);
+	apply!(matches, "max_depth", config.analysis.max_depth = args.max_depth);
+	apply!(matches, "modified_within", config.analysis.modified_within = args.modified_within);
+	apply!(matches, "tab_width", config.analysis.tab_width = args.tab_width);
+	apply!(matches, "split_mixed", config.analysis.split_mixed = args.split_mixed);
+	apply!(matches, "docstrings", config.analysis.docstrings = args.docstrings);
+	apply!(matches, "shebang", config.analysis.shebang = args.shebang);
+	apply!(matches, "unicode_line_breaks", config.analysis.unicode_line_breaks = args.unicode_line_breaks);
+	apply!(matches, "hash", config.analysis.hash = args.hash);
+	apply!(matches, "input_encoding", config.analysis.input_encoding = args.input_encoding);
+	apply!(matches, "embedded", config.analysis.embedded = args.embedded);
+	apply!(matches, "split_tests", config.analysis.split_tests = args.split_tests);
+	apply!(matches, "split_categories", config.analysis.split_categories = args.split_categories);
+	apply!(matches, "collapse_vendored", config.analysis.collapse_vendored = args.collapse_vendored);
+	apply!(matches, "editorconfig", config.analysis.editorconfig = args.editorconfig);
+	apply!(matches, "include_assets", config.analysis.include_assets = args.include_assets);
+	apply!(matches, "explain_detection", config.analysis.explain_detection = args.explain_detection);
+	apply!(matches, "detection_threshold", config.analysis.detection_threshold = args.detection_threshold);
+	if let Some(threshold) = args.binary_threshold {
+		config.analysis.binary_threshold = threshold;
+	}
+	apply!(matches, "io_concurrency", config.analysis.io_concurrency = args.io_concurrency);
+	apply!(matches, "symlinks", config.analysis.follow_symlinks = args.symlinks);
+	apply!(matches, "one_file_system", config.analysis.one_file_system = args.one_file_system);
+	apply!(matches, "fail_on_error", config.analysis.fail_on_error = args.fail_on_error);
+	apply!(matches, "strict", config.analysis.strict = args.strict);
+	merge_display(&mut config, args, matches);
 	if cli_overrode(matches, "exclude") {
 		config.analysis.exclude_patterns.extend(args.exclude.clone());
 	}
+	if cli_overrode(matches, "exclude_dir") {
+		config.analysis.exclude_dirs.extend(args.exclude_dir.clone());
+	}
 	if cli_overrode(matches, "include_lang") {
 		config.analysis.include_languages.extend(args.include_lang.clone());
 	}
 	if cli_overrode(matches, "exclude_lang") {
 		config.analysis.exclude_languages.extend(args.exclude_lang.clone());
 	}
-	if !path_overridden
-		&& config.path_overridden
-		&& let Some(source) = &config.source
-		&& config.path.is_relative()
-		&& let Some(parent) = source.parent()
-	{
-		config.path = parent.join(&config.path);
-	}
+	resolve_cache(&mut config, args);
+	config.analysis.collect_skipped = args.errors_json.is_some();
 	config.display.precision = config.display.precision.min(6);
+	config.display.size_precision = config.display.size_precision.map(|precision| precision.min(6));
+	config.display.dir_depth = config.display.dir_depth.max(1);
+	config.analysis.tab_width = config.analysis.tab_width.clamp(1, 16);
+	config.analysis.detection_threshold = config.analysis.detection_threshold.max(1);
+	config.analysis.binary_threshold = config.analysis.binary_threshold.min(100);
+	config.analysis.binary_thresholds.values_mut().for_each(|threshold| *threshold = (*threshold).min(100));
+	config.analysis.io_concurrency = config.analysis.io_concurrency.map(|n| n.max(1));
+	validate_output(&config, args)?;
 	ensure!(
 		config.analysis.include_languages.is_empty() || config.analysis.exclude_languages.is_empty(),
 		"Config cannot set both include_languages and exclude_languages"
 	);
+	validate_languages(&config.analysis.include_languages)?;
+	validate_languages(&config.analysis.exclude_languages)?;
+	for alias in config.display_aliases.iter().chain(&config.rollups) {
+		validate_languages(&alias.languages)?;
+	}
 	Ok(config)
 }
 
+/// Apply the display-related arguments; the template path is resolved against the config file's directory.
+fn merge_display(config: &mut Config, args: &AnalyzeArgs, matches: &ArgMatches) {
+	apply!(matches, "number_style", config.display.number_style = args.number_style);
+	apply!(matches, "size_style", config.display.size_units = args.size_style);
+	apply!(matches, "size_raw", config.display.size_raw = args.size_raw);
+	apply!(matches, "size_precision", config.display.size_precision = args.size_precision);
+	apply!(matches, "percent_precision", config.display.precision = args.percent_precision);
+	apply!(matches, "rounding", config.display.rounding = args.rounding);
+	apply!(matches, "balanced_percentages", config.display.balanced_percentages = args.balanced_percentages);
+	apply!(matches, "language_sort", config.display.sort_by = args.language_sort);
+	apply!(matches, "sort_direction", config.display.sort_direction = args.sort_direction);
+	apply!(matches, "file_sort_by", config.display.file_sort_by = args.file_sort_by);
+	apply!(matches, "file_sort_direction", config.display.file_sort_direction = args.file_sort_direction);
+	apply!(matches, "output", config.display.output = args.output);
+	apply!(matches, "root_label", config.display.root_label.clone_from(&args.root_label));
+	apply!(matches, "summary_format", config.display.summary_format.clone_from(&args.summary_format));
+	if let Some(delimiter) = args.delimiter {
+		config.display.csv_delimiter = delimiter;
+	}
+	apply!(matches, "null", config.display.null_terminated = args.null);
+	apply!(matches, "path_languages", config.display.path_languages = args.path_languages);
+	apply!(matches, "template", config.display.template.clone_from(&args.template));
+	apply!(matches, "indent", config.display.indent = args.indent);
+	apply!(matches, "top_languages", config.display.top_languages = args.top_languages);
+	apply!(matches, "min_lines", config.display.min_lines = args.min_lines);
+	apply!(matches, "by_dir", config.display.by_dir = args.by_dir);
+	if let Some(depth) = args.group_by_dir {
+		config.display.by_dir = true;
+		config.display.dir_depth = usize::from(depth);
+	}
+	apply!(matches, "path_depth", config.display.path_depth = args.path_depth.map(usize::from));
+	apply!(matches, "no_summary", config.display.show_summary = !args.no_summary);
+	apply!(matches, "show_empty", config.display.show_empty = args.show_empty);
+	apply!(matches, "show_indentation", config.display.show_indentation = args.show_indentation);
+	apply!(matches, "show_comment_styles", config.display.show_comment_styles = args.show_comment_styles);
+	apply!(matches, "show_encodings", config.display.show_encodings = args.show_encodings);
+	apply!(matches, "normalized_size", config.display.normalized_size = args.normalized_size);
+	apply!(matches, "bars", config.display.bars = args.bars);
+	apply!(matches, "sparkline", config.display.sparkline = args.sparkline);
+	apply!(matches, "embed_config", config.display.embed_config = args.embed_config);
+	apply!(matches, "aggregate_extensions", config.display.aggregate_extensions = args.aggregate_extensions);
+	apply!(matches, "by_paradigm", config.display.by_paradigm = args.by_paradigm);
+	apply!(matches, "color", config.display.color = args.color);
+	apply!(matches, "no_timestamp", config.display.timestamp = !args.no_timestamp);
+}
+
+/// Reject language filters that name no supported language, since they would silently match nothing.
+fn validate_languages(names: &[String]) -> Result<()> {
+	for name in names {
+		if LANGUAGES.iter().any(|lang| lang.name.eq_ignore_ascii_case(name)) {
+			continue;
+		}
+		match langs::closest_name(name) {
+			Some(suggestion) => bail!("Unknown language `{name}`; did you mean `{suggestion}`?"),
+			None => bail!("Unknown language `{name}`; run `cs langs` to list supported languages"),
+		}
+	}
+	Ok(())
+}
+
+/// Apply `--cache`, where a bare flag uses the default file name in the analysis root, or next to it when the root
+/// is a file. A relative path from a config file was already resolved against that file when it was loaded.
+fn resolve_cache(config: &mut Config, args: &AnalyzeArgs) {
+	if let Some(cache) = &args.cache {
+		let dir = config.path.parent().filter(|_| config.path.is_file()).unwrap_or(&config.path);
+		config.analysis.cache = Some(cache.clone().unwrap_or_else(|| dir.join(DEFAULT_CACHE_FILE)));
+	}
+}
+
+/// Reject output settings that cannot produce a well-formed report.
+fn validate_output(config: &Config, args: &AnalyzeArgs) -> Result<()> {
+	ensure!(
+		args.delimiter.is_none()
+			|| config.display.output == OutputFormat::Csv
+			|| args.formats.contains(&OutputFormat::Csv),
+		"--delimiter only applies to CSV output"
+	);
+	ensure!(
+		!args.null || config.display.output == OutputFormat::Paths || args.formats.contains(&OutputFormat::Paths),
+		"--null only applies to paths output"
+	);
+	ensure!(
+		!args.path_languages
+			|| config.display.output == OutputFormat::Paths
+			|| args.formats.contains(&OutputFormat::Paths),
+		"--path-languages only applies to paths output"
+	);
+	ensure!(
+		!args.embed_config
+			|| matches!(config.display.output, OutputFormat::Json | OutputFormat::JsonCompact)
+			|| args.formats.iter().any(|format| matches!(format, OutputFormat::Json | OutputFormat::JsonCompact)),
+		"--embed-config only applies to JSON output"
+	);
+	ensure!(!args.blame || config.display.output == OutputFormat::Human, "--blame only applies to human output");
+	ensure!(
+		!args.stream || matches!(config.display.output, OutputFormat::Csv | OutputFormat::Tsv),
+		"--stream only applies to CSV and TSV output"
+	);
+	ensure!(
+		!args.stream || config.analysis.verbosity == Verbosity::Verbose,
+		"--stream writes per-file rows and needs --verbose"
+	);
+	ensure!(
+		!matches!(config.display.csv_delimiter, '"' | '\n' | '\r'),
+		"CSV delimiter cannot be a quote or a line break"
+	);
+	#[cfg(feature = "sqlite")]
+	ensure!(
+		config.display.output != OutputFormat::Sqlite || args.output_file.is_some() || args.output_dir.is_some(),
+		"SQLite output is a binary database; write it to a file with --output-file"
+	);
+	for (idx, format) in args.formats.iter().enumerate() {
+		ensure!(
+			args.formats[..idx].iter().all(|other| other.extension() != format.extension()),
+			"--formats would write `report.{}` more than once",
+			format.extension()
+		);
+	}
+	Ok(())
+}
+
 fn cli_overrode(matches: &ArgMatches, id: &str) -> bool {
 	matches.value_source(id) == Some(ValueSource::CommandLine)
 }
@@ -243,7 +668,7 @@ mod tests {
 	use clap::{CommandFactory, FromArgMatches};
 	use codestats::{
 		config::Config,
-		display::{IndentStyle, Verbosity},
+		display::{IndentStyle, OutputFormat, Verbosity},
 	};
 
 	use super::{AnalyzeArgs, Cli, merge_config};
@@ -273,6 +698,20 @@ mod tests {
 		assert_eq!(merged.path, expected);
 	}
 
+	#[test]
+	fn layered_configs_override_left_to_right() {
+		let base =
+			write_config("[analysis]\ntab_width = 4\ninclude_hidden = true\n[display]\ntemplate = \"base.tera\"\n");
+		let repo = write_config("[analysis]\ntab_width = 2\n");
+		let config = Config::from_files(&[&base, &repo]).expect("load configs");
+		let (args, matches) = parse_cli(&["cs"]);
+		let merged = merge_config(config, &args, &matches).expect("merge config");
+		assert_eq!(merged.analysis.tab_width, 2);
+		assert!(merged.analysis.include_hidden);
+		assert_eq!(merged.display.template, Some(base.parent().expect("config parent").join("base.tera")));
+		assert_eq!(merged.source, Some(repo));
+	}
+
 	#[test]
 	fn merge_preserves_cli_path_override() {
 		let config_path = write_config("path = \"fixtures\"\n");
@@ -291,6 +730,75 @@ mod tests {
 		assert_eq!(merged.display.precision, 6);
 	}
 
+	#[test]
+	fn merge_no_ignore_parent_overrides_config() {
+		let config_path = write_config("[analysis]\nignore_parents = true\n");
+		let config = Config::from_file(&config_path).expect("load config");
+		let (args, matches) = parse_cli(&["cs", "--no-ignore-parent"]);
+		let merged = merge_config(config, &args, &matches).expect("merge config");
+		assert!(!merged.analysis.ignore_parents);
+	}
+
+	#[test]
+	fn merge_follow_gitignore_only_disables_other_ignore_sources() {
+		let config_path = write_config("[analysis]\nrespect_gitignore = false\ngit_exclude = true\n");
+		let config = Config::from_file(&config_path).expect("load config");
+		let (args, matches) = parse_cli(&["cs", "--follow-gitignore-only"]);
+		let merged = merge_config(config, &args, &matches).expect("merge config");
+		assert!(merged.analysis.respect_gitignore);
+		assert!(!merged.analysis.git_global);
+		assert!(!merged.analysis.git_exclude);
+		assert!(!merged.analysis.dot_ignore);
+	}
+
+	#[test]
+	fn merge_resolves_cache_paths() {
+		let (args, matches) = parse_cli(&["cs", "--cache", "src"]);
+		let merged = merge_config(Config::default(), &args, &matches).expect("merge config");
+		assert_eq!(merged.analysis.cache, Some(PathBuf::from("src/.codestats-cache")));
+		let (args, matches) = parse_cli(&["cs", "--cache=/tmp/cs.cache"]);
+		let merged = merge_config(Config::default(), &args, &matches).expect("merge config");
+		assert_eq!(merged.analysis.cache, Some(PathBuf::from("/tmp/cs.cache")));
+		let config_path = write_config("[analysis]\ncache = \"build/cache\"\n");
+		let config = Config::from_file(&config_path).expect("load config");
+		let (args, matches) = parse_cli(&["cs"]);
+		let merged = merge_config(config, &args, &matches).expect("merge config");
+		assert_eq!(merged.analysis.cache, Some(config_path.parent().expect("config parent").join("build/cache")));
+	}
+
+	#[test]
+	fn formats_parse_comma_separated_list() {
+		let (args, _) = parse_cli(&["cs", "--formats", "json,csv", "--output-dir", "reports"]);
+		assert_eq!(args.formats, [OutputFormat::Json, OutputFormat::Csv]);
+		assert_eq!(args.output_dir, Some(PathBuf::from("reports")));
+	}
+
+	#[test]
+	fn merge_rejects_formats_sharing_an_extension() {
+		let (args, matches) = parse_cli(&["cs", "--formats", "json,json-compact", "--output-dir", "reports"]);
+		let err = merge_config(Config::default(), &args, &matches).unwrap_err();
+		assert!(err.to_string().contains("report.json"), "{err}");
+	}
+
+	#[test]
+	fn merge_delimiter_requires_csv_output() {
+		let (args, matches) = parse_cli(&["cs", "-o", "csv", "--delimiter", ";"]);
+		let merged = merge_config(Config::default(), &args, &matches).expect("merge config");
+		assert_eq!(merged.display.csv_delimiter, ';');
+		let (args, matches) = parse_cli(&["cs", "-o", "json", "--delimiter", ";"]);
+		assert!(merge_config(Config::default(), &args, &matches).is_err());
+	}
+
+	#[test]
+	fn merge_stream_requires_verbose_separated_values() {
+		let (args, matches) = parse_cli(&["cs", "-v", "-o", "tsv", "--stream"]);
+		assert!(merge_config(Config::default(), &args, &matches).is_ok());
+		let (args, matches) = parse_cli(&["cs", "-o", "csv", "--stream"]);
+		assert!(merge_config(Config::default(), &args, &matches).is_err());
+		let (args, matches) = parse_cli(&["cs", "-v", "-o", "json", "--stream"]);
+		assert!(merge_config(Config::default(), &args, &matches).is_err());
+	}
+
 	#[test]
 	fn merge_extends_exclude_patterns_from_cli() {
 		let config_path = write_config("[analysis]\nexclude_patterns = [\"target\"]\n");
@@ -300,6 +808,18 @@ mod tests {
 		assert_eq!(merged.analysis.exclude_patterns, vec!["target".to_string(), "node_modules".to_string()]);
 	}
 
+	#[test]
+	fn merge_rejects_unknown_languages_with_a_suggestion() {
+		let (args, matches) = parse_cli(&["cs", "--lang", "RUST"]);
+		assert!(merge_config(Config::default(), &args, &matches).is_ok());
+		let (args, matches) = parse_cli(&["cs", "--lang", "Rst"]);
+		let err = merge_config(Config::default(), &args, &matches).unwrap_err();
+		assert_eq!(err.to_string(), "Unknown language `Rst`; did you mean `Rust`?");
+		let (args, matches) = parse_cli(&["cs", "--exclude-lang", "qqqqqqqq"]);
+		let err = merge_config(Config::default(), &args, &matches).unwrap_err();
+		assert_eq!(err.to_string(), "Unknown language `qqqqqqqq`; run `cs langs` to list supported languages");
+	}
+
 	#[test]
 	fn merge_rejects_conflicting_language_filters() {
 		let config_path = write_config("[analysis]\ninclude_languages = [\"Rust\"]\n");
@@ -319,6 +839,22 @@ mod tests {
 		assert!(merged.analysis.include_hidden);
 	}
 
+	#[test]
+	fn merge_group_by_dir_enables_dir_breakdown() {
+		let config_path = write_config("[display]\nby_dir = false\n");
+		let config = Config::from_file(&config_path).expect("load config");
+		let (args, matches) = parse_cli(&["cs", "--group-by-dir", "2"]);
+		let merged = merge_config(config, &args, &matches).expect("merge config");
+		assert!(merged.display.by_dir);
+		assert_eq!(merged.display.dir_depth, 2);
+	}
+
+	#[test]
+	fn no_config_conflicts_with_config() {
+		let result = Cli::command().try_get_matches_from(["cs", "--no-config", "--config", "custom.toml"]);
+		assert!(result.is_err(), "--no-config and --config should be mutually exclusive");
+	}
+
 	#[test]
 	fn merge_applies_indent_from_cli() {
 		let config_path = write_config("");
@@ -0,0 +1,294 @@
+use std::{
+	collections::BTreeMap,
+	fmt::Write as _,
+	fs,
+	io::{self, Write},
+	path::Path,
+	process::Command,
+};
+
+use anyhow::{Context as _, Result, ensure};
+use serde::Deserialize;
+
+/// The parts of a JSON report (`-o json`) that are compared.
+#[derive(Deserialize)]
+struct Report {
+	summary: Totals,
+	#[serde(default)]
+	languages: Vec<LanguageEntry>,
+}
+
+#[derive(Deserialize)]
+struct Totals {
+	#[serde(rename = "total_files")]
+	files: u64,
+	#[serde(rename = "total_lines")]
+	lines: u64,
+	#[serde(rename = "total_code_lines")]
+	code: u64,
+	#[serde(rename = "total_comment_lines")]
+	comments: u64,
+	#[serde(rename = "total_blank_lines")]
+	blanks: u64,
+}
+
+#[derive(Deserialize)]
+struct LanguageEntry {
+	name: String,
+	lines: u64,
+	code_lines: u64,
+	#[serde(default)]
+	files_detail: Option<Vec<FileEntry>>,
+}
+
+#[derive(Deserialize)]
+struct FileEntry {
+	path: String,
+	total_lines: u64,
+}
+
+/// How one file changed between the two reports.
+#[derive(Debug, PartialEq, Eq)]
+enum FileChange {
+	Changed { path: String, old: u64, new: u64 },
+	Renamed { from: String, to: String, old: u64, new: u64 },
+	Added { path: String, lines: u64 },
+	Removed { path: String, lines: u64 },
+}
+
+/// Compare the JSON reports at `old` and `new` and print the line deltas.
+///
+/// With `renames` (a git revision range, run in `repo`), files git reports as renamed in that range are paired up so
+/// their deltas are shown as one renamed file instead of a removal and an addition.
+pub fn run_diff(old: &Path, new: &Path, renames: Option<&str>, repo: &Path) -> Result<()> {
+	let old = read_report(old)?;
+	let new = read_report(new)?;
+	let renames = renames.map(|range| git_renames(repo, range)).transpose()?.unwrap_or_default();
+	let mut stdout = io::stdout();
+	stdout.write_all(render(&old, &new, &renames).as_bytes())?;
+	stdout.flush()?;
+	Ok(())
+}
+
+/// Summarize how `new`, a JSON report, moved since the report saved at `old`: the change in total lines, then every
+/// language whose line count changed, biggest movers first.
+pub fn trend(old: &Path, new: &str) -> Result<String> {
+	let old = read_report(old)?;
+	let new: Report = serde_json::from_str(new).context("The new report is not a codestats JSON report")?;
+	Ok(render_trend(&old, &new))
+}
+
+fn read_report(path: &Path) -> Result<Report> {
+	let content = fs::read_to_string(path).with_context(|| format!("Failed to read `{}`", path.display()))?;
+	serde_json::from_str(&content).with_context(|| format!("`{}` is not a codestats JSON report", path.display()))
+}
+
+/// Renamed `(old, new)` paths in `range`, as found by git's rename detection, relative to `repo`.
+///
+/// `repo` may be a subdirectory of the repository; only renames within it are listed.
+fn git_renames(repo: &Path, range: &str) -> Result<Vec<(String, String)>> {
+	let output = Command::new("git")
+		.arg("-C")
+		.arg(repo)
+		.args(["diff", "-M", "--relative", "--name-status", "-z", range])
+		.output()
+		.context("Failed to run git")?;
+	ensure!(
+		output.status.success(),
+		"git diff failed for `{range}`: {}",
+		String::from_utf8_lossy(&output.stderr).trim()
+	);
+	Ok(parse_renames(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Pick the renames out of `git diff --name-status -z` output, where a rename is `R<score>`, the old path and the new.
+fn parse_renames(name_status: &str) -> Vec<(String, String)> {
+	let mut fields = name_status.split('\0').filter(|field| !field.is_empty());
+	let mut renames = Vec::new();
+	while let Some(status) = fields.next() {
+		let paths = if status.starts_with(['R', 'C']) { 2 } else { 1 };
+		let mut paths = fields.by_ref().take(paths);
+		if status.starts_with('R')
+			&& let (Some(from), Some(to)) = (paths.next(), paths.next())
+		{
+			renames.push((from.to_owned(), to.to_owned()));
+		}
+		paths.for_each(drop);
+	}
+	renames
+}
+
+fn render(old: &Report, new: &Report, renames: &[(String, String)]) -> String {
+	let mut out = String::new();
+	let (o, n) = (&old.summary, &new.summary);
+	out.push_str("Totals:\n");
+	for (label, old, new) in [
+		("files", o.files, n.files),
+		("lines", o.lines, n.lines),
+		("code", o.code, n.code),
+		("comments", o.comments, n.comments),
+		("blanks", o.blanks, n.blanks),
+	] {
+		let _ = writeln!(out, "\t{label}: {old} -> {new} ({})", delta(old, new));
+	}
+	let mut languages: BTreeMap<&str, [(u64, u64); 2]> = BTreeMap::new();
+	for (side, report) in [old, new].into_iter().enumerate() {
+		for lang in &report.languages {
+			languages.entry(&lang.name).or_default()[side] = (lang.lines, lang.code_lines);
+		}
+	}
+	out.push_str("Languages:\n");
+	for (name, [(old_lines, old_code), (new_lines, new_code)]) in &languages {
+		if (old_lines, old_code) != (new_lines, new_code) {
+			let _ = writeln!(
+				out,
+				"\t{name}: {} lines, {} code",
+				delta(*old_lines, *new_lines),
+				delta(*old_code, *new_code)
+			);
+		}
+	}
+	let changes = file_changes(&files(old), &files(new), renames);
+	if !changes.is_empty() {
+		out.push_str("Files:\n");
+	}
+	for change in changes {
+		let _ = match change {
+			FileChange::Changed { path, old, new } => writeln!(out, "\t{path}: {}", delta(old, new)),
+			FileChange::Renamed { from, to, old, new } => writeln!(out, "\t{from} -> {to}: {}", delta(old, new)),
+			FileChange::Added { path, lines } => writeln!(out, "\t{path} (added): {}", delta(0, lines)),
+			FileChange::Removed { path, lines } => writeln!(out, "\t{path} (removed): {}", delta(lines, 0)),
+		};
+	}
+	out
+}
+
+fn render_trend(old: &Report, new: &Report) -> String {
+	let mut out = String::new();
+	let (old_lines, new_lines) = (old.summary.lines, new.summary.lines);
+	let _ = writeln!(out, "Lines: {old_lines} -> {new_lines} ({})", delta(old_lines, new_lines));
+	let mut languages: BTreeMap<&str, [u64; 2]> = BTreeMap::new();
+	for (side, report) in [old, new].into_iter().enumerate() {
+		for lang in &report.languages {
+			languages.entry(&lang.name).or_default()[side] = lang.lines;
+		}
+	}
+	let mut movers: Vec<_> = languages.into_iter().filter(|(_, [old, new])| old != new).collect();
+	movers.sort_by_key(|(_, [old, new])| std::cmp::Reverse(old.abs_diff(*new)));
+	for (name, [old, new]) in movers {
+		let _ = writeln!(out, "\t{name}: {}", delta(old, new));
+	}
+	out
+}
+
+fn delta(old: u64, new: u64) -> String {
+	if new >= old { format!("+{}", new - old) } else { format!("-{}", old - new) }
+}
+
+/// Line counts by path of every file listed in a verbose report.
+fn files(report: &Report) -> BTreeMap<&str, u64> {
+	report
+		.languages
+		.iter()
+		.flat_map(|lang| lang.files_detail.iter().flatten())
+		.map(|file| (file.path.as_str(), file.total_lines))
+		.collect()
+}
+
+/// Compare files by path, pairing a removed and an added file when `renames` names them as a rename.
+///
+/// Git paths are relative to the directory git ran in and report paths to the analyzed path, which are the same when
+/// `--repo` is the analyzed path. A git path matches the report path equal to it or ending in `/` followed by it, so
+/// reports whose paths start with `./` match too.
+fn file_changes(old: &BTreeMap<&str, u64>, new: &BTreeMap<&str, u64>, renames: &[(String, String)]) -> Vec<FileChange> {
+	let mut removed: BTreeMap<&str, u64> =
+		old.iter().filter(|(path, _)| !new.contains_key(*path)).map(|(p, n)| (*p, *n)).collect();
+	let mut added: BTreeMap<&str, u64> =
+		new.iter().filter(|(path, _)| !old.contains_key(*path)).map(|(p, n)| (*p, *n)).collect();
+	let mut changes: Vec<FileChange> = old
+		.iter()
+		.filter_map(|(path, &old_lines)| {
+			let &new_lines = new.get(path)?;
+			(old_lines != new_lines).then(|| FileChange::Changed {
+				path: (*path).to_owned(),
+				old: old_lines,
+				new: new_lines,
+			})
+		})
+		.collect();
+	for (from, to) in renames {
+		let Some(from) = find_path(&removed, from) else { continue };
+		let Some(to) = find_path(&added, to) else { continue };
+		let (old_lines, new_lines) = (removed.remove(from).unwrap_or_default(), added.remove(to).unwrap_or_default());
+		changes.push(FileChange::Renamed { from: from.to_owned(), to: to.to_owned(), old: old_lines, new: new_lines });
+	}
+	changes.extend(added.into_iter().map(|(path, lines)| FileChange::Added { path: path.to_owned(), lines }));
+	changes.extend(removed.into_iter().map(|(path, lines)| FileChange::Removed { path: path.to_owned(), lines }));
+	changes
+}
+
+fn find_path<'a>(paths: &BTreeMap<&'a str, u64>, git_path: &str) -> Option<&'a str> {
+	paths
+		.keys()
+		.copied()
+		.find(|path| path.strip_suffix(git_path).is_some_and(|prefix| prefix.is_empty() || prefix.ends_with('/')))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn report(lines: u64, languages: &[(&str, u64)]) -> Report {
+		Report {
+			summary: Totals { files: 0, lines, code: 0, comments: 0, blanks: 0 },
+			languages: languages
+				.iter()
+				.map(|&(name, lines)| LanguageEntry { name: name.to_owned(), lines, code_lines: 0, files_detail: None })
+				.collect(),
+		}
+	}
+
+	#[test]
+	fn trend_lists_changed_languages_by_size_of_change() {
+		let old = report(100, &[("Rust", 60), ("Python", 30), ("TOML", 10)]);
+		let new = report(113, &[("Rust", 78), ("Python", 25), ("Go", 10)]);
+		assert_eq!(
+			render_trend(&old, &new),
+			"Lines: 100 -> 113 (+13)\n\tRust: +18\n\tGo: +10\n\tTOML: -10\n\tPython: -5\n"
+		);
+	}
+
+	#[test]
+	fn parse_renames_keeps_only_renamed_pairs() {
+		let output = "M\0src/lib.rs\0R087\0src/old.rs\0src/new.rs\0C100\0a.rs\0b.rs\0A\0src/added.rs\0";
+		assert_eq!(parse_renames(output), [("src/old.rs".to_owned(), "src/new.rs".to_owned())]);
+	}
+
+	#[test]
+	fn renamed_files_are_paired_instead_of_added_and_removed() {
+		let old = BTreeMap::from([("./src/old.rs", 10), ("./src/gone.rs", 4), ("./src/lib.rs", 7)]);
+		let new = BTreeMap::from([("./src/new.rs", 12), ("./src/lib.rs", 9)]);
+		let renames = [("src/old.rs".to_owned(), "src/new.rs".to_owned())];
+		assert_eq!(
+			file_changes(&old, &new, &renames),
+			[
+				FileChange::Changed { path: "./src/lib.rs".to_owned(), old: 7, new: 9 },
+				FileChange::Renamed {
+					from: "./src/old.rs".to_owned(),
+					to: "./src/new.rs".to_owned(),
+					old: 10,
+					new: 12
+				},
+				FileChange::Removed { path: "./src/gone.rs".to_owned(), lines: 4 },
+			]
+		);
+		assert_eq!(file_changes(&old, &new, &[]).len(), 4);
+	}
+
+	#[test]
+	fn git_paths_match_whole_path_components() {
+		let paths = BTreeMap::from([("./mysrc/a.rs", 1)]);
+		assert_eq!(find_path(&paths, "src/a.rs"), None);
+		assert_eq!(find_path(&paths, "mysrc/a.rs"), Some("./mysrc/a.rs"));
+	}
+}
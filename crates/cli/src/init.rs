@@ -18,21 +18,114 @@ const DEFAULT_CONFIG_TEMPLATE: &str = "\
 # Respect .gitignore rules when scanning
 # respect_gitignore = true
 
+# When respecting .gitignore, also apply the global gitignore, .git/info/exclude and .ignore files
+# git_global = true
+# git_exclude = true
+# dot_ignore = true
+
+# Also apply ignore files found in directories above the analysis root
+# ignore_parents = true
+
 # Include hidden files and directories
 # include_hidden = false
 
 # Follow symbolic links
 # follow_symlinks = false
 
+# Don't descend into directories on a different filesystem than the analyzed path
+# one_file_system = false
+
 # Count generated files (lockfiles, minified assets) excluded by default
 # include_generated = false
 
+# Count dependency lockfiles while still skipping minified assets
+# include_lockfiles = false
+
+# File names treated as dependency lockfiles, skipped unless include_lockfiles or include_generated is set.
+# Setting this replaces the built-in list, which covers the lockfiles of most package managers
+# lockfile_patterns = [\"Cargo.lock\", \"package-lock.json\", \"yarn.lock\", \"poetry.lock\", \"go.sum\"]
+
+# Skip files whose name or content marks them as generated code
+# exclude_generated = false
+
+# Markers in the first 10 lines that identify generated files when exclude_generated is set
+# generated_markers = [\"@generated\", \"DO NOT EDIT\", \"Code generated by\"]
+
+# Filename globs that identify generated files when exclude_generated is set
+# generated_patterns = [\"*.pb.go\", \"*_pb2.py\", \"*_pb2_grpc.py\", \"*.g.dart\", \"*.freezed.dart\"]
+
 # Limit directory traversal depth
 # max_depth = 5
 
+# Only count files modified within this long ago (units: s, m, h, d, w)
+# modified_within = \"30d\"
+
+# Columns a tab advances to when measuring line length (1-16)
+# tab_width = 8
+
+# Count lines holding both code and a comment as mixed instead of code
+# split_mixed = false
+
+# Count docstrings (triple-quoted strings with no code before them) as \"comment\" or \"code\"
+# docstrings = \"comment\"
+
+# Count shebang lines as \"separate\" (their own category), \"code\", \"comment\", or \"ignore\" them entirely
+# shebang = \"separate\"
+
+# Also end lines at the Unicode line separators U+2028, U+2029 and NEL (U+0085)
+# unicode_line_breaks = false
+
+# Add a hash of each file's content to verbose JSON and template output
+# hash = false
+
+# Decode every file with this encoding instead of detecting UTF-8 or UTF-16, for trees in one legacy encoding
+# input_encoding = \"shift_jis\"
+
+# Count comment lines starting with one of these folding markers as code instead of comments
+# fold_markers = [\"#region\", \"#endregion\"]
+
+# Attribute Markdown fenced code blocks and HTML <script>/<style> bodies to their own language
+# embedded = false
+
+# Report test and non-test totals separately in the summary
+# split_tests = false
+
+# Report totals per file category, as set by the [[category]] tables
+# split_categories = false
+
+# Globs matched against file names and relative paths to classify test files when split_tests is set
+# test_patterns = [\"**/tests/**\", \"*_test.*\", \"test_*.*\", \"*.spec.*\"]
+
+# Report files in vendored directories as one separate bucket instead of under their languages
+# collapse_vendored = false
+
+# Globs matched against relative paths to classify vendored files when collapse_vendored is set
+# vendored_patterns = [\"**/vendor/**\", \"**/node_modules/**\", \"**/third_party/**\", \"**/.venv/**\"]
+
+# Assign languages from `codestats_language` keys in the .editorconfig at the analyzed path
+# editorconfig = false
+
+# Count binary files by size in a separate assets bucket instead of skipping them
+# include_assets = false
+
+# Print candidate language scores to stderr for files whose language is chosen by content
+# explain_detection = false
+
+# Minimum content score needed to pick among languages sharing an extension (comment match 50, keyword 10)
+# detection_threshold = 1
+
+# Skip a file as binary when more than this percentage of its sampled bytes are control characters (0-100)
+# binary_threshold = 20
+
+# Read at most this many files at once, for network filesystems where parallel reads thrash (unbounded when unset)
+# io_concurrency = 4
+
 # Glob patterns to exclude (can specify multiple)
 # exclude_patterns = [\"*.tmp\", \"node_modules/*\"]
 
+# Directory names whose subtrees are skipped without being read (can specify multiple)
+# exclude_dirs = [\"target\", \"node_modules\"]
+
 # Only analyze these languages (case-insensitive)
 # include_languages = []
 
@@ -42,25 +135,69 @@ const DEFAULT_CONFIG_TEMPLATE: &str = "\
 # Exit with non-zero status if any files are skipped due to errors
 # fail_on_error = false
 
+# Stop at the first unreadable file or directory instead of skipping it (no report is printed)
+# strict = false
+
+# Keep per-file counts here so unchanged files are not read again (relative to this file)
+# cache = \".codestats-cache\"
+
+# Binary thresholds for particular extensions, in place of binary_threshold
+# [analysis.binary_thresholds]
+# svg = 5
+
 [display]
-# Number formatting: plain, comma, underscore, space
+# Number formatting: plain, comma, underscore, space, indian (12,34,567)
 # number_style = \"plain\"
 
 # Size units: binary (KiB) or decimal (KB)
 # size_units = \"binary\"
 
+# Write sizes in bytes instead of scaling them to KiB, MiB, ...
+# size_raw = false
+
+# Decimals in scaled sizes (0-6); unset picks 2, 1 or 0 by magnitude
+# size_precision = 2
+
 # Decimal places for percentages (0-6)
 # precision = 1
 
-# Sort by: lines, code, comments, blanks, files, size, name
+# Percentage rounding: nearest, up (half-up), down (truncate)
+# rounding = \"nearest\"
+
+# Round line-type percentages together so each breakdown sums to exactly 100
+# balanced_percentages = false
+
+# Sort by: lines, code, comments, blanks, files, size, name, comment-pct, code-pct, ratio (code per comment line)
 # sort_by = \"lines\"
 
 # Sort direction: asc, desc
 # sort_direction = \"desc\"
 
-# Output format: human, json, json-compact, csv, tsv, markdown, html
+# Sort key and direction for per-file details in verbose output; unset, files are sorted like languages
+# file_sort_by = \"name\"
+# file_sort_direction = \"asc\"
+
+# Output format: human, json, json-compact, csv, tsv, markdown, html, paths, sqlite (sqlite needs --output-file)
 # output = \"human\"
 
+# Field separator for csv output
+# csv_delimiter = \",\"
+
+# End each record of paths output with a NUL byte instead of a newline, for xargs -0
+# null_terminated = false
+
+# Prefix each record of paths output with the file's language and a tab
+# path_languages = false
+
+# Tera template used instead of the output format (relative to this file)
+# template = \"report.tera\"
+
+# Name shown in reports instead of the analyzed path
+# root_label = \"myrepo\"
+
+# Template for the first line of the human overview ({files}, {lines}, {code}, {size} and {path})
+# summary_format = \"{path}: {code} code lines in {files} files\"
+
 # Indentation style: \"tab\" or a number 1-8 for spaces
 # indent = \"tab\"
 
@@ -72,6 +209,75 @@ const DEFAULT_CONFIG_TEMPLATE: &str = "\
 
 # Show a breakdown by directory instead of by language
 # by_dir = false
+
+# Number of leading directory levels to group by in the directory breakdown
+# dir_depth = 1
+
+# In verbose output, show only the last N components of each file path
+# path_depth = 3
+
+# Print the summary overview before the language breakdown
+# show_summary = true
+
+# List languages and display alias groups that have no files, with zero counts
+# show_empty = false
+
+# Report tab-indented vs space-indented lines for each language
+# show_indentation = false
+
+# Report line-comment vs block-comment lines for each language
+# show_comment_styles = false
+
+# Report how many files start with a byte-order mark
+# show_encodings = false
+
+# Also report sizes with CRLF line endings counted as LF
+# normalized_size = false
+
+# Draw a bar of each language's share of lines in human output when writing to a terminal
+# bars = false
+
+# Draw a sparkline of each language's file-size distribution in verbose human output
+# sparkline = false
+
+# Color human output: \"auto\" (terminal only, unless NO_COLOR is set), \"always\", or \"never\"
+# color = \"auto\"
+
+# Record when the report was generated in JSON, HTML and template output
+# timestamp = true
+
+# Embed these settings in JSON reports as a \"config\" object
+# embed_config = false
+
+# Merge the languages of each [[rollup]] table into one entry
+# aggregate_extensions = false
+
+# Report languages grouped by paradigm (systems, general, scripting, markup, config, data, other)
+# in place of display aliases and rollups
+# by_paradigm = false
+
+# Reports kept in the --track directory; older ones are removed after each run
+# track_history = 30
+
+# Report several languages under one name (repeat the table for more groups)
+# [[display_alias]]
+# name = \"Frontend JS\"
+# languages = [\"TypeScript\", \"JavaScript\"]
+
+# Languages merged by aggregate_extensions (replaces the default C/C++ rollup)
+# [[rollup]]
+# name = \"C/C++\"
+# languages = [\"C\", \"C++\", \"Objective-C\", \"Objective-C++\"]
+
+# File categories used by split_categories, tried top to bottom (replaces the default
+# generated, test, docs, config and source categories)
+# [[category]]
+# name = \"test\"
+# patterns = [\"**/tests/**\", \"*_test.*\"]
+#
+# [[category]]
+# name = \"source\"
+# patterns = [\"*\"]
 ";
 
 pub fn run_init(output: Option<PathBuf>, force: bool) -> Result<()> {
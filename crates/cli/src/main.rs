@@ -1,19 +1,36 @@
 #![warn(clippy::all, clippy::cargo, clippy::nursery, clippy::pedantic, clippy::perf)]
 #![deny(warnings)]
 
+mod blame;
 mod cli;
 mod completions;
 mod config_discovery;
+mod diff;
 mod init;
+mod run_stats;
+mod track;
+mod version;
 
-use std::io::{self, Write as _};
+use std::{
+	env,
+	fs::{self, File},
+	io::{self, BufWriter, IsTerminal as _, Write},
+	path::Path,
+	sync::Arc,
+	time::Instant,
+};
 
+#[cfg(not(feature = "template"))]
+use anyhow::bail;
 use anyhow::{Result, anyhow, ensure};
+use blame::BlameMatrix;
 use cli::{Cli, Commands};
+#[cfg(feature = "template")]
+use codestats::display::{Formatter, TemplateFormatter};
 use codestats::{
-	analysis::CodeAnalyzer,
-	config::{AnalyzerConfig, Config},
-	display::{ViewOptions, get_formatter},
+	analysis::{AnalysisResults, CodeAnalyzer},
+	config::{AnalyzerConfig, Config, paradigm_groups},
+	display::{FileRowStream, FormatterContext, OutputFormat, ReportRoot, ViewOptions, get_formatter},
 	langs,
 };
 use terminal_size::terminal_size;
@@ -21,27 +38,20 @@ use terminal_size::terminal_size;
 fn main() -> Result<()> {
 	let (cli, matches) = Cli::parse_with_matches();
 	if let Some(command) = cli.command {
-		match command {
-			Commands::Completions { shell } => {
-				shell.generate_completions()?;
-				return Ok(());
-			}
-			Commands::Langs => {
-				let mut stdout = io::stdout();
-				let terminal_width = terminal_size().map_or(80, |(w, _)| usize::from(w.0));
-				langs::print_all_languages(&mut stdout, terminal_width)?;
-				stdout.flush()?;
-				return Ok(());
-			}
-			Commands::Init { output, force } => {
-				init::run_init(output, force)?;
-				return Ok(());
-			}
-		}
+		return run_command(command);
 	}
 	let analyze = &cli.analyze;
-	let config = if let Some(ref config_path) = analyze.config {
-		Config::from_file(config_path)?
+	if analyze.print_config_path {
+		match config_discovery::find_config_file() {
+			Some(path) => println!("{}", path.display()),
+			None => println!("no config file found; using defaults"),
+		}
+		return Ok(());
+	}
+	let config = if !analyze.config.is_empty() {
+		Config::from_files(&analyze.config)?
+	} else if analyze.no_config {
+		Config::default()
 	} else {
 		config_discovery::load_default()?
 	};
@@ -50,16 +60,193 @@ fn main() -> Result<()> {
 	if config.path.is_file() {
 		ensure!(config.path.metadata().is_ok(), "Cannot read file metadata for `{}`", config.path.display());
 	}
-	let analyzer_config: AnalyzerConfig = (&config).into();
-	let analyzer = CodeAnalyzer::new(&config.path, analyzer_config);
-	let results = analyzer.analyze()?;
+	let mut analyzer_config: AnalyzerConfig = (&config).into();
+	analyzer_config.collect_file_details |= analyze.formats.iter().any(|format| format.lists_files()) || analyze.blame;
+	let stream = analyze.stream.then(|| stream_files(&config, analyze.output_file.as_deref())).transpose()?;
+	let mut analyzer = CodeAnalyzer::new(&config.path, analyzer_config);
+	if let Some(stream) = &stream {
+		analyzer = analyzer.with_file_sink(stream.clone());
+	}
+	let started = Instant::now();
+	let mut results = analyzer.analyze()?;
+	let elapsed = started.elapsed();
+	results.request_languages(&config.analysis.include_languages);
+	let blame = analyze.blame.then(|| blame::blame(&results, &config.path, config.analysis.docstrings)).transpose()?;
+	if config.display.by_paradigm {
+		results.apply_display_aliases(&paradigm_groups());
+	} else {
+		results.apply_display_aliases(&config.display_aliases);
+		if config.display.aggregate_extensions {
+			results.apply_display_aliases(&config.rollups);
+		}
+	}
 	let view_options: ViewOptions = (&config).into();
-	let formatter = get_formatter(config.display.output);
-	let mut stdout = io::stdout();
-	formatter.write_output(&results, &config.path, view_options, &mut stdout)?;
-	stdout.flush()?;
+	if let Some(track_dir) = &analyze.track {
+		track::track(track_dir, &results, &config, view_options)?;
+	}
+	if let Some(stream) = &stream {
+		stream.finish()?;
+	} else if let Some(output_dir) = &analyze.output_dir {
+		write_reports(&results, &config, view_options, &analyze.formats, output_dir)?;
+	} else {
+		write_single(&results, &config, view_options, analyze.output_file.as_deref(), blame.as_ref())?;
+	}
+	if analyze.stats {
+		run_stats::report(&results, elapsed, &FormatterContext::new(view_options))?;
+	}
+	if let Some(errors_json) = &analyze.errors_json {
+		write_skipped_files(&results, errors_json)?;
+	}
 	if config.analysis.fail_on_error && results.skipped_entries() > 0 {
 		return Err(anyhow!("Skipped {} entries due to errors", results.skipped_entries()));
 	}
 	Ok(())
 }
+
+/// Run a subcommand instead of analyzing a tree.
+fn run_command(command: Commands) -> Result<()> {
+	match command {
+		Commands::Completions { shell } => Ok(shell.generate_completions()?),
+		Commands::Langs { json, wrap } => {
+			let mut stdout = io::stdout();
+			if json {
+				langs::print_language_index(&mut stdout)?;
+			} else {
+				let width = wrap.unwrap_or_else(|| terminal_size().map_or(80, |(w, _)| usize::from(w.0)));
+				langs::print_all_languages(&mut stdout, width)?;
+			}
+			stdout.flush()?;
+			Ok(())
+		}
+		Commands::Init { output, force } => init::run_init(output, force),
+		Commands::Diff { old, new, detect_renames, repo } => {
+			diff::run_diff(&old, &new, detect_renames.as_deref(), &repo)
+		}
+		Commands::Version { json } => version::run_version(json),
+		Commands::ValidateLanguages { file } => {
+			let content = fs::read_to_string(&file).map_err(|e| anyhow!("Failed to read `{}`: {e}", file.display()))?;
+			let languages =
+				langs::parse_languages(&content).map_err(|e| anyhow!("`{}` is invalid: {e}", file.display()))?;
+			println!("`{}` is valid ({} languages)", file.display(), languages.len());
+			Ok(())
+		}
+		Commands::KeywordHits { file } => keyword_hits(&file),
+	}
+}
+
+/// Write the files skipped during the analysis to `path` as a JSON array of `{path, reason}` records, sorted by path.
+fn write_skipped_files(results: &AnalysisResults, path: &Path) -> Result<()> {
+	let mut skipped = results.skipped_files().to_vec();
+	skipped.sort_by(|a, b| a.path().cmp(b.path()).then_with(|| a.reason().cmp(b.reason())));
+	let file = File::create(path).map_err(|e| anyhow!("Failed to create `{}`: {e}", path.display()))?;
+	let mut writer = BufWriter::new(file);
+	serde_json::to_writer_pretty(&mut writer, &skipped)?;
+	writeln!(writer)?;
+	writer.flush()?;
+	Ok(())
+}
+
+/// The analyzed path reports describe, shown as the `--root-label` when one is given.
+fn report_root(config: &Config) -> ReportRoot<'_> {
+	ReportRoot::new(&config.path).with_label(config.display.root_label.as_deref())
+}
+
+/// The settings to embed in JSON reports, present only when `display.embed_config` is set.
+fn embedded_config(config: &Config) -> Result<Option<serde_json::Value>> {
+	config.display.embed_config.then(|| config.report_settings()).transpose()
+}
+
+/// Write the report in the configured format to `output_file`, or to stdout when there is none, followed by the
+/// `--blame` breakdown when there is one.
+fn write_single(
+	results: &AnalysisResults,
+	config: &Config,
+	mut view_options: ViewOptions,
+	output_file: Option<&Path>,
+	blame: Option<&BlameMatrix>,
+) -> Result<()> {
+	let formatter = match &config.display.template {
+		#[cfg(feature = "template")]
+		Some(template) => Formatter::Template(TemplateFormatter::from_file(template)?),
+		#[cfg(not(feature = "template"))]
+		Some(_) => bail!("Custom templates require codestats to be built with the `template` feature"),
+		None => get_formatter(config.display.output)
+			.with_csv_delimiter(config.display.csv_delimiter)
+			.with_null_terminator(config.display.null_terminated)
+			.with_path_languages(config.display.path_languages)
+			.with_summary_format(config.display.summary_format.clone())
+			.with_embedded_config(embedded_config(config)?),
+	};
+	let mut writer: Box<dyn Write> = match output_file {
+		Some(output_file) => Box::new(BufWriter::new(
+			File::create(output_file).map_err(|e| anyhow!("Failed to create `{}`: {e}", output_file.display()))?,
+		)),
+		None => Box::new(io::stdout()),
+	};
+	// Bars fall back to the plain percentages when piped, where block characters are just noise.
+	let is_terminal = output_file.is_none() && io::stdout().is_terminal();
+	if config.display.bars && is_terminal {
+		view_options.bar_width = Some(terminal_size().map_or(80, |(w, _)| usize::from(w.0)));
+	}
+	let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+	view_options.color = config.display.color.enabled(is_terminal, no_color);
+	formatter.write_output(results, report_root(config), view_options, &mut writer)?;
+	if let Some(blame) = blame {
+		blame.write(&mut writer, &FormatterContext::new(view_options))?;
+	}
+	writer.flush()?;
+	Ok(())
+}
+
+/// Start streaming file rows in the configured CSV or TSV format to `output_file`, or to stdout when there is none.
+fn stream_files(config: &Config, output_file: Option<&Path>) -> Result<Arc<FileRowStream>> {
+	let writer: Box<dyn Write + Send> = match output_file {
+		Some(output_file) => Box::new(BufWriter::new(
+			File::create(output_file).map_err(|e| anyhow!("Failed to create `{}`: {e}", output_file.display()))?,
+		)),
+		None => Box::new(BufWriter::new(io::stdout())),
+	};
+	let formatter = get_formatter(config.display.output).with_csv_delimiter(config.display.csv_delimiter);
+	Ok(Arc::new(formatter.stream_files(writer, config.into())?))
+}
+
+/// Write one `report.<ext>` file per format into `output_dir`, reusing the same analysis results.
+fn write_reports(
+	results: &AnalysisResults,
+	config: &Config,
+	view_options: ViewOptions,
+	formats: &[OutputFormat],
+	output_dir: &Path,
+) -> Result<()> {
+	fs::create_dir_all(output_dir)
+		.map_err(|e| anyhow!("Failed to create output directory `{}`: {e}", output_dir.display()))?;
+	for &format in formats {
+		let report_path = output_dir.join(format!("report.{}", format.extension()));
+		let file =
+			File::create(&report_path).map_err(|e| anyhow!("Failed to create `{}`: {e}", report_path.display()))?;
+		let mut writer = BufWriter::new(file);
+		let formatter = get_formatter(format)
+			.with_csv_delimiter(config.display.csv_delimiter)
+			.with_null_terminator(config.display.null_terminated)
+			.with_path_languages(config.display.path_languages)
+			.with_embedded_config(embedded_config(config)?);
+		formatter.write_output(results, report_root(config), view_options, &mut writer)?;
+		writer.flush()?;
+	}
+	Ok(())
+}
+
+/// Print, for each language `file`'s name could be, its detection score and the keywords that occur in the file.
+fn keyword_hits(file: &Path) -> Result<()> {
+	let bytes = fs::read(file).map_err(|e| anyhow!("Failed to read `{}`: {e}", file.display()))?;
+	let name = file.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+	let hits = langs::keyword_hits(&name, &String::from_utf8_lossy(&bytes));
+	ensure!(!hits.is_empty(), "No language matches the file name `{name}`");
+	for candidate in hits {
+		let keywords: Vec<String> =
+			candidate.keywords.iter().map(|(keyword, count)| format!("{keyword} x{count}")).collect();
+		let keywords = if keywords.is_empty() { "no keywords".to_owned() } else { keywords.join(", ") };
+		println!("{} (score {}): {keywords}", candidate.language.name, candidate.score);
+	}
+	Ok(())
+}
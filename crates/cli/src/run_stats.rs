@@ -0,0 +1,69 @@
+use std::{
+	fs,
+	io::{self, Write},
+	time::Duration,
+};
+
+use anyhow::Result;
+use codestats::{analysis::AnalysisResults, display::FormatterContext};
+
+/// Print how long the analysis took, its throughput and the peak memory of the process to stderr, for `--stats`.
+pub fn report(results: &AnalysisResults, elapsed: Duration, ctx: &FormatterContext) -> Result<()> {
+	let mut stderr = io::stderr().lock();
+	let seconds = elapsed.as_secs_f64();
+	writeln!(
+		stderr,
+		"Analyzed {} files ({} lines) in {seconds:.2}s: {} files/s, {} lines/s.",
+		ctx.number(results.total_files()),
+		ctx.number(results.total_lines()),
+		ctx.number(per_second(results.total_files(), seconds)),
+		ctx.number(per_second(results.total_lines(), seconds)),
+	)?;
+	match peak_rss() {
+		Some(bytes) => writeln!(stderr, "Peak memory: {}.", ctx.size(bytes))?,
+		None => writeln!(stderr, "Peak memory: unavailable on this platform.")?,
+	}
+	Ok(())
+}
+
+#[expect(
+	clippy::cast_possible_truncation,
+	clippy::cast_precision_loss,
+	clippy::cast_sign_loss,
+	reason = "throughput is display-only and never negative; rounding to a whole count is intended"
+)]
+fn per_second(count: u64, seconds: f64) -> u64 {
+	if seconds > 0.0 { (count as f64 / seconds).round() as u64 } else { count }
+}
+
+/// Peak resident set size of this process in bytes, or `None` where it cannot be read.
+///
+/// Read from the `VmHWM` line of `/proc/self/status`, so it is only available on Linux. It includes file pages mapped
+/// for large files, which the kernel can reclaim under memory pressure.
+fn peak_rss() -> Option<u64> {
+	parse_vm_hwm(&fs::read_to_string("/proc/self/status").ok()?)
+}
+
+fn parse_vm_hwm(status: &str) -> Option<u64> {
+	let line = status.lines().find_map(|line| line.strip_prefix("VmHWM:"))?;
+	let kib = line.trim().strip_suffix("kB")?.trim().parse::<u64>().ok()?;
+	kib.checked_mul(1024)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn vm_hwm_is_read_in_bytes() {
+		let status = "Name:\tcs\nVmPeak:\t  20000 kB\nVmHWM:\t    1536 kB\nVmRSS:\t    1024 kB\n";
+		assert_eq!(parse_vm_hwm(status), Some(1536 * 1024));
+		assert_eq!(parse_vm_hwm("Name:\tcs\n"), None);
+	}
+
+	#[test]
+	fn throughput_handles_instant_runs() {
+		assert_eq!(per_second(10, 0.5), 20);
+		assert_eq!(per_second(10, 0.0), 10);
+	}
+}
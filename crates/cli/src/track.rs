@@ -0,0 +1,58 @@
+use std::{
+	fs,
+	io::{self, Write},
+	path::{Path, PathBuf},
+	time::SystemTime,
+};
+
+use anyhow::{Context as _, Result};
+use codestats::{
+	analysis::AnalysisResults,
+	config::Config,
+	display::{OutputFormat, ViewOptions, formatting::rfc3339_utc, get_formatter},
+};
+
+use crate::{diff, report_root};
+
+const PREFIX: &str = "codestats-";
+const EXTENSION: &str = ".json";
+
+/// Save a JSON report of `results` into the `--track` directory `dir` and print to stderr how it moved since the
+/// previous saved report, then remove all but the `display.track_history` newest reports.
+///
+/// Reports are named after the time they were saved, e.g. `codestats-20240501T123000Z.json`, so they sort by age.
+/// A second run within the same second replaces the first one's report after comparing against it.
+pub fn track(dir: &Path, results: &AnalysisResults, config: &Config, view_options: ViewOptions) -> Result<()> {
+	let mut report = Vec::new();
+	get_formatter(OutputFormat::Json).write_output(results, report_root(config), view_options, &mut report)?;
+	fs::create_dir_all(dir).with_context(|| format!("Failed to create track directory `{}`", dir.display()))?;
+	if let Some(previous) = history(dir)?.last() {
+		let summary = diff::trend(previous, &String::from_utf8_lossy(&report))?;
+		let name = previous.file_name().unwrap_or_default().to_string_lossy();
+		let mut stderr = io::stderr();
+		write!(stderr, "Since {name}:\n{summary}")?;
+		stderr.flush()?;
+	}
+	let stamp: String = rfc3339_utc(SystemTime::now()).chars().filter(|c| !matches!(c, '-' | ':')).collect();
+	let path = dir.join(format!("{PREFIX}{stamp}{EXTENSION}"));
+	fs::write(&path, &report).with_context(|| format!("Failed to write `{}`", path.display()))?;
+	let saved = history(dir)?;
+	for old in &saved[..saved.len().saturating_sub(config.display.track_history.max(1))] {
+		fs::remove_file(old).with_context(|| format!("Failed to remove `{}`", old.display()))?;
+	}
+	Ok(())
+}
+
+/// The reports saved in `dir`, oldest first.
+fn history(dir: &Path) -> Result<Vec<PathBuf>> {
+	let mut reports = Vec::new();
+	for entry in fs::read_dir(dir).with_context(|| format!("Failed to read track directory `{}`", dir.display()))? {
+		let path = entry?.path();
+		let name = path.file_name().unwrap_or_default().to_string_lossy();
+		if name.starts_with(PREFIX) && name.ends_with(EXTENSION) && path.is_file() {
+			reports.push(path);
+		}
+	}
+	reports.sort();
+	Ok(reports)
+}
@@ -0,0 +1,36 @@
+use std::io::{self, Write};
+
+use anyhow::Result;
+use codestats::langs::LANGUAGES;
+use serde_json::json;
+
+use crate::cli::output_format_values;
+
+/// Git commit the binary was built from, when built inside a git checkout.
+const GIT_COMMIT: Option<&str> = option_env!("CODESTATS_GIT_COMMIT");
+
+/// Print the version and capabilities of this build, as JSON when `json` is set.
+pub fn run_version(json: bool) -> Result<()> {
+	let mut stdout = io::stdout();
+	let formats = output_format_values();
+	if json {
+		let info = json!({
+			"version": env!("CARGO_PKG_VERSION"),
+			"git_commit": GIT_COMMIT,
+			"languages": LANGUAGES.len(),
+			"output_formats": formats,
+			"template": cfg!(feature = "template"),
+		});
+		serde_json::to_writer_pretty(&mut stdout, &info)?;
+		writeln!(stdout)?;
+	} else {
+		match GIT_COMMIT {
+			Some(commit) => writeln!(stdout, "codestats {} ({commit})", env!("CARGO_PKG_VERSION"))?,
+			None => writeln!(stdout, "codestats {}", env!("CARGO_PKG_VERSION"))?,
+		}
+		writeln!(stdout, "Languages: {}", LANGUAGES.len())?;
+		writeln!(stdout, "Output formats: {}", formats.join(", "))?;
+	}
+	stdout.flush()?;
+	Ok(())
+}
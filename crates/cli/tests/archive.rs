@@ -0,0 +1,46 @@
+#![cfg(feature = "archive")]
+
+use std::{
+	fs,
+	io::{Cursor, Write as _},
+	path::PathBuf,
+};
+
+mod common;
+
+fn write_zip(path: &PathBuf, members: &[(&str, &str)]) {
+	let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+	let options = zip::write::SimpleFileOptions::default();
+	for (name, contents) in members {
+		writer.start_file(*name, options).expect("start zip member");
+		writer.write_all(contents.as_bytes()).expect("write zip member");
+	}
+	fs::write(path, writer.finish().expect("finish zip").into_inner()).expect("write zip file");
+}
+
+#[test]
+fn zip_members_are_analyzed_by_name() {
+	let root_dir = common::temp_dir("zip");
+	let root = root_dir.path();
+	let archive = root.join("release.zip");
+	write_zip(
+		&archive,
+		&[
+			("release/src/main.rs", "fn main() {}\n// entry\n"),
+			("release/app.py", "print('hi')\n"),
+			("release/.github/ci.py", "x = 1\n"),
+			("release/logo.png", "\u{0}\u{0}PNG"),
+		],
+	);
+	let archive_arg = archive.to_str().expect("Non-UTF-8 temp dir");
+	let all = common::summary(archive_arg, &[]);
+	assert_eq!(all["total_files"], 2);
+	assert_eq!(all["total_lines"], 3);
+	assert_eq!(all["total_comment_lines"], 1);
+	assert_eq!(all["largest_file_by_lines"]["path"], "release/src/main.rs");
+	let excluded = common::summary(archive_arg, &["-e", "*.py"]);
+	assert_eq!(excluded["total_files"], 1);
+	let filtered = common::summary(archive_arg, &["-L", "Python"]);
+	assert_eq!(filtered["total_files"], 1);
+	assert_eq!(filtered["largest_file_by_lines"]["path"], "release/app.py");
+}
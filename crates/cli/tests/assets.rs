@@ -0,0 +1,18 @@
+use std::fs;
+
+mod common;
+
+#[test]
+fn include_assets_records_binary_files_apart_from_source_totals() {
+	let root_dir = common::temp_dir("bucket");
+	let root = root_dir.path();
+	fs::write(root.join("main.rs"), "fn main() {}\n").expect("write source file");
+	fs::write(root.join("logo.png"), [0x89, b'P', b'N', b'G', 0, 0, 0, 0x0d, 0, 0]).expect("write binary file");
+	let root_arg = root.to_str().expect("Non-UTF-8 temp dir");
+	let without = common::summary(root_arg, &[]);
+	assert!(without.get("assets").is_none(), "assets should only be reported when requested");
+	let with = common::summary(root_arg, &["--include-assets"]);
+	assert_eq!(with["total_files"], 1);
+	assert_eq!(with["assets"]["files"], 1);
+	assert_eq!(with["assets"]["size"], 10);
+}
@@ -0,0 +1,67 @@
+use std::{
+	env, fs,
+	path::{Path, PathBuf},
+	process::Command,
+};
+
+mod common;
+
+/// Markup with six control bytes in 95, about 6%: under the default binary threshold but over a strict one.
+const MARKUP: &[u8] =
+	b"<svg xmlns=\"http://www.w3.org/2000/svg\">\n<!-- \x01\x02\x03\x04\x05\x06 -->\n<rect width=\"10\" \
+	height=\"10\"/>\n</svg>\n";
+
+/// Create `src/logo.svg` and `src/data.xml` under `root`, both holding [`MARKUP`], and return `src`.
+fn markup_tree(root: &Path) -> PathBuf {
+	let src = root.join("src");
+	fs::create_dir_all(&src).expect("create src");
+	fs::write(src.join("logo.svg"), MARKUP).expect("write logo.svg");
+	fs::write(src.join("data.xml"), MARKUP).expect("write data.xml");
+	src
+}
+
+/// Run codestats on `path` and return the names of the languages it counted.
+fn languages(path: &Path, extra: &[&str]) -> Vec<String> {
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.arg(path)
+		.args(["-o", "json", "--sort-by", "name", "--sort-direction", "asc"])
+		.args(extra)
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	let report: serde_json::Value = serde_json::from_slice(&output.stdout).expect("valid JSON");
+	report["languages"]
+		.as_array()
+		.expect("languages")
+		.iter()
+		.map(|lang| lang["name"].as_str().expect("name").to_owned())
+		.collect()
+}
+
+#[test]
+fn binary_threshold_tightens_the_binary_check() {
+	let root_dir = common::temp_dir("flag");
+	let root = root_dir.path();
+	let src = markup_tree(root);
+	assert_eq!(languages(&src, &["--no-config"]), ["SVG", "XML"]);
+	assert!(languages(&src, &["--no-config", "--binary-threshold", "5"]).is_empty());
+}
+
+#[test]
+fn extension_thresholds_override_the_global_one() {
+	let root_dir = common::temp_dir("extension");
+	let root = root_dir.path();
+	let src = markup_tree(root);
+	let config = root.join("codestats.toml");
+	fs::write(&config, "[analysis.binary_thresholds]\nSVG = 5\n").expect("write config");
+	assert_eq!(languages(&src, &["--config", config.to_str().expect("utf-8 path")]), ["XML"]);
+}
+
+#[test]
+fn binary_threshold_above_100_is_rejected() {
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.args(["--no-config", "--binary-threshold", "101", "."])
+		.output()
+		.expect("run codestats");
+	assert!(!output.status.success());
+}
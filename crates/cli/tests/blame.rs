@@ -0,0 +1,68 @@
+use std::{env, fs, path::Path, process::Command};
+
+mod common;
+
+fn git(repo: &Path, args: &[&str]) {
+	let output = Command::new("git")
+		.arg("-C")
+		.arg(repo)
+		.args(["-c", "user.email=dev@example.com", "-c", "commit.gpgsign=false"])
+		.args(args)
+		.output()
+		.expect("run git");
+	assert!(output.status.success(), "git {args:?}: {}", String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn blame_breaks_down_lines_by_author_and_language() {
+	let root_dir = common::temp_dir("repo");
+	let root = root_dir.path();
+	git(root, &["init", "-q"]);
+	fs::write(root.join("main.rs"), "// entry point\nfn main() {}\n").expect("write main.rs");
+	git(root, &["add", "."]);
+	git(root, &["-c", "user.name=Ada", "commit", "-q", "-m", "first"]);
+	fs::write(root.join("main.rs"), "// entry point\nfn main() {}\n\nfn helper() {}\n").expect("write main.rs");
+	fs::write(root.join("tool.py"), "print(1)\n").expect("write tool.py");
+	git(root, &["add", "."]);
+	git(root, &["-c", "user.name=Grace", "commit", "-q", "-m", "second"]);
+	fs::write(root.join("untracked.rs"), "fn untracked() {}\n").expect("write untracked.rs");
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.arg(root)
+		.args(["--no-config", "--blame"])
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	let stdout = String::from_utf8_lossy(&output.stdout);
+	let breakdown = &stdout[stdout.find("Author breakdown:\n").expect("author breakdown")..];
+	assert_eq!(
+		breakdown,
+		"Author breakdown:\nGrace:\n\tLines: 3 lines (60.0% of total).\n\t\tRust: 2 lines: 1 code, 0 comments, 1 \
+		 blank.\n\t\tPython: 1 line: 1 code, 0 comments, 0 blanks.\nAda:\n\tLines: 2 lines (40.0% of \
+		 total).\n\t\tRust: 2 lines: 1 code, 1 comment, 0 blanks.\n"
+	);
+}
+
+#[test]
+fn blame_outside_a_git_repository_fails() {
+	let root_dir = common::temp_dir("plain");
+	let root = root_dir.path();
+	fs::write(root.join("main.rs"), "fn main() {}\n").expect("write main.rs");
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.arg(root)
+		.args(["--no-config", "--blame"])
+		.env("GIT_CEILING_DIRECTORIES", env::temp_dir())
+		.output()
+		.expect("run codestats");
+	assert!(!output.status.success());
+	assert!(String::from_utf8_lossy(&output.stderr).contains("inside a git work tree"));
+}
+
+#[test]
+fn blame_requires_human_output() {
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.args(["--no-config", "--blame", "-o", "json", "."])
+		.output()
+		.expect("run codestats");
+	assert!(!output.status.success());
+	assert!(String::from_utf8_lossy(&output.stderr).contains("--blame only applies to human output"));
+}
@@ -0,0 +1,32 @@
+use std::{env, fs, process::Command};
+
+mod common;
+
+#[test]
+fn languages_are_reported_by_paradigm() {
+	let root_dir = common::temp_dir("groups");
+	let root = root_dir.path();
+	fs::write(root.join("main.rs"), "fn main() {}\nfn helper() {}\n").expect("write main.rs");
+	fs::write(root.join("lib.c"), "#include <stdio.h>\nint main(void) { return 0; }\n").expect("write lib.c");
+	fs::write(root.join("tool.py"), "print(1)\n").expect("write tool.py");
+	fs::write(root.join("Main.hs"), "main = pure ()\n").expect("write Main.hs");
+	fs::write(root.join("hello.factor"), "USE: io\n").expect("write hello.factor");
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.arg(root)
+		.args(["--no-config", "--by-paradigm", "-o", "json"])
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	let report: serde_json::Value = serde_json::from_slice(&output.stdout).expect("valid JSON");
+	let mut groups: Vec<(String, u64)> = report["languages"]
+		.as_array()
+		.expect("languages")
+		.iter()
+		.map(|lang| (lang["name"].as_str().expect("name").to_owned(), lang["files"].as_u64().expect("files")))
+		.collect();
+	groups.sort();
+	assert_eq!(
+		groups,
+		[("general".to_owned(), 1), ("other".to_owned(), 1), ("scripting".to_owned(), 1), ("systems".to_owned(), 2)]
+	);
+}
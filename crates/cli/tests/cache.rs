@@ -0,0 +1,50 @@
+use std::{
+	env,
+	fs::{self, File},
+	path::Path,
+	process::Command,
+	time::{Duration, SystemTime},
+};
+
+mod common;
+
+fn code_lines(root: &Path) -> u64 {
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.args([root.to_str().expect("Non-UTF-8 temp dir"), "--no-config", "--cache", "-o", "json"])
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("valid JSON output");
+	json["summary"]["total_code_lines"].as_u64().expect("total_code_lines in summary")
+}
+
+fn rewrite(path: &Path, contents: &str, modified: SystemTime) {
+	fs::write(path, contents).expect("write source file");
+	File::options().write(true).open(path).and_then(|file| file.set_modified(modified)).expect("set mtime");
+}
+
+#[test]
+fn cache_reuses_counts_until_mtime_or_size_changes() {
+	let root_dir = common::temp_dir("reuse");
+	let root = root_dir.path();
+	let source = root.join("lib.rs");
+	let modified = SystemTime::now() - Duration::from_secs(60);
+	rewrite(&source, "fn a() {}\n", modified);
+	assert_eq!(code_lines(root), 1);
+	assert!(root.join(".codestats-cache").is_file(), "cache should be written into the analyzed path");
+	// Same size and mtime: the stale cached count is reused without reading the file.
+	rewrite(&source, "// a() {}\n", modified);
+	assert_eq!(code_lines(root), 1);
+	rewrite(&source, "// a() {}\n", modified + Duration::from_secs(1));
+	assert_eq!(code_lines(root), 0);
+}
+
+#[test]
+fn cache_for_a_single_file_is_written_beside_it() {
+	let root_dir = common::temp_dir("single_file");
+	let source = root_dir.path().join("lib.rs");
+	rewrite(&source, "fn a() {}\n", SystemTime::now() - Duration::from_secs(60));
+	assert_eq!(code_lines(&source), 1);
+	assert!(root_dir.path().join(".codestats-cache").is_file(), "cache should be written beside the analyzed file");
+	assert_eq!(code_lines(&source), 1);
+}
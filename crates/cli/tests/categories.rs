@@ -0,0 +1,81 @@
+use std::{env, fs, path::Path, process::Command};
+
+mod common;
+
+fn write(path: &Path, contents: &str) {
+	fs::create_dir_all(path.parent().expect("file has a parent")).expect("create parent dir");
+	fs::write(path, contents).expect("write file");
+}
+
+fn run_json(tree: &Path, extra: &[&str]) -> serde_json::Value {
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.arg(tree)
+		.args(["--split-categories", "-v", "-o", "json"])
+		.args(extra)
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	serde_json::from_slice(&output.stdout).expect("valid JSON")
+}
+
+fn file_categories(report: &serde_json::Value) -> Vec<(String, String)> {
+	let mut files: Vec<_> = report["languages"]
+		.as_array()
+		.expect("languages array")
+		.iter()
+		.flat_map(|language| language["files_detail"].as_array().expect("files detail").iter())
+		.map(|file| (file["path"].as_str().unwrap().to_owned(), file["category"].as_str().unwrap_or("").to_owned()))
+		.collect();
+	files.sort();
+	files
+}
+
+#[test]
+fn default_categories_split_source_tests_and_docs() {
+	let root_dir = common::temp_dir("defaults");
+	let root = root_dir.path();
+	write(&root.join("src/main.rs"), "fn main() {}\n\nfn helper() {}\n");
+	write(&root.join("tests/cli.rs"), "#[test]\nfn runs() {}\n");
+	write(&root.join("docs/guide.md"), "# Guide\n\nSome text.\n");
+	let report = run_json(root, &["--no-config"]);
+	let categories: Vec<_> = report["summary"]["categories"]
+		.as_array()
+		.expect("categories array")
+		.iter()
+		.map(|category| (category["name"].as_str().unwrap(), category["files"].as_u64().unwrap()))
+		.collect();
+	assert_eq!(categories, [("docs", 1), ("source", 1), ("test", 1)]);
+	assert_eq!(
+		file_categories(&report),
+		[
+			("docs/guide.md".into(), "docs".into()),
+			("src/main.rs".into(), "source".into()),
+			("tests/cli.rs".into(), "test".into())
+		]
+	);
+}
+
+#[test]
+fn configured_categories_apply_top_to_bottom() {
+	let root_dir = common::temp_dir("configured");
+	let root = root_dir.path();
+	let tree = root.join("tree");
+	write(&tree.join("tests/fixtures/input.rs"), "fn fixture() {}\n");
+	write(&tree.join("tests/cli.rs"), "#[test]\nfn runs() {}\n");
+	write(&tree.join("src/lib.rs"), "pub fn lib() {}\n");
+	let config = root.join("codestats.toml");
+	write(
+		&config,
+		"[[category]]\nname = \"fixtures\"\npatterns = [\"**/fixtures/**\"]\n\n[[category]]\nname = \"test\"\npatterns = [\"**/tests/**\"]\n",
+	);
+	let report = run_json(&tree, &["--config", config.to_str().expect("UTF-8 path")]);
+	assert_eq!(
+		file_categories(&report),
+		[
+			("src/lib.rs".into(), String::new()),
+			("tests/cli.rs".into(), "test".into()),
+			("tests/fixtures/input.rs".into(), "fixtures".into())
+		]
+	);
+	assert_eq!(report["summary"]["total_files"], 3);
+}
@@ -0,0 +1,23 @@
+//! Helpers shared by the integration tests.
+
+use std::process::Command;
+
+use tempfile::TempDir;
+
+/// Create an empty directory under the system temp dir, removed when the returned guard drops, even if the test fails.
+pub fn temp_dir(label: &str) -> TempDir {
+	tempfile::Builder::new().prefix(&format!("codestats_{label}_")).tempdir().expect("create temp dir")
+}
+
+/// Run codestats on `root` with JSON output and `extra` arguments, returning the report's `summary` object.
+#[allow(dead_code, reason = "each test binary compiles this module, but only some of them read summaries")]
+pub fn summary(root: &str, extra: &[&str]) -> serde_json::Value {
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.args([root, "--no-config", "-o", "json"])
+		.args(extra)
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("valid JSON output");
+	json["summary"].clone()
+}
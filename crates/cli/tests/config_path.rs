@@ -0,0 +1,27 @@
+use std::{env, fs, path::Path, process::Command};
+
+mod common;
+
+fn print_config_path(dir: &Path) -> String {
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.arg("--print-config-path")
+		.current_dir(dir)
+		.env("HOME", dir)
+		.env("XDG_CONFIG_HOME", dir.join("xdg"))
+		.env("APPDATA", dir.join("appdata"))
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	String::from_utf8(output.stdout).expect("UTF-8 output").trim_end().to_owned()
+}
+
+#[test]
+fn print_config_path_names_the_discovered_file() {
+	let root_dir = common::temp_dir("discovery");
+	let root = root_dir.path();
+	assert_eq!(print_config_path(root), "no config file found; using defaults");
+	fs::write(root.join("codestats.toml"), "").expect("write config");
+	assert_eq!(print_config_path(root), "codestats.toml");
+	fs::write(root.join(".codestats.toml"), "").expect("write config");
+	assert_eq!(print_config_path(root), ".codestats.toml");
+}
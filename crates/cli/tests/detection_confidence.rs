@@ -0,0 +1,47 @@
+use std::{env, fs, path::Path, process::Command};
+
+mod common;
+
+/// Run codestats on `root` and return every listed file's `(path, detection_confidence)`, sorted by path.
+fn confidences(root: &Path, extra: &[&str]) -> Vec<(String, serde_json::Value)> {
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.arg(root)
+		.args(["--no-config", "-o", "json"])
+		.args(extra)
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	let report: serde_json::Value = serde_json::from_slice(&output.stdout).expect("valid JSON");
+	let mut files: Vec<_> = report["languages"]
+		.as_array()
+		.expect("languages")
+		.iter()
+		.flat_map(|lang| lang["files_detail"].as_array().into_iter().flatten())
+		.map(|file| {
+			let confidence = file.get("detection_confidence").cloned().unwrap_or_default();
+			(file["path"].as_str().expect("path").to_owned(), confidence)
+		})
+		.collect();
+	files.sort_by(|a, b| a.0.cmp(&b.0));
+	files
+}
+
+#[test]
+fn verbose_files_report_what_decided_their_language() {
+	let root_dir = common::temp_dir("verbose");
+	let root = root_dir.path();
+	fs::write(root.join("main.rs"), "fn main() {}\n").expect("write main.rs");
+	fs::write(root.join("deploy"), "#!/usr/bin/env python3\nprint(1)\n").expect("write deploy");
+	fs::write(root.join("Foo.m"), "@interface Foo : NSObject\n@end\n").expect("write Foo.m");
+	fs::write(root.join("empty.rs"), "").expect("write empty.rs");
+	let cache = root.join("counts.cache");
+	let cache = format!("--cache={}", cache.to_str().expect("utf-8 path"));
+	let expected = [
+		("Foo.m".to_owned(), serde_json::json!(30)),
+		("deploy".to_owned(), serde_json::json!("shebang")),
+		("empty.rs".to_owned(), serde_json::json!("filename")),
+		("main.rs".to_owned(), serde_json::json!("filename")),
+	];
+	assert_eq!(confidences(root, &["-v", &cache]), expected);
+	assert_eq!(confidences(root, &["-v", &cache]), expected, "cached files keep their confidence");
+}
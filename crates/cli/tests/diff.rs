@@ -0,0 +1,74 @@
+use std::{env, fs, path::Path, process::Command};
+
+mod common;
+
+fn write_report(root: &Path, report: &Path) {
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.args([root.to_str().expect("Non-UTF-8 temp dir"), "--no-config", "-v", "-o", "json"])
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	fs::write(report, output.stdout).expect("write report");
+}
+
+#[test]
+fn diff_reports_total_language_and_file_deltas() {
+	let root_dir = common::temp_dir("src");
+	let root = root_dir.path();
+	let reports_dir = common::temp_dir("reports");
+	let reports = reports_dir.path();
+	let (old, new) = (reports.join("old.json"), reports.join("new.json"));
+	fs::write(root.join("main.rs"), "fn main() {}\n").expect("write source file");
+	write_report(root, &old);
+	fs::write(root.join("main.rs"), "fn main() {\n\tlib();\n}\n").expect("write source file");
+	fs::write(root.join("app.py"), "print('hi')\n").expect("write source file");
+	write_report(root, &new);
+	let output = Command::new(env!("CARGO_BIN_EXE_cs")).arg("diff").args([&old, &new]).output().expect("run diff");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	let stdout = String::from_utf8_lossy(&output.stdout);
+	assert!(stdout.contains("lines: 1 -> 4 (+3)"), "{stdout}");
+	assert!(stdout.contains("Rust: +2 lines, +2 code"), "{stdout}");
+	assert!(stdout.contains("main.rs: +2"), "{stdout}");
+	assert!(stdout.contains("app.py (added): +1"), "{stdout}");
+}
+
+fn git(repo: &Path, args: &[&str]) {
+	let output = Command::new("git")
+		.arg("-C")
+		.arg(repo)
+		.args(["-c", "user.email=dev@example.com", "-c", "user.name=Dev", "-c", "commit.gpgsign=false"])
+		.args(args)
+		.output()
+		.expect("run git");
+	assert!(output.status.success(), "git {args:?}: {}", String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn renames_are_detected_when_a_subdirectory_was_analyzed() {
+	let root_dir = common::temp_dir("renames_repo");
+	let root = root_dir.path();
+	let sub = root.join("sub");
+	fs::create_dir(&sub).expect("create subdirectory");
+	let reports_dir = common::temp_dir("renames_reports");
+	let reports = reports_dir.path();
+	let (old, new) = (reports.join("old.json"), reports.join("new.json"));
+	git(root, &["init", "-q"]);
+	fs::write(sub.join("old.rs"), "fn a() {}\nfn b() {}\nfn c() {}\n").expect("write source file");
+	git(root, &["add", "."]);
+	git(root, &["commit", "-q", "-m", "first"]);
+	write_report(&sub, &old);
+	git(root, &["mv", "sub/old.rs", "sub/new.rs"]);
+	git(root, &["commit", "-q", "-m", "rename"]);
+	write_report(&sub, &new);
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.arg("diff")
+		.args([&old, &new])
+		.args(["--detect-renames", "HEAD~1..HEAD", "--repo"])
+		.arg(&sub)
+		.output()
+		.expect("run diff");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	let stdout = String::from_utf8_lossy(&output.stdout);
+	assert!(stdout.contains("old.rs -> new.rs: +0"), "{stdout}");
+	assert!(!stdout.contains("(added)"), "{stdout}");
+}
@@ -0,0 +1,30 @@
+use std::{path::Path, process::Command};
+
+/// Run codestats on the Python docstring fixture and return its `(code, comment)` line counts.
+fn docstring_counts(extra: &[&str]) -> (u64, u64) {
+	let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/python/docstrings.py");
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.arg(&fixture)
+		.args(["--no-config", "-o", "json"])
+		.args(extra)
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("valid JSON output");
+	let summary = &json["summary"];
+	(
+		summary["total_code_lines"].as_u64().expect("code lines"),
+		summary["total_comment_lines"].as_u64().expect("comment lines"),
+	)
+}
+
+#[test]
+fn docstrings_count_as_comments_by_default() {
+	assert_eq!(docstring_counts(&[]), (7, 6));
+	assert_eq!(docstring_counts(&["--docstrings", "comment"]), (7, 6));
+}
+
+#[test]
+fn docstrings_count_as_code_when_requested() {
+	assert_eq!(docstring_counts(&["--docstrings", "code"]), (12, 1));
+}
@@ -0,0 +1,40 @@
+use std::{path::Path, process::Command};
+
+fn run(extra: &[&str]) -> std::process::Output {
+	let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/bash");
+	Command::new(env!("CARGO_BIN_EXE_cs"))
+		.arg(&fixtures)
+		.args(["--no-config"])
+		.args(extra)
+		.output()
+		.expect("run codestats")
+}
+
+fn report(extra: &[&str]) -> serde_json::Value {
+	let output = run(extra);
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	serde_json::from_slice(&output.stdout).expect("valid JSON output")
+}
+
+#[test]
+fn embedded_config_records_the_effective_settings() {
+	let json = report(&["-o", "json", "--embed-config", "--sort-by", "name", "--exclude-dir", "target"]);
+	let config = &json["config"];
+	assert_eq!(config["display"]["sort_by"], "name");
+	assert_eq!(config["analysis"]["exclude_dirs"], serde_json::json!(["target"]));
+	assert!(config.get("path").is_none(), "the analyzed path is machine-specific: {config}");
+	assert!(report(&["-o", "json"]).get("config").is_none());
+}
+
+#[test]
+fn embedded_config_uses_the_root_label_as_path() {
+	let json = report(&["-o", "json-compact", "--embed-config", "--root-label", "myrepo"]);
+	assert_eq!(json["config"]["path"], "myrepo");
+}
+
+#[test]
+fn embed_config_requires_json_output() {
+	let output = run(&["--embed-config"]);
+	assert!(!output.status.success());
+	assert!(String::from_utf8_lossy(&output.stderr).contains("--embed-config only applies to JSON output"));
+}
@@ -0,0 +1,49 @@
+use std::{fs, path::Path, process::Command};
+
+use serde_json::Value;
+
+mod common;
+
+/// Run codestats with `--embedded` on `root` and return the `lines` of each reported language by name.
+fn language_lines(root: &Path, extra: &[&str]) -> Vec<(String, u64)> {
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.arg(root)
+		.args(["--no-config", "-o", "json", "--embedded"])
+		.args(extra)
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	let json: Value = serde_json::from_slice(&output.stdout).expect("valid JSON output");
+	json["languages"]
+		.as_array()
+		.expect("languages array")
+		.iter()
+		.map(|language| (language["name"].as_str().unwrap().to_owned(), language["lines"].as_u64().unwrap()))
+		.collect()
+}
+
+fn markdown_with_rust_fence(label: &str) -> tempfile::TempDir {
+	let root_dir = common::temp_dir(label);
+	fs::write(root_dir.path().join("README.md"), "# Title\n\n```rust\nfn main() {}\n```\n").unwrap();
+	root_dir
+}
+
+#[test]
+fn embedded_regions_are_counted_as_their_own_language() {
+	let root_dir = markdown_with_rust_fence("embedded_split");
+	assert_eq!(language_lines(root_dir.path(), &[]), [("Markdown".to_owned(), 4), ("Rust".to_owned(), 1)]);
+}
+
+#[test]
+fn filtered_out_embedded_languages_stay_with_the_host_file() {
+	let root_dir = markdown_with_rust_fence("embedded_include");
+	assert_eq!(language_lines(root_dir.path(), &["-L", "Markdown"]), [("Markdown".to_owned(), 5)]);
+	let root_dir = markdown_with_rust_fence("embedded_exclude");
+	assert_eq!(language_lines(root_dir.path(), &["--exclude-lang", "rust"]), [("Markdown".to_owned(), 5)]);
+}
+
+#[test]
+fn embedded_languages_of_a_filtered_out_host_are_not_counted() {
+	let root_dir = markdown_with_rust_fence("embedded_host");
+	assert_eq!(language_lines(root_dir.path(), &["-L", "Rust"]), []);
+}
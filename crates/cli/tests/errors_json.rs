@@ -0,0 +1,31 @@
+use std::{env, fs, process::Command};
+
+mod common;
+
+#[test]
+fn errors_json_lists_skipped_files_with_reasons() {
+	let root_dir = common::temp_dir("tree");
+	let root = root_dir.path();
+	let tree = root.join("tree");
+	fs::create_dir_all(&tree).expect("create tree");
+	fs::write(tree.join("main.rs"), "fn main() {}\n").expect("write source file");
+	fs::write(tree.join("logo.png"), [0x89, b'P', b'N', b'G', 0, 0, 0, 0]).expect("write binary file");
+	fs::write(tree.join("notes.unknownext"), "plain words\n").expect("write unrecognized file");
+	let errors_json = root.join("skipped.json");
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.arg(&tree)
+		.args(["--no-config", "-o", "json", "--errors-json"])
+		.arg(&errors_json)
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	let skipped: serde_json::Value =
+		serde_json::from_str(&fs::read_to_string(&errors_json).expect("read errors JSON")).expect("valid JSON");
+	assert_eq!(
+		skipped,
+		serde_json::json!([
+			{ "path": "logo.png", "reason": "binary" },
+			{ "path": "notes.unknownext", "reason": "unrecognized" },
+		])
+	);
+}
@@ -0,0 +1,57 @@
+use std::{
+	env, fs,
+	path::Path,
+	process::{Command, Output},
+};
+
+mod common;
+
+fn run(root: &Path, extra: &[&str]) -> Output {
+	Command::new(env!("CARGO_BIN_EXE_cs"))
+		.arg(root)
+		.args(["--no-config", "-o", "json", "-v"])
+		.args(extra)
+		.output()
+		.expect("run codestats")
+}
+
+#[test]
+fn excluded_directories_are_pruned_at_any_depth() {
+	let root_dir = common::temp_dir("depth");
+	let root = root_dir.path();
+	for dir in ["src", "target/debug", "crates/cli/target", "targets"] {
+		fs::create_dir_all(root.join(dir)).expect("create fixture dir");
+	}
+	fs::write(root.join("src/main.rs"), "fn main() {}\n").expect("write main.rs");
+	fs::write(root.join("target/debug/build.rs"), "fn build() {}\n").expect("write build output");
+	fs::write(root.join("crates/cli/target/gen.rs"), "fn generated() {}\n").expect("write nested build output");
+	fs::write(root.join("targets/keep.rs"), "fn keep() {}\n").expect("write similarly named dir");
+	let output = run(root, &["--exclude-dir", "target"]);
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	let report: serde_json::Value = serde_json::from_slice(&output.stdout).expect("valid JSON");
+	let mut paths: Vec<_> = report["languages"][0]["files_detail"]
+		.as_array()
+		.expect("files detail")
+		.iter()
+		.map(|file| file["path"].as_str().expect("path").to_owned())
+		.collect();
+	paths.sort();
+	assert_eq!(paths, ["src/main.rs", "targets/keep.rs"]);
+}
+
+/// A dangling symlink fails a strict walk that follows links as soon as it is read, so a successful run shows the
+/// pruned directory was never opened.
+#[cfg(unix)]
+#[test]
+fn excluded_directories_are_never_read() {
+	let root_dir = common::temp_dir("unread");
+	let root = root_dir.path();
+	fs::create_dir_all(root.join("node_modules/pkg")).expect("create fixture dir");
+	fs::write(root.join("main.rs"), "fn main() {}\n").expect("write main.rs");
+	std::os::unix::fs::symlink(root.join("missing"), root.join("node_modules/pkg/broken"))
+		.expect("create dangling symlink");
+	let strict = ["--strict", "--symlinks"];
+	assert!(!run(root, &strict).status.success(), "the dangling symlink should fail an unpruned strict walk");
+	let output = run(root, &[&strict[..], &["--exclude-dir", "node_modules"]].concat());
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+}
@@ -0,0 +1,36 @@
+use std::{env, fs, path::Path, process::Command};
+
+mod common;
+
+/// Run codestats on `path` and return each detected language with its `(files, lines)`.
+fn languages(path: &Path) -> Vec<(String, u64, u64)> {
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.arg(path)
+		.args(["--no-config", "-o", "json"])
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	let report: serde_json::Value = serde_json::from_slice(&output.stdout).expect("valid JSON");
+	report["languages"]
+		.as_array()
+		.expect("languages")
+		.iter()
+		.map(|lang| {
+			let name = lang["name"].as_str().expect("name").to_owned();
+			(name, lang["files"].as_u64().expect("files"), lang["lines"].as_u64().expect("lines"))
+		})
+		.collect()
+}
+
+#[test]
+fn extensionless_scripts_are_detected_by_shebang_inside_directories() {
+	let root_dir = common::temp_dir("bin");
+	let root = root_dir.path();
+	fs::create_dir_all(root.join("bin")).expect("create bin");
+	fs::write(root.join("bin/deploy"), "#!/usr/bin/env python3\nimport sys\nprint(sys.argv)\n").expect("write deploy");
+	fs::write(root.join("bin/setup"), "#!/bin/sh\nset -e\n").expect("write setup");
+	fs::write(root.join("bin/placeholder"), "").expect("write placeholder");
+	assert_eq!(languages(root), [("Python".to_owned(), 1, 3), ("Shell".to_owned(), 1, 2)]);
+	assert_eq!(languages(&root.join("bin/deploy")), [("Python".to_owned(), 1, 3)]);
+	assert!(languages(&root.join("bin/placeholder")).is_empty());
+}
@@ -1,5 +1,5 @@
 #!/usr/bin/env bash
-# expect: total=8 code=4 comment=2 blank=1 shebang=1
+# expect: total=8 code=4 comment=2 blank=1 shebang=1 language=Bash
 # small bash script
 echo "hello"
 
@@ -0,0 +1,4 @@
+#!/usr/bin/env fish
+# expect: total=4 code=2 comment=1 blank=0 shebang=1 language=Fish
+set -l name world
+echo "Hello, $name!"
@@ -0,0 +1,12 @@
+! expect: total=12 code=7 comment=4 blank=1 shebang=0
+C     fixed-form fortran: C in column 1 starts a comment
+      PROGRAM SUMUP
+      INTEGER A, B, C
+c     the marker letter is also a variable name
+      A = 1
+      B = 2
+      CALL ADD(A, B, C)
+
+! note
+      PRINT *, 'Done!'
+      END
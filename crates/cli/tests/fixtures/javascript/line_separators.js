@@ -0,0 +1,5 @@
+// expect: total=5 code=2 comment=2 blank=1 shebang=0
+// statements below are separated by U+2028 and U+2029
+
+var a = 1; var b = 2; // after a paragraph separator
+const s = "x";
@@ -0,0 +1,8 @@
+(* expect: total=8 code=5 comment=2 blank=1 shebang=0 *)
+(*comment*)
+let product = List.fold_left ( * ) 1 [2; 3]
+
+let mul = (*)
+let square x = mul x x
+let cube x = x * square x
+let () = print_int (cube product)
@@ -0,0 +1,16 @@
+# expect: total=16 code=7 comment=6 blank=3 shebang=0
+"""Module docstring."""
+import textwrap
+
+
+def greet(name):
+	"""
+	Say hello to `name`.
+	"""
+	message = """
+	Hello, {name}!
+	"""
+	return textwrap.dedent(message).format(name=name)
+
+'''Another docstring-style string.'''
+print(greet("world"))  # trailing comment
@@ -0,0 +1,11 @@
+// expect: total=11 code=8 comment=3 blank=0 shebang=0
+// comment markers inside string literals are not comments
+fn main() {
+	let url = "http://example.com";
+	let open = "/* not a comment";
+	let quote = "\"/*";
+	let raw = r#"/* "raw" */"#;
+	let c = '"'; let d = "/*";
+	println!("{url}{open}{quote}{raw}{c}{d}");
+}
+// done
@@ -0,0 +1,4 @@
+#!/bin/sh
+# expect: total=4 code=2 comment=1 blank=0 shebang=1 language=Shell
+set -eu
+exec "$@"
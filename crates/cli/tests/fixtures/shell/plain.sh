@@ -0,0 +1,2 @@
+# expect: total=2 code=1 comment=1 blank=0 shebang=0 language=Shell
+echo "no shebang"
@@ -0,0 +1,6 @@
+// expect: total=6 code=3 comment=2 blank=1 shebang=0
+// VtgJISŕۑꂽ{̃RǵAGR[fBOw肷ƐŐA܂B
+
+fn main() {
+	println!("ɂ");
+}
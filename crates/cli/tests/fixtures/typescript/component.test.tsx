@@ -0,0 +1,8 @@
+// expect: total=8 code=4 comment=2 blank=2 shebang=0 language=TypeScript
+// compound test suffix fixture
+
+import { render } from "./render";
+
+test("renders", () => {
+	expect(render(<div />)).toBeTruthy();
+});
@@ -0,0 +1,3 @@
+#!/usr/bin/env zsh
+# expect: total=3 code=1 comment=1 blank=0 shebang=1 language=ZSH
+print -P "%F{green}ok%f"
@@ -0,0 +1,78 @@
+use std::{env, fs, path::Path, process::Command};
+
+mod common;
+
+/// Run codestats on `root` and return every listed file's `(path, hash)`, sorted by path.
+fn file_hashes(root: &Path, extra: &[&str]) -> Vec<(String, Option<String>)> {
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.arg(root)
+		.args(["--no-config", "-v", "-o", "json"])
+		.args(extra)
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	let report: serde_json::Value = serde_json::from_slice(&output.stdout).expect("valid JSON");
+	let mut files: Vec<_> = report["languages"]
+		.as_array()
+		.expect("languages")
+		.iter()
+		.flat_map(|lang| lang["files_detail"].as_array().expect("files_detail"))
+		.map(|file| {
+			let hash = file.get("hash").map(|hash| hash.as_str().expect("hash string").to_owned());
+			(file["path"].as_str().expect("path").to_owned(), hash)
+		})
+		.collect();
+	files.sort();
+	files
+}
+
+#[test]
+fn hashes_are_only_listed_when_requested() {
+	let root_dir = common::temp_dir("absent");
+	let root = root_dir.path();
+	fs::write(root.join("main.rs"), "fn main() {}\n").expect("write main.rs");
+	assert_eq!(file_hashes(root, &[]), [("main.rs".to_owned(), None)]);
+}
+
+#[test]
+fn hashes_follow_the_content() {
+	let root_dir = common::temp_dir("content");
+	let root = root_dir.path();
+	fs::write(root.join("a.rs"), "fn a() {}\n").expect("write a.rs");
+	fs::write(root.join("b.rs"), "fn a() {}\n").expect("write b.rs");
+	fs::write(root.join("empty.py"), "").expect("write empty.py");
+	fs::write(root.join("wide.py"), b"\xff\xfex\0=\x001\0\n\0").expect("write wide.py");
+	let before = file_hashes(root, &["--hash"]);
+	assert_eq!(before[0].1, before[1].1, "same content, same hash: {before:?}");
+	assert_eq!(before[2], ("empty.py".to_owned(), Some("cbf29ce484222325".to_owned())));
+	assert_eq!(before[3].1.as_deref().map(str::len), Some(16));
+	fs::write(root.join("b.rs"), "fn b() {}\n").expect("write b.rs");
+	let after = file_hashes(root, &["--hash"]);
+	assert_eq!(after[0], before[0]);
+	assert_ne!(after[1], before[1]);
+}
+
+#[test]
+fn hashes_cover_content_past_the_line_length_cap() {
+	let root_dir = common::temp_dir("long_line");
+	let root = root_dir.path();
+	let line = "x".repeat(1_500_000);
+	fs::write(root.join("a.js"), format!("{line}a")).expect("write a.js");
+	fs::write(root.join("b.js"), format!("{line}b")).expect("write b.js");
+	let hashes = file_hashes(root, &["--hash"]);
+	assert!(hashes[0].1.is_some());
+	assert_ne!(hashes[0].1, hashes[1].1, "{hashes:?}");
+}
+
+#[test]
+fn cached_files_keep_their_hash() {
+	let root_dir = common::temp_dir("cache");
+	let root = root_dir.path();
+	fs::write(root.join("main.rs"), "fn main() {}\n").expect("write main.rs");
+	let cache = root.join("counts.cache");
+	let cache = cache.to_str().expect("utf-8 path");
+	let fresh = file_hashes(root, &["--hash", &format!("--cache={cache}")]);
+	let replayed = file_hashes(root, &["--hash", &format!("--cache={cache}")]);
+	assert!(fresh[0].1.is_some());
+	assert_eq!(fresh, replayed);
+}
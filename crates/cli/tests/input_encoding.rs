@@ -0,0 +1,41 @@
+use std::{path::Path, process::Command};
+
+use serde_json::Value;
+
+/// Longest line, in columns, of the Shift-JIS fixture: its comment line holds 59 characters in 112 bytes.
+const LONGEST_LINE: u64 = 59;
+
+fn max_line_length(extra: &[&str]) -> u64 {
+	let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/shift_jis/greeting.rs");
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.arg(&fixture)
+		.args(["--no-config", "-o", "json", "-v"])
+		.args(extra)
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	let report: Value = serde_json::from_slice(&output.stdout).expect("valid JSON");
+	assert_eq!(report["languages"][0]["name"], "Rust");
+	report["languages"][0]["max_line_length"].as_u64().expect("max_line_length")
+}
+
+#[test]
+fn forced_encoding_decodes_shift_jis_characters() {
+	assert_eq!(max_line_length(&["--input-encoding", "shift_jis"]), LONGEST_LINE);
+	assert_eq!(max_line_length(&["--input-encoding", "SJIS"]), LONGEST_LINE);
+}
+
+#[test]
+fn detected_encoding_misreads_shift_jis() {
+	assert!(max_line_length(&[]) > LONGEST_LINE);
+}
+
+#[test]
+fn unknown_encoding_labels_are_rejected() {
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.args(["--no-config", "--input-encoding", "klingon", "."])
+		.output()
+		.expect("run codestats");
+	assert!(!output.status.success());
+	assert!(output.stdout.is_empty());
+}
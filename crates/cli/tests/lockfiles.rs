@@ -0,0 +1,18 @@
+use std::fs;
+
+mod common;
+
+#[test]
+fn include_lockfiles_counts_lockfiles_but_not_minified_assets() {
+	let root_dir = common::temp_dir("include");
+	let root = root_dir.path();
+	fs::write(root.join("main.rs"), "fn main() {}\n").expect("write source file");
+	fs::write(root.join("package-lock.json"), "{\n  \"lockfileVersion\": 3\n}\n").expect("write lockfile");
+	fs::write(root.join("app.min.js"), "var a=1;\n").expect("write minified file");
+	let root_arg = root.to_str().expect("Non-UTF-8 temp dir");
+	assert_eq!(common::summary(root_arg, &[])["total_files"], 1);
+	let with_lockfiles = common::summary(root_arg, &["--include-lockfiles"]);
+	assert_eq!(with_lockfiles["total_files"], 2);
+	assert_eq!(with_lockfiles["total_lines"], 4);
+	assert_eq!(common::summary(root_arg, &["--include-generated"])["total_files"], 3);
+}
@@ -0,0 +1,46 @@
+use std::{env, fs, path::Path, process::Command};
+
+mod common;
+
+/// Create `top.rs`, `a/mid.rs` and `a/b/deep.rs` under `root`.
+fn nested_tree(root: &Path) {
+	fs::create_dir_all(root.join("a/b")).expect("create nested dirs");
+	fs::write(root.join("top.rs"), "fn top() {}\n").expect("write top.rs");
+	fs::write(root.join("a/mid.rs"), "fn mid() {}\n").expect("write mid.rs");
+	fs::write(root.join("a/b/deep.rs"), "fn deep() {}\n").expect("write deep.rs");
+}
+
+fn counted_files(root: &Path, extra: &[&str]) -> u64 {
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.arg(root)
+		.args(["-o", "json"])
+		.args(extra)
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	let report: serde_json::Value = serde_json::from_slice(&output.stdout).expect("valid JSON");
+	report["summary"]["total_files"].as_u64().expect("total_files")
+}
+
+#[test]
+fn max_depth_leaves_out_files_below_the_limit() {
+	let root_dir = common::temp_dir("flag");
+	let root = root_dir.path();
+	nested_tree(root);
+	assert_eq!(counted_files(root, &["--no-config"]), 3);
+	assert_eq!(counted_files(root, &["--no-config", "--max-depth", "1"]), 1);
+	assert_eq!(counted_files(root, &["--no-config", "--max-depth", "2"]), 2);
+	// Depth 0 keeps only the given path itself: nothing inside a directory, but a single file still counts.
+	assert_eq!(counted_files(root, &["--no-config", "--max-depth", "0"]), 0);
+	assert_eq!(counted_files(&root.join("top.rs"), &["--no-config", "--max-depth", "0"]), 1);
+}
+
+#[test]
+fn max_depth_can_be_set_in_the_config_file() {
+	let root_dir = common::temp_dir("config");
+	let root = root_dir.path();
+	nested_tree(&root.join("src"));
+	let config = root.join("codestats.toml");
+	fs::write(&config, "[analysis]\nmax_depth = 2\n").expect("write config");
+	assert_eq!(counted_files(&root.join("src"), &["--config", config.to_str().expect("utf-8 path")]), 2);
+}
@@ -0,0 +1,27 @@
+use std::{
+	env,
+	fs::{self, File},
+	process::Command,
+	time::{Duration, SystemTime},
+};
+
+mod common;
+
+#[test]
+fn modified_within_skips_files_older_than_the_window() {
+	let root_dir = common::temp_dir("window");
+	let root = root_dir.path();
+	fs::write(root.join("fresh.rs"), "fn fresh() {}\n").expect("write fresh file");
+	let stale = root.join("stale.rs");
+	fs::write(&stale, "fn stale() {}\nfn older() {}\n").expect("write stale file");
+	let old = SystemTime::now() - Duration::from_secs(3 * 86_400);
+	File::options().write(true).open(&stale).and_then(|file| file.set_modified(old)).expect("set mtime");
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.args([root.to_str().expect("Non-UTF-8 temp dir"), "--no-config", "--modified-within", "1d", "-o", "json"])
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("valid JSON output");
+	assert_eq!(json["summary"]["total_files"], 1);
+	assert_eq!(json["summary"]["total_code_lines"], 1);
+}
@@ -0,0 +1,17 @@
+use std::fs;
+
+mod common;
+
+#[test]
+fn normalized_size_counts_crlf_line_endings_as_lf() {
+	let root_dir = common::temp_dir("crlf");
+	let root = root_dir.path();
+	fs::write(root.join("main.rs"), "fn main() {\r\n}\r\n").expect("write CRLF file");
+	fs::write(root.join("lib.rs"), "pub fn f() {}\n").expect("write LF file");
+	let root_arg = root.to_str().expect("Non-UTF-8 temp dir");
+	let without = common::summary(root_arg, &[]);
+	assert!(without.get("normalized_size").is_none(), "normalized size should only be reported when requested");
+	let with = common::summary(root_arg, &["--normalized-size"]);
+	assert_eq!(with["total_size"], 30);
+	assert_eq!(with["normalized_size"], 28);
+}
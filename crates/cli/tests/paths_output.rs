@@ -0,0 +1,77 @@
+use std::{fs, process::Command};
+
+use tempfile::TempDir;
+
+mod common;
+
+fn fixture(label: &str) -> TempDir {
+	let root = common::temp_dir(label);
+	fs::create_dir_all(root.path().join("src")).expect("create src");
+	fs::write(root.path().join("src/main.rs"), "fn main() {}\n").expect("write main.rs");
+	fs::write(root.path().join("build.py"), "print('hi')\n").expect("write build.py");
+	root
+}
+
+#[test]
+fn paths_output_lists_files_without_verbose() {
+	let root = fixture("lines");
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.arg(root.path())
+		.args(["--no-config", "-o", "paths"])
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	assert_eq!(output.stdout, b"build.py\nsrc/main.rs\n");
+}
+
+#[test]
+fn path_languages_prefix_each_path() {
+	let root = fixture("languages");
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.arg(root.path())
+		.args(["--no-config", "-o", "paths", "--path-languages"])
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	assert_eq!(output.stdout, b"Python\tbuild.py\nRust\tsrc/main.rs\n");
+}
+
+#[test]
+fn null_ends_each_path_with_nul() {
+	let root = fixture("null");
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.arg(root.path())
+		.args(["--no-config", "-o", "paths", "--null"])
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	assert_eq!(output.stdout, b"build.py\0src/main.rs\0");
+}
+
+#[test]
+fn null_is_rejected_for_other_formats() {
+	let root = fixture("rejected");
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.arg(root.path())
+		.args(["--no-config", "-o", "json", "--null"])
+		.output()
+		.expect("run codestats");
+	assert!(!output.status.success());
+	assert!(String::from_utf8_lossy(&output.stderr).contains("--null only applies to paths output"));
+}
+
+#[cfg(unix)]
+#[test]
+fn non_utf8_names_are_written_unchanged() {
+	use std::{ffi::OsStr, os::unix::ffi::OsStrExt as _};
+
+	let root = common::temp_dir("raw");
+	fs::write(root.path().join(OsStr::from_bytes(b"caf\xe9.rs")), "fn main() {}\n").expect("write non-UTF-8 name");
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.arg(root.path())
+		.args(["--no-config", "-o", "paths", "--null"])
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	assert_eq!(output.stdout, b"caf\xe9.rs\0");
+}
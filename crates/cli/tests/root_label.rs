@@ -0,0 +1,44 @@
+use std::{fs, path::Path, process::Command};
+
+mod common;
+
+#[test]
+fn root_label_replaces_the_analysis_path_but_not_the_walk_root() {
+	let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/bash");
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.args([
+			fixtures.to_str().expect("Non-UTF-8 fixture path"),
+			"--no-config",
+			"--root-label",
+			"myrepo",
+			"-o",
+			"json",
+		])
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("valid JSON output");
+	assert_eq!(json["analysis_path"], "myrepo");
+	assert!(json["summary"]["total_files"].as_u64().is_some_and(|files| files > 0), "fixtures should still be walked");
+}
+
+#[test]
+fn root_label_does_not_change_directory_buckets() {
+	let root_dir = common::temp_dir("root_label_dirs");
+	let root = root_dir.path();
+	fs::create_dir(root.join("src")).unwrap();
+	fs::write(root.join("src/lib.rs"), "fn a() {}\n").unwrap();
+	fs::write(root.join("build.rs"), "fn main() {}\n").unwrap();
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.arg(root)
+		.args(["--no-config", "--root-label", "src", "-D", "-o", "json"])
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("valid JSON output");
+	assert_eq!(json["analysis_path"], "src");
+	let mut dirs: Vec<&str> =
+		json["directories"].as_array().unwrap().iter().map(|dir| dir["path"].as_str().unwrap()).collect();
+	dirs.sort_unstable();
+	assert_eq!(dirs, ["(root)", "src"]);
+}
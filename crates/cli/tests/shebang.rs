@@ -0,0 +1,43 @@
+use std::{path::Path, process::Command};
+
+/// Run codestats on the Bash shebang fixture and return its `(total, code, comment, shebang)` line counts.
+fn shebang_counts(extra: &[&str]) -> (u64, u64, u64, u64) {
+	let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/bash/shebang.sh");
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.arg(&fixture)
+		.args(["--no-config", "-o", "json"])
+		.args(extra)
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("valid JSON output");
+	let summary = &json["summary"];
+	let count = |field: &str| summary[field].as_u64().expect(field);
+	(count("total_lines"), count("total_code_lines"), count("total_comment_lines"), count("total_shebang_lines"))
+}
+
+#[test]
+fn shebangs_are_counted_separately_by_default() {
+	assert_eq!(shebang_counts(&[]), (8, 4, 2, 1));
+	assert_eq!(shebang_counts(&["--shebang", "separate"]), (8, 4, 2, 1));
+}
+
+#[test]
+fn shebangs_can_be_merged_into_code_or_comments() {
+	assert_eq!(shebang_counts(&["--shebang", "code"]), (8, 5, 2, 0));
+	assert_eq!(shebang_counts(&["--shebang", "comment"]), (8, 4, 3, 0));
+}
+
+#[test]
+fn ignored_shebangs_leave_the_totals() {
+	assert_eq!(shebang_counts(&["--shebang", "ignore"]), (7, 4, 2, 0));
+}
+
+#[test]
+fn unknown_shebang_mode_is_rejected() {
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.args(["--no-config", "--shebang", "drop", "."])
+		.output()
+		.expect("run codestats");
+	assert!(!output.status.success());
+}
@@ -0,0 +1,29 @@
+use std::{env, fs, process::Command};
+
+mod common;
+
+fn language_names(root: &str, extra: &[&str]) -> Vec<String> {
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.args([root, "--no-config", "-o", "json", "--lang", "Rust", "--lang", "Go"])
+		.args(extra)
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("valid JSON output");
+	json["languages"]
+		.as_array()
+		.expect("languages array")
+		.iter()
+		.map(|lang| lang["name"].as_str().expect("language name").to_owned())
+		.collect()
+}
+
+#[test]
+fn requested_languages_without_files_are_listed_only_with_show_empty() {
+	let root_dir = common::temp_dir("lang");
+	let root = root_dir.path();
+	fs::write(root.join("main.rs"), "fn main() {}\n").expect("write source file");
+	let root_arg = root.to_str().expect("Non-UTF-8 temp dir");
+	assert_eq!(language_names(root_arg, &[]), ["Rust"]);
+	assert_eq!(language_names(root_arg, &["--show-empty"]), ["Rust", "Go"]);
+}
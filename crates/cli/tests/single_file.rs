@@ -0,0 +1,21 @@
+use std::{fs, process::Command};
+
+mod common;
+
+#[test]
+fn a_file_analyzed_on_its_own_is_reported_by_name() {
+	let root_dir = common::temp_dir("single_file");
+	let source = root_dir.path().join("page.tpl");
+	fs::write(root_dir.path().join(".editorconfig"), "[*.tpl]\ncodestats_language = HTML\n").unwrap();
+	fs::write(&source, "<p>hi</p>\n").unwrap();
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.arg(&source)
+		.args(["--no-config", "--editorconfig", "-v", "-o", "json"])
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("valid JSON output");
+	assert_eq!(json["summary"]["largest_file_by_lines"]["path"], "page.tpl");
+	assert_eq!(json["languages"][0]["name"], "HTML");
+	assert_eq!(json["languages"][0]["files_detail"][0]["path"], "page.tpl");
+}
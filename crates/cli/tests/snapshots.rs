@@ -0,0 +1,71 @@
+//! Golden-file tests pinning the rendering of each text output format.
+//!
+//! Every format is run over the fixed tree under `tests/snapshots/tree` and compared with the checked-in file next to
+//! it. Run with `UPDATE_SNAPSHOTS=1` to rewrite the expected files after an intended output change.
+
+use std::{env, fs, path::Path, process::Command};
+
+/// Output formats checked against a snapshot, with the extension of their expected file.
+const FORMATS: &[(&str, &str)] = &[
+	("human", "txt"),
+	("json", "json"),
+	("json-compact", "json"),
+	("csv", "csv"),
+	("tsv", "tsv"),
+	#[cfg(feature = "markdown")]
+	("markdown", "md"),
+	#[cfg(feature = "html")]
+	("html", "html"),
+];
+
+/// Render the snapshot tree as `format`, with the analyzed path replaced by a fixed label.
+fn render(tree: &Path, format: &str) -> String {
+	let tree_arg = tree.to_str().expect("Non-UTF-8 snapshot tree path");
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.args([
+			tree_arg,
+			"--no-config",
+			"--root-label",
+			"tree",
+			"--color",
+			"never",
+			"--no-timestamp",
+			"-v",
+			"-o",
+			format,
+		])
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "{format} failed\nstderr: {}", String::from_utf8_lossy(&output.stderr));
+	let rendered = String::from_utf8(output.stdout).expect("text output is UTF-8");
+	// Paths should already be relative to the root, but keep a stray absolute one from making the snapshot
+	// machine-specific.
+	// The version is replaced too, so releases do not churn the snapshots.
+	rendered.replace(tree_arg, "tree").replace(env!("CARGO_PKG_VERSION"), "VERSION").replace("\r\n", "\n")
+}
+
+#[test]
+fn output_formats_match_snapshots() {
+	let snapshots = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots");
+	let tree = snapshots.join("tree");
+	let update = env::var_os("UPDATE_SNAPSHOTS").is_some();
+	let mut mismatches = Vec::new();
+	for &(format, extension) in FORMATS {
+		let actual = render(&tree, format);
+		let path = snapshots.join(format!("{format}.{extension}"));
+		if update {
+			fs::write(&path, &actual).unwrap_or_else(|err| panic!("Failed to write {}: {err}", path.display()));
+			continue;
+		}
+		let expected = fs::read_to_string(&path)
+			.unwrap_or_else(|err| panic!("Failed to read {} (run with UPDATE_SNAPSHOTS=1): {err}", path.display()));
+		if actual != expected.replace("\r\n", "\n") {
+			mismatches.push(format!("--- {}\n+++ {format} output\n{actual}", path.display()));
+		}
+	}
+	assert!(
+		mismatches.is_empty(),
+		"output differs from snapshots (rerun with UPDATE_SNAPSHOTS=1 if intended):\n{}",
+		mismatches.join("\n")
+	);
+}
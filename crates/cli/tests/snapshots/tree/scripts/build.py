@@ -0,0 +1,7 @@
+#!/usr/bin/env python3
+"""Build helper for the snapshot fixture."""
+
+import sys
+
+# Exit with the number of arguments.
+sys.exit(len(sys.argv) - 1)
@@ -0,0 +1,9 @@
+//! Entry point for the snapshot fixture.
+
+/* Block comment
+   spanning lines. */
+fn main() {
+	let greeting = "hello"; // trailing note
+
+	println!("{greeting}");
+}
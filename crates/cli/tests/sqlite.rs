@@ -0,0 +1,56 @@
+#![cfg(feature = "sqlite")]
+
+use std::{env, fs, process::Command};
+
+use rusqlite::Connection;
+
+mod common;
+
+#[test]
+fn sqlite_output_is_written_to_the_output_file() {
+	let root_dir = common::temp_dir("file");
+	let root = root_dir.path();
+	fs::write(root.join("main.rs"), "fn main() {}\n").expect("write source file");
+	let database = root.join("stats.db");
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.args([root.to_str().expect("Non-UTF-8 temp dir"), "--no-config", "-o", "sqlite", "--output-file"])
+		.arg(&database)
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	assert!(output.stdout.is_empty());
+	let bytes = fs::read(&database).expect("read database");
+	assert!(bytes.starts_with(b"SQLite format 3\0"), "not a SQLite database");
+}
+
+#[test]
+fn sqlite_output_to_stdout_is_rejected() {
+	let root_dir = common::temp_dir("stdout");
+	let root = root_dir.path();
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.args([root.to_str().expect("Non-UTF-8 temp dir"), "--no-config", "-o", "sqlite"])
+		.output()
+		.expect("run codestats");
+	assert!(!output.status.success());
+	assert!(String::from_utf8_lossy(&output.stderr).contains("--output-file"));
+	assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn sqlite_output_lists_files_without_verbose() {
+	let root_dir = common::temp_dir("files");
+	let root = root_dir.path();
+	fs::write(root.join("main.rs"), "fn main() {}\n").expect("write source file");
+	fs::write(root.join("lib.rs"), "pub fn a() {}\n").expect("write source file");
+	let out_dir = common::temp_dir("files_db");
+	let database = out_dir.path().join("stats.db");
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.args([root.to_str().expect("Non-UTF-8 temp dir"), "--no-config", "-o", "sqlite", "--output-file"])
+		.arg(&database)
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	let conn = Connection::open(&database).expect("open database");
+	let files: u64 = conn.query_row("SELECT COUNT(*) FROM files", [], |row| row.get(0)).expect("count files");
+	assert_eq!(files, 2);
+}
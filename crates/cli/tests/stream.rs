@@ -0,0 +1,55 @@
+use std::{env, fs, path::Path, process::Command};
+
+mod common;
+
+fn codestats(root: &Path, args: &[&str]) -> std::process::Output {
+	Command::new(env!("CARGO_BIN_EXE_cs")).arg(root).arg("--no-config").args(args).output().expect("run codestats")
+}
+
+#[test]
+fn stream_writes_header_then_one_row_per_file() {
+	let root_dir = common::temp_dir("csv");
+	let root = root_dir.path();
+	fs::write(root.join("main.rs"), "fn main() {}\n\n// done\n").expect("write main.rs");
+	fs::write(root.join("app.py"), "print(1)\n").expect("write app.py");
+	fs::write(root.join("empty.rs"), "").expect("write empty.rs");
+	let output = codestats(root, &["-v", "-o", "csv", "--stream"]);
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	let stdout = String::from_utf8(output.stdout).expect("utf-8 output");
+	let mut lines = stdout.lines();
+	assert_eq!(
+		lines.next(),
+		Some("language,file_path,total_lines,code_lines,comment_lines,blank_lines,shebang_lines,size,size_human")
+	);
+	let mut rows: Vec<_> = lines.collect();
+	rows.sort_unstable();
+	assert_eq!(
+		rows,
+		["Python,app.py,1,1,0,0,0,9,9 B", "Rust,empty.rs,0,0,0,0,0,0,0 B", "Rust,main.rs,3,1,1,1,0,22,22 B"]
+	);
+}
+
+#[test]
+fn stream_writes_tsv_to_output_file() {
+	let root_dir = common::temp_dir("tsv");
+	let root = root_dir.path();
+	let src = root.join("src");
+	fs::create_dir_all(&src).expect("create src");
+	fs::write(src.join("main.rs"), "fn main() {}\n").expect("write main.rs");
+	let report = root.join("files.tsv");
+	let output = codestats(&src, &["-v", "-o", "tsv", "--stream", "--output-file", report.to_str().expect("utf-8")]);
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	assert!(output.stdout.is_empty());
+	let written = fs::read_to_string(&report).expect("read report");
+	assert_eq!(written.lines().nth(1), Some("Rust\tmain.rs\t1\t1\t0\t0\t0\t13\t13 B"));
+}
+
+#[test]
+fn stream_needs_verbose_csv_or_tsv() {
+	let root_dir = common::temp_dir("rejected");
+	let root = root_dir.path();
+	for args in [&["-o", "csv", "--stream"][..], &["-v", "-o", "json", "--stream"]] {
+		let output = codestats(root, args);
+		assert!(!output.status.success(), "{args:?} should be rejected");
+	}
+}
@@ -0,0 +1,28 @@
+#![cfg(unix)]
+
+use std::{env, fs, os::unix::fs::symlink, process::Command};
+
+mod common;
+
+#[test]
+fn strict_stops_at_an_unreadable_entry_where_fail_on_error_reports_first() {
+	let root_dir = common::temp_dir("dangling");
+	let root = root_dir.path();
+	fs::write(root.join("main.rs"), "fn main() {}\n").expect("write source file");
+	symlink(root.join("missing.rs"), root.join("dangling.rs")).expect("create dangling symlink");
+	let run = |flag: &str| {
+		Command::new(env!("CARGO_BIN_EXE_cs"))
+			.arg(root)
+			.args(["--no-config", "--symlinks", "-o", "json", flag])
+			.output()
+			.expect("run codestats")
+	};
+	let lenient = run("--fail-on-error");
+	assert!(!lenient.status.success());
+	assert!(!lenient.stdout.is_empty(), "--fail-on-error should still print the report");
+	let strict = run("--strict");
+	assert!(!strict.status.success());
+	assert!(strict.stdout.is_empty(), "--strict should stop before the report");
+	let stderr = String::from_utf8_lossy(&strict.stderr);
+	assert!(stderr.contains("dangling.rs"), "stderr: {stderr}");
+}
@@ -0,0 +1,70 @@
+use std::{
+	env, fs,
+	path::Path,
+	process::{Command, Output},
+};
+
+mod common;
+
+fn track(root: &Path, history: &Path, extra: &[&str]) -> Output {
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.arg(root.join("src"))
+		.arg("--track")
+		.arg(history)
+		.args(extra)
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	output
+}
+
+fn saved_reports(history: &Path) -> Vec<String> {
+	let mut names: Vec<String> = fs::read_dir(history)
+		.expect("read history")
+		.map(|entry| entry.expect("history entry").file_name().to_string_lossy().into_owned())
+		.collect();
+	names.sort();
+	names
+}
+
+#[test]
+fn track_prints_the_movement_since_the_previous_run() {
+	let root_dir = common::temp_dir("movement");
+	let root = root_dir.path();
+	let history = root.join("history");
+	fs::create_dir_all(root.join("src")).expect("create src");
+	fs::write(root.join("src/main.rs"), "fn main() {}\n").expect("write main.rs");
+	let first = track(root, &history, &["--no-config"]);
+	assert!(String::from_utf8_lossy(&first.stderr).is_empty());
+	assert_eq!(saved_reports(&history).len(), 1);
+	fs::write(root.join("src/main.rs"), "fn main() {}\n\nfn helper() {}\n").expect("write main.rs");
+	fs::write(root.join("src/tool.py"), "print(1)\n").expect("write tool.py");
+	let second = track(root, &history, &["--no-config"]);
+	let stderr = String::from_utf8_lossy(&second.stderr);
+	assert!(stderr.starts_with("Since codestats-"), "stderr: {stderr}");
+	assert!(stderr.ends_with(":\nLines: 1 -> 4 (+3)\n\tRust: +2\n\tPython: +1\n"), "stderr: {stderr}");
+	assert!(String::from_utf8_lossy(&second.stdout).contains("Rust"));
+}
+
+#[test]
+fn track_keeps_only_the_configured_history() {
+	let root_dir = common::temp_dir("history");
+	let root = root_dir.path();
+	let history = root.join("history");
+	fs::create_dir_all(root.join("src")).expect("create src");
+	fs::write(root.join("src/main.rs"), "fn main() {}\n").expect("write main.rs");
+	track(root, &history, &["--no-config"]);
+	let saved = history.join(&saved_reports(&history)[0]);
+	for stamp in ["20000101T000000Z", "20000101T000001Z"] {
+		fs::copy(&saved, history.join(format!("codestats-{stamp}.json"))).expect("copy report");
+	}
+	fs::rename(&saved, history.join("codestats-20000101T000002Z.json")).expect("rename report");
+	fs::write(history.join("notes.txt"), "kept\n").expect("write notes");
+	let config = root.join("codestats.toml");
+	fs::write(&config, "[display]\ntrack_history = 2\n").expect("write config");
+	track(root, &history, &["--config", config.to_str().expect("utf-8 path")]);
+	let reports = saved_reports(&history);
+	assert_eq!(reports.len(), 3, "{reports:?}");
+	assert_eq!(reports[0], "codestats-20000101T000002Z.json");
+	assert_eq!(reports[2], "notes.txt");
+}
@@ -0,0 +1,27 @@
+use std::{path::Path, process::Command};
+
+/// Run codestats on the U+2028 fixture and return its `(total, code, comment, blank)` line counts.
+fn line_counts(extra: &[&str]) -> (u64, u64, u64, u64) {
+	let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/javascript/line_separators.js");
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.arg(&fixture)
+		.args(["--no-config", "-o", "json"])
+		.args(extra)
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("valid JSON output");
+	let summary = &json["summary"];
+	let count = |field: &str| summary[field].as_u64().expect(field);
+	(count("total_lines"), count("total_code_lines"), count("total_comment_lines"), count("total_blank_lines"))
+}
+
+#[test]
+fn unicode_separators_join_lines_by_default() {
+	assert_eq!(line_counts(&[]), (5, 2, 2, 1));
+}
+
+#[test]
+fn unicode_separators_end_lines_when_enabled() {
+	assert_eq!(line_counts(&["--unicode-line-breaks"]), (7, 3, 3, 1));
+}
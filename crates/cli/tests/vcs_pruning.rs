@@ -0,0 +1,28 @@
+use std::{env, fs, process::Command};
+
+mod common;
+
+#[test]
+fn hidden_scan_skips_vcs_directories() {
+	let root_dir = common::temp_dir("hidden");
+	let root = root_dir.path();
+	for dir in [".git/hooks", ".hg", ".svn", ".github/workflows"] {
+		fs::create_dir_all(root.join(dir)).expect("create fixture dir");
+	}
+	fs::write(root.join("main.rs"), "fn main() {}\n").expect("write main.rs");
+	fs::write(root.join(".git/hooks/pre-commit.sh"), "echo hook\n").expect("write git hook");
+	fs::write(root.join(".hg/hook.py"), "print('hook')\n").expect("write hg file");
+	fs::write(root.join(".svn/hook.py"), "print('hook')\n").expect("write svn file");
+	fs::write(root.join(".github/workflows/ci.sh"), "echo ci\n").expect("write workflow");
+	let output = Command::new(env!("CARGO_BIN_EXE_cs"))
+		.args([root.to_str().expect("Non-UTF-8 temp dir"), "--hidden", "--no-gitignore", "-o", "json", "-v"])
+		.output()
+		.expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	let stdout = String::from_utf8_lossy(&output.stdout);
+	assert!(stdout.contains("main.rs"), "stdout: {stdout}");
+	assert!(stdout.contains("ci.sh"), "dotfile directories should still be counted: {stdout}");
+	for vcs in [".git", ".hg", ".svn"] {
+		assert!(!stdout.contains(&format!("\"{vcs}/")), "{vcs} contents leaked into output: {stdout}");
+	}
+}
@@ -0,0 +1,22 @@
+use std::fs;
+
+mod common;
+
+#[test]
+fn collapse_vendored_moves_vendored_files_out_of_language_totals() {
+	let root_dir = common::temp_dir("bucket");
+	let root = root_dir.path();
+	fs::create_dir_all(root.join("vendor/dep")).expect("create vendor dir");
+	fs::write(root.join("main.rs"), "fn main() {}\n").expect("write source file");
+	fs::write(root.join("vendor/dep/lib.rs"), "pub fn dep() {}\n// vendored\n").expect("write vendored file");
+	let root_arg = root.to_str().expect("Non-UTF-8 temp dir");
+	let without = common::summary(root_arg, &[]);
+	assert!(without.get("vendored").is_none(), "vendored bucket should only be reported when requested");
+	assert_eq!(without["total_files"], 2);
+	let with = common::summary(root_arg, &["--collapse-vendored"]);
+	assert_eq!(with["total_files"], 1);
+	assert_eq!(with["total_lines"], 1);
+	assert_eq!(with["vendored"]["files"], 1);
+	assert_eq!(with["vendored"]["lines"], 2);
+	assert_eq!(with["vendored"]["comment_lines"], 1);
+}
@@ -0,0 +1,12 @@
+use std::process::Command;
+
+#[test]
+fn version_json_describes_capabilities() {
+	let output = Command::new(env!("CARGO_BIN_EXE_cs")).args(["version", "--json"]).output().expect("run codestats");
+	assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+	let info: serde_json::Value = serde_json::from_slice(&output.stdout).expect("valid JSON");
+	assert_eq!(info["version"], env!("CARGO_PKG_VERSION"));
+	assert!(info["languages"].as_u64().is_some_and(|count| count > 0));
+	let formats = info["output_formats"].as_array().expect("formats array");
+	assert!(formats.iter().any(|format| format == "json"), "formats: {formats:?}");
+}
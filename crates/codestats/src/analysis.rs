@@ -4,18 +4,34 @@
 //! 2. I/O Strategy ([`file_io`]): Chooses between buffered and memory-mapped reading based on file size.
 //! 3. Encoding Detection ([`encoding`]): Detects file encoding and filters out binary files.
 //! 4. Line Classification ([`line_classifier`]): Categorizes each line as code, comment, blank, or shebang.
-//! 5. Line Counting ([`line_counter`]): Accumulates line statistics for each file.
+//! 5. Line Counting ([`line_counter`]): Accumulates line statistics for each file, optionally routing embedded regions ([`embedded`]) to their own language.
 //! 6. Statistics Aggregation ([`stats`]): accumulation of code stats themselves, respecting the verbose setting.
+//!
+//! A path naming a `.zip` or `.tar`/`.tar.gz` file skips stage 1: its members are read into memory ([`archive`]) and fed through stages 3 to 6 one at a time.
+//!
+//! With caching enabled ([`cache`]), stages 2 to 5 are skipped for files whose modification time and size match the previous run.
 
 mod analyzer;
+#[cfg(feature = "archive")]
+mod archive;
+pub mod cache;
+pub mod categories;
+mod editorconfig;
+mod embedded;
 mod encoding;
 mod file_io;
 pub mod generated;
 mod line_classifier;
 mod line_counter;
+mod notebook;
 mod pipeline;
 pub mod stats;
+pub mod test_files;
+pub mod vendored;
 
 pub use analyzer::CodeAnalyzer;
-pub use line_classifier::LineType;
-pub use stats::{AnalysisResults, FileStats, LanguageStats};
+pub use encoding::DEFAULT_BINARY_THRESHOLD;
+pub use line_classifier::{CommentState, CommentStyle, Docstrings, LineType, Shebangs, classify_line, classify_reader};
+pub use stats::{
+	AnalysisResults, FileSink, FileStats, IndentationStyle, LanguageStats, LargestFile, SkippedFile, SubsetTotals,
+};
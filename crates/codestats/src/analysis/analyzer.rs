@@ -5,13 +5,51 @@ use std::{
 		Arc, Mutex, PoisonError,
 		atomic::{AtomicU64, Ordering},
 	},
+	time::{SystemTime, UNIX_EPOCH},
 };
 
-use anyhow::Result;
-use ignore::{WalkBuilder, overrides::OverrideBuilder};
+use anyhow::{Context as _, Error, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::{
+	DirEntry, WalkBuilder,
+	overrides::{Override, OverrideBuilder},
+};
+
+#[cfg(feature = "archive")]
+use super::archive::{self, ArchiveKind};
+use super::{
+	cache::{AnalysisCache, CacheEntry, CacheKey},
+	categories::CategoryMatcher,
+	editorconfig::LanguageOverrides,
+	file_io::IoLimiter,
+	generated::GeneratedDetector,
+	pipeline::{self, ProcessOptions},
+	stats::{AnalysisResults, FileSink},
+	test_files::TestFileMatcher,
+	vendored::VendoredMatcher,
+};
+use crate::{
+	config::{AnalyzerConfig, InputEncoding},
+	display::Verbosity,
+};
+
+/// Version control metadata directories that are never worth counting, even with hidden files enabled.
+const VCS_DIRS: &[&str] = &[".git", ".hg", ".svn"];
+
+fn is_vcs_dir(entry: &DirEntry) -> bool {
+	entry.file_type().is_some_and(|ft| ft.is_dir())
+		&& entry.file_name().to_str().is_some_and(|name| VCS_DIRS.contains(&name))
+}
 
-use super::{pipeline, stats::AnalysisResults};
-use crate::{config::AnalyzerConfig, display::Verbosity};
+/// Whether `entry` is a directory below the root whose name matches one of the `exclude_dirs` globs.
+fn is_excluded_dir(entry: &DirEntry, excluded: &GlobSet) -> bool {
+	entry.depth() > 0 && entry.file_type().is_some_and(|ft| ft.is_dir()) && excluded.is_match(entry.file_name())
+}
+
+/// Keep `err` as the error that stopped a strict walk, unless another worker already stopped it first.
+fn keep_first_error(slot: &Mutex<Option<Error>>, err: Error) {
+	slot.lock().unwrap_or_else(PoisonError::into_inner).get_or_insert(err);
+}
 
 /// Thread-local accumulator for parallel file analysis.
 ///
@@ -21,9 +59,12 @@ use crate::{config::AnalyzerConfig, display::Verbosity};
 /// - On `Drop`, the local results are merged into the shared sink in one batch.
 ///
 /// This dramatically reduces mutex contention compared to locking on every file processed, while ensuring all results are properly collected even if a worker panics.
+/// Cache entries for the next run are batched the same way.
 struct LocalAggregator {
 	sink: Arc<Mutex<Vec<AnalysisResults>>>,
 	local: AnalysisResults,
+	cache_sink: Arc<Mutex<Vec<(String, CacheEntry)>>>,
+	cache_updates: Vec<(String, CacheEntry)>,
 }
 
 impl Drop for LocalAggregator {
@@ -31,6 +72,11 @@ impl Drop for LocalAggregator {
 		let local = std::mem::take(&mut self.local);
 		let mut sink = self.sink.lock().unwrap_or_else(PoisonError::into_inner);
 		sink.push(local);
+		drop(sink);
+		if !self.cache_updates.is_empty() {
+			let mut cache_sink = self.cache_sink.lock().unwrap_or_else(PoisonError::into_inner);
+			cache_sink.append(&mut self.cache_updates);
+		}
 	}
 }
 
@@ -38,13 +84,24 @@ impl Drop for LocalAggregator {
 pub struct CodeAnalyzer {
 	root: PathBuf,
 	config: AnalyzerConfig,
+	file_sink: Option<Arc<dyn FileSink>>,
 }
 
 impl CodeAnalyzer {
 	/// Create a new analyzer rooted at `path`.
 	#[must_use]
 	pub fn new(path: &Path, config: AnalyzerConfig) -> Self {
-		Self { root: path.to_path_buf(), config }
+		Self { root: path.to_path_buf(), config, file_sink: None }
+	}
+
+	/// Hand each file's details to `sink` as soon as the file is counted, instead of keeping them in the results.
+	///
+	/// Only applies when per-file details are collected; the language and summary totals are still aggregated as usual,
+	/// but no language lists its files afterwards.
+	#[must_use]
+	pub fn with_file_sink(mut self, sink: Arc<dyn FileSink>) -> Self {
+		self.file_sink = Some(sink);
+		self
 	}
 
 	/// Analyze the configured path for code statistics
@@ -55,79 +112,83 @@ impl CodeAnalyzer {
 	/// - The path cannot be accessed
 	/// - File I/O operations fail during analysis
 	/// - UTF-8 decoding fails for file contents
+	/// - The cache is enabled and cannot be written
+	/// - Strict mode is enabled and an entry cannot be walked or processed; the walk stops at the first such entry
 	///
 	/// # Panics
 	///
 	/// May panic if the internal Arc or Mutex operations fail unexpectedly,
 	/// which should hopefully never happen.
 	pub fn analyze(&self) -> Result<AnalysisResults> {
+		#[cfg(feature = "archive")]
+		if let Some(kind) = ArchiveKind::from_path(&self.root)
+			&& self.root.is_file()
+		{
+			return self.analyze_archive(kind);
+		}
 		let error_counter = Arc::new(AtomicU64::new(0));
+		let first_error = Arc::new(Mutex::new(None));
+		let first_error_for_walk = Arc::clone(&first_error);
+		let strict = self.config.analysis.strict;
 		let verbosity = self.config.analysis.verbosity;
-		let collect_details = self.config.collect_file_details;
-		let include_languages = self.config.analysis.include_languages.clone();
-		let exclude_languages = self.config.analysis.exclude_languages.clone();
+		let options = Arc::new(self.process_options()?);
+		let options_for_walk = Arc::clone(&options);
 		let root = self.root.clone();
 		let aggregates = Arc::new(Mutex::new(Vec::new()));
 		let aggregates_for_walk = Arc::clone(&aggregates);
+		let cache_updates = Arc::new(Mutex::new(Vec::new()));
+		let cache_updates_for_walk = Arc::clone(&cache_updates);
 		let error_counter_for_walk = Arc::clone(&error_counter);
-		let mut builder = WalkBuilder::new(&self.root);
-		builder
-			.follow_links(self.config.analysis.follow_symlinks)
-			.ignore(self.config.analysis.respect_gitignore)
-			.git_ignore(self.config.analysis.respect_gitignore)
-			.git_global(self.config.analysis.respect_gitignore)
-			.git_exclude(self.config.analysis.respect_gitignore)
-			.require_git(false)
-			.hidden(!self.config.analysis.include_hidden)
-			.max_depth(self.config.analysis.max_depth);
-		let needs_overrides =
-			!self.config.analysis.exclude_patterns.is_empty() || !self.config.analysis.include_generated;
-		if needs_overrides {
-			let mut override_builder = OverrideBuilder::new(&self.root);
-			if !self.config.analysis.include_generated {
-				for pattern in super::generated::PATTERNS {
-					override_builder.add(&format!("!{pattern}"))?;
-				}
-			}
-			for pattern in &self.config.analysis.exclude_patterns {
-				// OverrideBuilder treats patterns without '!' as include rules, so we invert to enforce exclusion.
-				let glob: Cow<'_, str> = if pattern.starts_with('!') {
-					Cow::Borrowed(pattern.as_str())
-				} else {
-					Cow::Owned(format!("!{pattern}"))
-				};
-				override_builder.add(&glob)?;
-			}
-			builder.overrides(override_builder.build()?);
-		}
+		let builder = self.walk_builder()?;
 		builder.build_parallel().run(move || {
-			let mut aggregator =
-				LocalAggregator { sink: Arc::clone(&aggregates_for_walk), local: AnalysisResults::default() };
+			let mut aggregator = LocalAggregator {
+				sink: Arc::clone(&aggregates_for_walk),
+				local: AnalysisResults::default(),
+				cache_sink: Arc::clone(&cache_updates_for_walk),
+				cache_updates: Vec::new(),
+			};
 			let error_counter = Arc::clone(&error_counter_for_walk);
-			let include_languages = include_languages.clone();
-			let exclude_languages = exclude_languages.clone();
+			let first_error = Arc::clone(&first_error_for_walk);
+			let options = Arc::clone(&options_for_walk);
 			let root = root.clone();
 			Box::new(move |entry_result| {
 				match entry_result {
 					Ok(entry) if entry.file_type().is_some_and(|ft| ft.is_file()) => {
-						if let Err(err) = pipeline::process_file(
-							entry.path(),
-							&root,
-							&mut aggregator.local,
-							collect_details,
-							&include_languages,
-							&exclude_languages,
-						) {
+						let LocalAggregator { local, cache_updates, .. } = &mut aggregator;
+						if let Err(err) = pipeline::process_file(entry.path(), &root, local, cache_updates, &options) {
+							if strict {
+								let err = err.context(format!("Failed to process {}", entry.path().display()));
+								keep_first_error(&first_error, err);
+								return ignore::WalkState::Quit;
+							}
 							if verbosity == Verbosity::Verbose {
 								eprintln!("Failed to process {}: {err}", entry.path().display());
 							}
+							if options.collect_skipped {
+								local.add_skipped_file(
+									pipeline::relative_path(entry.path(), &root),
+									format!("io-error: {err:#}"),
+								);
+							}
 							error_counter.fetch_add(1, Ordering::Relaxed);
 						}
 					}
 					Err(err) => {
+						if strict {
+							keep_first_error(
+								&first_error,
+								Error::new(err).context("Failed to walk the directory tree"),
+							);
+							return ignore::WalkState::Quit;
+						}
 						if verbosity == Verbosity::Verbose {
 							eprintln!("Walker error: {err}");
 						}
+						if options.collect_skipped {
+							let path = walk_error_path(&err)
+								.map_or_else(String::new, |path| pipeline::relative_path(path, &root));
+							aggregator.local.add_skipped_file(path, format!("io-error: {err}"));
+						}
 						error_counter.fetch_add(1, Ordering::Relaxed);
 					}
 					_ => {}
@@ -135,23 +196,259 @@ impl CodeAnalyzer {
 				ignore::WalkState::Continue
 			})
 		});
+		let first_error = first_error.lock().unwrap_or_else(PoisonError::into_inner).take();
+		if let Some(err) = first_error {
+			return Err(err);
+		}
 		let partials = Arc::try_unwrap(aggregates)
 			.map_err(|_| anyhow::anyhow!("Failed to unwrap aggregates Arc - walker still holds references"))?
 			.into_inner()
 			.unwrap_or_else(PoisonError::into_inner);
-		let mut results = partials.into_iter().fold(AnalysisResults::with_language_capacity(), |mut acc, local| {
-			acc.merge(local);
-			acc
+		let mut results = AnalysisResults::combine(partials);
+		if let (Some(cache_path), Some(cache)) = (&self.config.analysis.cache, &options.cache) {
+			let entries = Arc::try_unwrap(cache_updates)
+				.map_err(|_| anyhow::anyhow!("Failed to unwrap cache updates Arc - walker still holds references"))?
+				.into_inner()
+				.unwrap_or_else(PoisonError::into_inner);
+			cache.save(cache_path, entries)?;
+		}
+		report_skipped(&mut results, error_counter.load(Ordering::Relaxed), verbosity);
+		Ok(results)
+	}
+
+	/// Analyze the members of the archive at the configured path one by one, without extracting it.
+	///
+	/// Exclude patterns, excluded directories, generated-file patterns, hidden-file and depth settings apply to member
+	/// paths, but ignore files inside the archive, `modified_within` and the cache do not.
+	#[cfg(feature = "archive")]
+	fn analyze_archive(&self, kind: ArchiveKind) -> Result<AnalysisResults> {
+		let options = self.process_options()?;
+		let overrides = self.overrides()?;
+		let excluded_dirs = self.excluded_dirs()?;
+		let verbosity = self.config.analysis.verbosity;
+		let mut results = AnalysisResults::default();
+		let mut skipped = 0;
+		archive::for_each_member(&self.root, kind, |path, member| {
+			if !self.includes_member(path, overrides.as_ref(), &excluded_dirs)
+				|| pipeline::filtered_out_by_name(path, &options)
+			{
+				return Ok(());
+			}
+			let Some(bytes) = archive::read_member(member, archive::MAX_MEMBER_SIZE)? else {
+				if verbosity == Verbosity::Verbose {
+					eprintln!("Skipping {path}: larger than {} bytes", archive::MAX_MEMBER_SIZE);
+				}
+				if options.collect_skipped {
+					results.add_skipped_file(path, "too-large");
+				}
+				skipped += 1;
+				return Ok(());
+			};
+			if let Err(err) = pipeline::process_bytes(path, bytes, &mut results, &options) {
+				if self.config.analysis.strict {
+					return Err(err.context(format!("Failed to process {path}")));
+				}
+				if verbosity == Verbosity::Verbose {
+					eprintln!("Failed to process {path}: {err}");
+				}
+				if options.collect_skipped {
+					results.add_skipped_file(path, format!("io-error: {err:#}"));
+				}
+				skipped += 1;
+			}
+			Ok(())
+		})?;
+		report_skipped(&mut results, skipped, verbosity);
+		Ok(results)
+	}
+
+	/// Check whether the archive member at `path` passes the filters the directory walker would apply.
+	#[cfg(feature = "archive")]
+	fn includes_member(&self, path: &str, overrides: Option<&Override>, excluded_dirs: &GlobSet) -> bool {
+		let components: Vec<_> = path.split('/').filter(|component| !component.is_empty()).collect();
+		if self.config.analysis.max_depth.is_some_and(|depth| components.len() > depth) {
+			return false;
+		}
+		let mut prefix = String::new();
+		for (idx, component) in components.iter().enumerate() {
+			let is_dir = idx + 1 < components.len();
+			if (is_dir && (VCS_DIRS.contains(component) || excluded_dirs.is_match(component)))
+				|| (!self.config.analysis.include_hidden && component.starts_with('.'))
+			{
+				return false;
+			}
+			if !prefix.is_empty() {
+				prefix.push('/');
+			}
+			prefix.push_str(component);
+			if overrides.is_some_and(|overrides| overrides.matched(&prefix, is_dir).is_ignore()) {
+				return false;
+			}
+		}
+		true
+	}
+
+	/// Build the per-file settings shared by every worker thread.
+	fn process_options(&self) -> Result<ProcessOptions> {
+		let generated = if self.config.analysis.exclude_generated {
+			Some(GeneratedDetector::new(
+				&self.config.analysis.generated_markers,
+				&self.config.analysis.generated_patterns,
+			)?)
+		} else {
+			None
+		};
+		let tests = if self.config.analysis.split_tests {
+			Some(TestFileMatcher::new(&self.config.analysis.test_patterns)?)
+		} else {
+			None
+		};
+		let vendored = if self.config.analysis.collapse_vendored {
+			Some(VendoredMatcher::new(&self.config.analysis.vendored_patterns)?)
+		} else {
+			None
+		};
+		let categories =
+			if self.config.categories.is_empty() { None } else { Some(CategoryMatcher::new(&self.config.categories)?) };
+		let language_overrides =
+			if self.config.analysis.editorconfig { LanguageOverrides::load(&self.root)? } else { None };
+		let cache = self.config.analysis.cache.as_ref().map(|path| {
+			let key = CacheKey::new(&self.config.analysis, language_overrides.as_ref());
+			AnalysisCache::load(path, key.clone()).unwrap_or_else(|err| {
+				if self.config.analysis.verbosity == Verbosity::Verbose {
+					eprintln!("Ignoring unusable cache: {err:#}");
+				}
+				AnalysisCache::new(key)
+			})
 		});
-		let skipped = error_counter.load(Ordering::Relaxed);
-		if skipped > 0 {
-			if verbosity == Verbosity::Verbose {
-				eprintln!("Skipped {skipped} entries due to errors.");
-			} else {
-				eprintln!("Skipped {skipped} entries due to errors (re-run with --verbose for details).");
+		Ok(ProcessOptions {
+			collect_details: self.config.collect_file_details,
+			file_sink: self.file_sink.clone(),
+			include_languages: self.config.analysis.include_languages.clone(),
+			exclude_languages: self.config.analysis.exclude_languages.clone(),
+			generated,
+			tests,
+			vendored,
+			categories,
+			language_overrides,
+			cache,
+			canonical_root: self
+				.config
+				.analysis
+				.follow_symlinks
+				.then(|| std::fs::canonicalize(&self.root).ok())
+				.flatten(),
+			tab_width: self.config.analysis.tab_width,
+			split_mixed: self.config.analysis.split_mixed,
+			fold_markers: self.config.analysis.fold_markers.clone(),
+			docstrings: self.config.analysis.docstrings,
+			shebangs: self.config.analysis.shebang,
+			unicode_line_breaks: self.config.analysis.unicode_line_breaks,
+			hash: self.config.analysis.hash,
+			input_encoding: self.config.analysis.input_encoding.map(InputEncoding::encoding),
+			embedded: self.config.analysis.embedded,
+			explain_detection: self.config.analysis.explain_detection,
+			detection_threshold: self.config.analysis.detection_threshold,
+			binary_threshold: self.config.analysis.binary_threshold,
+			binary_thresholds: self
+				.config
+				.analysis
+				.binary_thresholds
+				.iter()
+				.map(|(extension, &threshold)| (extension.to_ascii_lowercase(), threshold))
+				.collect(),
+			include_assets: self.config.analysis.include_assets,
+			modified_after: self
+				.config
+				.analysis
+				.modified_within
+				.map(|age| SystemTime::now().checked_sub(age.duration()).unwrap_or(UNIX_EPOCH)),
+			collect_skipped: self.config.analysis.collect_skipped,
+			io_limit: self.config.analysis.io_concurrency.map(IoLimiter::new),
+		})
+	}
+
+	/// Configure the directory walker from the analysis settings.
+	fn walk_builder(&self) -> Result<WalkBuilder> {
+		let analysis = &self.config.analysis;
+		let excluded_dirs = self.excluded_dirs()?;
+		let mut builder = WalkBuilder::new(&self.root);
+		builder
+			.follow_links(analysis.follow_symlinks)
+			.same_file_system(analysis.one_file_system)
+			.ignore(analysis.respect_gitignore && analysis.dot_ignore)
+			.git_ignore(analysis.respect_gitignore)
+			.git_global(analysis.respect_gitignore && analysis.git_global)
+			.git_exclude(analysis.respect_gitignore && analysis.git_exclude)
+			.parents(analysis.ignore_parents)
+			.require_git(false)
+			.hidden(!self.config.analysis.include_hidden)
+			.max_depth(self.config.analysis.max_depth)
+			.filter_entry(move |entry| !is_vcs_dir(entry) && !is_excluded_dir(entry, &excluded_dirs));
+		if let Some(overrides) = self.overrides()? {
+			builder.overrides(overrides);
+		}
+		Ok(builder)
+	}
+
+	/// Build the directory-name globs from `exclude_dirs`, matched against each directory's name to prune its subtree.
+	fn excluded_dirs(&self) -> Result<GlobSet> {
+		let mut builder = GlobSetBuilder::new();
+		for name in &self.config.analysis.exclude_dirs {
+			builder.add(Glob::new(name).with_context(|| format!("Invalid excluded directory `{name}`"))?);
+		}
+		Ok(builder.build()?)
+	}
+
+	/// Build the exclusions from `exclude_patterns` and the generated-file patterns, if there are any.
+	fn overrides(&self) -> Result<Option<Override>> {
+		let needs_overrides =
+			!self.config.analysis.exclude_patterns.is_empty() || !self.config.analysis.include_generated;
+		if needs_overrides {
+			let mut override_builder = OverrideBuilder::new(&self.root);
+			if !self.config.analysis.include_generated {
+				for pattern in super::generated::PATTERNS {
+					override_builder.add(&format!("!{pattern}"))?;
+				}
+				if !self.config.analysis.include_lockfiles {
+					for pattern in &self.config.analysis.lockfile_patterns {
+						override_builder.add(&format!("!{pattern}"))?;
+					}
+				}
 			}
+			for pattern in &self.config.analysis.exclude_patterns {
+				// OverrideBuilder treats patterns without '!' as include rules, so we invert to enforce exclusion.
+				let glob: Cow<'_, str> = if pattern.starts_with('!') {
+					Cow::Borrowed(pattern.as_str())
+				} else {
+					Cow::Owned(format!("!{pattern}"))
+				};
+				override_builder.add(&glob)?;
+			}
+			return Ok(Some(override_builder.build()?));
+		}
+		Ok(None)
+	}
+}
+
+/// The path a walker error is about, if it names one.
+fn walk_error_path(err: &ignore::Error) -> Option<&Path> {
+	match err {
+		ignore::Error::WithPath { path, .. } => Some(path),
+		ignore::Error::WithDepth { err, .. } | ignore::Error::WithLineNumber { err, .. } => walk_error_path(err),
+		ignore::Error::Loop { child, .. } => Some(child),
+		_ => None,
+	}
+}
+
+/// Record the number of entries skipped due to errors, and mention them on stderr.
+fn report_skipped(results: &mut AnalysisResults, skipped: u64, verbosity: Verbosity) {
+	if skipped > 0 {
+		if verbosity == Verbosity::Verbose {
+			eprintln!("Skipped {skipped} entries due to errors.");
+		} else {
+			eprintln!("Skipped {skipped} entries due to errors (re-run with --verbose for details).");
 		}
-		results.set_skipped_entries(skipped);
-		Ok(results)
 	}
+	results.set_skipped_entries(skipped);
 }
@@ -0,0 +1,206 @@
+//! Reading `.zip`, `.tar` and `.tar.gz` archives without extracting them.
+//!
+//! Each regular file in the archive is handed to the caller in turn along with its path inside the archive, which then
+//! drives language detection exactly as a file name on disk would. The caller decides from the path whether to read
+//! the member into memory, up to [`MAX_MEMBER_SIZE`] bytes. Directory entries, links and other special entries are
+//! skipped, and nested archives are not opened.
+
+use std::{
+	fs::File,
+	io::{BufReader, Read},
+	path::Path,
+};
+
+use anyhow::{Context as _, Result};
+use flate2::read::GzDecoder;
+
+/// Largest archive member read into memory; larger members are skipped.
+pub(super) const MAX_MEMBER_SIZE: u64 = 256 << 20;
+
+/// Archive formats recognized from the name of the analyzed path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ArchiveKind {
+	Zip,
+	Tar,
+	TarGz,
+}
+
+/// File name suffixes of each archive format, matched against the lowercased name.
+const SUFFIXES: &[(&str, ArchiveKind)] = &[
+	(".zip", ArchiveKind::Zip),
+	(".tar", ArchiveKind::Tar),
+	(".tar.gz", ArchiveKind::TarGz),
+	(".tgz", ArchiveKind::TarGz),
+];
+
+impl ArchiveKind {
+	/// Recognize an archive from the extension of `path`, ignoring case.
+	pub(super) fn from_path(path: &Path) -> Option<Self> {
+		let name = path.file_name()?.to_string_lossy().to_ascii_lowercase();
+		SUFFIXES.iter().find(|(suffix, _)| name.ends_with(suffix)).map(|&(_, kind)| kind)
+	}
+}
+
+/// Call `visit` with the path and an unread reader of every regular file in the archive at `path`, in archive order.
+///
+/// Paths are `/`-separated and relative, with any leading `./` or `/` removed. A member `visit` does not read is
+/// skipped over.
+///
+/// # Errors
+///
+/// Returns an error if the archive cannot be opened or is corrupt, or the first error returned by `visit`.
+pub(super) fn for_each_member(
+	path: &Path,
+	kind: ArchiveKind,
+	mut visit: impl FnMut(&str, &mut dyn Read) -> Result<()>,
+) -> Result<()> {
+	let file = File::open(path).with_context(|| format!("Failed to open archive {}", path.display()))?;
+	let reader = BufReader::new(file);
+	match kind {
+		ArchiveKind::Zip => for_each_zip_member(reader, &mut visit),
+		ArchiveKind::Tar => for_each_tar_member(reader, &mut visit),
+		ArchiveKind::TarGz => for_each_tar_member(GzDecoder::new(reader), &mut visit),
+	}
+	.with_context(|| format!("Failed to read archive {}", path.display()))
+}
+
+fn for_each_zip_member(
+	reader: BufReader<File>,
+	visit: &mut impl FnMut(&str, &mut dyn Read) -> Result<()>,
+) -> Result<()> {
+	let mut archive = zip::ZipArchive::new(reader)?;
+	for index in 0..archive.len() {
+		let mut member = archive.by_index(index)?;
+		if !member.is_file() {
+			continue;
+		}
+		let name = member_path(member.name()).to_owned();
+		visit(&name, &mut member)?;
+	}
+	Ok(())
+}
+
+fn for_each_tar_member(reader: impl Read, visit: &mut impl FnMut(&str, &mut dyn Read) -> Result<()>) -> Result<()> {
+	let mut archive = tar::Archive::new(reader);
+	for member in archive.entries()? {
+		let mut member = member?;
+		if !member.header().entry_type().is_file() {
+			continue;
+		}
+		let name = String::from_utf8_lossy(&member.path_bytes()).into_owned();
+		visit(member_path(&name), &mut member)?;
+	}
+	Ok(())
+}
+
+/// Read the rest of `member` into memory, or return `None` without reading past `limit` if it holds more than `limit`
+/// bytes.
+///
+/// # Errors
+///
+/// Returns an error if the member cannot be read.
+pub(super) fn read_member(member: &mut dyn Read, limit: u64) -> Result<Option<Vec<u8>>> {
+	let mut bytes = Vec::new();
+	member.take(limit.saturating_add(1)).read_to_end(&mut bytes)?;
+	Ok((bytes.len() as u64 <= limit).then_some(bytes))
+}
+
+/// Normalize a member name to the relative form used as its display path.
+fn member_path(name: &str) -> &str {
+	let mut name = name;
+	while let Some(rest) = name.strip_prefix("./").or_else(|| name.strip_prefix('/')) {
+		name = rest;
+	}
+	name
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::{Cursor, Write as _};
+
+	use rstest::rstest;
+
+	use super::*;
+
+	#[rstest]
+	#[case::zip("release.zip", Some(ArchiveKind::Zip))]
+	#[case::upper_zip("RELEASE.ZIP", Some(ArchiveKind::Zip))]
+	#[case::tar("release.tar", Some(ArchiveKind::Tar))]
+	#[case::tar_gz("release-1.0.tar.gz", Some(ArchiveKind::TarGz))]
+	#[case::tgz("release.tgz", Some(ArchiveKind::TarGz))]
+	#[case::plain_gz("notes.txt.gz", None)]
+	#[case::source("main.rs", None)]
+	fn test_archive_kind_from_path(#[case] name: &str, #[case] expected: Option<ArchiveKind>) {
+		assert_eq!(ArchiveKind::from_path(Path::new(name)), expected);
+	}
+
+	#[rstest]
+	#[case::plain("src/main.rs", "src/main.rs")]
+	#[case::dot_slash("./src/main.rs", "src/main.rs")]
+	#[case::absolute("/src/main.rs", "src/main.rs")]
+	fn test_member_path(#[case] name: &str, #[case] expected: &str) {
+		assert_eq!(member_path(name), expected);
+	}
+
+	fn members(path: &Path, kind: ArchiveKind) -> Vec<(String, Vec<u8>)> {
+		let mut members = Vec::new();
+		for_each_member(path, kind, |name, member| {
+			members.push((name.to_owned(), read_member(member, MAX_MEMBER_SIZE)?.unwrap()));
+			Ok(())
+		})
+		.unwrap();
+		members
+	}
+
+	fn temp_path(name: &str) -> std::path::PathBuf {
+		let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos();
+		std::env::temp_dir().join(format!("codestats-archive-{nanos}-{name}"))
+	}
+
+	#[test]
+	fn zip_members_skip_directories() {
+		let path = temp_path("test.zip");
+		let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+		let options = zip::write::SimpleFileOptions::default();
+		writer.add_directory("src/", options).unwrap();
+		writer.start_file("src/main.rs", options).unwrap();
+		writer.write_all(b"fn main() {}\n").unwrap();
+		std::fs::write(&path, writer.finish().unwrap().into_inner()).unwrap();
+		let found = members(&path, ArchiveKind::Zip);
+		std::fs::remove_file(&path).unwrap();
+		assert_eq!(found, [("src/main.rs".to_owned(), b"fn main() {}\n".to_vec())]);
+	}
+
+	#[test]
+	fn tar_gz_members_are_decompressed() {
+		let path = temp_path("test.tar.gz");
+		let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default()));
+		let contents = b"print('hi')\n";
+		let mut header = tar::Header::new_gnu();
+		header.set_size(contents.len() as u64);
+		header.set_mode(0o644);
+		header.set_cksum();
+		builder.append_data(&mut header, "./pkg/app.py", &contents[..]).unwrap();
+		std::fs::write(&path, builder.into_inner().unwrap().finish().unwrap()).unwrap();
+		let found = members(&path, ArchiveKind::TarGz);
+		std::fs::remove_file(&path).unwrap();
+		assert_eq!(found, [("pkg/app.py".to_owned(), contents.to_vec())]);
+	}
+
+	#[test]
+	fn members_over_the_limit_are_not_read() {
+		assert_eq!(read_member(&mut Cursor::new(b"12345"), 5).unwrap(), Some(b"12345".to_vec()));
+		let mut member = Cursor::new(b"123456789");
+		assert_eq!(read_member(&mut member, 5).unwrap(), None);
+		assert_eq!(member.position(), 6);
+	}
+
+	#[test]
+	fn corrupt_archive_is_an_error() {
+		let path = temp_path("corrupt.zip");
+		std::fs::write(&path, b"not a zip").unwrap();
+		let result = for_each_member(&path, ArchiveKind::Zip, |_, _| Ok(()));
+		std::fs::remove_file(&path).unwrap();
+		assert!(result.is_err());
+	}
+}
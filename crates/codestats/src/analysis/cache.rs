@@ -0,0 +1,336 @@
+//! On-disk cache of per-file counts, so re-runs only re-read files whose modification time or size changed.
+//!
+//! Entries are keyed by the file's path relative to the analysis root. The whole cache is discarded when the
+//! language definitions, the codestats version, or any option that changes how lines are counted differs from the
+//! run that wrote it.
+
+use std::{
+	collections::{BTreeMap, HashMap},
+	fs::{self, Metadata},
+	io::ErrorKind,
+	path::Path,
+	time::UNIX_EPOCH,
+};
+
+use anyhow::{Context as _, Result};
+use serde::{Deserialize, Serialize};
+
+use super::{
+	editorconfig::LanguageOverrides,
+	line_classifier::{Docstrings, Shebangs},
+	line_counter::FileCounts,
+	stats::FileContribution,
+};
+use crate::{
+	config::{AnalysisConfig, InputEncoding},
+	langs::{DetectionConfidence, LANGUAGES, LANGUAGES_HASH, Language},
+};
+
+/// Cache file name used when `--cache` is given without a path, placed in the analysis root.
+pub const DEFAULT_CACHE_FILE: &str = ".codestats-cache";
+
+/// Bumped whenever the on-disk layout changes.
+const CACHE_FORMAT: u32 = 8;
+
+/// Everything a cached entry's counts depend on besides the file itself.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[expect(clippy::struct_excessive_bools, reason = "each bool mirrors an analysis option that changes the counts")]
+pub(super) struct CacheKey {
+	format: u32,
+	codestats: String,
+	languages: u64,
+	tab_width: u8,
+	split_mixed: bool,
+	fold_markers: Vec<String>,
+	docstrings: Docstrings,
+	shebangs: Shebangs,
+	unicode_line_breaks: bool,
+	hash: bool,
+	input_encoding: Option<InputEncoding>,
+	embedded: bool,
+	/// Language filters, present only with `embedded`, where they decide which regions stay with the host file.
+	embedded_filter: Option<(Vec<String>, Vec<String>)>,
+	detection_threshold: u32,
+	binary_threshold: u8,
+	binary_thresholds: BTreeMap<String, u8>,
+	/// Generated-file markers and patterns, present only when generated files are excluded.
+	generated: Option<(Vec<String>, Vec<String>)>,
+	/// Section patterns and languages from `.editorconfig`, which decide the language ahead of detection.
+	language_overrides: Vec<(String, String)>,
+}
+
+impl CacheKey {
+	pub(super) fn new(analysis: &AnalysisConfig, overrides: Option<&LanguageOverrides>) -> Self {
+		Self {
+			format: CACHE_FORMAT,
+			codestats: env!("CARGO_PKG_VERSION").to_owned(),
+			languages: LANGUAGES_HASH,
+			tab_width: analysis.tab_width,
+			split_mixed: analysis.split_mixed,
+			fold_markers: analysis.fold_markers.clone(),
+			docstrings: analysis.docstrings,
+			shebangs: analysis.shebang,
+			unicode_line_breaks: analysis.unicode_line_breaks,
+			hash: analysis.hash,
+			input_encoding: analysis.input_encoding,
+			embedded: analysis.embedded,
+			embedded_filter: analysis
+				.embedded
+				.then(|| (analysis.include_languages.clone(), analysis.exclude_languages.clone())),
+			detection_threshold: analysis.detection_threshold,
+			binary_threshold: analysis.binary_threshold,
+			binary_thresholds: analysis.binary_thresholds.clone(),
+			generated: analysis
+				.exclude_generated
+				.then(|| (analysis.generated_markers.clone(), analysis.generated_patterns.clone())),
+			language_overrides: overrides
+				.into_iter()
+				.flat_map(LanguageOverrides::rules)
+				.map(|(pattern, language)| (pattern.to_owned(), language.to_owned()))
+				.collect(),
+		}
+	}
+}
+
+/// Modification time and size of a file, compared to decide whether a cached entry is still valid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(super) struct FileStamp {
+	modified_secs: u64,
+	modified_nanos: u32,
+	size: u64,
+}
+
+impl FileStamp {
+	/// Read the stamp from `metadata`, or `None` when the platform does not report modification times.
+	pub(super) fn from_metadata(metadata: &Metadata) -> Option<Self> {
+		let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+		Some(Self { modified_secs: modified.as_secs(), modified_nanos: modified.subsec_nanos(), size: metadata.len() })
+	}
+}
+
+/// What the pipeline concluded about a file, in a form that can be replayed without reading it again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Outcome {
+	Generated,
+	Counted { counts: FileCounts, bom: bool },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum StoredOutcome {
+	Generated,
+	Counted {
+		host: FileContribution,
+		embedded: Vec<(String, FileContribution)>,
+		bom: bool,
+		minified: bool,
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		hash: Option<u64>,
+	},
+}
+
+/// A single cached file, keyed by its path in [`AnalysisCache`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct CacheEntry {
+	stamp: FileStamp,
+	language: String,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	detection: Option<DetectionConfidence>,
+	outcome: StoredOutcome,
+}
+
+impl CacheEntry {
+	pub(super) fn new(
+		stamp: FileStamp,
+		language: &Language,
+		detection: DetectionConfidence,
+		outcome: &Outcome,
+	) -> Self {
+		let outcome = match outcome {
+			Outcome::Generated => StoredOutcome::Generated,
+			Outcome::Counted { counts, bom } => StoredOutcome::Counted {
+				host: counts.host,
+				embedded: counts
+					.embedded
+					.iter()
+					.map(|&(language, contribution)| (language.name.to_owned(), contribution))
+					.collect(),
+				bom: *bom,
+				minified: counts.minified,
+				hash: counts.hash,
+			},
+		};
+		Self { stamp, language: language.name.to_owned(), detection: Some(detection), outcome }
+	}
+
+	/// What decided the file's language when it was counted.
+	pub(super) const fn detection(&self) -> Option<DetectionConfidence> {
+		self.detection
+	}
+
+	/// Resolve the stored language names, or `None` if one no longer exists.
+	fn resolve(&self) -> Option<(&'static Language, Outcome)> {
+		let language = language_named(&self.language)?;
+		let outcome = match &self.outcome {
+			StoredOutcome::Generated => Outcome::Generated,
+			StoredOutcome::Counted { host, embedded, bom, minified, hash } => Outcome::Counted {
+				counts: FileCounts {
+					host: *host,
+					embedded: embedded
+						.iter()
+						.map(|(name, contribution)| language_named(name).map(|language| (language, *contribution)))
+						.collect::<Option<_>>()?,
+					minified: *minified,
+					hash: *hash,
+				},
+				bom: *bom,
+			},
+		};
+		Some((language, outcome))
+	}
+}
+
+fn language_named(name: &str) -> Option<&'static Language> {
+	LANGUAGES.iter().find(|language| language.name == name)
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile<K, E> {
+	key: K,
+	entries: E,
+}
+
+/// Cached entries loaded from a previous run, along with the settings the current run expects.
+#[derive(Debug)]
+pub(super) struct AnalysisCache {
+	key: CacheKey,
+	entries: HashMap<String, CacheEntry>,
+}
+
+impl AnalysisCache {
+	/// Create an empty cache for a run with the settings in `key`.
+	pub(super) fn new(key: CacheKey) -> Self {
+		Self { key, entries: HashMap::new() }
+	}
+
+	/// Load the cache at `path`, starting empty when the file is missing or was written with settings other than `key`.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the file exists but cannot be read or parsed.
+	pub(super) fn load(path: &Path, key: CacheKey) -> Result<Self> {
+		let contents = match fs::read(path) {
+			Ok(contents) => contents,
+			Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Self::new(key)),
+			Err(err) => return Err(err).with_context(|| format!("Failed to read cache {}", path.display())),
+		};
+		let file: CacheFile<CacheKey, HashMap<String, CacheEntry>> =
+			serde_json::from_slice(&contents).with_context(|| format!("Failed to parse cache {}", path.display()))?;
+		if file.key != key {
+			return Ok(Self::new(key));
+		}
+		Ok(Self { key, entries: file.entries })
+	}
+
+	/// Look up `path`, returning its entry and resolved outcome when the stamp still matches.
+	pub(super) fn lookup(&self, path: &str, stamp: FileStamp) -> Option<(&CacheEntry, &'static Language, Outcome)> {
+		let entry = self.entries.get(path).filter(|entry| entry.stamp == stamp)?;
+		let (language, outcome) = entry.resolve()?;
+		Some((entry, language, outcome))
+	}
+
+	/// Write `entries` to `path`, replacing any previous cache.
+	///
+	/// Only files seen during the current run are written, so deleted files drop out of the cache.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the cache cannot be serialized or written.
+	pub(super) fn save(&self, path: &Path, entries: Vec<(String, CacheEntry)>) -> Result<()> {
+		let file = CacheFile { key: &self.key, entries: entries.into_iter().collect::<BTreeMap<_, _>>() };
+		let contents = serde_json::to_vec(&file)?;
+		let mut temp = path.as_os_str().to_owned();
+		temp.push(".tmp");
+		fs::write(&temp, contents).with_context(|| format!("Failed to write cache {}", path.display()))?;
+		fs::rename(&temp, path).with_context(|| format!("Failed to write cache {}", path.display()))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::{SystemTime, UNIX_EPOCH};
+
+	use super::*;
+
+	fn temp_path(label: &str) -> std::path::PathBuf {
+		let unique = SystemTime::now().duration_since(UNIX_EPOCH).expect("system time is set").as_nanos();
+		std::env::temp_dir().join(format!("codestats_cache_test_{}_{unique}_{label}", std::process::id()))
+	}
+
+	fn counted() -> (String, CacheEntry, FileStamp) {
+		let rust = language_named("Rust").unwrap();
+		let markdown = language_named("Markdown").unwrap();
+		let stamp = FileStamp { modified_secs: 10, modified_nanos: 5, size: 100 };
+		let counts = FileCounts {
+			host: FileContribution::new(10, 8, 1, 1, 0, 100).with_mixed_lines(1),
+			embedded: vec![(markdown, FileContribution::new(2, 2, 0, 0, 0, 0))],
+			minified: false,
+			hash: Some(0x1234),
+		};
+		(
+			"src/lib.rs".to_owned(),
+			CacheEntry::new(stamp, rust, DetectionConfidence::Filename, &Outcome::Counted { counts, bom: true }),
+			stamp,
+		)
+	}
+
+	fn key(analysis: &AnalysisConfig) -> CacheKey {
+		CacheKey::new(analysis, None)
+	}
+
+	#[test]
+	fn saved_entries_round_trip_while_stamp_matches() {
+		let path = temp_path("round_trip");
+		let analysis = AnalysisConfig::default();
+		let (name, entry, stamp) = counted();
+		let expected = entry.resolve().unwrap().1;
+		AnalysisCache::new(key(&analysis)).save(&path, vec![(name, entry)]).unwrap();
+		let cache = AnalysisCache::load(&path, key(&analysis)).unwrap();
+		let (_, language, outcome) = cache.lookup("src/lib.rs", stamp).unwrap();
+		assert_eq!(language.name, "Rust");
+		assert_eq!(outcome, expected);
+		let touched = FileStamp { modified_secs: 11, ..stamp };
+		assert!(cache.lookup("src/lib.rs", touched).is_none());
+		assert!(cache.lookup("src/main.rs", stamp).is_none());
+		fs::remove_file(&path).unwrap();
+	}
+
+	#[test]
+	fn changed_counting_options_discard_the_cache() {
+		let path = temp_path("options");
+		let (name, entry, stamp) = counted();
+		AnalysisCache::new(key(&AnalysisConfig::default())).save(&path, vec![(name, entry)]).unwrap();
+		let split = AnalysisConfig { split_mixed: true, ..AnalysisConfig::default() };
+		assert!(AnalysisCache::load(&path, key(&split)).unwrap().lookup("src/lib.rs", stamp).is_none());
+		let folded = AnalysisConfig { fold_markers: vec!["#region".to_owned()], ..AnalysisConfig::default() };
+		assert!(AnalysisCache::load(&path, key(&folded)).unwrap().lookup("src/lib.rs", stamp).is_none());
+		let filtered = AnalysisConfig { include_languages: vec!["Rust".to_owned()], ..AnalysisConfig::default() };
+		assert!(AnalysisCache::load(&path, key(&filtered)).unwrap().lookup("src/lib.rs", stamp).is_some());
+		let embedded = AnalysisConfig { embedded: true, ..AnalysisConfig::default() };
+		let (name, entry, _) = counted();
+		AnalysisCache::new(key(&embedded)).save(&path, vec![(name, entry)]).unwrap();
+		let embedded_filtered = AnalysisConfig { include_languages: vec!["Rust".to_owned()], ..embedded };
+		assert!(AnalysisCache::load(&path, key(&embedded_filtered)).unwrap().lookup("src/lib.rs", stamp).is_none());
+		fs::remove_file(&path).unwrap();
+	}
+
+	#[test]
+	fn missing_cache_loads_empty_and_corrupt_cache_errors() {
+		let path = temp_path("corrupt");
+		let analysis = AnalysisConfig::default();
+		assert!(AnalysisCache::load(&path, key(&analysis)).unwrap().entries.is_empty());
+		fs::write(&path, "not json").unwrap();
+		assert!(AnalysisCache::load(&path, key(&analysis)).is_err());
+		fs::remove_file(&path).unwrap();
+	}
+}
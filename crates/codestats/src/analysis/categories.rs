@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+
+use crate::config::FileCategory;
+
+/// Assigns each file the first category whose globs match its filename or relative path, when `split_categories` is
+/// enabled.
+#[derive(Debug)]
+pub struct CategoryMatcher {
+	rules: Vec<(Arc<str>, GlobSet)>,
+}
+
+impl CategoryMatcher {
+	/// Build a matcher from category rules, which are tried in order.
+	///
+	/// # Errors
+	///
+	/// Returns an error if a glob pattern is invalid.
+	pub fn new(categories: &[FileCategory]) -> Result<Self> {
+		let rules = categories
+			.iter()
+			.map(|category| {
+				let mut builder = GlobSetBuilder::new();
+				for pattern in &category.patterns {
+					builder
+						.add(Glob::new(pattern).with_context(|| {
+							format!("Invalid pattern `{pattern}` for category `{}`", category.name)
+						})?);
+				}
+				Ok((Arc::from(category.name.as_str()), builder.build()?))
+			})
+			.collect::<Result<_>>()?;
+		Ok(Self { rules })
+	}
+
+	/// Name of the first category matching the file at `path` (relative to the analysis root, `/`-separated) named
+	/// `filename`, or `None` when no rule matches.
+	#[must_use]
+	pub fn classify(&self, filename: &str, path: &str) -> Option<&Arc<str>> {
+		self.rules.iter().find(|(_, globs)| globs.is_match(filename) || globs.is_match(path)).map(|(name, _)| name)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use rstest::rstest;
+
+	use super::*;
+	use crate::config::default_categories;
+
+	#[rstest]
+	#[case::source("src/main.rs", Some("source"))]
+	#[case::test_dir("tests/cli.rs", Some("test"))]
+	#[case::docs_dir("docs/guide/intro.rs", Some("docs"))]
+	#[case::markdown("README.md", Some("docs"))]
+	#[case::config("Cargo.toml", Some("config"))]
+	#[case::generated_before_test("tests/api_pb2.py", Some("generated"))]
+	fn default_categories_apply_top_to_bottom(#[case] path: &str, #[case] expected: Option<&str>) {
+		let matcher = CategoryMatcher::new(&default_categories()).unwrap();
+		let filename = path.rsplit('/').next().unwrap();
+		assert_eq!(matcher.classify(filename, path).map(AsRef::as_ref), expected);
+	}
+
+	#[test]
+	fn unmatched_files_have_no_category() {
+		let rules = [FileCategory { name: "docs".into(), patterns: vec!["*.md".into()] }];
+		let matcher = CategoryMatcher::new(&rules).unwrap();
+		assert_eq!(matcher.classify("main.rs", "src/main.rs"), None);
+	}
+
+	#[test]
+	fn invalid_pattern_names_its_category() {
+		let rules = [FileCategory { name: "broken".into(), patterns: vec!["[".into()] }];
+		let err = CategoryMatcher::new(&rules).unwrap_err();
+		assert!(err.to_string().contains("broken"), "{err:#}");
+	}
+}
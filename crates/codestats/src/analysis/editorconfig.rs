@@ -0,0 +1,150 @@
+//! Language hints read from the `.editorconfig` at the analysis root.
+//!
+//! Only the `codestats_language` key is read; every other key and the `root` preamble are ignored. A section such as
+//! `[*.tmpl]` with `codestats_language = HTML` assigns matching files to that language ahead of normal detection.
+//! As in `EditorConfig`, a pattern without a `/` matches the file name at any depth, and later sections win.
+
+use std::{fs, io::ErrorKind, path::Path};
+
+use anyhow::{Context as _, Result, bail};
+use globset::{GlobBuilder, GlobMatcher};
+
+use crate::langs::{LANGUAGES, Language};
+
+/// File name looked up in the analysis root.
+const FILE_NAME: &str = ".editorconfig";
+/// Section key naming the language for matching files.
+const LANGUAGE_KEY: &str = "codestats_language";
+
+/// Glob-to-language assignments that take precedence over detection.
+#[derive(Debug)]
+pub(super) struct LanguageOverrides {
+	rules: Vec<(String, GlobMatcher, &'static Language)>,
+}
+
+impl LanguageOverrides {
+	/// Read `.editorconfig` from `root`, or from its directory when `root` is a file, returning `None` when it does
+	/// not exist or assigns no languages.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the file cannot be read, a section glob is invalid, or a language is unknown.
+	pub(super) fn load(root: &Path) -> Result<Option<Self>> {
+		let dir = root.parent().filter(|_| root.is_file()).unwrap_or(root);
+		let path = dir.join(FILE_NAME);
+		let contents = match fs::read_to_string(&path) {
+			Ok(contents) => contents,
+			Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+			Err(err) => return Err(err).with_context(|| format!("Failed to read {}", path.display())),
+		};
+		let overrides = Self::parse(&contents).with_context(|| format!("Invalid {}", path.display()))?;
+		Ok((!overrides.rules.is_empty()).then_some(overrides))
+	}
+
+	fn parse(contents: &str) -> Result<Self> {
+		let mut rules = Vec::new();
+		let mut section: Option<&str> = None;
+		for (idx, line) in contents.lines().enumerate() {
+			let line = line.trim();
+			if line.is_empty() || line.starts_with(['#', ';']) {
+				continue;
+			}
+			if let Some(pattern) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+				section = Some(pattern);
+				continue;
+			}
+			let Some((key, value)) = line.split_once('=') else {
+				continue;
+			};
+			if !key.trim().eq_ignore_ascii_case(LANGUAGE_KEY) {
+				continue;
+			}
+			let Some(pattern) = section else {
+				bail!("line {}: `{LANGUAGE_KEY}` must be inside a section", idx + 1);
+			};
+			let name = value.trim();
+			let Some(language) = LANGUAGES.iter().find(|lang| lang.name.eq_ignore_ascii_case(name)) else {
+				bail!("line {}: unknown language `{name}`", idx + 1);
+			};
+			rules.push((pattern.to_owned(), section_glob(pattern)?, language));
+		}
+		Ok(Self { rules })
+	}
+
+	/// Language assigned to the file at `path` (relative to the analysis root, `/`-separated), if any.
+	pub(super) fn language_for(&self, path: &str) -> Option<&'static Language> {
+		self.rules.iter().rev().find(|(_, glob, _)| glob.is_match(path)).map(|&(_, _, language)| language)
+	}
+
+	/// The `(section, language)` pairs in file order, used to invalidate cached detection results.
+	pub(super) fn rules(&self) -> impl Iterator<Item = (&str, &'static str)> {
+		self.rules.iter().map(|(pattern, _, language)| (pattern.as_str(), language.name))
+	}
+}
+
+/// Translate an `EditorConfig` section pattern into a glob over root-relative paths.
+fn section_glob(pattern: &str) -> Result<GlobMatcher> {
+	let glob = match pattern.strip_prefix('/') {
+		Some(anchored) => anchored.to_owned(),
+		None if pattern.contains('/') => pattern.to_owned(),
+		None => format!("**/{pattern}"),
+	};
+	Ok(GlobBuilder::new(&glob)
+		.literal_separator(true)
+		.build()
+		.with_context(|| format!("Invalid section pattern `[{pattern}]`"))?
+		.compile_matcher())
+}
+
+#[cfg(test)]
+mod tests {
+	use rstest::rstest;
+	use tempfile::TempDir;
+
+	use super::*;
+
+	const CONFIG: &str = "root = true
+
+[*]
+indent_style = tab
+
+# Templates are HTML with a custom extension.
+[*.{tmpl,tpl}]
+codestats_language = html
+
+[/scripts/*.run]
+codestats_language = bash
+
+[vendor/legacy.tpl]
+codestats_language = JavaScript
+";
+
+	#[rstest]
+	#[case::basename_any_depth("web/views/index.tmpl", Some("HTML"))]
+	#[case::brace_alternative("page.tpl", Some("HTML"))]
+	#[case::anchored("scripts/build.run", Some("Bash"))]
+	#[case::anchored_not_nested("tools/scripts/build.run", None)]
+	#[case::later_section_wins("vendor/legacy.tpl", Some("JavaScript"))]
+	#[case::unlisted("src/main.rs", None)]
+	fn language_for_matches_sections(#[case] path: &str, #[case] expected: Option<&str>) {
+		let overrides = LanguageOverrides::parse(CONFIG).unwrap();
+		assert_eq!(overrides.language_for(path).map(|lang| lang.name), expected);
+	}
+
+	#[test]
+	fn parse_rejects_unknown_languages_and_keys_outside_sections() {
+		let err = LanguageOverrides::parse("[*.x]\ncodestats_language = Nope\n").unwrap_err();
+		assert!(err.to_string().contains("unknown language `Nope`"), "{err}");
+		let err = LanguageOverrides::parse("codestats_language = Rust\n").unwrap_err();
+		assert!(err.to_string().contains("inside a section"), "{err}");
+	}
+
+	#[test]
+	fn load_reads_the_directory_of_a_file_root() {
+		let dir = TempDir::new().unwrap();
+		fs::write(dir.path().join(FILE_NAME), CONFIG).unwrap();
+		fs::write(dir.path().join("page.tpl"), "<p></p>\n").unwrap();
+		let overrides = LanguageOverrides::load(&dir.path().join("page.tpl")).unwrap().expect("overrides");
+		assert_eq!(overrides.language_for("page.tpl").map(|lang| lang.name), Some("HTML"));
+	}
+}
@@ -0,0 +1,167 @@
+//! Attribution of embedded code regions to the language they are written in.
+//!
+//! Only a few well-known containers are recognized: fenced code blocks in Markdown, and `<script>`/`<style>`
+//! elements in HTML. Delimiter lines (the fences and tags themselves) stay with the host language.
+
+use crate::langs::{self, LANGUAGES, Language};
+
+#[derive(Debug, Clone, Copy)]
+enum Container {
+	Markdown,
+	Html,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Closer {
+	/// A Markdown fence made of at least `len` repetitions of `marker`.
+	Fence { marker: u8, len: usize },
+	/// An HTML closing tag, lowercase.
+	Tag(&'static str),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Region {
+	/// `None` when the fence names a language we do not know, in which case lines stay with the host.
+	language: Option<&'static Language>,
+	closer: Closer,
+}
+
+/// Tracks which embedded region, if any, the current line of a container file belongs to.
+#[derive(Debug)]
+pub(super) struct EmbeddedScanner {
+	container: Container,
+	active: Option<Region>,
+}
+
+impl EmbeddedScanner {
+	/// Create a scanner for `host`, or `None` if the language cannot embed other languages.
+	pub(super) fn for_language(host: &Language) -> Option<Self> {
+		let container = match host.name {
+			"Markdown" => Container::Markdown,
+			"HTML" => Container::Html,
+			_ => return None,
+		};
+		Some(Self { container, active: None })
+	}
+
+	/// Return the embedded language `line` belongs to, or `None` if it belongs to the host file.
+	pub(super) fn route(&mut self, line: &str) -> Option<&'static Language> {
+		if let Some(region) = self.active {
+			if closes(line, region.closer) {
+				self.active = None;
+				return None;
+			}
+			return region.language;
+		}
+		self.active = match self.container {
+			Container::Markdown => open_fence(line),
+			Container::Html => open_tag(line),
+		};
+		None
+	}
+}
+
+fn closes(line: &str, closer: Closer) -> bool {
+	match closer {
+		Closer::Fence { marker, len } => {
+			let trimmed = line.trim();
+			let run = trimmed.bytes().take_while(|&b| b == marker).count();
+			run >= len && run == trimmed.len()
+		}
+		Closer::Tag(tag) => line.to_ascii_lowercase().contains(tag),
+	}
+}
+
+fn open_fence(line: &str) -> Option<Region> {
+	let trimmed = line.trim_start();
+	let marker = *trimmed.as_bytes().first().filter(|&&b| b == b'`' || b == b'~')?;
+	let len = trimmed.bytes().take_while(|&b| b == marker).count();
+	if len < 3 {
+		return None;
+	}
+	let info = trimmed[len..].trim().trim_start_matches(['{', '.']);
+	let name = info.split(|c: char| c.is_whitespace() || c == ',' || c == '}').next().unwrap_or_default();
+	Some(Region { language: fence_language(name), closer: Closer::Fence { marker, len } })
+}
+
+/// Resolve a fence info string such as `rust` or `py` to a language, by name first and then by file extension.
+fn fence_language(name: &str) -> Option<&'static Language> {
+	if name.is_empty() {
+		return None;
+	}
+	LANGUAGES
+		.iter()
+		.find(|lang| lang.name.eq_ignore_ascii_case(name))
+		.or_else(|| langs::detect_language_info(&format!("file.{}", name.to_ascii_lowercase()), None))
+}
+
+fn open_tag(line: &str) -> Option<Region> {
+	let lower = line.to_ascii_lowercase();
+	let (language, open, close) = if lower.contains("<script") {
+		("JavaScript", "<script", "</script")
+	} else if lower.contains("<style") {
+		("CSS", "<style", "</style")
+	} else {
+		return None;
+	};
+	let start = lower.find(open)?;
+	// An element opened and closed on the same line has no body lines to attribute.
+	if lower[start..].contains(close) {
+		return None;
+	}
+	let language = LANGUAGES.iter().find(|lang| lang.name == language);
+	Some(Region { language, closer: Closer::Tag(close) })
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn language(name: &str) -> &'static Language {
+		LANGUAGES.iter().find(|lang| lang.name == name).unwrap()
+	}
+
+	fn route_all(host: &str, lines: &[&str]) -> Vec<Option<&'static str>> {
+		let mut scanner = EmbeddedScanner::for_language(language(host)).expect("container language");
+		lines.iter().map(|line| scanner.route(line).map(|lang| lang.name)).collect()
+	}
+
+	#[test]
+	fn non_container_languages_have_no_scanner() {
+		assert!(EmbeddedScanner::for_language(language("Rust")).is_none());
+	}
+
+	#[test]
+	fn markdown_fences_route_to_info_string_language() {
+		let routed = route_all("Markdown", &["# Title", "```rust", "fn main() {}", "```", "text"]);
+		assert_eq!(routed, [None, None, Some("Rust"), None, None]);
+	}
+
+	#[test]
+	fn markdown_fences_resolve_extensions_and_tildes() {
+		let routed = route_all("Markdown", &["~~~~ py title=x", "print(1)", "~~~", "still python", "~~~~"]);
+		assert_eq!(routed, [None, Some("Python"), Some("Python"), Some("Python"), None]);
+	}
+
+	#[test]
+	fn markdown_fences_without_known_language_stay_with_host() {
+		let routed = route_all("Markdown", &["```", "plain", "```", "```nosuchlang", "x", "```"]);
+		assert_eq!(routed, [None, None, None, None, None, None]);
+	}
+
+	#[test]
+	fn html_script_and_style_bodies_are_embedded() {
+		let lines = [
+			"<html>",
+			"<script type=\"module\">",
+			"let x = 1;",
+			"</script>",
+			"<STYLE>",
+			"body { margin: 0; }",
+			"</style>",
+			"<script src=\"a.js\"></script>",
+		];
+		let routed = route_all("HTML", &lines);
+		assert_eq!(routed, [None, None, Some("JavaScript"), None, None, Some("CSS"), None, None]);
+	}
+}
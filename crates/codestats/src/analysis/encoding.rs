@@ -4,11 +4,10 @@ use anyhow::Result;
 use encoding_rs::{CoderResult, Decoder, Encoding, UTF_8, UTF_16BE, UTF_16LE};
 use memchr::memchr;
 
-use super::{line_classifier::CommentState, line_counter::LineCounts, stats::AnalysisResults};
-use crate::langs::Language;
+use super::line_counter::{ContentHash, FileCounter, FileCounts, FileTarget};
 
-/// Percentage of non-text bytes in a sample that indicates a binary file.
-const BINARY_THRESHOLD_PERCENT: usize = 20;
+/// Percentage of control bytes in a sample above which a file is treated as binary, unless configured otherwise.
+pub const DEFAULT_BINARY_THRESHOLD: u8 = 20;
 /// Chunk size for incremental UTF-16 decoding.
 const UTF16_DECODE_CHUNK_SIZE: usize = 8 * 1024;
 
@@ -18,6 +17,13 @@ pub(super) struct FileEncoding {
 	pub(super) bom_len: usize,
 }
 
+impl FileEncoding {
+	/// Bytes one ASCII character such as `\r` takes on disk.
+	pub(super) fn code_unit_len(self) -> u64 {
+		if is_utf16(self.encoding) { 2 } else { 1 }
+	}
+}
+
 pub(super) fn detect_encoding(samples: &[u8]) -> FileEncoding {
 	if let Some((encoding, bom_len)) = Encoding::for_bom(samples) {
 		FileEncoding { encoding, bom_len }
@@ -26,6 +32,12 @@ pub(super) fn detect_encoding(samples: &[u8]) -> FileEncoding {
 	}
 }
 
+/// Use `encoding` for a file whatever its content, skipping a leading byte-order mark only when it is `encoding`'s own.
+pub(super) fn forced_encoding(encoding: &'static Encoding, samples: &[u8]) -> FileEncoding {
+	let bom_len = Encoding::for_bom(samples).filter(|&(found, _)| found == encoding).map_or(0, |(_, len)| len);
+	FileEncoding { encoding, bom_len }
+}
+
 pub(super) fn decode_bytes(bytes: &[u8], encoding: FileEncoding, strip_bom: bool) -> Cow<'_, str> {
 	let mut slice = bytes;
 	if strip_bom && encoding.bom_len > 0 && slice.len() >= encoding.bom_len {
@@ -35,7 +47,8 @@ pub(super) fn decode_bytes(bytes: &[u8], encoding: FileEncoding, strip_bom: bool
 	decoded
 }
 
-pub(super) fn is_probably_binary(sample: &[u8], encoding: FileEncoding) -> bool {
+/// Treat `sample` as binary when it holds a NUL byte or more than `threshold` percent control bytes.
+pub(super) fn is_probably_binary(sample: &[u8], encoding: FileEncoding, threshold: u8) -> bool {
 	if sample.is_empty() {
 		return false;
 	}
@@ -44,7 +57,13 @@ pub(super) fn is_probably_binary(sample: &[u8], encoding: FileEncoding) -> bool
 	}
 	let non_text = sample.iter().filter(|b| matches!(**b, 0x00..=0x08 | 0x0B | 0x0C | 0x0E..=0x1F | 0x7F)).count();
 	let non_text_pct = non_text * 100 / sample.len();
-	non_text_pct > BINARY_THRESHOLD_PERCENT || sample.contains(&0)
+	non_text_pct > usize::from(threshold) || sample.contains(&0)
+}
+
+/// Binary check for a file whose encoding was forced: the user vouches that files are text, so only a NUL byte, which
+/// no single-byte or multi-byte legacy encoding uses for text, marks one as binary.
+pub(super) fn is_binary_despite_forced_encoding(sample: &[u8], encoding: FileEncoding) -> bool {
+	!is_utf16(encoding.encoding) && sample.contains(&0)
 }
 
 pub(super) fn is_utf16(encoding: &'static Encoding) -> bool {
@@ -71,18 +90,8 @@ fn detect_utf16_without_bom(samples: &[u8]) -> Option<FileEncoding> {
 	None
 }
 
-pub(super) fn process_utf16_bytes(
-	display_path: &str,
-	file_size: u64,
-	results: &mut AnalysisResults,
-	collect_details: bool,
-	language: &'static Language,
-	encoding: FileEncoding,
-	bytes: &[u8],
-) {
-	use super::line_counter::finish_file_stats;
-	let mut line_counts = LineCounts::default();
-	let mut comment_state = CommentState::new();
+pub(super) fn process_utf16_bytes(target: &FileTarget, encoding: FileEncoding, bytes: &[u8]) -> FileCounts {
+	let mut counter = FileCounter::new(target, encoding);
 	let mut is_first_line = true;
 	let mut decoder = encoding.encoding.new_decoder_without_bom_handling();
 	let mut pending = String::new();
@@ -95,38 +104,40 @@ pub(super) fn process_utf16_bytes(
 		decode_to_string(&mut decoder, chunk, false, &mut output);
 		pending.push_str(&output);
 		output.clear();
-		drain_lines(&mut pending, language, &mut line_counts, &mut comment_state, &mut is_first_line, false);
+		drain_lines(&mut pending, &mut counter, &mut is_first_line, false);
 	}
 	decode_to_string(&mut decoder, &[], true, &mut output);
 	pending.push_str(&output);
 	output.clear();
-	drain_lines(&mut pending, language, &mut line_counts, &mut comment_state, &mut is_first_line, true);
-	finish_file_stats(display_path, file_size, results, collect_details, language, &line_counts);
+	drain_lines(&mut pending, &mut counter, &mut is_first_line, true);
+	let hash = target.hasher().map(|mut hasher| {
+		hasher.update(bytes);
+		hasher.finish()
+	});
+	FileCounts { hash, ..counter.finish(target.size) }
 }
 
 pub(super) fn process_utf16_stream<R: Read>(
-	display_path: &str,
-	file_size: u64,
-	results: &mut AnalysisResults,
-	collect_details: bool,
-	language: &'static Language,
+	target: &FileTarget,
 	encoding: FileEncoding,
 	reader: &mut R,
-) -> Result<()> {
-	use super::line_counter::finish_file_stats;
-	let mut line_counts = LineCounts::default();
-	let mut comment_state = CommentState::new();
+) -> Result<FileCounts> {
+	let mut counter = FileCounter::new(target, encoding);
 	let mut is_first_line = true;
 	let mut decoder = encoding.encoding.new_decoder_without_bom_handling();
 	let mut pending = String::new();
 	let mut output = String::new();
 	let mut buffer = vec![0u8; UTF16_DECODE_CHUNK_SIZE];
 	let mut skip_bom = encoding.bom_len;
+	let mut hasher = target.hasher();
 	loop {
 		let read = reader.read(&mut buffer)?;
 		if read == 0 {
 			break;
 		}
+		if let Some(hasher) = &mut hasher {
+			hasher.update(&buffer[..read]);
+		}
 		let mut slice = &buffer[..read];
 		if skip_bom > 0 {
 			if read <= skip_bom {
@@ -139,14 +150,13 @@ pub(super) fn process_utf16_stream<R: Read>(
 		decode_to_string(&mut decoder, slice, false, &mut output);
 		pending.push_str(&output);
 		output.clear();
-		drain_lines(&mut pending, language, &mut line_counts, &mut comment_state, &mut is_first_line, false);
+		drain_lines(&mut pending, &mut counter, &mut is_first_line, false);
 	}
 	decode_to_string(&mut decoder, &[], true, &mut output);
 	pending.push_str(&output);
 	output.clear();
-	drain_lines(&mut pending, language, &mut line_counts, &mut comment_state, &mut is_first_line, true);
-	finish_file_stats(display_path, file_size, results, collect_details, language, &line_counts);
-	Ok(())
+	drain_lines(&mut pending, &mut counter, &mut is_first_line, true);
+	Ok(FileCounts { hash: hasher.map(ContentHash::finish), ..counter.finish(target.size) })
 }
 
 fn decode_to_string(decoder: &mut Decoder, chunk: &[u8], last: bool, output: &mut String) {
@@ -162,26 +172,19 @@ fn decode_to_string(decoder: &mut Decoder, chunk: &[u8], last: bool, output: &mu
 	}
 }
 
-fn drain_lines(
-	pending: &mut String,
-	language: &'static Language,
-	line_counts: &mut LineCounts,
-	comment_state: &mut CommentState,
-	is_first_line: &mut bool,
-	flush_final: bool,
-) {
+fn drain_lines(pending: &mut String, counter: &mut FileCounter, is_first_line: &mut bool, flush_final: bool) {
 	let mut start = 0usize;
 	let bytes = pending.as_bytes();
 	while let Some(pos) = memchr(b'\n', &bytes[start..]) {
 		let line_end = start + pos + 1;
 		let line = &pending[start..line_end];
-		line_counts.classify_and_count(line, Some(language), comment_state, *is_first_line);
+		counter.count_line(line, *is_first_line);
 		*is_first_line = false;
 		start = line_end;
 	}
 	if flush_final && start < pending.len() {
 		let line = &pending[start..];
-		line_counts.classify_and_count(line, Some(language), comment_state, *is_first_line);
+		counter.count_line(line, *is_first_line);
 		*is_first_line = false;
 		start = pending.len();
 	}
@@ -211,7 +214,40 @@ mod tests {
 		#[case] expected: bool,
 	) {
 		let file_encoding = FileEncoding { encoding, bom_len };
-		assert_eq!(is_probably_binary(&sample, file_encoding), expected);
+		assert_eq!(is_probably_binary(&sample, file_encoding, DEFAULT_BINARY_THRESHOLD), expected);
+	}
+
+	#[rstest]
+	#[case::strict_threshold_flags_a_few_control_bytes(5, true)]
+	#[case::default_threshold_lets_them_pass(DEFAULT_BINARY_THRESHOLD, false)]
+	#[case::full_threshold_only_flags_nul(100, false)]
+	fn test_binary_threshold(#[case] threshold: u8, #[case] expected: bool) {
+		let sample = [b"\x01\x02".as_slice(), &[b'a'; 18]].concat();
+		let file_encoding = FileEncoding { encoding: UTF_8, bom_len: 0 };
+		assert_eq!(is_probably_binary(&sample, file_encoding, threshold), expected);
+	}
+
+	#[rstest]
+	#[case::no_bom(b"// text".to_vec(), 0)]
+	#[case::own_bom(vec![0xEF, 0xBB, 0xBF, b'a'], 3)]
+	#[case::other_bom(vec![0xFF, 0xFE, b'a', 0x00], 0)]
+	fn test_forced_encoding_skips_only_its_own_bom(#[case] sample: Vec<u8>, #[case] bom_len: usize) {
+		let forced = forced_encoding(UTF_8, &sample);
+		assert_eq!(forced.encoding, UTF_8);
+		assert_eq!(forced.bom_len, bom_len);
+	}
+
+	#[rstest]
+	#[case::control_bytes_are_text(UTF_8, vec![0x01, 0x02, 0x03, b'a'], false)]
+	#[case::nul_is_binary(UTF_8, vec![b'a', 0x00], true)]
+	#[case::utf16_nul_is_text(UTF_16LE, vec![b'a', 0x00], false)]
+	fn test_is_binary_despite_forced_encoding(
+		#[case] encoding: &'static Encoding,
+		#[case] sample: Vec<u8>,
+		#[case] expected: bool,
+	) {
+		let file_encoding = FileEncoding { encoding, bom_len: 0 };
+		assert_eq!(is_binary_despite_forced_encoding(&sample, file_encoding), expected);
 	}
 
 	#[rstest]
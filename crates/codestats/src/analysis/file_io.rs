@@ -1,7 +1,9 @@
 use std::{
 	fs::File,
-	io::{BufRead, BufReader, Read, Seek, SeekFrom},
+	io::{self, BufRead, BufReader, Read, Seek, SeekFrom},
 	path::Path,
+	sync::{Condvar, Mutex, PoisonError},
+	time::Duration,
 };
 
 use anyhow::{Context as _, Result};
@@ -9,13 +11,17 @@ use memmap2::Mmap;
 
 use super::{
 	encoding::{self, FileEncoding},
-	line_counter,
-	stats::AnalysisResults,
+	line_counter::{self, ContentHash, FileCounts, FileTarget},
+	notebook,
 };
-use crate::langs::Language;
 
+/// Lines longer than this are cut short and the rest of the line skipped, so one enormous line (as in minified
+/// JavaScript) cannot grow the line buffer to the size of the file. Files with such a line are counted as minified.
+pub(super) const MAX_LINE_BYTES: usize = 1024 * 1024;
 /// Files larger than this threshold are memory-mapped instead of buffered.
 const MMAP_THRESHOLD: u64 = 256 * 1024;
+/// Files modified more recently than this are read buffered, since they may still be being written.
+const SETTLE_TIME: Duration = Duration::from_secs(2);
 /// Size of sample chunks extracted from files for binary/language detection. For large files, we sample from both the start and middle of the file.
 const SAMPLE_SIZE: usize = 4 * 1024;
 const MAX_SAMPLE: usize = SAMPLE_SIZE * 2;
@@ -40,19 +46,65 @@ impl std::ops::Deref for SampleBuf {
 }
 
 pub(super) trait LineSource {
+	/// Call `f` with each line, including its ending, cut short after [`MAX_LINE_BYTES`].
 	fn for_each_line<F>(&mut self, f: &mut F) -> Result<()>
 	where
 		F: FnMut(&[u8]);
+
+	/// Whether a line was cut short at [`MAX_LINE_BYTES`], which marks the file as minified.
+	fn truncated(&self) -> bool;
+
+	/// [`ContentHash`] of every byte consumed, skipped line tails included, present only when hashing was requested.
+	fn hash(&self) -> Option<u64>;
 }
 
 pub(super) struct BufLineSource<R: BufRead> {
 	reader: R,
 	buffer: Vec<u8>,
+	truncated: bool,
+	hasher: Option<ContentHash>,
 }
 
 impl<R: BufRead> BufLineSource<R> {
 	pub(super) fn new(reader: R) -> Self {
-		Self { reader, buffer: Vec::with_capacity(1024) }
+		Self { reader, buffer: Vec::with_capacity(1024), truncated: false, hasher: None }
+	}
+
+	/// Hash every byte consumed with `hasher`, when there is one.
+	pub(super) const fn with_hasher(mut self, hasher: Option<ContentHash>) -> Self {
+		self.hasher = hasher;
+		self
+	}
+
+	/// Read the next line into the buffer, keeping at most [`MAX_LINE_BYTES`] of it and skipping the rest.
+	///
+	/// Returns the number of bytes consumed from the reader, which is zero at the end of the input.
+	fn read_line(&mut self) -> io::Result<usize> {
+		self.buffer.clear();
+		let mut consumed = 0;
+		loop {
+			let available = match self.reader.fill_buf() {
+				Ok(available) => available,
+				Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+				Err(err) => return Err(err),
+			};
+			if available.is_empty() {
+				return Ok(consumed);
+			}
+			let newline = memchr::memchr(b'\n', available);
+			let end = newline.map_or(available.len(), |offset| offset + 1);
+			let room = MAX_LINE_BYTES - self.buffer.len();
+			self.truncated |= end > room;
+			self.buffer.extend_from_slice(&available[..end.min(room)]);
+			if let Some(hasher) = &mut self.hasher {
+				hasher.update(&available[..end]);
+			}
+			self.reader.consume(end);
+			consumed += end;
+			if newline.is_some() {
+				return Ok(consumed);
+			}
+		}
 	}
 }
 
@@ -61,26 +113,37 @@ impl<R: BufRead> LineSource for BufLineSource<R> {
 	where
 		F: FnMut(&[u8]),
 	{
-		loop {
-			self.buffer.clear();
-			let bytes_read = self.reader.read_until(b'\n', &mut self.buffer)?;
-			if bytes_read == 0 {
-				break;
-			}
+		while self.read_line()? > 0 {
 			f(&self.buffer);
 		}
 		Ok(())
 	}
+
+	fn truncated(&self) -> bool {
+		self.truncated
+	}
+
+	fn hash(&self) -> Option<u64> {
+		self.hasher.map(ContentHash::finish)
+	}
 }
 
 pub(super) struct MmapLineSource<'a> {
 	bytes: &'a [u8],
 	pos: usize,
+	truncated: bool,
+	hasher: Option<ContentHash>,
 }
 
 impl<'a> MmapLineSource<'a> {
 	pub(super) const fn new(bytes: &'a [u8]) -> Self {
-		Self { bytes, pos: 0 }
+		Self { bytes, pos: 0, truncated: false, hasher: None }
+	}
+
+	/// Hash every byte consumed with `hasher`, when there is one.
+	pub(super) const fn with_hasher(mut self, hasher: Option<ContentHash>) -> Self {
+		self.hasher = hasher;
+		self
 	}
 }
 
@@ -93,61 +156,138 @@ impl LineSource for MmapLineSource<'_> {
 			let line_end =
 				memchr::memchr(b'\n', &self.bytes[self.pos..]).map_or(self.bytes.len(), |offset| self.pos + offset + 1);
 			let line_bytes = &self.bytes[self.pos..line_end];
-			f(line_bytes);
+			self.truncated |= line_bytes.len() > MAX_LINE_BYTES;
+			if let Some(hasher) = &mut self.hasher {
+				hasher.update(line_bytes);
+			}
+			f(&line_bytes[..line_bytes.len().min(MAX_LINE_BYTES)]);
 			self.pos = line_end;
 		}
 		Ok(())
 	}
+
+	fn truncated(&self) -> bool {
+		self.truncated
+	}
+
+	fn hash(&self) -> Option<u64> {
+		self.hasher.map(ContentHash::finish)
+	}
 }
 
 pub(super) enum FileSource {
 	Buffered(File),
 	Mapped(Mmap),
+	/// Contents already read into memory, such as an archive member.
+	#[cfg(feature = "archive")]
+	Memory(Vec<u8>),
 }
 
 impl FileSource {
+	/// Open the file at `file_path`, whose size was `file_size` when the walk found it.
+	///
+	/// Large files are memory-mapped, unless they look like they are still being written: the size no longer matches
+	/// `file_size`, or the file was modified within [`SETTLE_TIME`]. Those are read buffered instead, as is a file
+	/// whose mapping comes out at a different length than expected.
 	pub(super) fn open(file_path: &Path, file_size: u64) -> Result<Self> {
 		let file = File::open(file_path).with_context(|| format!("Failed to open file {}", file_path.display()))?;
-		if file_size >= MMAP_THRESHOLD {
-			// SAFETY: Memory-mapping is safe under these conditions:
-			// 1. We only read from the mmap, never write.
-			// 2. The mapping is dropped before returning, so no references escape.
-			// 3. ASSUMPTION: The file will not be modified by external processes during analysis. This is a reasonable assumption for typical code analysis workflows where files are stable during the scan. However, concurrent modifications by other processes could cause undefined behavior.
-			let mmap = unsafe { Mmap::map(&file) }
-				.with_context(|| format!("Failed to memory-map file {}", file_path.display()))?;
-			Ok(Self::Mapped(mmap))
-		} else {
-			Ok(Self::Buffered(file))
+		if file_size < MMAP_THRESHOLD || !is_settled(&file, file_size) {
+			return Ok(Self::Buffered(file));
+		}
+		// SAFETY: Memory-mapping is safe under these conditions:
+		// 1. We only read from the mmap, never write.
+		// 2. The mapping is dropped before returning, so no references escape.
+		// 3. ASSUMPTION: The file will not be modified by external processes during analysis. The size and mtime checks
+		//    above skip files that are visibly changing, which narrows the window, but cannot close it: a file truncated
+		//    after the checks still makes reads past its new end fault (SIGBUS on Unix) and abort the process.
+		let mmap = unsafe { Mmap::map(&file) }
+			.with_context(|| format!("Failed to memory-map file {}", file_path.display()))?;
+		if mmap.len() as u64 != file_size {
+			drop(mmap);
+			return Ok(Self::Buffered(file));
 		}
+		Ok(Self::Mapped(mmap))
 	}
 
 	pub(super) fn sample(&mut self, file_size: u64) -> Result<SampleBuf> {
 		match self {
 			Self::Buffered(file) => sample_file(file, file_size),
 			Self::Mapped(mmap) => Ok(sample_from_slice(mmap)),
+			#[cfg(feature = "archive")]
+			Self::Memory(bytes) => Ok(sample_from_slice(bytes)),
 		}
 	}
 
-	pub(super) fn process(
-		self,
-		display_path: &str,
-		file_size: u64,
-		results: &mut AnalysisResults,
-		collect_details: bool,
-		language: &'static Language,
-		encoding: FileEncoding,
-	) -> Result<()> {
+	pub(super) fn process(self, target: &FileTarget, encoding: FileEncoding) -> Result<FileCounts> {
+		if notebook::is_notebook(target.language) {
+			return match self {
+				Self::Buffered(mut file) => {
+					let mut bytes = Vec::new();
+					file.read_to_end(&mut bytes)?;
+					process_notebook(target, encoding, &bytes)
+				}
+				Self::Mapped(mmap) => process_notebook(target, encoding, &mmap),
+				#[cfg(feature = "archive")]
+				Self::Memory(bytes) => process_notebook(target, encoding, &bytes),
+			};
+		}
 		match self {
-			Self::Buffered(file) => {
-				process_file_buffered(display_path, file, file_size, results, collect_details, language, encoding)
-			}
-			Self::Mapped(mmap) => {
-				process_file_mmap(display_path, file_size, results, collect_details, language, encoding, &mmap)
-			}
+			Self::Buffered(file) => process_file_buffered(target, file, encoding),
+			Self::Mapped(mmap) => process_file_mmap(target, encoding, &mmap),
+			#[cfg(feature = "archive")]
+			Self::Memory(bytes) => process_bytes(target, encoding, &bytes),
 		}
 	}
 }
 
+/// Whether `file` still has the size the walk saw and has not been modified within [`SETTLE_TIME`], so it is unlikely
+/// to change while mapped. A file whose metadata cannot be read is treated as unsettled.
+/// Counting semaphore bounding how many files are open for reading at once, independently of the walker threads.
+///
+/// On high-latency storage (NFS, SMB, cloud mounts) reads from every walker thread at once queue up on the network,
+/// so capping them keeps the walk moving without the reads thrashing.
+pub(super) struct IoLimiter {
+	available: Mutex<usize>,
+	released: Condvar,
+}
+
+impl IoLimiter {
+	/// Allow at most `permits` files to be read at once; `0` is treated as `1`.
+	pub(super) fn new(permits: usize) -> Self {
+		Self { available: Mutex::new(permits.max(1)), released: Condvar::new() }
+	}
+
+	/// Block until a read slot is free, holding it until the returned guard is dropped.
+	pub(super) fn acquire(&self) -> IoPermit<'_> {
+		let mut available = self.available.lock().unwrap_or_else(PoisonError::into_inner);
+		while *available == 0 {
+			available = self.released.wait(available).unwrap_or_else(PoisonError::into_inner);
+		}
+		*available -= 1;
+		drop(available);
+		IoPermit { limiter: self }
+	}
+}
+
+/// A read slot taken from an [`IoLimiter`], given back on drop.
+pub(super) struct IoPermit<'a> {
+	limiter: &'a IoLimiter,
+}
+
+impl Drop for IoPermit<'_> {
+	fn drop(&mut self) {
+		*self.limiter.available.lock().unwrap_or_else(PoisonError::into_inner) += 1;
+		self.limiter.released.notify_one();
+	}
+}
+
+fn is_settled(file: &File, file_size: u64) -> bool {
+	file.metadata().is_ok_and(|metadata| {
+		metadata.len() == file_size
+			&& metadata.modified().is_ok_and(|modified| modified.elapsed().is_ok_and(|elapsed| elapsed >= SETTLE_TIME))
+	})
+}
+
 fn sample_ranges(file_len: u64) -> (usize, Option<(u64, usize)>) {
 	let start_len = usize::try_from(file_len.min(SAMPLE_SIZE as u64))
 		.expect("SAMPLE_SIZE is 4096, well within usize range on any supported platform");
@@ -193,64 +333,71 @@ fn sample_from_slice(file_bytes: &[u8]) -> SampleBuf {
 	buf
 }
 
-fn process_file_buffered(
-	display_path: &str,
-	file: File,
-	file_size: u64,
-	results: &mut AnalysisResults,
-	collect_details: bool,
-	language: &'static Language,
-	encoding: FileEncoding,
-) -> Result<()> {
+fn process_file_buffered(target: &FileTarget, file: File, encoding: FileEncoding) -> Result<FileCounts> {
 	if encoding::is_utf16(encoding.encoding) {
 		let mut reader = BufReader::with_capacity(64 * 1024, file);
-		return encoding::process_utf16_stream(
-			display_path,
-			file_size,
-			results,
-			collect_details,
-			language,
-			encoding,
-			&mut reader,
-		);
+		return encoding::process_utf16_stream(target, encoding, &mut reader);
 	}
 	let reader = BufReader::with_capacity(64 * 1024, file);
-	let mut source = BufLineSource::new(reader);
-	line_counter::process_lines(display_path, file_size, results, collect_details, language, encoding, &mut source)
+	let mut source = BufLineSource::new(reader).with_hasher(target.hasher());
+	line_counter::process_lines(target, encoding, &mut source)
+}
+
+fn process_file_mmap(target: &FileTarget, encoding: FileEncoding, mmap: &Mmap) -> Result<FileCounts> {
+	process_bytes(target, encoding, mmap.as_ref())
 }
 
-fn process_file_mmap(
-	display_path: &str,
-	file_size: u64,
-	results: &mut AnalysisResults,
-	collect_details: bool,
-	language: &'static Language,
-	encoding: FileEncoding,
-	mmap: &Mmap,
-) -> Result<()> {
-	let file_bytes = mmap.as_ref();
+/// Count a notebook by cell, falling back to plain line counting when it cannot be parsed.
+fn process_notebook(target: &FileTarget, encoding: FileEncoding, file_bytes: &[u8]) -> Result<FileCounts> {
+	let text = encoding::decode_bytes(file_bytes, encoding, true);
+	let Some(counts) = notebook::count_cells(target, &text) else {
+		return process_bytes(target, encoding, file_bytes);
+	};
+	let hash = target.hasher().map(|mut hasher| {
+		hasher.update(file_bytes);
+		hasher.finish()
+	});
+	Ok(FileCounts { hash, ..counts })
+}
+
+fn process_bytes(target: &FileTarget, encoding: FileEncoding, file_bytes: &[u8]) -> Result<FileCounts> {
 	if encoding::is_utf16(encoding.encoding) {
-		encoding::process_utf16_bytes(
-			display_path,
-			file_size,
-			results,
-			collect_details,
-			language,
-			encoding,
-			file_bytes,
-		);
-		return Ok(());
-	}
-	let mut source = MmapLineSource::new(file_bytes);
-	line_counter::process_lines(display_path, file_size, results, collect_details, language, encoding, &mut source)
+		return Ok(encoding::process_utf16_bytes(target, encoding, file_bytes));
+	}
+	let mut source = MmapLineSource::new(file_bytes).with_hasher(target.hasher());
+	line_counter::process_lines(target, encoding, &mut source)
 }
 
 #[cfg(test)]
 mod tests {
+	use std::time::{SystemTime, UNIX_EPOCH};
+
 	use rstest::rstest;
 
 	use super::*;
 
+	#[test]
+	fn io_limiter_bounds_concurrent_permits() {
+		use std::sync::atomic::{AtomicUsize, Ordering};
+
+		let limiter = IoLimiter::new(2);
+		let active = AtomicUsize::new(0);
+		let peak = AtomicUsize::new(0);
+		std::thread::scope(|scope| {
+			for _ in 0..8 {
+				scope.spawn(|| {
+					let _permit = limiter.acquire();
+					let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+					peak.fetch_max(now, Ordering::SeqCst);
+					std::thread::sleep(Duration::from_millis(5));
+					active.fetch_sub(1, Ordering::SeqCst);
+				});
+			}
+		});
+		assert!(peak.load(Ordering::SeqCst) <= 2);
+		assert_eq!(*limiter.available.lock().unwrap(), 2);
+	}
+
 	#[rstest]
 	#[case::empty(b"" as &[u8], vec![])]
 	#[case::single_no_newline(b"hello" as &[u8], vec![b"hello".to_vec()])]
@@ -306,6 +453,59 @@ mod tests {
 		assert_eq!(&samples[..SAMPLE_SIZE], &data[..SAMPLE_SIZE]);
 	}
 
+	fn line_lengths(source: &mut impl LineSource) -> Vec<usize> {
+		let mut lengths = Vec::new();
+		source.for_each_line(&mut |line| lengths.push(line.len())).unwrap();
+		lengths
+	}
+
+	#[test]
+	fn test_long_lines_are_cut_short_by_both_sources() {
+		let mut data = vec![b'x'; MAX_LINE_BYTES + 10];
+		data.extend_from_slice(b"\nshort\n");
+		let mut buffered = BufLineSource::new(BufReader::with_capacity(4096, data.as_slice()));
+		let mut mapped = MmapLineSource::new(&data);
+		assert_eq!(line_lengths(&mut buffered), [MAX_LINE_BYTES, 6]);
+		assert_eq!(line_lengths(&mut mapped), [MAX_LINE_BYTES, 6]);
+		assert!(buffered.truncated() && mapped.truncated());
+		let mut short = BufLineSource::new(BufReader::new(b"a\nb".as_slice()));
+		assert_eq!(line_lengths(&mut short), [2, 1]);
+		assert!(!short.truncated());
+	}
+
+	#[test]
+	fn test_hashes_cover_the_skipped_part_of_long_lines() {
+		let hash = |data: &[u8]| {
+			let mut buffered =
+				BufLineSource::new(BufReader::with_capacity(4096, data)).with_hasher(Some(ContentHash::new()));
+			let mut mapped = MmapLineSource::new(data).with_hasher(Some(ContentHash::new()));
+			line_lengths(&mut buffered);
+			line_lengths(&mut mapped);
+			let mut whole = ContentHash::new();
+			whole.update(data);
+			assert_eq!(buffered.hash(), Some(whole.finish()));
+			assert_eq!(mapped.hash(), Some(whole.finish()));
+			whole.finish()
+		};
+		let mut data = vec![b'x'; MAX_LINE_BYTES + 10];
+		let before = hash(&data);
+		*data.last_mut().unwrap() = b'y';
+		assert_ne!(hash(&data), before);
+	}
+
+	#[test]
+	fn test_open_maps_only_settled_files() {
+		let unique = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+		let path = std::env::temp_dir().join(format!("codestats_file_io_test_{}_{unique}.txt", std::process::id()));
+		let size = MMAP_THRESHOLD * 2;
+		std::fs::write(&path, vec![b'x'; usize::try_from(size).unwrap()]).unwrap();
+		assert!(matches!(FileSource::open(&path, size).unwrap(), FileSource::Buffered(_)), "just written");
+		File::options().write(true).open(&path).unwrap().set_modified(SystemTime::now() - SETTLE_TIME * 2).unwrap();
+		assert!(matches!(FileSource::open(&path, size).unwrap(), FileSource::Mapped(_)));
+		assert!(matches!(FileSource::open(&path, size + 1).unwrap(), FileSource::Buffered(_)), "size changed");
+		std::fs::remove_file(&path).unwrap();
+	}
+
 	#[test]
 	fn test_buf_line_source_multiple_lines() {
 		use std::io::Cursor;
@@ -1,7 +1,14 @@
-/// Glob patterns for well-known generated files excluded by default.
+use aho_corasick::AhoCorasick;
+use anyhow::{Context as _, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+
+/// Glob patterns for minified assets excluded by default.
 /// Pass `--include-generated` to count these files.
-pub const PATTERNS: &[&str] = &[
-	// Lockfiles
+pub const PATTERNS: &[&str] = &["*.min.js", "*.min.mjs", "*.min.css"];
+
+/// Dependency lockfile names excluded by default, configurable through `lockfile_patterns`.
+/// Pass `--include-lockfiles` or `--include-generated` to count these files.
+pub const DEFAULT_LOCKFILES: &[&str] = &[
 	"Cargo.lock",
 	"package-lock.json",
 	"yarn.lock",
@@ -25,8 +32,98 @@ pub const PATTERNS: &[&str] = &[
 	"cpanfile.snapshot",
 	"gradle.lockfile",
 	".terraform.lock.hcl",
-	// Minified assets
-	"*.min.js",
-	"*.min.mjs",
-	"*.min.css",
 ];
+
+/// Content markers that identify machine-generated source when `exclude_generated` is enabled.
+pub const DEFAULT_MARKERS: &[&str] = &["@generated", "DO NOT EDIT", "Code generated by"];
+
+/// Number of leading lines searched for a content marker. Generators put their notice in the file header, as in Go's
+/// `// Code generated ... DO NOT EDIT.`, so a marker further down is ordinary text such as a string literal.
+const HEADER_LINES: usize = 10;
+
+/// Filename globs that identify machine-generated source when `exclude_generated` is enabled.
+pub const DEFAULT_NAME_PATTERNS: &[&str] = &["*.pb.go", "*_pb2.py", "*_pb2_grpc.py", "*.g.dart", "*.freezed.dart"];
+
+/// Matches files against generated-code markers in their header lines and against filename globs.
+#[derive(Debug)]
+pub struct GeneratedDetector {
+	markers: Option<AhoCorasick>,
+	names: GlobSet,
+}
+
+impl GeneratedDetector {
+	/// Build a detector from content markers and filename glob patterns.
+	///
+	/// # Errors
+	///
+	/// Returns an error if a marker automaton cannot be built or a glob pattern is invalid.
+	pub fn new(markers: &[String], name_patterns: &[String]) -> Result<Self> {
+		let markers = if markers.is_empty() {
+			None
+		} else {
+			Some(AhoCorasick::new(markers).context("Failed to build generated-file markers")?)
+		};
+		let mut builder = GlobSetBuilder::new();
+		for pattern in name_patterns {
+			builder.add(Glob::new(pattern).with_context(|| format!("Invalid generated-file pattern `{pattern}`"))?);
+		}
+		Ok(Self { markers, names: builder.build()? })
+	}
+
+	/// Check whether `filename` matches one of the generated-file globs.
+	#[must_use]
+	pub fn matches_name(&self, filename: &str) -> bool {
+		self.names.is_match(filename)
+	}
+
+	/// Check whether one of the first [`HEADER_LINES`] lines of `content` contains a generated-code marker.
+	#[must_use]
+	pub fn matches_content(&self, content: &str) -> bool {
+		self.markers
+			.as_ref()
+			.is_some_and(|markers| content.lines().take(HEADER_LINES).any(|line| markers.is_match(line)))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use rstest::rstest;
+
+	use super::*;
+
+	fn default_detector() -> GeneratedDetector {
+		let markers: Vec<String> = DEFAULT_MARKERS.iter().map(ToString::to_string).collect();
+		let names: Vec<String> = DEFAULT_NAME_PATTERNS.iter().map(ToString::to_string).collect();
+		GeneratedDetector::new(&markers, &names).unwrap()
+	}
+
+	#[rstest]
+	#[case::go_header("// Code generated by protoc-gen-go. DO NOT EDIT.\npackage foo\n", true)]
+	#[case::at_generated("/* @generated */\nexport const x = 1;\n", true)]
+	#[case::plain("fn main() {}\n", false)]
+	#[case::lowercase_is_not_a_marker("// do not edit lightly\n", false)]
+	#[case::after_a_license_header("// Copyright\n//\n// @generated by tool\nfn a() {}\n", true)]
+	#[case::in_a_string_literal_below_the_header(
+		"use std::io;\n\nfn a() {}\n\nfn b() {}\n\nfn c() {}\n\nfn d() {}\n\nconst M: &str = \"DO NOT EDIT\";\n",
+		false
+	)]
+	#[case::deep_in_the_body(&format!("{}// Code generated by hand, not really\n", "fn a() {}\n".repeat(50)), false)]
+	fn test_matches_content(#[case] content: &str, #[case] expected: bool) {
+		assert_eq!(default_detector().matches_content(content), expected);
+	}
+
+	#[rstest]
+	#[case::protobuf_go("api.pb.go", true)]
+	#[case::protobuf_python("api_pb2.py", true)]
+	#[case::plain_go("main.go", false)]
+	fn test_matches_name(#[case] filename: &str, #[case] expected: bool) {
+		assert_eq!(default_detector().matches_name(filename), expected);
+	}
+
+	#[test]
+	fn empty_markers_never_match_content() {
+		let detector = GeneratedDetector::new(&[], &[]).unwrap();
+		assert!(!detector.matches_content("// @generated"));
+		assert!(!detector.matches_name("api.pb.go"));
+	}
+}
@@ -1,18 +1,25 @@
-use std::borrow::Cow;
+use std::{
+	io::{self, BufRead},
+	iter,
+};
 
 use memchr::{memchr2, memrchr};
+use serde::{Deserialize, Serialize};
 
 use crate::langs::{
-	Language,
-	scoring::{BlockCommentMatchers, language_matchers},
+	self, Language,
+	scoring::{BlockCommentMatchers, StringMatchers, language_matchers},
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum LineType {
 	Code,
 	Comment,
 	Blank,
 	Shebang,
+	/// Code sharing its line with a comment, e.g. a trailing `// note`.
+	Mixed,
 }
 
 impl LineType {
@@ -23,6 +30,7 @@ impl LineType {
 			Self::Comment => "comment",
 			Self::Blank => "blank",
 			Self::Shebang => "shebang",
+			Self::Mixed => "mixed",
 		}
 	}
 
@@ -33,6 +41,7 @@ impl LineType {
 			Self::Comment => "comments",
 			Self::Blank => "blanks",
 			Self::Shebang => "shebangs",
+			Self::Mixed => "mixed",
 		}
 	}
 
@@ -43,23 +52,84 @@ impl LineType {
 			Self::Comment => "Comments",
 			Self::Blank => "Blanks",
 			Self::Shebang => "Shebangs",
+			Self::Mixed => "Mixed",
 		}
 	}
 }
 
-/// Tracks nested block comment state across lines.
+/// How docstrings are counted in languages whose block comments are really triple-quoted strings, such as Python.
+///
+/// A triple-quoted string that opens with nothing but whitespace before it on its line is taken to be a docstring.
+/// One that follows code, as in `x = """`, is an ordinary string literal and always counts as code.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Docstrings {
+	/// Count docstring lines as comments.
+	#[default]
+	Comment,
+	/// Count docstring lines as code, like any other string literal.
+	Code,
+}
+
+impl_names!(Docstrings, "docstrings mode", {
+	Comment => "comment",
+	Code => "code",
+});
+
+/// How shebang lines such as `#!/usr/bin/env python` are tallied.
+///
+/// Other tools disagree on shebangs, so the modes other than [`Shebangs::Separate`] make counts line up with theirs.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Shebangs {
+	/// Count shebang lines in their own category.
+	#[default]
+	Separate,
+	/// Count shebang lines as code.
+	Code,
+	/// Count shebang lines as line comments.
+	Comment,
+	/// Leave shebang lines out of every count, including total lines.
+	Ignore,
+}
+
+impl_names!(Shebangs, "shebang mode", {
+	Separate => "separate",
+	Code => "code",
+	Comment => "comment",
+	Ignore => "ignore",
+});
+
+/// Tracks nested block comment and multi-line string state across lines.
+///
+/// A block comment, raw string or template literal can span many lines, so one state must be threaded through every
+/// [`classify_line`] call for a file, in order. Start each file with a fresh state and never share one between files or
+/// languages.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct CommentState {
 	block_comment_depth: usize,
+	/// The open block is a string literal that followed code, not a docstring.
+	in_string: bool,
+	/// Start pattern of a raw string or template literal left open by an earlier line.
+	open_string: Option<usize>,
+	docstrings: Docstrings,
 }
 
 impl CommentState {
 	#[must_use]
 	#[inline]
-	pub(crate) fn new() -> Self {
+	pub fn new() -> Self {
 		Self::default()
 	}
 
+	/// Count docstrings as `docstrings` says in languages that have them; see [`Docstrings`].
+	#[must_use]
+	#[inline]
+	pub const fn with_docstrings(mut self, docstrings: Docstrings) -> Self {
+		self.docstrings = docstrings;
+		self
+	}
+
 	#[inline]
 	const fn enter_first_block(&mut self) {
 		self.block_comment_depth = 1;
@@ -86,12 +156,36 @@ impl CommentState {
 	}
 }
 
+/// Find the first marker reported by `find` that does not start inside a string literal of `line`.
+///
+/// `find` searches `line` from the given byte offset and returns the position and length of a marker. Without
+/// `strings`, its first result is taken as is.
+#[inline]
+fn find_outside_strings(
+	line: &str,
+	strings: Option<&StringMatchers>,
+	find: impl Fn(usize) -> Option<(usize, usize)>,
+) -> Option<(usize, usize)> {
+	let mut from = 0;
+	loop {
+		let marker = find(from);
+		let Some(strings) = strings else {
+			return marker;
+		};
+		match strings.find(line, from) {
+			Some((start, end)) if marker.is_none_or(|(pos, _)| start < pos) => from = end,
+			_ => return marker,
+		}
+	}
+}
+
 /// Process block comments on a line, updating state and detecting code.
 /// Returns: (`remaining_line_portion`, `has_code_outside_comments`)
 #[inline]
 fn handle_block_comments<'a>(
 	line: &'a str,
 	matchers: &BlockCommentMatchers,
+	strings: Option<&StringMatchers>,
 	comment_state: &mut CommentState,
 	nested: bool,
 ) -> (&'a str, bool) {
@@ -99,7 +193,11 @@ fn handle_block_comments<'a>(
 	let mut has_code = false;
 	while !line_remainder.is_empty() {
 		if !comment_state.is_in_comment() {
-			if let Some((pos, start_len)) = matchers.find_block_start(line_remainder) {
+			let rest = line_remainder;
+			let start = find_outside_strings(rest, strings, |from| {
+				matchers.find_block_start(&rest[from..]).map(|(pos, len)| (from + pos, len))
+			});
+			if let Some((pos, start_len)) = start {
 				if pos > 0 && contains_non_whitespace(&line_remainder[..pos]) {
 					has_code = true;
 				}
@@ -124,66 +222,164 @@ fn handle_block_comments<'a>(
 	(line_remainder, has_code)
 }
 
-/// Classify a line as code, comment, blank, or shebang.
+/// Which kind of comment marker made a line a comment line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentStyle {
+	/// A line-comment marker such as `//` or `#`.
+	Line,
+	/// A block comment such as `/* ... */`, including lines inside one.
+	Block,
+}
+
+/// Classify a line as code, comment, blank, shebang, or mixed (code and a comment on the same line), along with the
+/// comment style of comment lines.
+///
+/// The style is `None` for every line type other than [`LineType::Comment`]. A comment line that touches a block
+/// comment counts as [`CommentStyle::Block`] even if it also holds a line comment.
+///
+/// `comment_state` carries open block comments from one line to the next; see [`CommentState`]. Shebangs are only
+/// recognized when `is_first_line` is set.
 #[inline]
 pub fn classify_line(
 	line: &str,
 	lang_info: Option<&Language>,
 	comment_state: &mut CommentState,
 	is_first_line: bool,
-) -> LineType {
+) -> (LineType, Option<CommentStyle>) {
+	let (line_type, style) = classify(line, lang_info, comment_state, is_first_line);
+	(line_type, (line_type == LineType::Comment).then_some(style))
+}
+
+/// Classify each line read from `reader` as `language`, yielding the line type alongside the line text without its
+/// `\n` or `\r\n` terminator.
+///
+/// The comment state is threaded through the lines internally, so the stream must be consumed in order for
+/// multi-line block comments to be classified correctly. Invalid UTF-8 is replaced rather than rejected, and a leading
+/// byte order mark is dropped, as the analyzer does. Iteration stops after the first read error, which is yielded as
+/// the last item.
+///
+/// Lines holding both code and a comment are always yielded as [`LineType::Mixed`]; the analyzer counts them as code
+/// unless `--split-mixed` is set.
+pub fn classify_reader<R: BufRead>(
+	mut reader: R,
+	language: &Language,
+) -> impl Iterator<Item = io::Result<(LineType, String)>> {
+	let mut comment_state = CommentState::new();
+	let mut is_first_line = true;
+	let mut failed = false;
+	let mut buf = Vec::new();
+	iter::from_fn(move || {
+		if failed {
+			return None;
+		}
+		buf.clear();
+		match reader.read_until(b'\n', &mut buf) {
+			Ok(0) => None,
+			Ok(_) => {
+				let line = String::from_utf8_lossy(&buf);
+				let line = if is_first_line { line.strip_prefix('\u{feff}').unwrap_or(&line) } else { &line };
+				let (line_type, _) = classify_line(line, Some(language), &mut comment_state, is_first_line);
+				is_first_line = false;
+				let text = line.strip_suffix('\n').map_or(line, |rest| rest.strip_suffix('\r').unwrap_or(rest));
+				Some(Ok((line_type, text.to_owned())))
+			}
+			Err(err) => {
+				failed = true;
+				Some(Err(err))
+			}
+		}
+	})
+}
+
+#[inline]
+fn classify(
+	line: &str,
+	lang_info: Option<&Language>,
+	comment_state: &mut CommentState,
+	is_first_line: bool,
+) -> (LineType, CommentStyle) {
 	let trimmed = trim_ascii(line);
 	if trimmed.is_empty() {
-		return LineType::Blank;
+		return (LineType::Blank, CommentStyle::Line);
 	}
 	if is_first_line
 		&& trimmed.starts_with("#!")
 		&& let Some(lang) = lang_info
-		&& !lang.shebangs.is_empty()
+		&& langs::matches_shebang(lang, trimmed)
 	{
-		// Normalize shebang by removing optional space after `#!`
-		let normalized: Cow<'_, str> =
-			trimmed.strip_prefix("#! ").map_or(Cow::Borrowed(trimmed), |rest| Cow::Owned(format!("#!{rest}")));
-		if lang.shebangs.iter().any(|shebang| normalized.starts_with(shebang)) {
-			return LineType::Shebang;
-		}
+		return (LineType::Shebang, CommentStyle::Line);
 	}
 	let Some(lang) = lang_info else {
-		return LineType::Code;
+		return (LineType::Code, CommentStyle::Line);
 	};
-	let mut line_remainder = trimmed;
 	let matchers = language_matchers(lang);
-	#[expect(
-		clippy::option_if_let_else,
-		reason = "the two branches have different types of side effects (mutating line_remainder) that map_or_else cannot express cleanly"
-	)]
-	let mut has_code = if let Some(block_comments) = matchers.block_comments.as_ref() {
-		let (remainder, found_code) = handle_block_comments(trimmed, block_comments, comment_state, lang.nested_blocks);
+	let mut code = trimmed;
+	let continues_string = comment_state.open_string.is_some();
+	if let (Some(pattern), Some(strings)) = (comment_state.open_string.take(), matchers.strings.as_ref()) {
+		// The line starts inside a string, so its closing delimiter must not be read as the start of another.
+		let Some(end) = strings.close(trimmed, pattern) else {
+			comment_state.open_string = Some(pattern);
+			return (LineType::Code, CommentStyle::Line);
+		};
+		code = &trimmed[end..];
+	}
+	let mut line_remainder = code;
+	let mut has_comment = comment_state.is_in_comment();
+	let was_in_string = comment_state.in_string;
+	let mut has_code = continues_string;
+	if let Some(block_comments) = matchers.block_comments.as_ref() {
+		let (remainder, found_code) =
+			handle_block_comments(code, block_comments, matchers.strings.as_ref(), comment_state, lang.nested_blocks);
+		has_comment |= remainder.len() != code.len();
 		line_remainder = remainder;
-		found_code
-	} else {
-		false
-	};
+		has_code |= found_code;
+	}
+	if lang.docstrings && has_comment {
+		// The "block comments" are triple-quoted strings: only a docstring may count as a comment.
+		let literal = was_in_string || has_code;
+		if literal || comment_state.docstrings == Docstrings::Code {
+			has_code = true;
+			has_comment = false;
+		}
+		comment_state.in_string = literal && comment_state.is_in_comment();
+	}
 	if comment_state.is_in_comment() {
-		return if has_code { LineType::Code } else { LineType::Comment };
+		return (code_or_comment(has_code, has_comment), CommentStyle::Block);
 	}
+	let block_style = if has_comment { CommentStyle::Block } else { CommentStyle::Line };
 	if let Some(line_comments) = matchers.line_comments.as_ref() {
-		for matched in line_comments.find_iter(line_remainder) {
-			let token = lang.line_comments[matched.pattern().as_usize()];
-			if !is_valid_line_comment_match(line_remainder, matched.end(), token) {
-				continue;
-			}
-			let pos = matched.start();
-			if pos > 0 && contains_non_whitespace(&line_remainder[..pos]) {
-				has_code = true;
-			}
-			return if has_code { LineType::Code } else { LineType::Comment };
+		let rest = line_remainder;
+		let after_code = |pos: usize| pos > 0 && contains_non_whitespace(&rest[..pos]);
+		let found = find_outside_strings(rest, matchers.strings.as_ref(), |from| {
+			line_comments
+				.find_iter(&rest[from..])
+				.map(|matched| (from + matched.start(), from + matched.end(), matched.pattern().as_usize()))
+				.find(|&(pos, end, pattern)| {
+					is_valid_line_comment_match(rest, end, lang.line_comments[pattern])
+						&& !(after_code(pos) && lang.line_comment_must_start_line)
+				})
+				.map(|(pos, end, _)| (pos, end - pos))
+		});
+		if let Some((pos, _)) = found {
+			has_code |= after_code(pos);
+			return (code_or_comment(has_code, true), block_style);
 		}
 	}
 	if contains_non_whitespace(line_remainder) {
 		has_code = true;
+		comment_state.open_string = matchers.strings.as_ref().and_then(|strings| strings.open_at_end(line_remainder));
+	}
+	(code_or_comment(has_code, has_comment), block_style)
+}
+
+/// Pick the line type for a non-blank line given whether it holds code and whether it holds a comment.
+#[inline]
+const fn code_or_comment(has_code: bool, has_comment: bool) -> LineType {
+	match (has_code, has_comment) {
+		(true, true) => LineType::Mixed,
+		(true, false) => LineType::Code,
+		(false, _) => LineType::Comment,
 	}
-	if has_code { LineType::Code } else { LineType::Comment }
 }
 
 /// Fast ASCII-only whitespace trimming with newline handling. This is a performance-critical hot path called for every line of code analyzed.
@@ -296,7 +492,7 @@ mod tests {
 	#[case::tabs("\t\t")]
 	fn test_classify_blank_lines(#[case] line: &str) {
 		let mut state = CommentState::new();
-		assert_eq!(classify_line(line, None, &mut state, false), LineType::Blank);
+		assert_eq!(classify_line(line, None, &mut state, false).0, LineType::Blank);
 	}
 
 	#[rstest]
@@ -304,7 +500,7 @@ mod tests {
 	#[case("  more code  ")]
 	fn test_classify_code_without_language(#[case] line: &str) {
 		let mut state = CommentState::new();
-		assert_eq!(classify_line(line, None, &mut state, false), LineType::Code);
+		assert_eq!(classify_line(line, None, &mut state, false).0, LineType::Code);
 	}
 
 	#[test]
@@ -372,5 +568,226 @@ mod tests {
 		assert_eq!(LineType::Comment.plural_label(), "comments");
 		assert_eq!(LineType::Blank.title_label(), "Blanks");
 		assert_eq!(LineType::Shebang.singular_label(), "shebang");
+		assert_eq!(LineType::Mixed.title_label(), "Mixed");
+	}
+
+	#[rstest]
+	#[case::plain_code("let x = 1;", LineType::Code)]
+	#[case::line_comment("// note", LineType::Comment)]
+	#[case::trailing_line_comment("let x = 1; // note", LineType::Mixed)]
+	#[case::inline_block_comment("let x = /* one */ 1;", LineType::Mixed)]
+	#[case::block_comment_only("/* note */", LineType::Comment)]
+	fn test_classify_mixed_lines(#[case] line: &str, #[case] expected: LineType) {
+		let rust = crate::langs::LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		let mut state = CommentState::new();
+		assert_eq!(classify_line(line, Some(rust), &mut state, false).0, expected);
+	}
+
+	#[rstest]
+	#[case::url("let url = \"http://example.com\"; // home", LineType::Mixed)]
+	#[case::string_only("\"// not a comment\",", LineType::Code)]
+	#[case::block_start_in_string("let open = \"/*\";", LineType::Code)]
+	#[case::escaped_quote("let q = \"\\\"//\"; // note", LineType::Mixed)]
+	#[case::escaped_backslash("let s = \"\\\\\"; // note", LineType::Mixed)]
+	#[case::raw_string("let r = r#\"\" // \"#;", LineType::Code)]
+	#[case::char_literal("let c = '\"'; // note", LineType::Mixed)]
+	#[case::unterminated("let s = \"multi /* line", LineType::Code)]
+	fn test_classify_comment_markers_in_strings(#[case] line: &str, #[case] expected: LineType) {
+		let rust = crate::langs::LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		let mut state = CommentState::new();
+		assert_eq!(classify_line(line, Some(rust), &mut state, false).0, expected);
+		assert_eq!(state, CommentState::new(), "comment left open after {line:?}");
+	}
+
+	#[rstest]
+	#[case::single_quotes("print('# not a comment')", LineType::Code)]
+	#[case::other_quote_inside("x = \"it's\"  # note", LineType::Mixed)]
+	#[case::docstring_still_wins("\"\"\"Docstring.\"\"\"", LineType::Comment)]
+	fn test_classify_python_strings(#[case] line: &str, #[case] expected: LineType) {
+		let python = crate::langs::LANGUAGES.iter().find(|l| l.name == "Python").unwrap();
+		let mut state = CommentState::new();
+		assert_eq!(classify_line(line, Some(python), &mut state, false).0, expected);
+	}
+
+	#[rstest]
+	#[case::digit_separator("int x = 1'000; // note", LineType::Mixed)]
+	#[case::hex_digit_separator("int x = 0xFF'FF; // note", LineType::Mixed)]
+	#[case::char_literal("char c = '/'; int x = 1;", LineType::Code)]
+	#[case::prefixed_char_literal("auto c = u8'/'; int x = 1;", LineType::Code)]
+	fn test_classify_cpp_quotes(#[case] line: &str, #[case] expected: LineType) {
+		let cpp = crate::langs::LANGUAGES.iter().find(|l| l.name == "C++").unwrap();
+		let mut state = CommentState::new();
+		assert_eq!(classify_line(line, Some(cpp), &mut state, false).0, expected);
+	}
+
+	#[test]
+	fn test_classify_block_comment_after_cpp_digit_separator() {
+		let cpp = crate::langs::LANGUAGES.iter().find(|l| l.name == "C++").unwrap();
+		let mut state = CommentState::new();
+		let lines = ["int x = 1'000; /* start", "still comment", "*/", "int y = 2;"];
+		let types: Vec<_> = lines.iter().map(|line| classify_line(line, Some(cpp), &mut state, false).0).collect();
+		assert_eq!(types, [LineType::Mixed, LineType::Comment, LineType::Comment, LineType::Code]);
+	}
+
+	#[test]
+	fn test_classify_lines_after_a_multi_line_template_literal() {
+		let js = crate::langs::LANGUAGES.iter().find(|l| l.name == "JavaScript").unwrap();
+		let mut state = CommentState::new();
+		let lines = ["const s = `first", "// inside the literal", "last`; /* start", "still comment", "*/", "f();"];
+		let types: Vec<_> = lines.iter().map(|line| classify_line(line, Some(js), &mut state, false).0).collect();
+		assert_eq!(
+			types,
+			[LineType::Code, LineType::Code, LineType::Mixed, LineType::Comment, LineType::Comment, LineType::Code]
+		);
+		assert_eq!(state, CommentState::new());
+	}
+
+	#[test]
+	fn test_classify_multi_line_raw_string() {
+		let rust = crate::langs::LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		let mut state = CommentState::new();
+		let lines = ["let s = r#\"", "/* not a comment", "\"#; // note", "let x = 1;"];
+		let types: Vec<_> = lines.iter().map(|line| classify_line(line, Some(rust), &mut state, false).0).collect();
+		assert_eq!(types, [LineType::Code, LineType::Code, LineType::Mixed, LineType::Code]);
+	}
+
+	#[test]
+	fn test_classify_without_string_delimiters_keeps_markers_in_quotes() {
+		let lua = crate::langs::LANGUAGES.iter().find(|l| l.name == "Lua").unwrap();
+		assert!(lua.string_delimiters.is_empty() && lua.raw_string_delimiters.is_empty());
+		let mut state = CommentState::new();
+		assert_eq!(classify_line("x = \"a -- b\"", Some(lua), &mut state, false).0, LineType::Mixed);
+	}
+
+	#[rstest]
+	#[case::operator_section("let product = List.fold_left ( * ) 1 xs", LineType::Code)]
+	#[case::operator_value("let mul = (*)", LineType::Code)]
+	#[case::glued_to_identifier("let x = f(*p)", LineType::Code)]
+	#[case::unspaced_comment("(*comment*)", LineType::Comment)]
+	#[case::trailing_comment("let x = 1 (* one *)", LineType::Mixed)]
+	fn test_classify_boundary_block_comments(#[case] line: &str, #[case] expected: LineType) {
+		let ocaml = crate::langs::LANGUAGES.iter().find(|l| l.name == "OCaml").unwrap();
+		assert!(ocaml.require_boundary);
+		let mut state = CommentState::new();
+		assert_eq!(classify_line(line, Some(ocaml), &mut state, false).0, expected);
+		assert_eq!(state, CommentState::new(), "comment left open after {line:?}");
+	}
+
+	#[test]
+	fn test_classify_without_boundary_opens_comment_on_operator() {
+		let pascal = crate::langs::LANGUAGES.iter().find(|l| l.name == "Pascal").unwrap();
+		assert!(!pascal.require_boundary);
+		let mut state = CommentState::new();
+		assert_eq!(classify_line("x := (*)", Some(pascal), &mut state, false).0, LineType::Mixed);
+		assert!(state.is_in_comment());
+	}
+
+	#[rstest]
+	#[case::column_one_marker("C     compute the sum", LineType::Comment)]
+	#[case::bang_comment("! free-form note", LineType::Comment)]
+	#[case::marker_as_argument("      CALL ADD(A, C)", LineType::Code)]
+	#[case::marker_in_string("      PRINT *, 'Done!'", LineType::Code)]
+	fn test_classify_line_comments_that_must_start_the_line(#[case] line: &str, #[case] expected: LineType) {
+		let fortran = crate::langs::LANGUAGES.iter().find(|l| l.name == "Fortran").unwrap();
+		assert!(fortran.line_comment_must_start_line);
+		let mut state = CommentState::new();
+		assert_eq!(classify_line(line, Some(fortran), &mut state, false).0, expected);
+	}
+
+	#[test]
+	fn test_classify_code_after_closing_block_comment_is_mixed() {
+		let rust = crate::langs::LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		let mut state = CommentState::new();
+		assert_eq!(classify_line("/* start", Some(rust), &mut state, false).0, LineType::Comment);
+		assert_eq!(classify_line("end */ let x = 1;", Some(rust), &mut state, false).0, LineType::Mixed);
+	}
+
+	fn classify_python(lines: &[&str], docstrings: Docstrings) -> Vec<LineType> {
+		let python = crate::langs::LANGUAGES.iter().find(|l| l.name == "Python").unwrap();
+		assert!(python.docstrings);
+		let mut state = CommentState::new().with_docstrings(docstrings);
+		lines.iter().map(|line| classify_line(line, Some(python), &mut state, false).0).collect()
+	}
+
+	#[rstest]
+	#[case::comment(Docstrings::Comment, LineType::Comment)]
+	#[case::code(Docstrings::Code, LineType::Code)]
+	fn test_classify_docstrings(#[case] docstrings: Docstrings, #[case] docstring: LineType) {
+		let lines = ["\"\"\"Summary.", "Details.", "\"\"\"", "'\'\'One line.'\'\'", "x = 1"];
+		let expected = [docstring, docstring, docstring, docstring, LineType::Code];
+		assert_eq!(classify_python(&lines, docstrings), expected);
+	}
+
+	#[rstest]
+	#[case::comment(Docstrings::Comment)]
+	#[case::code(Docstrings::Code)]
+	fn test_classify_string_after_code_is_code(#[case] docstrings: Docstrings) {
+		let lines = ["query = \"\"\"", "SELECT 1", "\"\"\"", "print('\'\'inline'\'\')", "\"\"\"Docstring.\"\"\""];
+		let docstring = if docstrings == Docstrings::Code { LineType::Code } else { LineType::Comment };
+		let expected = [LineType::Code, LineType::Code, LineType::Code, LineType::Code, docstring];
+		assert_eq!(classify_python(&lines, docstrings), expected);
+	}
+
+	#[test]
+	fn test_docstrings_parse() {
+		assert_eq!("comment".parse(), Ok(Docstrings::Comment));
+		assert_eq!("code".parse(), Ok(Docstrings::Code));
+		assert!("string".parse::<Docstrings>().is_err());
+		assert_eq!(Docstrings::Code.to_string(), "code");
+	}
+
+	#[test]
+	fn test_shebangs_parse() {
+		for mode in [Shebangs::Separate, Shebangs::Code, Shebangs::Comment, Shebangs::Ignore] {
+			assert_eq!(mode.to_string().parse(), Ok(mode));
+		}
+		assert!("shebang".parse::<Shebangs>().is_err());
+	}
+
+	#[test]
+	fn test_classify_comment_styles() {
+		let rust = crate::langs::LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		let mut state = CommentState::new();
+		let mut style = |line| classify_line(line, Some(rust), &mut state, false).1;
+		assert_eq!(style("// note"), Some(CommentStyle::Line));
+		assert_eq!(style("/* one */"), Some(CommentStyle::Block));
+		assert_eq!(style("/* one */ // two"), Some(CommentStyle::Block));
+		assert_eq!(style("/* start"), Some(CommentStyle::Block));
+		assert_eq!(style("// still inside"), Some(CommentStyle::Block));
+		assert_eq!(style("end */"), Some(CommentStyle::Block));
+		assert_eq!(style("let x = 1; // note"), None);
+		assert_eq!(style(""), None);
+	}
+
+	#[test]
+	fn test_classify_reader_threads_comment_state() {
+		let rust = crate::langs::LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		let source = "/* start\r\nstill inside\n*/\n\nfn main() {} // run\nlet x = 1;";
+		let lines: Vec<_> = classify_reader(source.as_bytes(), rust).collect::<io::Result<_>>().unwrap();
+		assert_eq!(
+			lines,
+			[
+				(LineType::Comment, "/* start".to_owned()),
+				(LineType::Comment, "still inside".to_owned()),
+				(LineType::Comment, "*/".to_owned()),
+				(LineType::Blank, String::new()),
+				(LineType::Mixed, "fn main() {} // run".to_owned()),
+				(LineType::Code, "let x = 1;".to_owned()),
+			]
+		);
+	}
+
+	#[test]
+	fn test_classify_reader_drops_leading_bom() {
+		let python = crate::langs::LANGUAGES.iter().find(|l| l.name == "Python").unwrap();
+		let source = "\u{feff}#!/usr/bin/env python\n# note\n";
+		let lines: Vec<_> = classify_reader(source.as_bytes(), python).collect::<io::Result<_>>().unwrap();
+		assert_eq!(
+			lines,
+			[(LineType::Shebang, "#!/usr/bin/env python".to_owned()), (LineType::Comment, "# note".to_owned())]
+		);
+		let rust = crate::langs::LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		let lines: Vec<_> = classify_reader("\u{feff}// note\n".as_bytes(), rust).collect::<io::Result<_>>().unwrap();
+		assert_eq!(lines, [(LineType::Comment, "// note".to_owned())]);
 	}
 }
@@ -1,95 +1,430 @@
+use std::sync::Arc;
+
 use anyhow::Result;
 
 use super::{
+	embedded::EmbeddedScanner,
 	encoding::{FileEncoding, decode_bytes},
 	file_io::LineSource,
-	line_classifier::{self, CommentState, LineType},
-	stats::{AnalysisResults, FileContribution, FileStats},
+	line_classifier::{self, CommentState, CommentStyle, Docstrings, LineType, Shebangs},
+	stats::{AnalysisResults, FileContribution, FileSink, FileStats},
 };
-use crate::langs::Language;
+use crate::langs::{DetectionConfidence, Language};
+
+/// Line separators besides `\n` that end a line when Unicode line breaks are enabled: NEL, LINE SEPARATOR and
+/// PARAGRAPH SEPARATOR.
+const UNICODE_LINE_BREAKS: [char; 3] = ['\u{85}', '\u{2028}', '\u{2029}'];
+
+/// The `-L` and `--exclude-lang` filters, applied to each file's language and to the languages embedded in it.
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct LanguageFilter<'a> {
+	include: &'a [String],
+	exclude: &'a [String],
+}
+
+impl<'a> LanguageFilter<'a> {
+	pub(super) const fn new(include: &'a [String], exclude: &'a [String]) -> Self {
+		Self { include, exclude }
+	}
+
+	/// Whether `language` is counted: it is named by the include list when there is one, otherwise not excluded.
+	pub(super) fn allows(self, language: &Language) -> bool {
+		if !self.include.is_empty() {
+			return self.include.iter().any(|filter| filter.eq_ignore_ascii_case(language.name));
+		}
+		!self.exclude.iter().any(|filter| filter.eq_ignore_ascii_case(language.name))
+	}
+}
+
+/// Identity of the file currently being counted, carried through the I/O and decoding layers.
+#[expect(
+	clippy::struct_excessive_bools,
+	reason = "each bool is an independent per-file flag copied from the analysis options"
+)]
+pub(super) struct FileTarget<'a> {
+	pub(super) display_path: &'a str,
+	/// The path's bytes when they are not valid UTF-8, so reports that write raw paths can reproduce them.
+	pub(super) raw_path: Option<&'a [u8]>,
+	pub(super) size: u64,
+	pub(super) language: &'static Language,
+	/// What decided `language`, present only when per-file details are collected.
+	pub(super) detection: Option<DetectionConfidence>,
+	pub(super) collect_details: bool,
+	/// Receives the file's details in place of the results, present only when streaming them.
+	pub(super) file_sink: Option<&'a dyn FileSink>,
+	/// True when the file was reached through a symlinked file or directory.
+	pub(super) symlinked: bool,
+	/// Columns a tab advances to when measuring line length.
+	pub(super) tab_width: u8,
+	/// Count lines holding both code and a comment as mixed instead of code.
+	pub(super) split_mixed: bool,
+	/// Comment lines whose text starts with one of these (e.g. `#region`) are counted as code.
+	pub(super) fold_markers: &'a [String],
+	/// Count docstrings in languages that have them as comments or as code.
+	pub(super) docstrings: Docstrings,
+	/// Count shebang lines separately, as code, as comments, or not at all.
+	pub(super) shebangs: Shebangs,
+	/// Also end lines at the Unicode separators in [`UNICODE_LINE_BREAKS`].
+	pub(super) unicode_line_breaks: bool,
+	/// Hash the bytes read so verbose reports can tell when the file's content changed.
+	pub(super) hash: bool,
+	/// Attribute embedded regions (Markdown fences, HTML `<script>`/`<style>`) to their own language.
+	pub(super) embedded: bool,
+	/// Embedded languages this rejects are counted as part of the host file instead.
+	pub(super) language_filter: LanguageFilter<'a>,
+	/// True when the file matched a test-file pattern.
+	pub(super) is_test: bool,
+	/// True when the file matched a vendored pattern; its counts go to the vendored bucket, not its language.
+	pub(super) is_vendored: bool,
+	/// Category assigned by the category rules, present only when categories are split.
+	pub(super) category: Option<&'a Arc<str>>,
+}
+
+impl FileTarget<'_> {
+	/// A hasher for the file's content, present only when hashes are requested.
+	pub(super) const fn hasher(&self) -> Option<ContentHash> {
+		if self.hash { Some(ContentHash::new()) } else { None }
+	}
+}
+
+/// 64-bit FNV-1a over the bytes read from a file, fed as they stream past.
+///
+/// FNV-1a is chosen over `DefaultHasher` so hashes stay comparable across runs, platforms and toolchains.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct ContentHash(u64);
+
+impl ContentHash {
+	const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+	const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+	pub(super) const fn new() -> Self {
+		Self(Self::OFFSET_BASIS)
+	}
+
+	pub(super) fn update(&mut self, bytes: &[u8]) {
+		for &byte in bytes {
+			self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(Self::PRIME);
+		}
+	}
+
+	pub(super) const fn finish(self) -> u64 {
+		self.0
+	}
+}
 
 #[derive(Default)]
-pub(super) struct LineCounts {
+struct LineCounts<'a> {
 	pub(super) total: u64,
 	pub(super) code: u64,
 	pub(super) comment: u64,
 	pub(super) blank: u64,
 	pub(super) shebang: u64,
+	pub(super) mixed: u64,
+	pub(super) line_comment: u64,
+	pub(super) block_comment: u64,
+	pub(super) tab_indented: u64,
+	pub(super) space_indented: u64,
+	pub(super) max_line_length: u64,
+	tab_width: u64,
+	split_mixed: bool,
+	fold_markers: &'a [String],
+	shebangs: Shebangs,
 }
 
-impl LineCounts {
-	pub(super) fn classify_and_count(
+impl<'a> LineCounts<'a> {
+	fn new(tab_width: u8, split_mixed: bool, fold_markers: &'a [String], shebangs: Shebangs) -> Self {
+		Self { tab_width: u64::from(tab_width.max(1)), split_mixed, fold_markers, shebangs, ..Self::default() }
+	}
+
+	fn classify_and_count(
 		&mut self,
 		line: &str,
 		lang_info: Option<&Language>,
 		comment_state: &mut CommentState,
 		is_first_line: bool,
 	) {
-		let line_type = line_classifier::classify_line(line, lang_info, comment_state, is_first_line);
+		let (mut line_type, mut comment_style) =
+			line_classifier::classify_line(line, lang_info, comment_state, is_first_line);
+		if line_type == LineType::Comment && is_fold_marker(line, lang_info, self.fold_markers) {
+			(line_type, comment_style) = (LineType::Code, None);
+		}
+		if line_type == LineType::Shebang {
+			match self.shebangs {
+				Shebangs::Separate => {}
+				Shebangs::Code => line_type = LineType::Code,
+				Shebangs::Comment => (line_type, comment_style) = (LineType::Comment, Some(CommentStyle::Line)),
+				Shebangs::Ignore => return,
+			}
+		}
 		match line_type {
-			LineType::Code => self.code += 1,
+			LineType::Mixed if self.split_mixed => self.mixed += 1,
+			LineType::Code | LineType::Mixed => self.code += 1,
 			LineType::Comment => self.comment += 1,
 			LineType::Blank => self.blank += 1,
 			LineType::Shebang => self.shebang += 1,
 		}
+		match comment_style {
+			Some(CommentStyle::Line) => self.line_comment += 1,
+			Some(CommentStyle::Block) => self.block_comment += 1,
+			None => {}
+		}
+		if line_type != LineType::Blank {
+			match line.as_bytes().first() {
+				Some(b'\t') => self.tab_indented += 1,
+				Some(b' ') => self.space_indented += 1,
+				_ => {}
+			}
+		}
+		self.max_line_length = self.max_line_length.max(line_length(line, self.tab_width));
 		self.total += 1;
 	}
 }
 
-pub(super) fn process_lines<S>(
-	display_path: &str,
-	file_size: u64,
-	results: &mut AnalysisResults,
-	collect_details: bool,
+/// Line counts for one file, split between the host language and any embedded languages.
+pub(super) struct FileCounter<'a> {
 	language: &'static Language,
-	encoding: FileEncoding,
-	source: &mut S,
-) -> Result<()>
+	counts: LineCounts<'a>,
+	comment_state: CommentState,
+	scanner: Option<EmbeddedScanner>,
+	embedded: Vec<(&'static Language, LineCounts<'a>, CommentState)>,
+	tab_width: u8,
+	split_mixed: bool,
+	fold_markers: &'a [String],
+	docstrings: Docstrings,
+	shebangs: Shebangs,
+	unicode_line_breaks: bool,
+	language_filter: LanguageFilter<'a>,
+	/// Lines ending in `\r\n`, counted across the host and embedded languages.
+	crlf_lines: u64,
+	/// Bytes a `\r` takes on disk in the file's encoding.
+	cr_width: u64,
+}
+
+impl<'a> FileCounter<'a> {
+	pub(super) fn new(target: &FileTarget<'a>, encoding: FileEncoding) -> Self {
+		Self { cr_width: encoding.code_unit_len(), ..Self::with_language(target, target.language) }
+	}
+
+	/// Create a counter that classifies lines as `language` rather than the file's detected language.
+	pub(super) fn with_language(target: &FileTarget<'a>, language: &'static Language) -> Self {
+		Self {
+			language,
+			counts: LineCounts::new(target.tab_width, target.split_mixed, target.fold_markers, target.shebangs),
+			comment_state: CommentState::new().with_docstrings(target.docstrings),
+			scanner: if target.embedded { EmbeddedScanner::for_language(language) } else { None },
+			embedded: Vec::new(),
+			tab_width: target.tab_width,
+			split_mixed: target.split_mixed,
+			fold_markers: target.fold_markers,
+			docstrings: target.docstrings,
+			shebangs: target.shebangs,
+			unicode_line_breaks: target.unicode_line_breaks,
+			language_filter: target.language_filter,
+			crlf_lines: 0,
+			cr_width: 1,
+		}
+	}
+
+	pub(super) fn count_line(&mut self, line: &str, is_first_line: bool) {
+		if line.ends_with("\r\n") {
+			self.crlf_lines += 1;
+		}
+		if self.unicode_line_breaks && line.contains(UNICODE_LINE_BREAKS) {
+			for (idx, part) in line.split(UNICODE_LINE_BREAKS).enumerate() {
+				self.count_split_line(part, is_first_line && idx == 0);
+			}
+		} else {
+			self.count_split_line(line, is_first_line);
+		}
+	}
+
+	/// Count one line, already split at every line break in use, as the host language or an embedded one.
+	///
+	/// Lines of an embedded language the language filter rejects stay with the host.
+	fn count_split_line(&mut self, line: &str, is_first_line: bool) {
+		let filter = self.language_filter;
+		let routed = self.scanner.as_mut().and_then(|scanner| scanner.route(line));
+		let Some(embedded) = routed.filter(|&language| filter.allows(language)) else {
+			self.counts.classify_and_count(line, Some(self.language), &mut self.comment_state, is_first_line);
+			return;
+		};
+		let slot = if let Some(idx) = self.embedded.iter().position(|(lang, ..)| lang.index == embedded.index) {
+			idx
+		} else {
+			self.embedded.push((
+				embedded,
+				LineCounts::new(self.tab_width, self.split_mixed, self.fold_markers, self.shebangs),
+				CommentState::new().with_docstrings(self.docstrings),
+			));
+			self.embedded.len() - 1
+		};
+		let (lang, counts, comment_state) = &mut self.embedded[slot];
+		counts.classify_and_count(line, Some(lang), comment_state, false);
+	}
+
+	/// Collect the counts gathered so far; the file's `size` and its `\r\n` overhead are charged to the host language
+	/// only.
+	pub(super) fn finish(&self, size: u64) -> FileCounts {
+		FileCounts {
+			host: contribution(&self.counts, size).with_crlf_bytes(self.crlf_lines * self.cr_width),
+			embedded: self.embedded.iter().map(|(language, counts, _)| (*language, contribution(counts, 0))).collect(),
+			minified: false,
+			hash: None,
+		}
+	}
+}
+
+/// Everything counted in one file: the host language's contribution plus one per embedded language.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct FileCounts {
+	pub(super) host: FileContribution,
+	pub(super) embedded: Vec<(&'static Language, FileContribution)>,
+	/// True when a line was too long to be read whole; see [`MAX_LINE_BYTES`](super::file_io::MAX_LINE_BYTES).
+	pub(super) minified: bool,
+	/// [`ContentHash`] of the bytes read, present only when hashes are requested.
+	pub(super) hash: Option<u64>,
+}
+
+pub(super) fn process_lines<S>(target: &FileTarget, encoding: FileEncoding, source: &mut S) -> Result<FileCounts>
 where
 	S: LineSource,
 {
 	let mut is_first_line = true;
-	let line_counts = count_lines_with(
-		|handle| {
-			source.for_each_line(&mut |line_bytes| {
-				let decoded = decode_bytes(line_bytes, encoding, is_first_line);
-				handle(decoded.as_ref(), is_first_line);
-				is_first_line = false;
-			})
-		},
-		language,
-	)?;
-	finish_file_stats(display_path, file_size, results, collect_details, language, &line_counts);
-	Ok(())
-}
-
-pub(super) fn finish_file_stats(
-	display_path: &str,
-	file_size: u64,
-	results: &mut AnalysisResults,
-	collect_details: bool,
-	language: &'static Language,
-	line_counts: &LineCounts,
-) {
-	let total = line_counts.total;
-	let code = line_counts.code;
-	let comment = line_counts.comment;
-	let blank = line_counts.blank;
-	let shebang = line_counts.shebang;
-	let contribution = FileContribution::new(total, code, comment, blank, shebang, file_size);
-	let file_stats = collect_details
-		.then(|| FileStats::new(display_path.to_owned(), total, code, comment, blank, shebang, file_size));
-	results.add_file_stats(language, contribution, file_stats);
-}
-
-fn count_lines_with(
-	mut for_each: impl FnMut(&mut dyn FnMut(&str, bool)) -> Result<()>,
-	language: &'static Language,
-) -> Result<LineCounts> {
-	let mut line_counts = LineCounts::default();
-	let mut comment_state = CommentState::new();
-	for_each(&mut |line, is_first_line| {
-		line_counts.classify_and_count(line, Some(language), &mut comment_state, is_first_line);
+	let mut counter = FileCounter::new(target, encoding);
+	source.for_each_line(&mut |line_bytes| {
+		let decoded = decode_bytes(line_bytes, encoding, is_first_line);
+		counter.count_line(decoded.as_ref(), is_first_line);
+		is_first_line = false;
 	})?;
-	Ok(line_counts)
+	Ok(FileCounts { minified: source.truncated(), hash: source.hash(), ..counter.finish(target.size) })
+}
+
+const fn contribution(line_counts: &LineCounts, size: u64) -> FileContribution {
+	FileContribution::new(
+		line_counts.total,
+		line_counts.code,
+		line_counts.comment,
+		line_counts.blank,
+		line_counts.shebang,
+		size,
+	)
+	.with_mixed_lines(line_counts.mixed)
+	.with_indentation(line_counts.tab_indented, line_counts.space_indented)
+	.with_comment_styles(line_counts.line_comment, line_counts.block_comment)
+	.with_max_line_length(line_counts.max_line_length)
+}
+
+/// Merge one file's counts into `results`, tagging them with the per-file flags from `target`.
+pub(super) fn record_file(target: &FileTarget, results: &mut AnalysisResults, counts: &FileCounts) {
+	if target.is_vendored {
+		results.add_vendored_file(&counts.host);
+		for (_, contribution) in &counts.embedded {
+			results.add_vendored_lines(contribution);
+		}
+		return;
+	}
+	let file_stats = target.collect_details.then(|| {
+		counts
+			.host
+			.file_stats(target.display_path.to_owned())
+			.with_raw_path(target.raw_path.map(Box::from))
+			.with_symlinked(target.symlinked)
+			.with_category(target.category.cloned())
+			.with_hash(counts.hash)
+			.with_detection(target.detection)
+	});
+	let file_stats = hand_off(target.file_sink, target.language, file_stats);
+	results.add_file_stats(target.language, counts.host.with_test(target.is_test), file_stats);
+	if let Some(category) = target.category {
+		results.add_category_file(category, &counts.host);
+		for (_, contribution) in &counts.embedded {
+			results.add_category_lines(category, contribution);
+		}
+	}
+	let lines = counts
+		.embedded
+		.iter()
+		.fold(counts.host.total_lines(), |lines, (_, contribution)| lines.saturating_add(contribution.total_lines()));
+	results.track_largest_file(target.display_path, lines, counts.host.size());
+	for &(language, contribution) in &counts.embedded {
+		results.add_embedded_stats(language, contribution.with_test(target.is_test));
+	}
+}
+
+/// Pass `file_stats` to `sink` when there is one, leaving nothing for the results to keep.
+pub(super) fn hand_off(
+	sink: Option<&dyn FileSink>,
+	language: &'static Language,
+	file_stats: Option<FileStats>,
+) -> Option<FileStats> {
+	match (sink, file_stats) {
+		(Some(sink), Some(file_stats)) => {
+			sink.accept(language, file_stats);
+			None
+		}
+		(_, file_stats) => file_stats,
+	}
+}
+
+/// Whether the comment line `line` is a folding marker: its text after any comment delimiter of `language` starts with
+/// one of `markers`, as in `// #region Helpers` or `/* #endregion */` with `#region` and `#endregion` as markers.
+fn is_fold_marker(line: &str, language: Option<&Language>, markers: &[String]) -> bool {
+	if markers.is_empty() {
+		return false;
+	}
+	let mut text = line.trim();
+	if let Some(language) = language {
+		let delimiters = language.line_comments.iter().chain(language.block_comments.iter().map(|(start, _)| start));
+		// Strip the longest delimiter so `///` is not left as `/` after stripping `//`.
+		if let Some(rest) =
+			delimiters.filter_map(|delimiter| text.strip_prefix(delimiter)).min_by_key(|rest| rest.len())
+		{
+			text = rest.trim_start();
+		}
+	}
+	markers.iter().any(|marker| text.starts_with(marker.as_str()))
+}
+
+/// Measure `line` in columns, expanding tabs to the next multiple of `tab_width` and counting each character once.
+fn line_length(line: &str, tab_width: u64) -> u64 {
+	line.trim_end_matches(['\r', '\n']).bytes().fold(0, |column, byte| match byte {
+		b'\t' => column + tab_width - column % tab_width,
+		// UTF-8 continuation bytes belong to the preceding character.
+		byte if byte & 0xC0 == 0x80 => column,
+		_ => column + 1,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use rstest::rstest;
+
+	use super::*;
+
+	#[rstest]
+	#[case::empty("", 8, 0)]
+	#[case::newline_only("\n", 8, 0)]
+	#[case::plain("fn main() {}\r\n", 8, 12)]
+	#[case::leading_tab("\tx\n", 8, 9)]
+	#[case::leading_tab_narrow("\tx\n", 4, 5)]
+	#[case::embedded_tab("ab\tc", 4, 5)]
+	#[case::tab_at_stop("abcd\tx", 4, 9)]
+	#[case::multibyte("\u{e9}t\u{e9}", 8, 3)]
+	fn test_line_length(#[case] line: &str, #[case] tab_width: u64, #[case] expected: u64) {
+		assert_eq!(line_length(line, tab_width), expected);
+	}
+
+	#[rstest]
+	#[case::line_comment("  // #region Helpers\n", true)]
+	#[case::no_space("//#endregion\n", true)]
+	#[case::block_comment("/* #region */\n", true)]
+	#[case::doc_comment("/// #region\n", true)]
+	#[case::mentioned_later("// see #region above\n", false)]
+	#[case::plain_comment("// helpers\n", false)]
+	fn test_is_fold_marker(#[case] line: &str, #[case] expected: bool) {
+		let rust = crate::langs::LANGUAGES.iter().find(|lang| lang.name == "Rust").unwrap();
+		let markers = ["#region".to_owned(), "#endregion".to_owned()];
+		assert_eq!(is_fold_marker(line, Some(rust), &markers), expected);
+		assert!(!is_fold_marker(line, Some(rust), &[]));
+	}
 }
@@ -0,0 +1,216 @@
+//! Cell-aware counting for Jupyter notebooks.
+//!
+//! A notebook is JSON, so its raw lines measure the serialization rather than the code. Instead, the source of each
+//! code cell is counted as the kernel's language (Python unless the metadata names another known language) and each
+//! Markdown cell as Markdown. The notebook still counts as one file of its own language, carrying its size but no
+//! lines, except those of cells whose language the language filter rejects. Raw cells and cell outputs are not counted.
+
+use serde::Deserialize;
+
+use super::{
+	line_counter::{FileCounter, FileCounts, FileTarget},
+	stats::FileContribution,
+};
+use crate::langs::{LANGUAGES, Language};
+
+/// Language of code cells when the notebook metadata does not name a known one.
+const DEFAULT_KERNEL_LANGUAGE: &str = "Python";
+
+#[derive(Deserialize)]
+struct Notebook {
+	cells: Vec<Cell>,
+	#[serde(default)]
+	metadata: Metadata,
+}
+
+#[derive(Default, Deserialize)]
+struct Metadata {
+	kernelspec: Option<KernelSpec>,
+	language_info: Option<LanguageInfo>,
+}
+
+#[derive(Deserialize)]
+struct KernelSpec {
+	language: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LanguageInfo {
+	name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Cell {
+	cell_type: String,
+	#[serde(default)]
+	source: Source,
+}
+
+/// Cell source, stored either as a list of lines (each keeping its newline) or as a single string.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Source {
+	Lines(Vec<String>),
+	Text(String),
+}
+
+impl Default for Source {
+	fn default() -> Self {
+		Self::Lines(Vec::new())
+	}
+}
+
+impl Source {
+	fn for_each_line(&self, mut f: impl FnMut(&str)) {
+		match self {
+			Self::Lines(lines) => lines.iter().flat_map(|line| line.split_inclusive('\n')).for_each(&mut f),
+			Self::Text(text) => text.split_inclusive('\n').for_each(&mut f),
+		}
+	}
+}
+
+/// Whether files of `language` should be counted by cell instead of by line.
+pub(super) fn is_notebook(language: &Language) -> bool {
+	language.name == "Jupyter Notebook"
+}
+
+/// Count the cells of the notebook in `text`, or return `None` if it is not a notebook this module understands
+/// (invalid JSON, or the pre-v4 format without a top-level `cells` list) so it can be counted line by line instead.
+pub(super) fn count_cells(target: &FileTarget, text: &str) -> Option<FileCounts> {
+	let notebook: Notebook = serde_json::from_str(text).ok()?;
+	let code_language = notebook.metadata.kernel_language().unwrap_or_else(|| language_named(DEFAULT_KERNEL_LANGUAGE));
+	let markdown = language_named("Markdown");
+	let mut code = FileCounter::with_language(target, code_language);
+	let mut prose = FileCounter::with_language(target, markdown);
+	for cell in &notebook.cells {
+		let counter = match cell.cell_type.as_str() {
+			"code" => &mut code,
+			"markdown" => &mut prose,
+			_ => continue,
+		};
+		cell.source.for_each_line(|line| counter.count_line(line, false));
+	}
+	let mut host = FileContribution::new(0, 0, 0, 0, 0, target.size);
+	let mut embedded = Vec::new();
+	for (language, counter) in [(code_language, code), (markdown, prose)] {
+		let counts = counter.finish(0);
+		if !target.language_filter.allows(language) {
+			host.absorb_lines(&counts.host);
+		} else if counts.host.total_lines() > 0 {
+			embedded.push((language, counts.host));
+		}
+		embedded.extend(counts.embedded);
+	}
+	Some(FileCounts { host, embedded, minified: false, hash: None })
+}
+
+impl Metadata {
+	fn kernel_language(&self) -> Option<&'static Language> {
+		let from_kernel = self.kernelspec.as_ref().and_then(|kernel| kernel.language.as_deref());
+		let from_info = self.language_info.as_ref().and_then(|info| info.name.as_deref());
+		[from_kernel, from_info]
+			.into_iter()
+			.flatten()
+			.find_map(|name| LANGUAGES.iter().find(|lang| lang.name.eq_ignore_ascii_case(name)))
+	}
+}
+
+fn language_named(name: &str) -> &'static Language {
+	LANGUAGES.iter().find(|lang| lang.name == name).expect("language is defined in languages.json5")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::analysis::{
+		line_classifier::{Docstrings, Shebangs},
+		line_counter::LanguageFilter,
+	};
+
+	fn target(embedded: bool) -> FileTarget<'static> {
+		FileTarget {
+			display_path: "analysis.ipynb",
+			raw_path: None,
+			size: 500,
+			language: language_named("Jupyter Notebook"),
+			detection: None,
+			collect_details: false,
+			file_sink: None,
+			symlinked: false,
+			tab_width: 4,
+			split_mixed: false,
+			fold_markers: &[],
+			docstrings: Docstrings::default(),
+			shebangs: Shebangs::default(),
+			unicode_line_breaks: false,
+			hash: false,
+			embedded,
+			language_filter: LanguageFilter::default(),
+			is_test: false,
+			is_vendored: false,
+			category: None,
+		}
+	}
+
+	fn lines(counts: &FileCounts) -> Vec<(&str, u64, u64, u64)> {
+		counts
+			.embedded
+			.iter()
+			.map(|(lang, contribution)| {
+				let stats = contribution.file_stats(String::new());
+				(lang.name, stats.total_lines(), stats.code_lines(), stats.comment_lines())
+			})
+			.collect()
+	}
+
+	#[test]
+	fn code_and_markdown_cells_are_counted_as_their_languages() {
+		let notebook = r##"{
+			"metadata": {"kernelspec": {"language": "python", "name": "python3"}},
+			"nbformat": 4,
+			"cells": [
+				{"cell_type": "markdown", "source": ["# Title\n", "\n", "Some prose."]},
+				{"cell_type": "code", "source": ["import os\n", "# comment\n", "print(os.name)"], "outputs": []},
+				{"cell_type": "raw", "source": "ignored\n"},
+				{"cell_type": "code", "source": "x = 1\n"}
+			]
+		}"##;
+		let counts = count_cells(&target(false), notebook).unwrap();
+		assert_eq!(counts.host, FileContribution::new(0, 0, 0, 0, 0, 500));
+		assert_eq!(lines(&counts), [("Python", 4, 3, 1), ("Markdown", 3, 2, 0)]);
+	}
+
+	#[test]
+	fn kernel_language_comes_from_metadata() {
+		let notebook =
+			r#"{"metadata": {"language_info": {"name": "R"}}, "cells": [{"cell_type": "code", "source": "x <- 1"}]}"#;
+		let counts = count_cells(&target(false), notebook).unwrap();
+		assert_eq!(lines(&counts), [("R", 1, 1, 0)]);
+	}
+
+	#[test]
+	fn fenced_blocks_in_markdown_cells_follow_the_embedded_option() {
+		let notebook = r#"{"cells": [{"cell_type": "markdown", "source": ["```rust\n", "fn a() {}\n", "```\n"]}]}"#;
+		let counts = count_cells(&target(true), notebook).unwrap();
+		assert_eq!(lines(&counts), [("Markdown", 2, 2, 0), ("Rust", 1, 1, 0)]);
+	}
+
+	#[test]
+	fn cells_of_filtered_out_languages_stay_with_the_notebook() {
+		let notebook = r##"{"cells": [
+			{"cell_type": "markdown", "source": "Some prose.\n"},
+			{"cell_type": "code", "source": ["import os\n", "# comment\n"]}
+		]}"##;
+		let exclude = ["markdown".to_owned()];
+		let target = FileTarget { language_filter: LanguageFilter::new(&[], &exclude), ..target(false) };
+		let counts = count_cells(&target, notebook).unwrap();
+		assert_eq!(counts.host, FileContribution::new(1, 1, 0, 0, 0, 500).with_max_line_length(11));
+		assert_eq!(lines(&counts), [("Python", 2, 1, 1)]);
+	}
+
+	#[test]
+	fn non_notebook_json_is_left_to_line_counting() {
+		assert!(count_cells(&target(false), "{\"worksheets\": []}").is_none());
+		assert!(count_cells(&target(false), "not json").is_none());
+	}
+}
@@ -1,23 +1,118 @@
-use std::{borrow::Cow, path::Path};
+use std::{
+	borrow::Cow,
+	collections::BTreeMap,
+	fs,
+	path::{Path, PathBuf},
+	sync::Arc,
+	time::SystemTime,
+};
 
 use anyhow::{Context as _, Result};
+use encoding_rs::Encoding;
 
 use super::{
-	encoding::{FileEncoding, decode_bytes, detect_encoding, is_probably_binary},
-	file_io::FileSource,
-	stats::{AnalysisResults, FileContribution, FileStats},
+	cache::{AnalysisCache, CacheEntry, FileStamp, Outcome},
+	categories::CategoryMatcher,
+	editorconfig::LanguageOverrides,
+	encoding::{
+		FileEncoding, decode_bytes, detect_encoding, forced_encoding, is_binary_despite_forced_encoding,
+		is_probably_binary,
+	},
+	file_io::{FileSource, IoLimiter},
+	generated::GeneratedDetector,
+	line_classifier::{Docstrings, Shebangs},
+	line_counter::{self, ContentHash, FileTarget, LanguageFilter},
+	stats::{AnalysisResults, FileContribution, FileSink, FileStats},
+	test_files::TestFileMatcher,
+	vendored::VendoredMatcher,
+};
+use crate::{
+	display::formatting::display_path,
+	langs::{self, DetectionConfidence, Language},
 };
-use crate::langs::{self, Language};
 
-/// Check if a language should be processed based on include/exclude filters.
-fn should_process_language(language: &Language, include_languages: &[String], exclude_languages: &[String]) -> bool {
-	if !include_languages.is_empty() {
-		return include_languages.iter().any(|filter| filter.eq_ignore_ascii_case(language.name));
+/// Per-walk settings shared by every worker thread.
+#[expect(clippy::struct_excessive_bools, reason = "each bool is an independent analysis option copied from the config")]
+pub(super) struct ProcessOptions {
+	pub(super) collect_details: bool,
+	/// Receives per-file details as files are counted instead of the results, present only when streaming them.
+	pub(super) file_sink: Option<Arc<dyn FileSink>>,
+	pub(super) include_languages: Vec<String>,
+	pub(super) exclude_languages: Vec<String>,
+	/// Present only when generated-file exclusion is enabled.
+	pub(super) generated: Option<GeneratedDetector>,
+	/// Present only when test splitting is enabled.
+	pub(super) tests: Option<TestFileMatcher>,
+	/// Present only when vendored code is collapsed into its own bucket.
+	pub(super) vendored: Option<VendoredMatcher>,
+	/// Present only when files are split into categories.
+	pub(super) categories: Option<CategoryMatcher>,
+	/// Languages assigned by the root `.editorconfig`, present only when enabled and the file assigns any.
+	pub(super) language_overrides: Option<LanguageOverrides>,
+	/// Entries from a previous run, present only when caching is enabled.
+	pub(super) cache: Option<AnalysisCache>,
+	/// Canonical analysis root, present only when following symlinks so symlinked files can be flagged.
+	pub(super) canonical_root: Option<PathBuf>,
+	pub(super) tab_width: u8,
+	pub(super) split_mixed: bool,
+	pub(super) fold_markers: Vec<String>,
+	pub(super) docstrings: Docstrings,
+	pub(super) shebangs: Shebangs,
+	/// End lines at Unicode line and paragraph separators as well as `\n`.
+	pub(super) unicode_line_breaks: bool,
+	/// Hash each file's content for verbose reports.
+	pub(super) hash: bool,
+	/// Encoding every file is decoded with, present only when detection is bypassed.
+	pub(super) input_encoding: Option<&'static Encoding>,
+	pub(super) embedded: bool,
+	/// Log candidate scores to stderr for files whose language is chosen by content scoring.
+	pub(super) explain_detection: bool,
+	/// Lowest content score that lets detection pick among several candidate languages.
+	pub(super) detection_threshold: u32,
+	pub(super) binary_threshold: u8,
+	/// Binary thresholds by lowercase extension, overriding `binary_threshold`.
+	pub(super) binary_thresholds: BTreeMap<String, u8>,
+	/// Record binary files as assets instead of skipping them.
+	pub(super) include_assets: bool,
+	/// Files last modified before this time are skipped.
+	pub(super) modified_after: Option<SystemTime>,
+	/// Record files skipped as binary or unrecognized in the results.
+	pub(super) collect_skipped: bool,
+	/// Present only when the number of files read at once is bounded.
+	pub(super) io_limit: Option<IoLimiter>,
+}
+
+impl ProcessOptions {
+	fn language_filter(&self) -> LanguageFilter<'_> {
+		LanguageFilter::new(&self.include_languages, &self.exclude_languages)
+	}
+
+	/// Binary threshold for `filename`: its extension's override when there is one, otherwise the global threshold.
+	fn binary_threshold_for(&self, filename: &str) -> u8 {
+		filename
+			.rsplit_once('.')
+			.and_then(|(_, extension)| self.binary_thresholds.get(&extension.to_ascii_lowercase()))
+			.copied()
+			.unwrap_or(self.binary_threshold)
 	}
-	if !exclude_languages.is_empty() {
-		return !exclude_languages.iter().any(|filter| filter.eq_ignore_ascii_case(language.name));
+}
+
+/// Check whether `file_path` was reached through a symlinked file or directory below `root`.
+fn reached_via_symlink(file_path: &Path, root: &Path, canonical_root: &Path) -> bool {
+	let Ok(relative) = file_path.strip_prefix(root) else {
+		return false;
+	};
+	fs::canonicalize(file_path).is_ok_and(|canonical| canonical != canonical_root.join(relative))
+}
+
+/// Get the file name used for language detection and name-based matching.
+fn detection_filename(file_path: &Path) -> Result<Cow<'_, str>> {
+	let filename = file_path.file_name().context("Missing file name")?.to_string_lossy();
+	if !filename.contains('\u{FFFD}') {
+		return Ok(filename);
 	}
-	true
+	// Fall back to a synthetic name so extension-based detection still works on non-UTF-8 filenames.
+	Ok(file_path.extension().map_or(filename, |ext| Cow::Owned(format!("file.{}", ext.to_string_lossy()))))
 }
 
 /// Helper to create error context for metadata reading operations.
@@ -25,66 +120,357 @@ fn read_metadata_context(path: &Path) -> String {
 	format!("Failed to read metadata for {}", path.display())
 }
 
+/// What the pipeline knows about a file before reading its contents, whether it lives on disk or in an archive.
+struct FileInfo<'a> {
+	display_path: String,
+	/// The relative path's bytes when they are not valid UTF-8.
+	raw_path: Option<Box<[u8]>>,
+	filename: Cow<'a, str>,
+	size: u64,
+	symlinked: bool,
+	is_test: bool,
+	is_vendored: bool,
+	category: Option<&'a Arc<str>>,
+	/// Language assigned ahead of detection, from `.editorconfig`.
+	language_override: Option<&'static Language>,
+}
+
+impl<'a> FileInfo<'a> {
+	fn new(
+		display_path: String,
+		filename: Cow<'a, str>,
+		size: u64,
+		symlinked: bool,
+		options: &'a ProcessOptions,
+	) -> Self {
+		let is_test = options.tests.as_ref().is_some_and(|matcher| matcher.matches(filename.as_ref(), &display_path));
+		let is_vendored = options.vendored.as_ref().is_some_and(|matcher| matcher.matches(&display_path));
+		let category =
+			options.categories.as_ref().and_then(|matcher| matcher.classify(filename.as_ref(), &display_path));
+		let language_override =
+			options.language_overrides.as_ref().and_then(|overrides| overrides.language_for(&display_path));
+		Self {
+			display_path,
+			raw_path: None,
+			filename,
+			size,
+			symlinked,
+			is_test,
+			is_vendored,
+			category,
+			language_override,
+		}
+	}
+
+	/// Keep the original bytes of a relative path that is not valid UTF-8.
+	fn with_raw_path(mut self, raw_path: Option<Box<[u8]>>) -> Self {
+		self.raw_path = raw_path;
+		self
+	}
+
+	/// The language assigned ahead of detection, if any, marked as an override.
+	fn language_override(&self) -> Option<(&'static Language, DetectionConfidence)> {
+		self.language_override.map(|language| (language, DetectionConfidence::Override))
+	}
+
+	/// Counting target for the file as `language`; `detection` is kept only when per-file details are collected.
+	fn target<'s>(
+		&'s self,
+		language: &'static Language,
+		detection: Option<DetectionConfidence>,
+		options: &'s ProcessOptions,
+	) -> FileTarget<'s> {
+		FileTarget {
+			display_path: &self.display_path,
+			raw_path: self.raw_path.as_deref(),
+			size: self.size,
+			language,
+			detection: detection.filter(|_| options.collect_details),
+			collect_details: options.collect_details,
+			file_sink: options.file_sink.as_deref(),
+			symlinked: self.symlinked,
+			tab_width: options.tab_width,
+			split_mixed: options.split_mixed,
+			fold_markers: &options.fold_markers,
+			docstrings: options.docstrings,
+			shebangs: options.shebangs,
+			unicode_line_breaks: options.unicode_line_breaks,
+			hash: options.hash,
+			embedded: options.embedded,
+			language_filter: options.language_filter(),
+			is_test: self.is_test,
+			is_vendored: self.is_vendored,
+			category: self.category,
+		}
+	}
+}
+
+/// Path of `path` relative to the analysis root, as shown in reports; a file analyzed on its own is shown by name.
+pub(super) fn relative_path(path: &Path, root: &Path) -> String {
+	display_path(relative_to_root(path, root))
+}
+
+fn relative_to_root<'p>(path: &'p Path, root: &Path) -> &'p Path {
+	match path.strip_prefix(root) {
+		Ok(relative) if relative.as_os_str().is_empty() => path.file_name().map_or(path, Path::new),
+		Ok(relative) => relative,
+		Err(_) => path,
+	}
+}
+
+/// The bytes of [`relative_path`] when they are not valid UTF-8, so the paths output can write the name unchanged.
+#[cfg(unix)]
+fn raw_relative_path(path: &Path, root: &Path) -> Option<Box<[u8]>> {
+	use std::os::unix::ffi::OsStrExt as _;
+	let relative = relative_to_root(path, root).as_os_str();
+	relative.to_str().is_none().then(|| relative.as_bytes().into())
+}
+
+/// Paths are only kept as raw bytes on Unix, where a file name can be any bytes.
+#[cfg(not(unix))]
+const fn raw_relative_path(_path: &Path, _root: &Path) -> Option<Box<[u8]>> {
+	None
+}
+
 /// Analyze a single file and merge its statistics into `results`.
 ///
+/// When caching is enabled, an unchanged file is replayed from the cache instead of being read, and every file
+/// whose outcome is known is pushed onto `cache_updates` for the next run.
+///
 /// Returns an error for I/O or decoding failures.
-pub fn process_file(
+pub(super) fn process_file(
 	file_path: &Path,
 	display_root: &Path,
 	results: &mut AnalysisResults,
-	collect_details: bool,
-	include_languages: &[String],
-	exclude_languages: &[String],
+	cache_updates: &mut Vec<(String, CacheEntry)>,
+	options: &ProcessOptions,
 ) -> Result<()> {
-	let display_path = file_path.strip_prefix(display_root).unwrap_or(file_path).display().to_string();
-	let filename_os = file_path.file_name().context("Missing file name")?;
-	let filename_lossy = filename_os.to_string_lossy();
-	let filename: Cow<'_, str> = if filename_lossy.contains('\u{FFFD}') {
-		// Fall back to a synthetic name so extension-based detection still works on non-UTF-8 filenames.
-		file_path.extension().map_or_else(
-			|| Cow::Borrowed(filename_lossy.as_ref()),
-			|ext| {
-				let ext_lossy = ext.to_string_lossy();
-				Cow::Owned(format!("file.{ext_lossy}"))
-			},
-		)
-	} else {
-		Cow::Borrowed(filename_lossy.as_ref())
-	};
+	let display_path = relative_path(file_path, display_root);
+	let symlinked = options
+		.canonical_root
+		.as_deref()
+		.is_some_and(|canonical_root| reached_via_symlink(file_path, display_root, canonical_root));
 	let metadata = file_path.metadata().with_context(|| read_metadata_context(file_path))?;
-	let file_size = metadata.len();
-	let language_from_name = langs::detect_language_info(filename.as_ref(), None);
-	if file_size == 0 {
-		if let Some(language) = language_from_name {
-			if !should_process_language(language, include_languages, exclude_languages) {
-				return Ok(());
-			}
-			let contribution = FileContribution::new(0, 0, 0, 0, 0, file_size);
-			let file_stats = collect_details.then(|| FileStats::new(display_path, 0, 0, 0, 0, 0, file_size));
-			results.add_file_stats(language, contribution, file_stats);
+	if let Some(cutoff) = options.modified_after {
+		let modified = metadata.modified().with_context(|| read_metadata_context(file_path))?;
+		if modified < cutoff {
+			return Ok(());
 		}
+	}
+	let info = FileInfo::new(display_path, detection_filename(file_path)?, metadata.len(), symlinked, options)
+		.with_raw_path(raw_relative_path(file_path, display_root));
+	if info.size == 0 {
+		record_empty_file(&info, results, options);
 		return Ok(());
 	}
-	let mut source = FileSource::open(file_path, file_size)?;
-	let sample_bytes = source.sample(file_size)?;
-	let Some((language, encoding)) = detect_language_and_encoding(filename.as_ref(), &sample_bytes) else {
+	let stamp = options.cache.as_ref().and_then(|_| FileStamp::from_metadata(&metadata));
+	if let (Some(cache), Some(stamp)) = (&options.cache, stamp)
+		&& let Some((entry, language, outcome)) = cache.lookup(&info.display_path, stamp)
+	{
+		cache_updates.push((info.display_path.clone(), entry.clone()));
+		if options.language_filter().allows(language) {
+			record_outcome(&info.target(language, entry.detection(), options), results, &outcome);
+		}
 		return Ok(());
-	};
-	if !should_process_language(language, include_languages, exclude_languages) {
+	}
+	let _permit = options.io_limit.as_ref().map(IoLimiter::acquire);
+	let source = FileSource::open(file_path, info.size)?;
+	if let Some((language, detection, outcome)) = count_source(&info, source, results, options)?
+		&& let Some(stamp) = stamp
+	{
+		cache_updates.push((info.display_path.clone(), CacheEntry::new(stamp, language, detection, &outcome)));
+	}
+	Ok(())
+}
+
+/// Analyze a file read into memory, such as an archive member, and merge its statistics into `results`.
+///
+/// `path` is the `/`-separated path shown in reports; its last component drives language detection.
+///
+/// Returns an error for decoding failures.
+#[cfg(feature = "archive")]
+pub(super) fn process_bytes(
+	path: &str,
+	bytes: Vec<u8>,
+	results: &mut AnalysisResults,
+	options: &ProcessOptions,
+) -> Result<()> {
+	let filename = path.rsplit('/').next().unwrap_or(path).to_owned();
+	let info = FileInfo::new(path.to_owned(), Cow::Owned(filename), bytes.len() as u64, false, options);
+	if info.size == 0 {
+		record_empty_file(&info, results, options);
 		return Ok(());
 	}
-	source.process(&display_path, file_size, results, collect_details, language, encoding)
+	count_source(&info, FileSource::Memory(bytes), results, options)?;
+	Ok(())
+}
+
+/// Whether the language filters rule out the file at `path` before its contents are read.
+///
+/// Only a language decided without content, by a language override or by a file name that matches a single
+/// language, is checked; a file whose language content could still decide is kept.
+#[cfg(feature = "archive")]
+pub(super) fn filtered_out_by_name(path: &str, options: &ProcessOptions) -> bool {
+	let filename = path.rsplit('/').next().unwrap_or(path);
+	options
+		.language_overrides
+		.as_ref()
+		.and_then(|overrides| overrides.language_for(path))
+		.or_else(|| langs::language_from_name(filename))
+		.is_some_and(|language| !options.language_filter().allows(language))
+}
+
+/// Record an empty file under the language its name suggests, if any.
+///
+/// There is no content to sniff, so an extensionless file is left out here; once it has a shebang it takes the
+/// non-empty path, where the content sample is always read before detection.
+fn record_empty_file(info: &FileInfo, results: &mut AnalysisResults, options: &ProcessOptions) {
+	let Some((language, detection)) = info
+		.language_override()
+		.or_else(|| langs::detect_language_with_score(info.filename.as_ref(), None, options.detection_threshold))
+	else {
+		return;
+	};
+	if !options.language_filter().allows(language) {
+		return;
+	}
+	if options.generated.as_ref().is_some_and(|detector| detector.matches_name(info.filename.as_ref())) {
+		results.add_generated_file();
+		return;
+	}
+	let contribution = FileContribution::new(0, 0, 0, 0, 0, 0).with_test(info.is_test);
+	if info.is_vendored {
+		results.add_vendored_file(&contribution);
+		return;
+	}
+	let file_stats = options.collect_details.then(|| {
+		FileStats::new(info.display_path.clone(), 0, 0, 0, 0, 0, 0)
+			.with_symlinked(info.symlinked)
+			.with_category(info.category.cloned())
+			.with_hash(options.hash.then(|| ContentHash::new().finish()))
+			.with_detection(Some(detection))
+	});
+	let file_stats = line_counter::hand_off(options.file_sink.as_deref(), language, file_stats);
+	results.add_file_stats(language, contribution, file_stats);
+	if let Some(category) = info.category {
+		results.add_category_file(category, &contribution);
+	}
+}
+
+/// Detect the language of a non-empty file from `source`, count it and merge the outcome into `results`.
+///
+/// Returns the language and outcome so they can be cached, or `None` when the file was skipped as binary,
+/// unrecognized or filtered out.
+fn count_source(
+	info: &FileInfo,
+	mut source: FileSource,
+	results: &mut AnalysisResults,
+	options: &ProcessOptions,
+) -> Result<Option<(&'static Language, DetectionConfidence, Outcome)>> {
+	let sample_bytes = source.sample(info.size)?;
+	let encoding = options
+		.input_encoding
+		.map_or_else(|| detect_encoding(&sample_bytes), |forced| forced_encoding(forced, &sample_bytes));
+	let binary = if options.input_encoding.is_some() {
+		is_binary_despite_forced_encoding(&sample_bytes, encoding)
+	} else {
+		is_probably_binary(&sample_bytes, encoding, options.binary_threshold_for(info.filename.as_ref()))
+	};
+	if binary {
+		if options.include_assets {
+			results.add_asset(info.size);
+		} else if options.collect_skipped {
+			results.add_skipped_file(info.display_path.as_str(), "binary");
+		}
+		return Ok(None);
+	}
+	let filename = info.filename.as_ref();
+	let explain = options.explain_detection.then_some(info.display_path.as_str());
+	let Some((language, detection)) = info
+		.language_override()
+		.or_else(|| detect_language(filename, &sample_bytes, encoding, options.detection_threshold, explain))
+	else {
+		if options.collect_skipped {
+			results.add_skipped_file(info.display_path.as_str(), "unrecognized");
+		}
+		return Ok(None);
+	};
+	if !options.language_filter().allows(language) {
+		return Ok(None);
+	}
+	let target = info.target(language, Some(detection), options);
+	let outcome = if let Some(detector) = &options.generated
+		&& (detector.matches_name(filename) || detector.matches_content(&decode_bytes(&sample_bytes, encoding, true)))
+	{
+		Outcome::Generated
+	} else {
+		Outcome::Counted { counts: source.process(&target, encoding)?, bom: encoding.bom_len > 0 }
+	};
+	record_outcome(&target, results, &outcome);
+	Ok(Some((language, detection, outcome)))
 }
 
-fn detect_language_from_samples(filename: &str, samples: &[u8], encoding: FileEncoding) -> Option<&'static Language> {
-	if is_probably_binary(samples, encoding) {
-		return None;
+/// Merge a freshly computed or cached outcome into `results`.
+fn record_outcome(target: &FileTarget, results: &mut AnalysisResults, outcome: &Outcome) {
+	match outcome {
+		Outcome::Generated => results.add_generated_file(),
+		Outcome::Counted { counts, bom } => {
+			line_counter::record_file(target, results, counts);
+			if *bom {
+				results.add_bom_file();
+			}
+			if counts.minified {
+				results.add_minified_file();
+			}
+		}
 	}
+}
+
+/// Detect the language of a text file from its name and content samples, along with what decided it.
+///
+/// When `explain` names the file, the candidate scores are logged to stderr if detection had to score the content.
+fn detect_language(
+	filename: &str,
+	samples: &[u8],
+	encoding: FileEncoding,
+	threshold: u32,
+	explain: Option<&str>,
+) -> Option<(&'static Language, DetectionConfidence)> {
 	let decoded = decode_bytes(samples, encoding, true);
-	langs::detect_language_info(filename, Some(decoded.as_ref()))
+	let detected = langs::detect_language_with_score(filename, Some(decoded.as_ref()), threshold);
+	if let Some(path) = explain
+		&& let Some(scores) = langs::explain_detection(filename, decoded.as_ref())
+	{
+		eprintln!("{}", describe_scores(path, detected.map(|(language, _)| language), &scores));
+	}
+	detected
+}
+
+/// Format one `--explain-detection` line, e.g. `src/app.m: Objective-C (MATLAB 10, Objective-C 30)`.
+fn describe_scores(path: &str, chosen: Option<&Language>, scores: &[(&Language, i32)]) -> String {
+	let scores = scores.iter().map(|(language, score)| format!("{} {score}", language.name)).collect::<Vec<_>>();
+	let chosen = chosen.map_or("undetected", |language| language.name);
+	format!("{path}: {chosen} ({})", scores.join(", "))
 }
 
-fn detect_language_and_encoding(filename: &str, samples: &[u8]) -> Option<(&'static Language, FileEncoding)> {
-	let encoding = detect_encoding(samples);
-	detect_language_from_samples(filename, samples, encoding).map(|language| (language, encoding))
+#[cfg(all(test, unix))]
+mod tests {
+	use std::os::unix::fs::symlink;
+
+	use tempfile::TempDir;
+
+	use super::*;
+
+	#[test]
+	fn reached_via_symlink_detects_linked_directories() {
+		let (root, target) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+		let (root, target) = (root.path(), target.path());
+		fs::create_dir_all(root.join("real")).unwrap();
+		fs::write(root.join("real/a.rs"), "fn a() {}\n").unwrap();
+		fs::write(target.join("b.rs"), "fn b() {}\n").unwrap();
+		symlink(target, root.join("link")).unwrap();
+		let canonical_root = fs::canonicalize(root).unwrap();
+		assert!(!reached_via_symlink(&root.join("real/a.rs"), root, &canonical_root));
+		assert!(reached_via_symlink(&root.join("link/b.rs"), root, &canonical_root));
+	}
 }
@@ -1,4 +1,12 @@
-use crate::langs::{LANGUAGES, Language};
+use std::sync::Arc;
+
+use anyhow::{Result, bail, ensure};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	config::DisplayAlias,
+	langs::{DetectionConfidence, LANGUAGES, Language},
+};
 
 /// Initial capacity for per-language file detail lists in verbose mode. Pre-allocating 256 slots reduces reallocations for most projects while avoiding excessive memory waste for languages with few files.
 const INITIAL_FILE_LIST_CAPACITY: usize = 256;
@@ -35,21 +43,27 @@ macro_rules! impl_percentage_methods {
 			pub fn shebang_percentage(&self) -> f64 {
 				percentage(self.$stats_field.shebang, self.$total_field)
 			}
+			#[must_use]
+			pub fn mixed_percentage(&self) -> f64 {
+				percentage(self.$stats_field.mixed, self.$total_field)
+			}
 		}
 	};
 }
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 struct LineStats {
 	code: u64,
 	comment: u64,
 	blank: u64,
 	shebang: u64,
+	/// Lines holding both code and a comment; only non-zero when mixed lines are split out of `code`.
+	mixed: u64,
 }
 
 impl LineStats {
 	const fn new(code: u64, comment: u64, blank: u64, shebang: u64) -> Self {
-		Self { code, comment, blank, shebang }
+		Self { code, comment, blank, shebang, mixed: 0 }
 	}
 
 	const fn merge(&mut self, other: &Self) {
@@ -57,15 +71,93 @@ impl LineStats {
 		self.comment = self.comment.saturating_add(other.comment);
 		self.blank = self.blank.saturating_add(other.blank);
 		self.shebang = self.shebang.saturating_add(other.shebang);
+		self.mixed = self.mixed.saturating_add(other.mixed);
+	}
+
+	/// Lines holding any code, whether or not mixed lines were split out of `code`.
+	const fn sloc(&self) -> u64 {
+		self.code.saturating_add(self.mixed)
+	}
+}
+
+/// Dominant leading-whitespace style across a set of lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndentationStyle {
+	Tabs,
+	Spaces,
+	Mixed,
+	None,
+}
+
+impl IndentationStyle {
+	#[must_use]
+	pub const fn label(self) -> &'static str {
+		match self {
+			Self::Tabs => "tabs",
+			Self::Spaces => "spaces",
+			Self::Mixed => "mixed",
+			Self::None => "none",
+		}
+	}
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct IndentationStats {
+	tab: u64,
+	space: u64,
+}
+
+impl IndentationStats {
+	const fn new(tab: u64, space: u64) -> Self {
+		Self { tab, space }
+	}
+
+	const fn merge(&mut self, other: &Self) {
+		self.tab = self.tab.saturating_add(other.tab);
+		self.space = self.space.saturating_add(other.space);
+	}
+
+	const fn dominant(&self) -> IndentationStyle {
+		if self.tab == 0 && self.space == 0 {
+			IndentationStyle::None
+		} else if self.tab > self.space {
+			IndentationStyle::Tabs
+		} else if self.space > self.tab {
+			IndentationStyle::Spaces
+		} else {
+			IndentationStyle::Mixed
+		}
+	}
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct CommentStyleStats {
+	line: u64,
+	block: u64,
+}
+
+impl CommentStyleStats {
+	const fn merge(&mut self, other: &Self) {
+		self.line = self.line.saturating_add(other.line);
+		self.block = self.block.saturating_add(other.block);
 	}
 }
 
 /// Aggregated data about a single file, used for updating totals without always storing per-file detail.
-#[derive(Debug, Clone, Copy)]
+///
+/// Serializable so the analysis cache can store it; the test flag is left out since it depends on the current
+/// test patterns rather than the file's contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FileContribution {
 	total_lines: u64,
 	line_stats: LineStats,
+	indentation: IndentationStats,
+	comment_styles: CommentStyleStats,
+	max_line_length: u64,
 	size: u64,
+	crlf_bytes: u64,
+	#[serde(skip)]
+	is_test: bool,
 }
 
 impl FileContribution {
@@ -78,7 +170,68 @@ impl FileContribution {
 		shebang_lines: u64,
 		size: u64,
 	) -> Self {
-		Self { total_lines, line_stats: LineStats::new(code_lines, comment_lines, blank_lines, shebang_lines), size }
+		Self {
+			total_lines,
+			line_stats: LineStats::new(code_lines, comment_lines, blank_lines, shebang_lines),
+			indentation: IndentationStats::new(0, 0),
+			comment_styles: CommentStyleStats { line: 0, block: 0 },
+			max_line_length: 0,
+			size,
+			crlf_bytes: 0,
+			is_test: false,
+		}
+	}
+
+	/// Attach the width of the file's longest line, in columns after tab expansion.
+	#[must_use]
+	pub const fn with_max_line_length(mut self, max_line_length: u64) -> Self {
+		self.max_line_length = max_line_length;
+		self
+	}
+
+	/// Attach the number of lines counted as mixed code and comment instead of code.
+	#[must_use]
+	pub const fn with_mixed_lines(mut self, mixed_lines: u64) -> Self {
+		self.line_stats.mixed = mixed_lines;
+		self
+	}
+
+	/// Mark whether the file was classified as a test.
+	#[must_use]
+	pub const fn with_test(mut self, is_test: bool) -> Self {
+		self.is_test = is_test;
+		self
+	}
+
+	/// Attach the number of tab-indented and space-indented lines found in the file.
+	#[must_use]
+	pub const fn with_indentation(mut self, tab_lines: u64, space_lines: u64) -> Self {
+		self.indentation = IndentationStats::new(tab_lines, space_lines);
+		self
+	}
+
+	/// Attach the number of comment lines made by line-comment markers and by block comments.
+	#[must_use]
+	pub const fn with_comment_styles(mut self, line_comment_lines: u64, block_comment_lines: u64) -> Self {
+		self.comment_styles = CommentStyleStats { line: line_comment_lines, block: block_comment_lines };
+		self
+	}
+
+	/// Attach the bytes taken by the `\r` of `\r\n` line endings, which the LF-normalized size leaves out.
+	#[must_use]
+	pub const fn with_crlf_bytes(mut self, crlf_bytes: u64) -> Self {
+		self.crlf_bytes = crlf_bytes;
+		self
+	}
+
+	/// Add the lines of `other` to this file, keeping its own size.
+	pub(crate) fn absorb_lines(&mut self, other: &Self) {
+		self.total_lines = self.total_lines.saturating_add(other.total_lines);
+		self.line_stats.merge(&other.line_stats);
+		self.indentation.merge(&other.indentation);
+		self.comment_styles.merge(&other.comment_styles);
+		self.max_line_length = self.max_line_length.max(other.max_line_length);
+		self.crlf_bytes = self.crlf_bytes.saturating_add(other.crlf_bytes);
 	}
 
 	#[must_use]
@@ -90,15 +243,32 @@ impl FileContribution {
 	pub const fn size(&self) -> u64 {
 		self.size
 	}
+
+	/// Build the per-file detail row matching this contribution.
+	pub(crate) const fn file_stats(&self, path: String) -> FileStats {
+		let stats = &self.line_stats;
+		FileStats::new(path, self.total_lines, stats.code, stats.comment, stats.blank, stats.shebang, self.size)
+			.with_mixed_lines(stats.mixed)
+			.with_max_line_length(self.max_line_length)
+	}
 }
 
 /// Statistics for a single file
-#[derive(Debug, Clone, PartialEq, Eq)]
+///
+/// Ordering compares the path first and then every count, so sorting by it is total.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct FileStats {
 	path: String,
+	/// The path's bytes when they are not valid UTF-8, in which case `path` is a lossy rendering of them.
+	raw_path: Option<Box<[u8]>>,
 	total_lines: u64,
 	line_stats: LineStats,
 	size: u64,
+	max_line_length: u64,
+	symlinked: bool,
+	category: Option<Arc<str>>,
+	hash: Option<u64>,
+	detection: Option<DetectionConfidence>,
 }
 
 impl FileStats {
@@ -125,18 +295,58 @@ impl FileStats {
 	) -> Self {
 		Self {
 			path,
+			raw_path: None,
 			total_lines,
 			line_stats: LineStats::new(code_lines, comment_lines, blank_lines, shebang_lines),
 			size,
+			max_line_length: 0,
+			symlinked: false,
+			category: None,
+			hash: None,
+			detection: None,
 		}
 	}
 
+	/// Attach the width of the file's longest line, in columns after tab expansion.
+	#[must_use]
+	pub const fn with_max_line_length(mut self, max_line_length: u64) -> Self {
+		self.max_line_length = max_line_length;
+		self
+	}
+
+	/// Attach the number of lines counted as mixed code and comment instead of code.
+	#[must_use]
+	pub const fn with_mixed_lines(mut self, mixed_lines: u64) -> Self {
+		self.line_stats.mixed = mixed_lines;
+		self
+	}
+
+	/// Mark whether the file was reached through a symlinked file or directory.
+	#[must_use]
+	pub const fn with_symlinked(mut self, symlinked: bool) -> Self {
+		self.symlinked = symlinked;
+		self
+	}
+
 	/// Get the file path
 	#[must_use]
 	pub fn path(&self) -> &str {
 		&self.path
 	}
 
+	/// Attach the path's original bytes, for a path that is not valid UTF-8.
+	#[must_use]
+	pub fn with_raw_path(mut self, raw_path: Option<Box<[u8]>>) -> Self {
+		self.raw_path = raw_path;
+		self
+	}
+
+	/// The path as it was found on disk: its original bytes when they are not valid UTF-8, otherwise [`Self::path`].
+	#[must_use]
+	pub fn path_bytes(&self) -> &[u8] {
+		self.raw_path.as_deref().unwrap_or(self.path.as_bytes())
+	}
+
 	#[must_use]
 	pub const fn total_lines(&self) -> u64 {
 		self.total_lines
@@ -166,6 +376,77 @@ impl FileStats {
 	pub const fn shebang_lines(&self) -> u64 {
 		self.line_stats.shebang
 	}
+
+	#[must_use]
+	pub const fn mixed_lines(&self) -> u64 {
+		self.line_stats.mixed
+	}
+
+	/// Get the source lines of code: every line holding code, including mixed lines when they are split out
+	#[must_use]
+	pub const fn sloc(&self) -> u64 {
+		self.line_stats.sloc()
+	}
+
+	/// Get the width of the longest line, in columns after tab expansion
+	#[must_use]
+	pub const fn max_line_length(&self) -> u64 {
+		self.max_line_length
+	}
+
+	/// Check whether the file was reached through a symlinked file or directory
+	#[must_use]
+	pub const fn is_symlinked(&self) -> bool {
+		self.symlinked
+	}
+
+	/// Attach the category the file was assigned by the category rules.
+	#[must_use]
+	pub fn with_category(mut self, category: Option<Arc<str>>) -> Self {
+		self.category = category;
+		self
+	}
+
+	/// Name of the category the file was assigned, when categories are split.
+	#[must_use]
+	pub fn category(&self) -> Option<&str> {
+		self.category.as_deref()
+	}
+
+	/// Attach the hash of the file's content.
+	#[must_use]
+	pub const fn with_hash(mut self, hash: Option<u64>) -> Self {
+		self.hash = hash;
+		self
+	}
+
+	/// 64-bit FNV-1a hash of the bytes read from the file, when hashes were requested.
+	#[must_use]
+	pub const fn hash(&self) -> Option<u64> {
+		self.hash
+	}
+
+	/// Attach what decided the file's language.
+	#[must_use]
+	pub const fn with_detection(mut self, detection: Option<DetectionConfidence>) -> Self {
+		self.detection = detection;
+		self
+	}
+
+	/// What decided the file's language: its name, its shebang, an override, or the winning content score.
+	#[must_use]
+	pub const fn detection(&self) -> Option<DetectionConfidence> {
+		self.detection
+	}
+}
+
+/// Receives each file's details as soon as the file is counted, instead of the results holding every file until the
+/// walk ends.
+///
+/// Set one with [`CodeAnalyzer::with_file_sink`](super::CodeAnalyzer::with_file_sink). It is called from every worker
+/// thread, in the order files finish rather than in report order.
+pub trait FileSink: Send + Sync {
+	fn accept(&self, language: &'static Language, file: FileStats);
 }
 
 /// Holds statistics about a programming language's usage throughout a project.
@@ -174,7 +455,11 @@ pub struct LanguageStats {
 	files: u64,
 	lines: u64,
 	line_stats: LineStats,
+	indentation: IndentationStats,
+	comment_styles: CommentStyleStats,
+	max_line_length: u64,
 	size: u64,
+	crlf_bytes: u64,
 	file_list: Vec<FileStats>,
 }
 
@@ -183,7 +468,11 @@ impl LanguageStats {
 		self.files = self.files.saturating_add(1);
 		self.lines = self.lines.saturating_add(contribution.total_lines());
 		self.line_stats.merge(&contribution.line_stats);
+		self.indentation.merge(&contribution.indentation);
+		self.comment_styles.merge(&contribution.comment_styles);
+		self.max_line_length = self.max_line_length.max(contribution.max_line_length);
 		self.size = self.size.saturating_add(contribution.size());
+		self.crlf_bytes = self.crlf_bytes.saturating_add(contribution.crlf_bytes);
 		if let Some(stats) = file_stats {
 			// Reserve capacity on first file to reduce reallocations
 			if self.file_list.is_empty() {
@@ -193,11 +482,24 @@ impl LanguageStats {
 		}
 	}
 
+	/// Add lines found embedded in another language's file, without counting a file or its size.
+	pub(crate) fn add_embedded(&mut self, contribution: &FileContribution) {
+		self.lines = self.lines.saturating_add(contribution.total_lines());
+		self.line_stats.merge(&contribution.line_stats);
+		self.indentation.merge(&contribution.indentation);
+		self.comment_styles.merge(&contribution.comment_styles);
+		self.max_line_length = self.max_line_length.max(contribution.max_line_length);
+	}
+
 	pub(crate) fn merge(&mut self, mut other: Self) {
 		self.files = self.files.saturating_add(other.files);
 		self.lines = self.lines.saturating_add(other.lines);
 		self.line_stats.merge(&other.line_stats);
+		self.indentation.merge(&other.indentation);
+		self.comment_styles.merge(&other.comment_styles);
+		self.max_line_length = self.max_line_length.max(other.max_line_length);
 		self.size = self.size.saturating_add(other.size);
+		self.crlf_bytes = self.crlf_bytes.saturating_add(other.crlf_bytes);
 		self.file_list.append(&mut other.file_list);
 	}
 
@@ -206,6 +508,12 @@ impl LanguageStats {
 		self.files
 	}
 
+	/// Whether no file or embedded line was counted for this language.
+	#[must_use]
+	pub const fn is_empty(&self) -> bool {
+		self.files == 0 && self.lines == 0
+	}
+
 	#[must_use]
 	pub const fn lines(&self) -> u64 {
 		self.lines
@@ -216,12 +524,25 @@ impl LanguageStats {
 		self.size
 	}
 
+	/// Get the size in bytes with every `\r\n` line ending counted as a single `\n`
+	#[must_use]
+	pub const fn normalized_size(&self) -> u64 {
+		self.size.saturating_sub(self.crlf_bytes)
+	}
+
 	/// Get the number of code lines across all files of this language
 	#[must_use]
 	pub const fn code_lines(&self) -> u64 {
 		self.line_stats.code
 	}
 
+	/// Get the source lines of code across all files of this language: every line holding code, including mixed
+	/// lines when they are split out
+	#[must_use]
+	pub const fn sloc(&self) -> u64 {
+		self.line_stats.sloc()
+	}
+
 	/// Get the number of comment lines across all files of this language
 	#[must_use]
 	pub const fn comment_lines(&self) -> u64 {
@@ -240,6 +561,48 @@ impl LanguageStats {
 		self.line_stats.shebang
 	}
 
+	/// Get the number of mixed code and comment lines across all files of this language
+	#[must_use]
+	pub const fn mixed_lines(&self) -> u64 {
+		self.line_stats.mixed
+	}
+
+	/// Get the number of non-blank lines indented with a leading tab
+	#[must_use]
+	pub const fn tab_lines(&self) -> u64 {
+		self.indentation.tab
+	}
+
+	/// Get the number of non-blank lines indented with a leading space
+	#[must_use]
+	pub const fn space_lines(&self) -> u64 {
+		self.indentation.space
+	}
+
+	/// Get the number of comment lines made by line-comment markers such as `//`
+	#[must_use]
+	pub const fn line_comment_lines(&self) -> u64 {
+		self.comment_styles.line
+	}
+
+	/// Get the number of comment lines inside or made by block comments such as `/* ... */`
+	#[must_use]
+	pub const fn block_comment_lines(&self) -> u64 {
+		self.comment_styles.block
+	}
+
+	/// Get the width of the longest line across all files of this language, in columns after tab expansion
+	#[must_use]
+	pub const fn max_line_length(&self) -> u64 {
+		self.max_line_length
+	}
+
+	/// Get the indentation style used by most indented lines of this language
+	#[must_use]
+	pub const fn dominant_indentation(&self) -> IndentationStyle {
+		self.indentation.dominant()
+	}
+
 	/// Get the list of individual file statistics for this language
 	#[must_use]
 	pub fn files_list(&self) -> &[FileStats] {
@@ -255,19 +618,174 @@ impl LanguageStats {
 	pub fn average_lines_per_file(&self) -> f64 {
 		if self.files == 0 { 0.0 } else { self.lines as f64 / self.files as f64 }
 	}
+
+	/// Calculate the average size in bytes of a line for this language, a rough measure of how verbose it is
+	#[must_use]
+	#[expect(
+		clippy::cast_precision_loss,
+		reason = "average line sizes are display-only; sub-unit precision loss is acceptable"
+	)]
+	pub fn bytes_per_line(&self) -> f64 {
+		if self.lines == 0 { 0.0 } else { self.size as f64 / self.lines as f64 }
+	}
 }
 
 impl_percentage_methods!(LanguageStats, lines, line_stats);
 
+/// Totals for a subset of files set apart by path: tests when test splitting is enabled, or vendored code when it is
+/// collapsed.
+///
+/// Test files also count towards the overall [`AnalysisResults`] totals, so non-test totals are the difference between
+/// the two. Vendored files are kept out of the overall totals.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SubsetTotals {
+	files: u64,
+	lines: u64,
+	line_stats: LineStats,
+	size: u64,
+}
+
+impl SubsetTotals {
+	const fn add_lines(&mut self, contribution: &FileContribution) {
+		self.lines = self.lines.saturating_add(contribution.total_lines());
+		self.line_stats.merge(&contribution.line_stats);
+	}
+
+	const fn add_file(&mut self, contribution: &FileContribution) {
+		self.files = self.files.saturating_add(1);
+		self.size = self.size.saturating_add(contribution.size());
+		self.add_lines(contribution);
+	}
+
+	const fn merge(&mut self, other: &Self) {
+		self.files = self.files.saturating_add(other.files);
+		self.lines = self.lines.saturating_add(other.lines);
+		self.line_stats.merge(&other.line_stats);
+		self.size = self.size.saturating_add(other.size);
+	}
+
+	#[must_use]
+	pub const fn files(&self) -> u64 {
+		self.files
+	}
+
+	#[must_use]
+	pub const fn lines(&self) -> u64 {
+		self.lines
+	}
+
+	#[must_use]
+	pub const fn code_lines(&self) -> u64 {
+		self.line_stats.code
+	}
+
+	#[must_use]
+	pub const fn comment_lines(&self) -> u64 {
+		self.line_stats.comment
+	}
+
+	#[must_use]
+	pub const fn blank_lines(&self) -> u64 {
+		self.line_stats.blank
+	}
+
+	#[must_use]
+	pub const fn size(&self) -> u64 {
+		self.size
+	}
+}
+
+/// The largest file seen for one measure, kept as a path and its counts rather than full per-file detail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LargestFile {
+	path: String,
+	lines: u64,
+	size: u64,
+}
+
+impl LargestFile {
+	/// Path of the file, as reported in per-file details.
+	#[must_use]
+	pub fn path(&self) -> &str {
+		&self.path
+	}
+
+	/// Total lines in the file, including lines attributed to embedded languages.
+	#[must_use]
+	pub const fn lines(&self) -> u64 {
+		self.lines
+	}
+
+	/// File size in bytes.
+	#[must_use]
+	pub const fn size(&self) -> u64 {
+		self.size
+	}
+}
+
+/// Whether a file with `value` at `path` should replace `current` as the largest by `measure`.
+///
+/// Ties go to the lexicographically smaller path so the winner does not depend on traversal or merge order. Files
+/// where the measure is zero never qualify.
+fn outranks(value: u64, path: &str, current: Option<&LargestFile>, measure: fn(&LargestFile) -> u64) -> bool {
+	value > 0
+		&& current.is_none_or(|current| {
+			let current_value = measure(current);
+			value > current_value || (value == current_value && path < current.path.as_str())
+		})
+}
+
+/// A file left out of the analysis, with why.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SkippedFile {
+	path: String,
+	reason: String,
+}
+
+impl SkippedFile {
+	/// Path of the file, as reported in per-file details.
+	#[must_use]
+	pub fn path(&self) -> &str {
+		&self.path
+	}
+
+	/// Why the file was skipped: `binary`, `unrecognized`, or `io-error: <message>`.
+	#[must_use]
+	pub fn reason(&self) -> &str {
+		&self.reason
+	}
+}
+
 /// Results of a code analysis operation
+///
+/// [`AnalysisResults::default`] is an empty result set with every total at zero. It is the identity for
+/// [`AnalysisResults::merge`], so partial results can be folded starting from it.
 #[derive(Debug, Default)]
 pub struct AnalysisResults {
 	total_files: u64,
 	total_lines: u64,
 	line_stats: LineStats,
 	total_size: u64,
+	total_crlf_bytes: u64,
 	skipped_entries: u64,
+	generated_files: u64,
+	bom_files: u64,
+	minified_files: u64,
+	asset_files: u64,
+	asset_size: u64,
+	test_totals: SubsetTotals,
+	vendored_totals: SubsetTotals,
+	largest_by_lines: Option<LargestFile>,
+	largest_by_size: Option<LargestFile>,
 	language_stats: Vec<LanguageStats>,
+	/// Languages merged under a display alias, reported in place of the languages they replaced.
+	language_groups: Vec<(String, LanguageStats)>,
+	/// Indices of languages named explicitly by the caller, reported even when nothing of theirs was found.
+	requested_languages: Vec<usize>,
+	/// Files left out as binary, unrecognized or unreadable, recorded only when the analysis collects them.
+	skipped_files: Vec<SkippedFile>,
+	/// Totals per file category, in the order categories were first seen, present only when categories are split.
+	category_totals: Vec<(Arc<str>, SubsetTotals)>,
 }
 
 impl AnalysisResults {
@@ -294,21 +812,126 @@ impl AnalysisResults {
 		self.total_lines = self.total_lines.saturating_add(contribution.total_lines());
 		self.line_stats.merge(&contribution.line_stats);
 		self.total_size = self.total_size.saturating_add(contribution.size());
+		self.total_crlf_bytes = self.total_crlf_bytes.saturating_add(contribution.crlf_bytes);
+		if contribution.is_test {
+			self.test_totals.add_file(&contribution);
+		}
 		self.language_stats[language.index].add_file(&contribution, file_stats);
 	}
 
-	pub(crate) fn merge(&mut self, other: Self) {
+	/// Attribute lines embedded in a file of another language (e.g. a fenced block in Markdown) to `language`.
+	///
+	/// The lines count towards the totals, but no file or size is added since the host file already counted both.
+	pub(crate) fn add_embedded_stats(&mut self, language: &'static Language, contribution: FileContribution) {
+		self.ensure_language_slot(language);
+		self.total_lines = self.total_lines.saturating_add(contribution.total_lines());
+		self.line_stats.merge(&contribution.line_stats);
+		if contribution.is_test {
+			self.test_totals.add_lines(&contribution);
+		}
+		self.language_stats[language.index].add_embedded(&contribution);
+	}
+
+	/// Consider the file at `path` for the largest-file records, given its total `lines` and `size` in bytes.
+	///
+	/// The path is only copied when the file becomes a new record.
+	pub(crate) fn track_largest_file(&mut self, path: &str, lines: u64, size: u64) {
+		if outranks(lines, path, self.largest_by_lines.as_ref(), LargestFile::lines) {
+			self.largest_by_lines = Some(LargestFile { path: path.to_owned(), lines, size });
+		}
+		if outranks(size, path, self.largest_by_size.as_ref(), LargestFile::size) {
+			self.largest_by_size = Some(LargestFile { path: path.to_owned(), lines, size });
+		}
+	}
+
+	fn merge_largest(current: &mut Option<LargestFile>, other: Option<LargestFile>, measure: fn(&LargestFile) -> u64) {
+		if let Some(other) = other
+			&& outranks(measure(&other), &other.path, current.as_ref(), measure)
+		{
+			*current = Some(other);
+		}
+	}
+
+	/// Merge `other` into these results, summing every total and concatenating per-file details.
+	///
+	/// Merging is associative, so results from independent [`CodeAnalyzer`](super::CodeAnalyzer) runs (for
+	/// example over separate subtrees) can be combined in any grouping. File paths are kept as reported by each
+	/// run, i.e. relative to that run's root.
+	pub fn merge(&mut self, other: Self) {
 		self.total_files = self.total_files.saturating_add(other.total_files);
 		self.total_lines = self.total_lines.saturating_add(other.total_lines);
 		self.line_stats.merge(&other.line_stats);
 		self.total_size = self.total_size.saturating_add(other.total_size);
+		self.total_crlf_bytes = self.total_crlf_bytes.saturating_add(other.total_crlf_bytes);
 		self.skipped_entries = self.skipped_entries.saturating_add(other.skipped_entries);
+		self.generated_files = self.generated_files.saturating_add(other.generated_files);
+		self.bom_files = self.bom_files.saturating_add(other.bom_files);
+		self.minified_files = self.minified_files.saturating_add(other.minified_files);
+		self.asset_files = self.asset_files.saturating_add(other.asset_files);
+		self.asset_size = self.asset_size.saturating_add(other.asset_size);
+		self.test_totals.merge(&other.test_totals);
+		self.vendored_totals.merge(&other.vendored_totals);
+		Self::merge_largest(&mut self.largest_by_lines, other.largest_by_lines, LargestFile::lines);
+		Self::merge_largest(&mut self.largest_by_size, other.largest_by_size, LargestFile::size);
 		if self.language_stats.len() < other.language_stats.len() {
 			self.language_stats.resize_with(other.language_stats.len(), LanguageStats::default);
 		}
 		for (idx, stats) in other.language_stats.into_iter().enumerate() {
 			self.language_stats[idx].merge(stats);
 		}
+		for (name, stats) in other.language_groups {
+			match self.language_groups.iter_mut().find(|(existing, _)| *existing == name) {
+				Some((_, group)) => group.merge(stats),
+				None => self.language_groups.push((name, stats)),
+			}
+		}
+		for idx in other.requested_languages {
+			if !self.requested_languages.contains(&idx) {
+				self.requested_languages.push(idx);
+			}
+		}
+		self.skipped_files.extend(other.skipped_files);
+		for (name, totals) in other.category_totals {
+			self.category_slot(&name).merge(&totals);
+		}
+	}
+
+	/// Combine any number of partial results into a single result set.
+	#[must_use]
+	pub fn combine(parts: impl IntoIterator<Item = Self>) -> Self {
+		parts.into_iter().fold(Self::with_language_capacity(), |mut acc, part| {
+			acc.merge(part);
+			acc
+		})
+	}
+
+	/// Combine partial results from sharded analyses, checking first that every part indexes languages the same way.
+	///
+	/// Per-language statistics are stored by [`Language::index`], so a part built against a different language table
+	/// would be merged into the wrong languages.
+	///
+	/// # Errors
+	///
+	/// Returns an error naming the offending part, without merging anything, if a part tracks more languages than
+	/// the [`LANGUAGES`] table of this build or requests a language index outside it.
+	pub fn from_parts(parts: Vec<Self>) -> Result<Self> {
+		for (part_idx, part) in parts.iter().enumerate() {
+			part.check_language_table().map_err(|err| err.context(format!("cannot combine part {part_idx}")))?;
+		}
+		Ok(Self::combine(parts))
+	}
+
+	fn check_language_table(&self) -> Result<()> {
+		ensure!(
+			self.language_stats.len() <= LANGUAGES.len(),
+			"results track {} languages but only {} are known",
+			self.language_stats.len(),
+			LANGUAGES.len()
+		);
+		if let Some(idx) = self.requested_languages.iter().find(|&&idx| idx >= LANGUAGES.len()) {
+			bail!("requested language index {idx} is out of range for {} languages", LANGUAGES.len());
+		}
+		Ok(())
 	}
 
 	#[must_use]
@@ -327,6 +950,22 @@ impl AnalysisResults {
 		self.total_size
 	}
 
+	/// Calculate the average size in bytes of a line across all files
+	#[must_use]
+	#[expect(
+		clippy::cast_precision_loss,
+		reason = "average line sizes are display-only; sub-unit precision loss is acceptable"
+	)]
+	pub fn bytes_per_line(&self) -> f64 {
+		if self.total_lines == 0 { 0.0 } else { self.total_size as f64 / self.total_lines as f64 }
+	}
+
+	/// Get the total size in bytes with every `\r\n` line ending counted as a single `\n`
+	#[must_use]
+	pub const fn total_normalized_size(&self) -> u64 {
+		self.total_size.saturating_sub(self.total_crlf_bytes)
+	}
+
 	/// Get the number of entries skipped due to errors.
 	#[must_use]
 	pub const fn skipped_entries(&self) -> u64 {
@@ -337,12 +976,137 @@ impl AnalysisResults {
 		self.skipped_entries = skipped;
 	}
 
+	/// Get the files left out as binary, unrecognized or unreadable. This stays empty unless the analysis was
+	/// configured to collect them.
+	#[must_use]
+	pub fn skipped_files(&self) -> &[SkippedFile] {
+		&self.skipped_files
+	}
+
+	pub(crate) fn add_skipped_file(&mut self, path: impl Into<String>, reason: impl Into<String>) {
+		self.skipped_files.push(SkippedFile { path: path.into(), reason: reason.into() });
+	}
+
+	/// Get the number of files skipped because they were detected as generated.
+	#[must_use]
+	pub const fn generated_files(&self) -> u64 {
+		self.generated_files
+	}
+
+	pub(crate) const fn add_generated_file(&mut self) {
+		self.generated_files = self.generated_files.saturating_add(1);
+	}
+
+	/// Get the number of counted files that start with a byte-order mark.
+	#[must_use]
+	pub const fn bom_files(&self) -> u64 {
+		self.bom_files
+	}
+
+	pub(crate) const fn add_bom_file(&mut self) {
+		self.bom_files = self.bom_files.saturating_add(1);
+	}
+
+	/// Get the number of counted files with a line too long to read whole, such as minified bundles.
+	#[must_use]
+	pub const fn minified_files(&self) -> u64 {
+		self.minified_files
+	}
+
+	pub(crate) const fn add_minified_file(&mut self) {
+		self.minified_files = self.minified_files.saturating_add(1);
+	}
+
+	/// Get the number of binary files recorded as assets. This stays at zero unless assets are included.
+	#[must_use]
+	pub const fn asset_files(&self) -> u64 {
+		self.asset_files
+	}
+
+	/// Get the total size in bytes of binary files recorded as assets.
+	#[must_use]
+	pub const fn asset_size(&self) -> u64 {
+		self.asset_size
+	}
+
+	/// Record a binary file that has a size but no lines. It is kept out of the language and file totals.
+	pub(crate) const fn add_asset(&mut self, size: u64) {
+		self.asset_files = self.asset_files.saturating_add(1);
+		self.asset_size = self.asset_size.saturating_add(size);
+	}
+
+	/// Get the file with the most lines, counting lines of embedded languages, or `None` if every file is empty.
+	#[must_use]
+	pub const fn largest_file_by_lines(&self) -> Option<&LargestFile> {
+		self.largest_by_lines.as_ref()
+	}
+
+	/// Get the file with the most bytes, or `None` if every file is empty.
+	#[must_use]
+	pub const fn largest_file_by_size(&self) -> Option<&LargestFile> {
+		self.largest_by_size.as_ref()
+	}
+
+	/// Get the totals for files classified as tests. These stay at zero unless test splitting is enabled.
+	#[must_use]
+	pub const fn test_totals(&self) -> &SubsetTotals {
+		&self.test_totals
+	}
+
+	/// Get the totals for files collapsed as vendored. These stay at zero unless vendored collapsing is enabled.
+	#[must_use]
+	pub const fn vendored_totals(&self) -> &SubsetTotals {
+		&self.vendored_totals
+	}
+
+	/// Record a vendored file in the vendored bucket, keeping it out of the language and overall totals.
+	pub(crate) const fn add_vendored_file(&mut self, contribution: &FileContribution) {
+		self.vendored_totals.add_file(contribution);
+	}
+
+	/// Add lines embedded in a vendored file to the vendored bucket.
+	pub(crate) const fn add_vendored_lines(&mut self, contribution: &FileContribution) {
+		self.vendored_totals.add_lines(contribution);
+	}
+
+	fn category_slot(&mut self, name: &Arc<str>) -> &mut SubsetTotals {
+		let idx = self.category_totals.iter().position(|(existing, _)| existing == name).unwrap_or_else(|| {
+			self.category_totals.push((Arc::clone(name), SubsetTotals::default()));
+			self.category_totals.len() - 1
+		});
+		&mut self.category_totals[idx].1
+	}
+
+	/// Totals for each file category that had files, keyed by category name in no particular order.
+	///
+	/// Category files also count towards the overall totals, so the categories of an analysis add up to them.
+	pub fn category_totals(&self) -> impl Iterator<Item = (&str, &SubsetTotals)> {
+		self.category_totals.iter().map(|(name, totals)| (name.as_ref(), totals))
+	}
+
+	/// Count a file in the totals of `category`, in addition to its language and the overall totals.
+	pub(crate) fn add_category_file(&mut self, category: &Arc<str>, contribution: &FileContribution) {
+		self.category_slot(category).add_file(contribution);
+	}
+
+	/// Add lines embedded in a file of `category` to that category's totals.
+	pub(crate) fn add_category_lines(&mut self, category: &Arc<str>, contribution: &FileContribution) {
+		self.category_slot(category).add_lines(contribution);
+	}
+
 	/// Get the total number of code lines across all files
 	#[must_use]
 	pub const fn total_code_lines(&self) -> u64 {
 		self.line_stats.code
 	}
 
+	/// Get the total source lines of code across all files: every line holding code, including mixed lines when
+	/// they are split out
+	#[must_use]
+	pub const fn total_sloc(&self) -> u64 {
+		self.line_stats.sloc()
+	}
+
 	/// Get the total number of comment lines across all files
 	#[must_use]
 	pub const fn total_comment_lines(&self) -> u64 {
@@ -361,13 +1125,66 @@ impl AnalysisResults {
 		self.line_stats.shebang
 	}
 
-	/// Iterate over languages that have at least one file, yielding both metadata and stats.
+	/// Get the total number of mixed code and comment lines across all files
+	#[must_use]
+	pub const fn total_mixed_lines(&self) -> u64 {
+		self.line_stats.mixed
+	}
+
+	/// Iterate over languages that have at least one file or embedded line, yielding both metadata and stats.
+	///
+	/// Languages merged by [`Self::apply_display_aliases`] are left out; see [`Self::reported_languages`].
 	pub fn languages(&self) -> impl Iterator<Item = (&'static Language, &LanguageStats)> {
 		LANGUAGES
 			.iter()
 			.enumerate()
 			.filter_map(|(idx, lang)| self.language_stats.get(idx).map(|stats| (lang, stats)))
-			.filter(|(_, stats)| stats.files() > 0)
+			.filter(|(_, stats)| !stats.is_empty())
+	}
+
+	/// Iterate over the entries a report lists by name: each remaining language, then each display alias group.
+	///
+	/// Unlike [`Self::languages`], this also yields languages recorded by [`Self::request_languages`] and alias groups
+	/// with no files, so reports can choose whether to show them; see `--show-empty`.
+	pub fn reported_languages(&self) -> impl Iterator<Item = (&str, &LanguageStats)> {
+		LANGUAGES
+			.iter()
+			.enumerate()
+			.filter_map(|(idx, lang)| self.language_stats.get(idx).map(|stats| (idx, lang, stats)))
+			.filter(|(idx, _, stats)| !stats.is_empty() || self.requested_languages.contains(idx))
+			.map(|(_, lang, stats)| (lang.name, stats))
+			.chain(self.language_groups.iter().map(|(name, stats)| (name.as_str(), stats)))
+	}
+
+	/// Record languages the caller asked for by name (e.g. with `--lang`), so reports can list them even when no
+	/// files of theirs were found.
+	///
+	/// Names match case-insensitively; unknown names are ignored.
+	pub fn request_languages(&mut self, names: &[String]) {
+		for name in names {
+			let Some(lang) = LANGUAGES.iter().find(|lang| lang.name.eq_ignore_ascii_case(name)) else { continue };
+			self.ensure_language_slot(lang);
+			if !self.requested_languages.contains(&lang.index) {
+				self.requested_languages.push(lang.index);
+			}
+		}
+	}
+
+	/// Merge the stats of the languages named by each alias into one group reported under the alias name.
+	///
+	/// Language names match case-insensitively. A language named by several aliases joins the first. Totals are
+	/// unchanged, since every file still counts once. Groups are kept even when none of their languages had files.
+	pub fn apply_display_aliases(&mut self, aliases: &[DisplayAlias]) {
+		for alias in aliases {
+			let mut group = LanguageStats::default();
+			for (lang, stats) in LANGUAGES.iter().zip(self.language_stats.iter_mut()) {
+				if alias.languages.iter().any(|name| name.eq_ignore_ascii_case(lang.name)) {
+					group.merge(std::mem::take(stats));
+					self.requested_languages.retain(|&idx| idx != lang.index);
+				}
+			}
+			self.language_groups.push((alias.name.clone(), group));
+		}
 	}
 }
 
@@ -409,6 +1226,92 @@ mod tests {
 		assert_eq!(a.code, u64::MAX);
 	}
 
+	#[rstest]
+	#[case::none(0, 0, IndentationStyle::None)]
+	#[case::tabs(5, 2, IndentationStyle::Tabs)]
+	#[case::spaces(1, 9, IndentationStyle::Spaces)]
+	#[case::mixed(3, 3, IndentationStyle::Mixed)]
+	fn test_indentation_dominant(#[case] tab: u64, #[case] space: u64, #[case] expected: IndentationStyle) {
+		assert_eq!(IndentationStats::new(tab, space).dominant(), expected);
+	}
+
+	#[test]
+	fn test_mixed_lines_aggregate() {
+		let rust = LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		let mut results = AnalysisResults::default();
+		results.add_file_stats(rust, FileContribution::new(10, 6, 1, 1, 0, 100).with_mixed_lines(2), None);
+		results.add_file_stats(rust, FileContribution::new(4, 3, 0, 0, 0, 40).with_mixed_lines(1), None);
+		assert_eq!(results.total_mixed_lines(), 3);
+		assert!((results.mixed_percentage() - 300.0 / 14.0).abs() < 1e-9);
+		let (_, stats) = results.languages().next().unwrap();
+		assert_eq!(stats.mixed_lines(), 3);
+		assert_eq!(stats.code_lines(), 9);
+	}
+
+	#[test]
+	fn test_sloc_counts_mixed_lines_as_code() {
+		let rust = LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		let contribution = FileContribution::new(10, 6, 1, 1, 0, 100).with_mixed_lines(2);
+		assert_eq!(contribution.file_stats("a.rs".to_owned()).sloc(), 8);
+		let mut results = AnalysisResults::default();
+		results.add_file_stats(rust, contribution, None);
+		results.add_file_stats(rust, FileContribution::new(4, 3, 0, 1, 0, 40), None);
+		assert_eq!(results.total_sloc(), 11);
+		let (_, stats) = results.languages().next().unwrap();
+		assert_eq!(stats.sloc(), 11);
+	}
+
+	#[test]
+	fn test_embedded_stats_count_lines_without_files() {
+		let markdown = LANGUAGES.iter().find(|l| l.name == "Markdown").unwrap();
+		let rust = LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		let mut results = AnalysisResults::default();
+		results.add_file_stats(markdown, FileContribution::new(4, 4, 0, 0, 0, 80), None);
+		results.add_embedded_stats(rust, FileContribution::new(3, 2, 1, 0, 0, 0));
+		assert_eq!(results.total_files(), 1);
+		assert_eq!(results.total_lines(), 7);
+		assert_eq!(results.total_size(), 80);
+		let languages: Vec<_> =
+			results.languages().map(|(lang, stats)| (lang.name, stats.files(), stats.lines())).collect();
+		assert!(languages.contains(&("Rust", 0, 3)));
+		assert!(languages.contains(&("Markdown", 1, 4)));
+	}
+
+	#[test]
+	fn test_language_stats_indentation() {
+		let mut ls = LanguageStats::default();
+		ls.add_file(&FileContribution::new(10, 10, 0, 0, 0, 100).with_indentation(6, 1), None);
+		ls.add_file(&FileContribution::new(10, 10, 0, 0, 0, 100).with_indentation(0, 3), None);
+		assert_eq!(ls.tab_lines(), 6);
+		assert_eq!(ls.space_lines(), 4);
+		assert_eq!(ls.dominant_indentation(), IndentationStyle::Tabs);
+	}
+
+	#[test]
+	fn test_normalized_size_leaves_out_crlf_bytes() {
+		let rust = crate::langs::LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		let mut results = AnalysisResults::default();
+		results.add_file_stats(rust, FileContribution::new(4, 4, 0, 0, 0, 40).with_crlf_bytes(4), None);
+		let mut other = AnalysisResults::default();
+		other.add_file_stats(rust, FileContribution::new(2, 2, 0, 0, 0, 20), None);
+		results.merge(other);
+		assert_eq!(results.total_size(), 60);
+		assert_eq!(results.total_normalized_size(), 56);
+		assert_eq!(results.language_stats[rust.index].normalized_size(), 56);
+	}
+
+	#[test]
+	fn test_language_stats_comment_styles() {
+		let mut ls = LanguageStats::default();
+		ls.add_file(&FileContribution::new(10, 4, 6, 0, 0, 100).with_comment_styles(4, 2), None);
+		ls.add_embedded(&FileContribution::new(3, 0, 3, 0, 0, 0).with_comment_styles(0, 3));
+		let mut other = LanguageStats::default();
+		other.add_file(&FileContribution::new(1, 0, 1, 0, 0, 10).with_comment_styles(1, 0), None);
+		ls.merge(other);
+		assert_eq!(ls.line_comment_lines(), 5);
+		assert_eq!(ls.block_comment_lines(), 5);
+	}
+
 	#[test]
 	fn test_file_contribution() {
 		let fc = FileContribution::new(100, 60, 20, 15, 5, 1024);
@@ -426,6 +1329,8 @@ mod tests {
 		assert_eq!(fs.blank_lines(), 15);
 		assert_eq!(fs.shebang_lines(), 5);
 		assert_eq!(fs.size(), 2048);
+		assert!(!fs.is_symlinked());
+		assert!(fs.with_symlinked(true).is_symlinked());
 	}
 
 	#[test]
@@ -482,6 +1387,16 @@ mod tests {
 		assert!((ls.average_lines_per_file() - 150.0).abs() < f64::EPSILON);
 	}
 
+	#[test]
+	fn test_language_stats_bytes_per_line() {
+		let mut ls = LanguageStats::default();
+		ls.add_file(&FileContribution::new(0, 0, 0, 0, 0, 0), None);
+		assert!(ls.bytes_per_line().abs() < f64::EPSILON);
+		ls.add_file(&FileContribution::new(100, 50, 25, 20, 5, 1000), None);
+		ls.add_file(&FileContribution::new(300, 100, 50, 40, 10, 5000), None);
+		assert!((ls.bytes_per_line() - 15.0).abs() < f64::EPSILON);
+	}
+
 	#[test]
 	fn test_language_stats_percentages() {
 		let mut ls = LanguageStats::default();
@@ -501,9 +1416,171 @@ mod tests {
 		// Simulating some skipped entries
 		a.set_skipped_entries(2);
 		b.set_skipped_entries(3);
+		a.add_skipped_file("logo.png", "binary");
+		b.add_skipped_file("notes.txt", "unrecognized");
 
 		a.merge(b);
 		assert_eq!(a.skipped_entries(), 5);
+		let skipped: Vec<_> = a.skipped_files().iter().map(|file| (file.path(), file.reason())).collect();
+		assert_eq!(skipped, [("logo.png", "binary"), ("notes.txt", "unrecognized")]);
+	}
+
+	#[test]
+	fn test_display_aliases_merge_languages_into_groups() {
+		let lang = |name| LANGUAGES.iter().find(|l| l.name == name).unwrap();
+		let mut results = AnalysisResults::default();
+		results.add_file_stats(
+			lang("TypeScript"),
+			FileContribution::new(10, 8, 1, 1, 0, 100),
+			Some(FileStats::new("a.ts".into(), 10, 8, 1, 1, 0, 100)),
+		);
+		results.add_file_stats(
+			lang("JavaScript"),
+			FileContribution::new(5, 5, 0, 0, 0, 40),
+			Some(FileStats::new("b.js".into(), 5, 5, 0, 0, 0, 40)),
+		);
+		results.add_file_stats(lang("Rust"), FileContribution::new(3, 3, 0, 0, 0, 30), None);
+		let alias =
+			DisplayAlias { name: "Frontend JS".into(), languages: vec!["typescript".into(), "JavaScript".into()] };
+		results.apply_display_aliases(&[alias]);
+		let reported: Vec<_> = results
+			.reported_languages()
+			.map(|(name, stats)| (name, stats.files(), stats.lines(), stats.size()))
+			.collect();
+		assert_eq!(reported, [("Rust", 1, 3, 30), ("Frontend JS", 2, 15, 140)]);
+		let (_, group) = results.reported_languages().last().unwrap();
+		let paths: Vec<_> = group.files_list().iter().map(FileStats::path).collect();
+		assert_eq!(paths, ["b.js", "a.ts"]);
+		assert_eq!(results.total_lines(), 18);
+	}
+
+	#[test]
+	fn test_requested_and_aliased_languages_are_reported_when_empty() {
+		let mut results = AnalysisResults::default();
+		results.add_file_stats(
+			LANGUAGES.iter().find(|l| l.name == "Rust").unwrap(),
+			FileContribution::new(3, 3, 0, 0, 0, 30),
+			None,
+		);
+		results.request_languages(&["go".into(), "Rust".into(), "Not A Language".into()]);
+		results.apply_display_aliases(&[DisplayAlias { name: "Web".into(), languages: vec!["CSS".into()] }]);
+		let reported: Vec<_> = results.reported_languages().map(|(name, stats)| (name, stats.is_empty())).collect();
+		assert_eq!(reported, [("Go", true), ("Rust", false), ("Web", true)]);
+		assert_eq!(results.languages().count(), 1);
+	}
+
+	#[test]
+	fn test_vendored_files_stay_out_of_totals() {
+		let lang = LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		let mut a = AnalysisResults::default();
+		let mut b = AnalysisResults::default();
+		a.add_file_stats(lang, FileContribution::new(10, 8, 1, 1, 0, 100), None);
+		a.add_vendored_file(&FileContribution::new(6, 4, 1, 1, 0, 50));
+		b.add_vendored_file(&FileContribution::new(4, 3, 0, 1, 0, 20));
+		b.add_vendored_lines(&FileContribution::new(2, 2, 0, 0, 0, 0));
+		a.merge(b);
+		assert_eq!((a.total_files(), a.total_lines(), a.total_size()), (1, 10, 100));
+		let vendored = a.vendored_totals();
+		assert_eq!((vendored.files(), vendored.lines(), vendored.code_lines(), vendored.size()), (2, 12, 9, 70));
+	}
+
+	#[test]
+	fn test_analysis_results_track_test_totals() {
+		let lang = LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		let mut a = AnalysisResults::default();
+		let mut b = AnalysisResults::default();
+		a.add_file_stats(lang, FileContribution::new(10, 8, 1, 1, 0, 100), None);
+		a.add_file_stats(lang, FileContribution::new(6, 4, 1, 1, 0, 50).with_test(true), None);
+		b.add_file_stats(lang, FileContribution::new(4, 3, 0, 1, 0, 20).with_test(true), None);
+		b.add_embedded_stats(lang, FileContribution::new(2, 2, 0, 0, 0, 0).with_test(true));
+		a.merge(b);
+		let tests = a.test_totals();
+		assert_eq!((tests.files(), tests.lines(), tests.code_lines(), tests.size()), (2, 12, 9, 70));
+		assert_eq!(a.total_lines(), 22);
+	}
+
+	#[test]
+	fn test_analysis_results_merge_generated_files() {
+		let mut a = AnalysisResults::default();
+		let mut b = AnalysisResults::default();
+		a.add_generated_file();
+		b.add_generated_file();
+		b.add_generated_file();
+		a.add_bom_file();
+		b.add_bom_file();
+		b.add_minified_file();
+		a.merge(b);
+		assert_eq!(a.generated_files(), 3);
+		assert_eq!(a.bom_files(), 2);
+		assert_eq!(a.minified_files(), 1);
+		assert_eq!(a.total_files(), 0);
+	}
+
+	#[test]
+	fn test_analysis_results_combine() {
+		let rust = LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		let python = LANGUAGES.iter().find(|l| l.name == "Python").unwrap();
+		let mut a = AnalysisResults::default();
+		a.add_file_stats(rust, FileContribution::new(10, 8, 1, 1, 0, 100), None);
+		let mut b = AnalysisResults::default();
+		b.add_file_stats(rust, FileContribution::new(5, 5, 0, 0, 0, 50), None);
+		b.add_file_stats(python, FileContribution::new(3, 2, 0, 0, 1, 30), None);
+		let combined = AnalysisResults::combine([a, b, AnalysisResults::default()]);
+		assert_eq!(combined.total_files(), 3);
+		assert_eq!(combined.total_lines(), 18);
+		assert_eq!(combined.total_code_lines(), 15);
+		assert_eq!(combined.total_size(), 180);
+		let languages: Vec<_> = combined.languages().map(|(lang, stats)| (lang.name, stats.files())).collect();
+		assert!(languages.contains(&("Rust", 2)));
+		assert!(languages.contains(&("Python", 1)));
+	}
+
+	#[test]
+	fn test_analysis_results_combine_empty() {
+		let combined = AnalysisResults::combine(Vec::new());
+		assert_eq!(combined.total_files(), 0);
+		assert_eq!(combined.languages().count(), 0);
+	}
+
+	#[test]
+	fn test_analysis_results_from_parts() {
+		let rust = LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		let mut a = AnalysisResults::default();
+		a.add_file_stats(rust, FileContribution::new(10, 8, 1, 1, 0, 100), None);
+		let mut b = AnalysisResults::default();
+		b.add_file_stats(rust, FileContribution::new(5, 5, 0, 0, 0, 50), None);
+		let combined = AnalysisResults::from_parts(vec![a, b]).unwrap();
+		assert_eq!(combined.total_files(), 2);
+		assert_eq!(combined.languages().map(|(_, stats)| stats.files()).sum::<u64>(), 2);
+	}
+
+	#[test]
+	fn test_analysis_results_from_parts_rejects_foreign_language_table() {
+		let oversized = AnalysisResults {
+			language_stats: vec![LanguageStats::default(); LANGUAGES.len() + 1],
+			..AnalysisResults::default()
+		};
+		let err = AnalysisResults::from_parts(vec![AnalysisResults::default(), oversized]).unwrap_err();
+		assert!(err.to_string().contains("part 1"), "{err:#}");
+		let out_of_range = AnalysisResults { requested_languages: vec![LANGUAGES.len()], ..AnalysisResults::default() };
+		assert!(AnalysisResults::from_parts(vec![out_of_range]).is_err());
+	}
+
+	#[test]
+	fn test_category_totals_merge_by_name() {
+		let test: Arc<str> = Arc::from("test");
+		let docs: Arc<str> = Arc::from("docs");
+		let mut a = AnalysisResults::default();
+		a.add_category_file(&test, &FileContribution::new(10, 8, 1, 1, 0, 100));
+		let mut b = AnalysisResults::default();
+		b.add_category_file(&docs, &FileContribution::new(4, 3, 0, 1, 0, 40));
+		b.add_category_file(&test, &FileContribution::new(5, 5, 0, 0, 0, 50));
+		b.add_category_lines(&test, &FileContribution::new(2, 2, 0, 0, 0, 0));
+		a.merge(b);
+		let mut totals: Vec<_> =
+			a.category_totals().map(|(name, totals)| (name, totals.files(), totals.lines())).collect();
+		totals.sort_unstable();
+		assert_eq!(totals, [("docs", 1, 4), ("test", 2, 17)]);
 	}
 
 	#[test]
@@ -517,4 +1594,22 @@ mod tests {
 		assert_eq!(results.total_blank_lines(), 0);
 		assert_eq!(results.total_shebang_lines(), 0);
 	}
+
+	#[test]
+	fn largest_files_prefer_bigger_values_then_smaller_paths_across_merges() {
+		let mut a = AnalysisResults::default();
+		a.track_largest_file("b.rs", 10, 100);
+		a.track_largest_file("empty.rs", 0, 0);
+		let mut b = AnalysisResults::default();
+		b.track_largest_file("a.rs", 10, 50);
+		b.track_largest_file("c.rs", 3, 400);
+		let mut reversed = AnalysisResults::default();
+		reversed.merge(b);
+		reversed.merge(a);
+		let lines = reversed.largest_file_by_lines().unwrap();
+		assert_eq!((lines.path(), lines.lines(), lines.size()), ("a.rs", 10, 50));
+		let size = reversed.largest_file_by_size().unwrap();
+		assert_eq!((size.path(), size.lines(), size.size()), ("c.rs", 3, 400));
+		assert!(AnalysisResults::default().largest_file_by_lines().is_none());
+	}
 }
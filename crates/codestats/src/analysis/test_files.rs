@@ -0,0 +1,59 @@
+use anyhow::{Context as _, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+
+/// Glob patterns that identify test files when `split_tests` is enabled.
+pub const DEFAULT_PATTERNS: &[&str] = &["**/tests/**", "*_test.*", "test_*.*", "*.spec.*"];
+
+/// Classifies files as tests by matching glob patterns against their filename and relative path.
+#[derive(Debug)]
+pub struct TestFileMatcher {
+	globs: GlobSet,
+}
+
+impl TestFileMatcher {
+	/// Build a matcher from glob patterns.
+	///
+	/// # Errors
+	///
+	/// Returns an error if a glob pattern is invalid.
+	pub fn new(patterns: &[String]) -> Result<Self> {
+		let mut builder = GlobSetBuilder::new();
+		for pattern in patterns {
+			builder.add(Glob::new(pattern).with_context(|| format!("Invalid test-file pattern `{pattern}`"))?);
+		}
+		Ok(Self { globs: builder.build()? })
+	}
+
+	/// Check whether the file at `path` (relative to the analysis root, `/`-separated) named `filename` is a test.
+	#[must_use]
+	pub fn matches(&self, filename: &str, path: &str) -> bool {
+		self.globs.is_match(filename) || self.globs.is_match(path)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use rstest::rstest;
+
+	use super::*;
+
+	#[rstest]
+	#[case::tests_dir("tests/cli.rs", true)]
+	#[case::nested_tests_dir("crates/cli/tests/vcs_pruning.rs", true)]
+	#[case::go_suffix("pkg/server_test.go", true)]
+	#[case::python_prefix("app/test_views.py", true)]
+	#[case::js_spec("src/button.spec.ts", true)]
+	#[case::plain("src/main.rs", false)]
+	#[case::testing_word_in_name("src/contest.rs", false)]
+	fn test_default_patterns(#[case] path: &str, #[case] expected: bool) {
+		let patterns: Vec<String> = DEFAULT_PATTERNS.iter().map(ToString::to_string).collect();
+		let matcher = TestFileMatcher::new(&patterns).unwrap();
+		let filename = path.rsplit('/').next().unwrap();
+		assert_eq!(matcher.matches(filename, path), expected);
+	}
+
+	#[test]
+	fn invalid_pattern_is_rejected() {
+		assert!(TestFileMatcher::new(&["[".to_string()]).is_err());
+	}
+}
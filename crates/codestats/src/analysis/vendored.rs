@@ -0,0 +1,57 @@
+use anyhow::{Context as _, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+
+/// Glob patterns that identify vendored code when `collapse_vendored` is enabled.
+pub const DEFAULT_PATTERNS: &[&str] = &["**/vendor/**", "**/node_modules/**", "**/third_party/**", "**/.venv/**"];
+
+/// Classifies files as vendored dependencies by matching glob patterns against their relative path.
+#[derive(Debug)]
+pub struct VendoredMatcher {
+	globs: GlobSet,
+}
+
+impl VendoredMatcher {
+	/// Build a matcher from glob patterns.
+	///
+	/// # Errors
+	///
+	/// Returns an error if a glob pattern is invalid.
+	pub fn new(patterns: &[String]) -> Result<Self> {
+		let mut builder = GlobSetBuilder::new();
+		for pattern in patterns {
+			builder.add(Glob::new(pattern).with_context(|| format!("Invalid vendored pattern `{pattern}`"))?);
+		}
+		Ok(Self { globs: builder.build()? })
+	}
+
+	/// Check whether the file at `path` (relative to the analysis root, `/`-separated) is vendored.
+	#[must_use]
+	pub fn matches(&self, path: &str) -> bool {
+		self.globs.is_match(path)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use rstest::rstest;
+
+	use super::*;
+
+	#[rstest]
+	#[case::root_vendor("vendor/github.com/pkg/errors/errors.go", true)]
+	#[case::nested_node_modules("web/node_modules/react/index.js", true)]
+	#[case::third_party("third_party/zlib/inflate.c", true)]
+	#[case::virtualenv(".venv/lib/python3.12/site.py", true)]
+	#[case::first_party("src/vendor.rs", false)]
+	#[case::similar_dir("src/vendors/list.rs", false)]
+	fn test_default_patterns(#[case] path: &str, #[case] expected: bool) {
+		let patterns: Vec<String> = DEFAULT_PATTERNS.iter().map(ToString::to_string).collect();
+		let matcher = VendoredMatcher::new(&patterns).unwrap();
+		assert_eq!(matcher.matches(path), expected);
+	}
+
+	#[test]
+	fn invalid_pattern_is_rejected() {
+		assert!(VendoredMatcher::new(&["[".to_string()]).is_err());
+	}
+}
@@ -1,13 +1,22 @@
 use std::{
-	fs,
+	collections::BTreeMap,
+	fmt, fs,
 	path::{Path, PathBuf},
+	str::FromStr,
+	time::Duration,
 };
 
 use anyhow::{Context, Result};
+use encoding_rs::Encoding;
 use serde::{Deserialize, Serialize};
 
-use crate::display::{
-	IndentStyle, LanguageSortKey, NumberStyle, OutputFormat, SizeStyle, SortDirection, Verbosity, ViewOptions,
+use crate::{
+	analysis::{DEFAULT_BINARY_THRESHOLD, Docstrings, Shebangs, generated, test_files, vendored},
+	display::{
+		ColorChoice, IndentStyle, LanguageSortKey, NumberStyle, OutputFormat, RoundingMode, SizeStyle, SortDirection,
+		SummaryFormat, Verbosity, ViewOptions, formatting::display_path,
+	},
+	langs,
 };
 
 /// Helper to create error context for config file reading operations.
@@ -29,6 +38,17 @@ pub struct Config {
 	pub path: PathBuf,
 	pub analysis: AnalysisConfig,
 	pub display: DisplayConfig,
+	/// Language groups reported under one name, from `[[display_alias]]` tables.
+	#[serde(rename = "display_alias")]
+	pub display_aliases: Vec<DisplayAlias>,
+	/// Language rollups from `[[rollup]]` tables, applied when `display.aggregate_extensions` is set. Defaults to
+	/// [`default_rollups`] when no table is given.
+	#[serde(rename = "rollup")]
+	pub rollups: Vec<DisplayAlias>,
+	/// File categories from `[[category]]` tables, applied when `analysis.split_categories` is set. Defaults to
+	/// [`default_categories`] when no table is given.
+	#[serde(rename = "category")]
+	pub categories: Vec<FileCategory>,
 	#[serde(skip)]
 	/// Path to the config file that provided these settings, if any.
 	pub source: Option<PathBuf>,
@@ -43,12 +63,78 @@ impl Default for Config {
 			path: PathBuf::from("."),
 			analysis: AnalysisConfig::default(),
 			display: DisplayConfig::default(),
+			display_aliases: Vec::new(),
+			rollups: default_rollups(),
+			categories: default_categories(),
 			source: None,
 			path_overridden: false,
 		}
 	}
 }
 
+/// Languages reported together under one name, e.g. TypeScript and JavaScript as "Frontend JS".
+///
+/// Only the report is affected: detection still assigns each file its own language, and the group's numbers are the
+/// sums of its languages'.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct DisplayAlias {
+	pub name: String,
+	pub languages: Vec<String>,
+}
+
+/// Rollups used when the config defines none: the C family, whose `.h` headers detection attributes to C even when
+/// they are C++ or Objective-C.
+#[must_use]
+pub fn default_rollups() -> Vec<DisplayAlias> {
+	vec![DisplayAlias {
+		name: "C/C++".to_owned(),
+		languages: ["C", "C++", "Objective-C", "Objective-C++"].map(str::to_owned).to_vec(),
+	}]
+}
+
+/// One group per entry of [`langs::PARADIGMS`] holding the languages tagged with it, then "other" for every untagged
+/// language.
+///
+/// Applied as display aliases, these report the codebase's mix of paradigms instead of its languages.
+#[must_use]
+pub fn paradigm_groups() -> Vec<DisplayAlias> {
+	let members = |paradigm: Option<&str>| {
+		langs::LANGUAGES.iter().filter(|lang| lang.paradigm == paradigm).map(|lang| lang.name.to_owned()).collect()
+	};
+	langs::PARADIGMS
+		.iter()
+		.map(|&paradigm| DisplayAlias { name: paradigm.to_owned(), languages: members(Some(paradigm)) })
+		.chain([DisplayAlias { name: "other".to_owned(), languages: members(None) }])
+		.collect()
+}
+
+/// A named group of files, such as tests or documentation, matched by globs against each file's name and relative path.
+///
+/// Categories are tried top to bottom and a file takes the first one it matches, so broad rules belong last.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct FileCategory {
+	pub name: String,
+	pub patterns: Vec<String>,
+}
+
+/// Categories used when the config defines none: generated, test, docs, config, and everything else as source.
+#[must_use]
+pub fn default_categories() -> Vec<FileCategory> {
+	fn category<'a>(name: &str, patterns: impl IntoIterator<Item = &'a &'a str>) -> FileCategory {
+		FileCategory { name: name.to_owned(), patterns: patterns.into_iter().map(ToString::to_string).collect() }
+	}
+	vec![
+		category("generated", generated::DEFAULT_NAME_PATTERNS.iter().chain(generated::PATTERNS)),
+		category("test", test_files::DEFAULT_PATTERNS),
+		category("docs", &["**/docs/**", "**/doc/**", "*.md", "*.markdown", "*.rst", "*.adoc", "*.txt"]),
+		category(
+			"config",
+			&["*.toml", "*.yaml", "*.yml", "*.json", "*.json5", "*.ini", "*.cfg", "*.conf", ".editorconfig"],
+		),
+		category("source", &["*"]),
+	]
+}
+
 /// Analysis settings loaded from TOML and the CLI.
 #[expect(
 	clippy::struct_excessive_bools,
@@ -59,14 +145,84 @@ impl Default for Config {
 pub struct AnalysisConfig {
 	pub verbosity: Verbosity,
 	pub respect_gitignore: bool,
+	/// Apply the user's global gitignore (`core.excludesFile`) when `respect_gitignore` is set.
+	pub git_global: bool,
+	/// Apply `.git/info/exclude` when `respect_gitignore` is set.
+	pub git_exclude: bool,
+	/// Apply `.ignore` files when `respect_gitignore` is set.
+	pub dot_ignore: bool,
+	pub ignore_parents: bool,
 	pub include_hidden: bool,
 	pub follow_symlinks: bool,
+	/// Skip directories on a different filesystem than the analysis root.
+	pub one_file_system: bool,
 	pub include_generated: bool,
+	/// Count the lockfiles matched by `lockfile_patterns`, which are otherwise skipped like other generated files.
+	pub include_lockfiles: bool,
+	/// Globs matched against file names to classify dependency lockfiles.
+	pub lockfile_patterns: Vec<String>,
+	pub exclude_generated: bool,
+	pub generated_markers: Vec<String>,
+	pub generated_patterns: Vec<String>,
 	pub max_depth: Option<usize>,
+	pub tab_width: u8,
+	pub split_mixed: bool,
+	/// Comment lines whose text starts with one of these folding markers (e.g. `#region`) are counted as code. Empty
+	/// by default, leaving every comment counted as a comment.
+	pub fold_markers: Vec<String>,
+	/// Count Python-style docstrings as comments or as code. Triple-quoted strings that follow code on their line are
+	/// string literals and always count as code.
+	pub docstrings: Docstrings,
+	/// Count shebang lines in their own category (the default), as code, as comments, or not at all.
+	pub shebang: Shebangs,
+	/// End lines at the Unicode line separators NEL (U+0085), U+2028 and U+2029 as well as at `\n`.
+	pub unicode_line_breaks: bool,
+	/// Record a 64-bit FNV-1a hash of each file's content in verbose reports, so changed files can be found without
+	/// comparing modification times.
+	pub hash: bool,
+	/// Decode every file with this encoding instead of detecting UTF-8 or UTF-16 from the content.
+	pub input_encoding: Option<InputEncoding>,
+	pub embedded: bool,
+	/// Report test and non-test totals separately in the summary.
+	pub split_tests: bool,
+	/// Classify each file by the `[[category]]` rules and report per-category totals in the summary.
+	pub split_categories: bool,
+	/// Globs matched against each file's name and relative path to classify it as a test.
+	pub test_patterns: Vec<String>,
+	/// Report files under vendored directories as one separate bucket instead of under their languages.
+	pub collapse_vendored: bool,
+	/// Globs matched against each file's relative path to classify it as vendored.
+	pub vendored_patterns: Vec<String>,
 	pub exclude_patterns: Vec<String>,
+	/// Directory names (or globs over names) whose subtrees are pruned from the walk without being read.
+	pub exclude_dirs: Vec<String>,
 	pub include_languages: Vec<String>,
 	pub exclude_languages: Vec<String>,
 	pub fail_on_error: bool,
+	/// Stop the analysis with an error at the first entry that cannot be walked or read, instead of skipping it.
+	pub strict: bool,
+	/// Assign languages from `codestats_language` keys in the root `.editorconfig` ahead of detection.
+	pub editorconfig: bool,
+	/// Log the candidate scores to stderr for each file whose language is chosen by content scoring.
+	pub explain_detection: bool,
+	/// Lowest content score that lets detection pick among several candidate languages.
+	pub detection_threshold: u32,
+	/// Percentage of control bytes in a file's sampled bytes above which it is skipped as binary (0-100).
+	pub binary_threshold: u8,
+	/// Binary thresholds for files with these extensions (without the dot, any case), in place of `binary_threshold`.
+	pub binary_thresholds: BTreeMap<String, u8>,
+	/// File that keeps per-file counts between runs so unchanged files are not read again.
+	pub cache: Option<PathBuf>,
+	/// Record the count and size of binary files in a separate assets bucket instead of skipping them.
+	pub include_assets: bool,
+	/// Only count files modified within this long before the analysis starts.
+	pub modified_within: Option<Age>,
+	/// Record each file skipped as binary, unrecognized or unreadable in
+	/// [`AnalysisResults::skipped_files`](crate::analysis::AnalysisResults::skipped_files).
+	#[serde(skip)]
+	pub collect_skipped: bool,
+	/// Most files read at once across all walker threads, for latency-bound storage. `None` leaves reads unbounded.
+	pub io_concurrency: Option<usize>,
 }
 
 impl Default for AnalysisConfig {
@@ -74,32 +230,217 @@ impl Default for AnalysisConfig {
 		Self {
 			verbosity: Verbosity::Regular,
 			respect_gitignore: true,
+			git_global: true,
+			git_exclude: true,
+			dot_ignore: true,
+			ignore_parents: true,
 			include_hidden: false,
 			follow_symlinks: false,
+			one_file_system: false,
 			include_generated: false,
+			include_lockfiles: false,
+			lockfile_patterns: generated::DEFAULT_LOCKFILES.iter().map(ToString::to_string).collect(),
+			exclude_generated: false,
+			generated_markers: generated::DEFAULT_MARKERS.iter().map(ToString::to_string).collect(),
+			generated_patterns: generated::DEFAULT_NAME_PATTERNS.iter().map(ToString::to_string).collect(),
 			max_depth: None,
+			tab_width: 8,
+			split_mixed: false,
+			fold_markers: Vec::new(),
+			docstrings: Docstrings::Comment,
+			shebang: Shebangs::Separate,
+			unicode_line_breaks: false,
+			hash: false,
+			input_encoding: None,
+			embedded: false,
+			split_tests: false,
+			split_categories: false,
+			test_patterns: test_files::DEFAULT_PATTERNS.iter().map(ToString::to_string).collect(),
+			collapse_vendored: false,
+			vendored_patterns: vendored::DEFAULT_PATTERNS.iter().map(ToString::to_string).collect(),
 			exclude_patterns: Vec::new(),
+			exclude_dirs: Vec::new(),
 			include_languages: Vec::new(),
 			exclude_languages: Vec::new(),
 			fail_on_error: false,
+			strict: false,
+			editorconfig: false,
+			explain_detection: false,
+			detection_threshold: langs::DEFAULT_DETECTION_THRESHOLD,
+			binary_threshold: DEFAULT_BINARY_THRESHOLD,
+			binary_thresholds: BTreeMap::new(),
+			cache: None,
+			include_assets: false,
+			modified_within: None,
+			collect_skipped: false,
+			io_concurrency: None,
 		}
 	}
 }
 
+/// A length of time written as a whole number and a unit, such as `30d` or `12h`.
+///
+/// Units are `s`, `m`, `h`, `d` (days), and `w` (weeks).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Age(Duration);
+
+impl Age {
+	const UNITS: [(char, u64); 5] = [('w', 7 * 86_400), ('d', 86_400), ('h', 3_600), ('m', 60), ('s', 1)];
+
+	#[must_use]
+	pub const fn duration(self) -> Duration {
+		self.0
+	}
+}
+
+impl FromStr for Age {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let invalid = || format!("invalid duration '{s}' (expected a number and a unit, e.g. 30d or 12h)");
+		let unit = s.chars().next_back().ok_or_else(invalid)?;
+		let &(_, seconds) = Self::UNITS.iter().find(|(name, _)| *name == unit).ok_or_else(invalid)?;
+		let count: u64 = s[..s.len() - unit.len_utf8()].parse().map_err(|_| invalid())?;
+		count.checked_mul(seconds).map(|total| Self(Duration::from_secs(total))).ok_or_else(invalid)
+	}
+}
+
+impl TryFrom<String> for Age {
+	type Error = String;
+
+	fn try_from(value: String) -> Result<Self, Self::Error> {
+		value.parse()
+	}
+}
+
+impl fmt::Display for Age {
+	/// Writes the age in the largest unit that divides it evenly, so parsing the output gives the same age back.
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let seconds = self.0.as_secs();
+		let (unit, size) =
+			Self::UNITS.into_iter().find(|&(_, size)| seconds != 0 && seconds.is_multiple_of(size)).unwrap_or(('s', 1));
+		write!(f, "{}{unit}", seconds / size)
+	}
+}
+
+impl From<Age> for String {
+	fn from(age: Age) -> Self {
+		age.to_string()
+	}
+}
+
+/// A text encoding named by one of its WHATWG labels, such as `shift_jis`, `gbk` or `koi8-r`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct InputEncoding(&'static Encoding);
+
+impl InputEncoding {
+	#[must_use]
+	pub const fn encoding(self) -> &'static Encoding {
+		self.0
+	}
+}
+
+impl FromStr for InputEncoding {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Encoding::for_label(s.as_bytes())
+			.map(Self)
+			.ok_or_else(|| format!("unknown encoding '{s}' (expected a label such as shift_jis, gbk or koi8-r)"))
+	}
+}
+
+impl TryFrom<String> for InputEncoding {
+	type Error = String;
+
+	fn try_from(value: String) -> Result<Self, Self::Error> {
+		value.parse()
+	}
+}
+
+impl fmt::Display for InputEncoding {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.0.name())
+	}
+}
+
+impl From<InputEncoding> for String {
+	fn from(encoding: InputEncoding) -> Self {
+		encoding.to_string()
+	}
+}
+
 /// Output formatting settings loaded from TOML and the CLI.
+#[expect(clippy::struct_excessive_bools, reason = "each bool toggles an independent optional report section")]
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(default)]
 pub struct DisplayConfig {
 	pub number_style: NumberStyle,
 	pub size_units: SizeStyle,
+	/// Write sizes in bytes instead of scaling them to larger units.
+	pub size_raw: bool,
+	/// Decimals in scaled sizes (0-6); unset picks 2, 1 or 0 by magnitude.
+	pub size_precision: Option<u8>,
 	pub precision: u8,
+	pub rounding: RoundingMode,
+	/// Adjust line-type percentages with the largest remainder method so they sum to exactly 100.
+	pub balanced_percentages: bool,
 	pub sort_by: LanguageSortKey,
 	pub sort_direction: SortDirection,
+	/// Sort key for per-file details in verbose output; unset sorts files like languages.
+	pub file_sort_by: Option<LanguageSortKey>,
+	/// Sort direction for per-file details in verbose output; unset uses `sort_direction`.
+	pub file_sort_direction: Option<SortDirection>,
 	pub output: OutputFormat,
+	/// Field separator used by the CSV output format.
+	pub csv_delimiter: char,
+	/// End each record of the paths output format with NUL instead of a newline.
+	pub null_terminated: bool,
+	/// Prefix each record of the paths output format with the file's language and a tab.
+	pub path_languages: bool,
+	/// Tera template file that replaces the built-in output format when set.
+	pub template: Option<PathBuf>,
+	/// Name shown in reports in place of the analyzed path, which is still the root that gets walked.
+	pub root_label: Option<String>,
+	/// Template for the first line of the human overview, such as `{path}: {code} code lines`.
+	pub summary_format: Option<SummaryFormat>,
 	pub indent: IndentStyle,
 	pub top_languages: Option<usize>,
 	pub min_lines: Option<u64>,
 	pub by_dir: bool,
+	/// Number of leading path components used to bucket files when `by_dir` is set.
+	pub dir_depth: usize,
+	/// Trailing path components kept when listing files in verbose human output.
+	pub path_depth: Option<usize>,
+	/// Print the summary overview before the language breakdown; turning it off leaves just the breakdown.
+	pub show_summary: bool,
+	/// List requested languages and display alias groups even when they have no files.
+	pub show_empty: bool,
+	pub show_indentation: bool,
+	/// Report line-comment vs block-comment lines for each language.
+	pub show_comment_styles: bool,
+	pub show_encodings: bool,
+	/// Report sizes with `\r\n` line endings counted as `\n` alongside the on-disk sizes.
+	pub normalized_size: bool,
+	/// Draw a bar of each language's share of lines in human output when writing to a terminal.
+	pub bars: bool,
+	/// Draw a sparkline of each language's file-size distribution in verbose human output.
+	pub sparkline: bool,
+	pub color: ColorChoice,
+	/// Record when the report was generated in JSON, HTML and template output; turn off for reproducible reports.
+	pub timestamp: bool,
+	/// Embed these settings in JSON reports so a saved report records how it was produced.
+	pub embed_config: bool,
+	/// Merge the languages of each `[[rollup]]` into one entry, e.g. the C family whose headers detection cannot
+	/// always tell apart.
+	pub aggregate_extensions: bool,
+	/// Report languages grouped by their paradigm (systems, general, scripting, markup, config, data or other) in place of
+	/// display aliases and rollups.
+	pub by_paradigm: bool,
+	/// Reports kept in the `--track` directory; older ones are removed after each run.
+	pub track_history: usize,
 }
 
 impl Default for DisplayConfig {
@@ -107,14 +448,42 @@ impl Default for DisplayConfig {
 		Self {
 			number_style: NumberStyle::Plain,
 			size_units: SizeStyle::Binary,
+			size_raw: false,
+			size_precision: None,
 			precision: 1,
+			rounding: RoundingMode::Nearest,
+			balanced_percentages: false,
 			sort_by: LanguageSortKey::Lines,
 			sort_direction: SortDirection::Desc,
+			file_sort_by: None,
+			file_sort_direction: None,
 			output: OutputFormat::Human,
+			csv_delimiter: ',',
+			null_terminated: false,
+			path_languages: false,
+			template: None,
+			root_label: None,
+			summary_format: None,
 			indent: IndentStyle::Tab,
 			top_languages: None,
 			min_lines: None,
 			by_dir: false,
+			dir_depth: 1,
+			path_depth: None,
+			show_summary: true,
+			show_empty: false,
+			show_indentation: false,
+			show_comment_styles: false,
+			show_encodings: false,
+			normalized_size: false,
+			bars: false,
+			sparkline: false,
+			color: ColorChoice::Auto,
+			timestamp: true,
+			embed_config: false,
+			aggregate_extensions: false,
+			by_paradigm: false,
+			track_history: 30,
 		}
 	}
 }
@@ -124,6 +493,8 @@ impl Default for DisplayConfig {
 pub struct AnalyzerConfig {
 	pub analysis: AnalysisConfig,
 	pub collect_file_details: bool,
+	/// Rules assigning files to categories, empty unless categories are split.
+	pub categories: Vec<FileCategory>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, Default)]
@@ -132,6 +503,9 @@ struct RawConfig {
 	path: Option<PathBuf>,
 	analysis: AnalysisConfig,
 	display: DisplayConfig,
+	display_alias: Vec<DisplayAlias>,
+	rollup: Option<Vec<DisplayAlias>>,
+	category: Option<Vec<FileCategory>>,
 }
 
 impl Config {
@@ -139,25 +513,118 @@ impl Config {
 	///
 	/// Returns an error if the file cannot be read or its TOML is invalid.
 	pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
-		let path = path.as_ref();
-		let contents = fs::read_to_string(path).with_context(|| read_config_context(path))?;
-		let raw: RawConfig = toml::from_str(&contents).with_context(|| parse_config_context(path))?;
-		let path_overridden = raw.path.is_some();
+		Self::from_files(&[path])
+	}
+
+	/// Load several config files layered left to right: each key a later file sets overrides the same key from
+	/// earlier files, tables are merged key by key, and arrays are replaced as a whole.
+	///
+	/// Relative `path`, `analysis.cache` and `display.template` values are resolved against the directory of the
+	/// file that sets them, and `source` names the last file.
+	///
+	/// # Errors
+	///
+	/// Returns an error if a file cannot be read or its TOML is invalid.
+	pub fn from_files<P: AsRef<Path>>(paths: &[P]) -> Result<Self> {
+		let mut merged = toml::Table::new();
+		for path in paths {
+			let path = path.as_ref();
+			let contents = fs::read_to_string(path).with_context(|| read_config_context(path))?;
+			let mut layer: toml::Table = toml::from_str(&contents).with_context(|| parse_config_context(path))?;
+			RawConfig::deserialize(layer.clone()).with_context(|| parse_config_context(path))?;
+			if let Some(parent) = path.parent() {
+				resolve_relative_paths(&mut layer, parent);
+			}
+			merge_tables(&mut merged, layer);
+		}
+		let path_overridden = merged.contains_key("path");
+		let raw = RawConfig::deserialize(merged).context("Failed to merge config files")?;
 		Ok(Self {
 			path: raw.path.unwrap_or_else(|| PathBuf::from(".")),
 			analysis: raw.analysis,
 			display: raw.display,
-			source: Some(path.to_path_buf()),
+			display_aliases: raw.display_alias,
+			rollups: raw.rollup.unwrap_or_else(default_rollups),
+			categories: raw.category.unwrap_or_else(default_categories),
+			source: paths.last().map(|path| path.as_ref().to_path_buf()),
 			path_overridden,
 		})
 	}
+
+	/// The settings as embedded in a report by `display.embed_config`.
+	///
+	/// The analyzed path depends on where the report was produced, so it is left out unless `display.root_label`
+	/// names the root, in which case the label stands in for it. Other path-valued settings are shown relative to the
+	/// analyzed path, or left out when they lie outside it.
+	///
+	/// # Errors
+	///
+	/// Returns an error if a setting cannot be represented as JSON.
+	pub fn report_settings(&self) -> Result<serde_json::Value> {
+		let mut settings = serde_json::to_value(self).context("Failed to serialize the config")?;
+		if let Some(settings) = settings.as_object_mut() {
+			match &self.display.root_label {
+				Some(label) => settings.insert("path".to_owned(), label.clone().into()),
+				None => settings.remove("path"),
+			};
+			for &(section, key) in PATH_KEYS {
+				let Some(table) = section.and_then(|section| settings.get_mut(section)?.as_object_mut()) else {
+					continue;
+				};
+				let Some(path) = table.get(key).and_then(serde_json::Value::as_str) else {
+					continue;
+				};
+				match Path::new(path).strip_prefix(&self.path) {
+					Ok(relative) => table.insert(key.to_owned(), display_path(relative).into()),
+					Err(_) => table.remove(key),
+				};
+			}
+		}
+		Ok(settings)
+	}
+}
+
+/// Overlay `layer` onto `base`, merging nested tables and replacing every other value.
+fn merge_tables(base: &mut toml::Table, layer: toml::Table) {
+	for (key, value) in layer {
+		match (base.get_mut(&key), value) {
+			(Some(toml::Value::Table(base_table)), toml::Value::Table(layer_table)) => {
+				merge_tables(base_table, layer_table);
+			}
+			(_, value) => {
+				base.insert(key, value);
+			}
+		}
+	}
+}
+
+/// Config keys holding paths, which are resolved relative to the file that sets them.
+const PATH_KEYS: &[(Option<&str>, &str)] =
+	&[(None, "path"), (Some("analysis"), "cache"), (Some("display"), "template")];
+
+/// Make the path-valued keys of one config file relative to `dir`, the directory holding that file.
+fn resolve_relative_paths(layer: &mut toml::Table, dir: &Path) {
+	for &(section, key) in PATH_KEYS {
+		let table = match section {
+			Some(section) => layer.get_mut(section).and_then(toml::Value::as_table_mut),
+			None => Some(&mut *layer),
+		};
+		if let Some(toml::Value::String(path)) = table.and_then(|table| table.get_mut(key))
+			&& Path::new(path.as_str()).is_relative()
+		{
+			*path = dir.join(&*path).to_string_lossy().into_owned();
+		}
+	}
 }
 
 impl From<&Config> for AnalyzerConfig {
 	fn from(config: &Config) -> Self {
 		Self {
 			analysis: config.analysis.clone(),
-			collect_file_details: config.analysis.verbosity == Verbosity::Verbose || config.display.by_dir,
+			collect_file_details: config.analysis.verbosity == Verbosity::Verbose
+				|| config.display.by_dir
+				|| config.display.output.lists_files(),
+			categories: if config.analysis.split_categories { config.categories.clone() } else { Vec::new() },
 		}
 	}
 }
@@ -168,13 +635,188 @@ impl From<&Config> for ViewOptions {
 			verbosity: config.analysis.verbosity,
 			number_style: config.display.number_style,
 			size_style: config.display.size_units,
+			size_raw: config.display.size_raw,
+			size_precision: config.display.size_precision,
 			percent_precision: config.display.precision,
+			rounding: config.display.rounding,
+			balanced_percentages: config.display.balanced_percentages,
 			language_sort_key: config.display.sort_by,
 			sort_direction: config.display.sort_direction,
+			file_sort_key: config.display.file_sort_by,
+			file_sort_direction: config.display.file_sort_direction,
 			indent_style: config.display.indent,
 			top_languages: config.display.top_languages,
 			min_lines: config.display.min_lines,
 			by_dir: config.display.by_dir,
+			dir_depth: config.display.dir_depth,
+			path_depth: config.display.path_depth,
+			show_summary: config.display.show_summary,
+			show_empty: config.display.show_empty,
+			show_indentation: config.display.show_indentation,
+			show_comment_styles: config.display.show_comment_styles,
+			show_encodings: config.display.show_encodings,
+			split_tests: config.analysis.split_tests,
+			split_categories: config.analysis.split_categories,
+			collapse_vendored: config.analysis.collapse_vendored,
+			include_assets: config.analysis.include_assets,
+			normalized_size: config.display.normalized_size,
+			// Only the caller knows whether output goes to a terminal and how wide it is.
+			bar_width: None,
+			sparkline: config.display.sparkline,
+			color: false,
+			timestamp: config.display.timestamp,
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use rstest::rstest;
+
+	use super::*;
+
+	#[rstest]
+	#[case::days("30d", 30 * 86_400, "30d")]
+	#[case::hours("12h", 12 * 3_600, "12h")]
+	#[case::normalized("120m", 7_200, "2h")]
+	#[case::weeks("2w", 14 * 86_400, "2w")]
+	#[case::zero("0d", 0, "0s")]
+	fn age_parses_and_round_trips(#[case] input: &str, #[case] seconds: u64, #[case] displayed: &str) {
+		let age: Age = input.parse().unwrap();
+		assert_eq!(age.duration(), Duration::from_secs(seconds));
+		assert_eq!(age.to_string(), displayed);
+		assert_eq!(displayed.parse::<Age>().unwrap(), age);
+	}
+
+	#[test]
+	fn display_aliases_are_read_from_array_tables() {
+		let raw: RawConfig = toml::from_str(
+			r#"
+			[[display_alias]]
+			name = "Frontend JS"
+			languages = ["TypeScript", "JavaScript"]
+			"#,
+		)
+		.unwrap();
+		assert_eq!(
+			raw.display_alias,
+			[DisplayAlias { name: "Frontend JS".into(), languages: vec!["TypeScript".into(), "JavaScript".into()] }]
+		);
+	}
+
+	#[test]
+	fn rollups_default_to_the_c_family_unless_configured() {
+		let dir = std::env::temp_dir().join(format!("codestats_rollup_test_{}", std::process::id()));
+		fs::create_dir_all(&dir).unwrap();
+		let defaults = dir.join("defaults.toml");
+		fs::write(&defaults, "[display]\naggregate_extensions = true\n").unwrap();
+		let config = Config::from_file(&defaults).unwrap();
+		assert!(config.display.aggregate_extensions);
+		assert_eq!(config.rollups, default_rollups());
+		let custom = dir.join("custom.toml");
+		fs::write(&custom, "[[rollup]]\nname = \"JVM\"\nlanguages = [\"Java\", \"Kotlin\"]\n").unwrap();
+		assert_eq!(
+			Config::from_file(&custom).unwrap().rollups,
+			[DisplayAlias { name: "JVM".into(), languages: vec!["Java".into(), "Kotlin".into()] }]
+		);
+		fs::remove_dir_all(&dir).unwrap();
+	}
+
+	#[test]
+	fn report_settings_replace_the_analyzed_path_with_the_root_label() {
+		let mut config = Config { path: PathBuf::from("/home/ci/checkout"), ..Config::default() };
+		let settings = config.report_settings().unwrap();
+		assert!(settings.get("path").is_none());
+		assert_eq!(settings["display"]["sort_by"], "lines");
+		config.display.root_label = Some("myrepo".to_owned());
+		assert_eq!(config.report_settings().unwrap()["path"], "myrepo");
+	}
+
+	#[test]
+	fn report_settings_relativize_paths_under_the_root_and_drop_the_rest() {
+		let mut config = Config { path: PathBuf::from("/home/ci/checkout"), ..Config::default() };
+		config.analysis.cache = Some(PathBuf::from("/home/ci/checkout/.codestats-cache"));
+		config.display.template = Some(PathBuf::from("/home/ci/templates/report.tera"));
+		config.display.root_label = Some("myrepo".to_owned());
+		let settings = config.report_settings().unwrap();
+		assert_eq!(settings["analysis"]["cache"], ".codestats-cache");
+		assert!(settings["display"].get("template").is_none());
+	}
+
+	#[test]
+	fn paradigm_groups_cover_every_language_once() {
+		let groups = paradigm_groups();
+		let names: Vec<&str> = groups.iter().map(|group| group.name.as_str()).collect();
+		assert_eq!(names, ["systems", "general", "scripting", "markup", "config", "data", "other"]);
+		assert_eq!(groups.iter().map(|group| group.languages.len()).sum::<usize>(), langs::LANGUAGES.len());
+		assert!(groups[0].languages.contains(&"Rust".to_owned()));
+		assert!(groups[1].languages.contains(&"Java".to_owned()));
+		assert!(groups[2].languages.contains(&"Python".to_owned()));
+	}
+
+	#[test]
+	fn categories_are_read_from_array_tables_in_order() {
+		let raw: RawConfig = toml::from_str(
+			r#"
+			[[category]]
+			name = "fixtures"
+			patterns = ["**/fixtures/**"]
+
+			[[category]]
+			name = "code"
+			patterns = ["*"]
+			"#,
+		)
+		.unwrap();
+		let names: Vec<_> = raw.category.unwrap().into_iter().map(|category| category.name).collect();
+		assert_eq!(names, ["fixtures", "code"]);
+		assert!(RawConfig::default().category.is_none());
+	}
+
+	#[rstest]
+	#[case::no_unit("30")]
+	#[case::unknown_unit("30y")]
+	#[case::no_number("d")]
+	#[case::negative("-1d")]
+	#[case::empty("")]
+	fn age_rejects_malformed_input(#[case] input: &str) {
+		assert!(input.parse::<Age>().is_err());
+	}
+
+	#[rstest]
+	#[case::canonical("shift_jis", "Shift_JIS")]
+	#[case::alias("SJIS", "Shift_JIS")]
+	#[case::cyrillic("koi8-r", "KOI8-R")]
+	fn input_encoding_resolves_labels(#[case] label: &str, #[case] name: &str) {
+		let encoding: InputEncoding = label.parse().unwrap();
+		assert_eq!(encoding.to_string(), name);
+		assert_eq!(name.parse::<InputEncoding>().unwrap(), encoding);
+		assert!("klingon".parse::<InputEncoding>().is_err());
+	}
+
+	#[test]
+	fn later_layers_override_only_the_keys_they_set() {
+		let mut base: toml::Table =
+			toml::from_str("[analysis]\ntab_width = 4\ninclude_hidden = true\nexclude_patterns = [\"a\", \"b\"]\n")
+				.unwrap();
+		let layer: toml::Table = toml::from_str("[analysis]\ntab_width = 2\nexclude_patterns = [\"c\"]\n").unwrap();
+		merge_tables(&mut base, layer);
+		let raw = RawConfig::deserialize(base).unwrap();
+		assert_eq!(raw.analysis.tab_width, 2);
+		assert!(raw.analysis.include_hidden);
+		assert_eq!(raw.analysis.exclude_patterns, ["c"]);
+	}
+
+	#[test]
+	fn relative_paths_resolve_against_their_file() {
+		let mut layer: toml::Table = toml::from_str(
+			"path = \"src\"\n[display]\ntemplate = \"report.tera\"\n[analysis]\ncache = \"/tmp/cache\"\n",
+		)
+		.unwrap();
+		resolve_relative_paths(&mut layer, Path::new("configs"));
+		let raw = RawConfig::deserialize(layer).unwrap();
+		assert_eq!(raw.path, Some(Path::new("configs").join("src")));
+		assert_eq!(raw.display.template, Some(Path::new("configs").join("report.tera")));
+		assert_eq!(raw.analysis.cache, Some(PathBuf::from("/tmp/cache")));
+	}
+}
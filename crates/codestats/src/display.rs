@@ -8,7 +8,10 @@
 //! - JSON ([`JsonFormatter`]): Pretty-printed JSON for easy processing and reading.
 //! - JSON Compact ([`JsonCompactFormatter`]): Minified JSON for minimal bandwidth.
 //! - Markdown ([`MarkdownFormatter`]): GitHub-flavored markdown for documentation.
+//! - Paths ([`PathsFormatter`]): Analyzed file paths, optionally with their language, one per line or NUL-terminated.
+//! - `SQLite` ([`SqliteFormatter`]): `SQLite` database with summary, language and file tables for ad-hoc SQL queries.
 //! - TSV ([`TsvFormatter`]): Tab-separated values for data pipelines.
+//! - Template ([`TemplateFormatter`]): User-supplied Tera template loaded at runtime.
 //!
 //! ## Customization Options
 //!
@@ -28,29 +31,38 @@ mod json;
 #[cfg(feature = "markdown")]
 mod markdown;
 mod options;
+mod paths;
 mod report;
 mod separated_values;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(feature = "template")]
+mod template;
 #[cfg(any(feature = "html", feature = "markdown"))]
 pub mod template_filters;
 
-use std::{
-	fmt::{self, Display},
-	io::Write,
-	path::Path,
-};
+use std::io::Write;
 
 use anyhow::Result;
-pub use formatting::{FormatterContext, apply_sort};
+pub use formatting::{FormatterContext, apply_sort, pluralize};
 #[cfg(feature = "html")]
 pub use html::HtmlFormatter;
-pub use human::HumanFormatter;
+pub use human::{HumanFormatter, SummaryFormat};
 pub use json::{JsonCompactFormatter, JsonFormatter};
 #[cfg(feature = "markdown")]
 pub use markdown::MarkdownFormatter;
-pub use options::{IndentStyle, LanguageSortKey, NumberStyle, SizeStyle, SortDirection, Verbosity, ViewOptions};
-pub use report::ReportData;
-pub use separated_values::{CsvFormatter, TsvFormatter};
+pub use options::{
+	ColorChoice, IndentStyle, LanguageSortKey, NumberStyle, RoundingMode, SizeStyle, SortDirection, Verbosity,
+	ViewOptions,
+};
+pub use paths::PathsFormatter;
+pub use report::{ReportData, ReportRoot};
+pub use separated_values::{CsvFormatter, FileRowStream, TsvFormatter};
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteFormatter;
+#[cfg(feature = "template")]
+pub use template::TemplateFormatter;
 
 use crate::analysis::AnalysisResults;
 
@@ -66,39 +78,53 @@ pub enum OutputFormat {
 	Markdown,
 	#[cfg(feature = "html")]
 	Html,
+	Paths,
+	#[cfg(feature = "sqlite")]
+	Sqlite,
 }
 
-impl Display for OutputFormat {
-	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl_names!(OutputFormat, "output format", {
+	Human => "human",
+	Json => "json",
+	JsonCompact => "json-compact",
+	Csv => "csv",
+	Tsv => "tsv",
+	#[cfg(feature = "markdown")]
+	Markdown => "markdown",
+	#[cfg(feature = "html")]
+	Html => "html",
+	Paths => "paths",
+	#[cfg(feature = "sqlite")]
+	Sqlite => "sqlite",
+});
+
+impl OutputFormat {
+	/// Whether reports in this format list every analyzed file, so the analysis must collect per-file details.
+	#[must_use]
+	pub const fn lists_files(self) -> bool {
 		match self {
-			Self::Human => write!(f, "human"),
-			Self::Json => write!(f, "json"),
-			Self::JsonCompact => write!(f, "json-compact"),
-			Self::Csv => write!(f, "csv"),
-			Self::Tsv => write!(f, "tsv"),
-			#[cfg(feature = "markdown")]
-			Self::Markdown => write!(f, "markdown"),
-			#[cfg(feature = "html")]
-			Self::Html => write!(f, "html"),
+			Self::Paths => true,
+			#[cfg(feature = "sqlite")]
+			Self::Sqlite => true,
+			_ => false,
 		}
 	}
-}
-
-impl std::str::FromStr for OutputFormat {
-	type Err = String;
 
-	fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-		match s {
-			"human" => Ok(Self::Human),
-			"json" => Ok(Self::Json),
-			"json-compact" => Ok(Self::JsonCompact),
-			"csv" => Ok(Self::Csv),
-			"tsv" => Ok(Self::Tsv),
+	/// File extension conventionally used for reports in this format, without the leading dot.
+	#[must_use]
+	pub const fn extension(self) -> &'static str {
+		match self {
+			Self::Human => "txt",
+			Self::Json | Self::JsonCompact => "json",
+			Self::Csv => "csv",
+			Self::Tsv => "tsv",
 			#[cfg(feature = "markdown")]
-			"markdown" => Ok(Self::Markdown),
+			Self::Markdown => "md",
 			#[cfg(feature = "html")]
-			"html" => Ok(Self::Html),
-			_ => Err(format!("invalid output format '{s}'")),
+			Self::Html => "html",
+			Self::Paths => "lst",
+			#[cfg(feature = "sqlite")]
+			Self::Sqlite => "sqlite",
 		}
 	}
 }
@@ -113,7 +139,7 @@ pub trait OutputFormatter {
 	fn write_output(
 		&self,
 		results: &AnalysisResults,
-		path: &Path,
+		root: ReportRoot<'_>,
 		view_options: ViewOptions,
 		writer: &mut dyn Write,
 	) -> Result<()>;
@@ -121,11 +147,11 @@ pub trait OutputFormatter {
 	fn prepare_report<'a>(
 		&self,
 		results: &'a AnalysisResults,
-		path: &Path,
+		root: ReportRoot<'_>,
 		view_options: ViewOptions,
 	) -> (FormatterContext, ReportData<'a>) {
 		let ctx = FormatterContext::new(view_options);
-		let report = ReportData::from_results(results, path, view_options.verbosity, &ctx);
+		let report = ReportData::from_results(results, root, view_options.verbosity, &ctx);
 		(ctx, report)
 	}
 }
@@ -140,9 +166,74 @@ pub enum Formatter {
 	Markdown(MarkdownFormatter),
 	#[cfg(feature = "html")]
 	Html(HtmlFormatter),
+	Paths(PathsFormatter),
+	#[cfg(feature = "sqlite")]
+	Sqlite(SqliteFormatter),
+	#[cfg(feature = "template")]
+	Template(TemplateFormatter),
 }
 
 impl Formatter {
+	/// Separate CSV fields with `delimiter` instead of a comma. Other formats are returned unchanged.
+	#[must_use]
+	pub fn with_csv_delimiter(self, delimiter: char) -> Self {
+		match self {
+			Self::Csv(_) => Self::Csv(CsvFormatter::with_delimiter(delimiter)),
+			other => other,
+		}
+	}
+
+	/// End each path record with NUL instead of a newline when `null` is set. Other formats are returned unchanged.
+	#[must_use]
+	pub fn with_null_terminator(self, null: bool) -> Self {
+		match self {
+			Self::Paths(_) if null => Self::Paths(PathsFormatter::null_terminated()),
+			other => other,
+		}
+	}
+
+	/// Prefix each path record with its language when `languages` is set. Other formats are returned unchanged.
+	#[must_use]
+	pub fn with_path_languages(self, languages: bool) -> Self {
+		match self {
+			Self::Paths(paths) if languages => Self::Paths(paths.with_languages()),
+			other => other,
+		}
+	}
+
+	/// Write the human overview line from `format` when one is given. Other formats are returned unchanged.
+	#[must_use]
+	pub fn with_summary_format(self, format: Option<SummaryFormat>) -> Self {
+		match (self, format) {
+			(Self::Human(_), Some(format)) => Self::Human(HumanFormatter::with_summary_format(format)),
+			(other, _) => other,
+		}
+	}
+
+	/// Embed `config` as a top-level `config` object in JSON reports when one is given. Other formats are returned
+	/// unchanged.
+	#[must_use]
+	pub fn with_embedded_config(self, config: Option<serde_json::Value>) -> Self {
+		match (self, config) {
+			(Self::Json(_), Some(config)) => Self::Json(JsonFormatter::with_config(config)),
+			(Self::JsonCompact(_), Some(config)) => Self::JsonCompact(JsonCompactFormatter::with_config(config)),
+			(other, _) => other,
+		}
+	}
+
+	/// Start a [`FileRowStream`] writing CSV or TSV file rows to `writer` as files are counted.
+	///
+	/// # Errors
+	///
+	/// Returns an error for formats other than CSV and TSV, or if the header row cannot be written.
+	pub fn stream_files(&self, writer: Box<dyn Write + Send>, view_options: ViewOptions) -> Result<FileRowStream> {
+		match self {
+			Self::Csv(f) => f.stream_files(writer, view_options),
+			Self::Tsv(f) => f.stream_files(writer, view_options),
+			_ => anyhow::bail!("Only CSV and TSV output can be streamed"),
+		}
+	}
+
 	/// Format and stream the analysis results to the provided writer.
 	///
 	/// # Errors
@@ -151,20 +242,25 @@ impl Formatter {
 	pub fn write_output(
 		&self,
 		results: &AnalysisResults,
-		path: &Path,
+		root: ReportRoot<'_>,
 		view_options: ViewOptions,
 		writer: &mut dyn Write,
 	) -> Result<()> {
 		match self {
-			Self::Human(f) => f.write_output(results, path, view_options, writer),
-			Self::Json(f) => f.write_output(results, path, view_options, writer),
-			Self::JsonCompact(f) => f.write_output(results, path, view_options, writer),
-			Self::Csv(f) => f.write_output(results, path, view_options, writer),
-			Self::Tsv(f) => f.write_output(results, path, view_options, writer),
+			Self::Human(f) => f.write_output(results, root, view_options, writer),
+			Self::Json(f) => f.write_output(results, root, view_options, writer),
+			Self::JsonCompact(f) => f.write_output(results, root, view_options, writer),
+			Self::Csv(f) => f.write_output(results, root, view_options, writer),
+			Self::Tsv(f) => f.write_output(results, root, view_options, writer),
 			#[cfg(feature = "markdown")]
-			Self::Markdown(f) => f.write_output(results, path, view_options, writer),
+			Self::Markdown(f) => f.write_output(results, root, view_options, writer),
 			#[cfg(feature = "html")]
-			Self::Html(f) => f.write_output(results, path, view_options, writer),
+			Self::Html(f) => f.write_output(results, root, view_options, writer),
+			Self::Paths(f) => f.write_output(results, root, view_options, writer),
+			#[cfg(feature = "sqlite")]
+			Self::Sqlite(f) => f.write_output(results, root, view_options, writer),
+			#[cfg(feature = "template")]
+			Self::Template(f) => f.write_output(results, root, view_options, writer),
 		}
 	}
 }
@@ -172,14 +268,17 @@ impl Formatter {
 #[must_use]
 pub fn get_formatter(format: OutputFormat) -> Formatter {
 	match format {
-		OutputFormat::Human => Formatter::Human(HumanFormatter),
-		OutputFormat::Json => Formatter::Json(JsonFormatter),
-		OutputFormat::JsonCompact => Formatter::JsonCompact(JsonCompactFormatter),
+		OutputFormat::Human => Formatter::Human(HumanFormatter::default()),
+		OutputFormat::Json => Formatter::Json(JsonFormatter::default()),
+		OutputFormat::JsonCompact => Formatter::JsonCompact(JsonCompactFormatter::default()),
 		OutputFormat::Csv => Formatter::Csv(CsvFormatter::default()),
 		OutputFormat::Tsv => Formatter::Tsv(TsvFormatter::default()),
 		#[cfg(feature = "markdown")]
 		OutputFormat::Markdown => Formatter::Markdown(MarkdownFormatter),
 		#[cfg(feature = "html")]
 		OutputFormat::Html => Formatter::Html(HtmlFormatter),
+		OutputFormat::Paths => Formatter::Paths(PathsFormatter::default()),
+		#[cfg(feature = "sqlite")]
+		OutputFormat::Sqlite => Formatter::Sqlite(SqliteFormatter),
 	}
 }
@@ -1,8 +1,13 @@
-use std::cmp::Ordering;
+use std::{
+	borrow::Cow,
+	cmp::Ordering,
+	path::Path,
+	time::{SystemTime, UNIX_EPOCH},
+};
 
 use num_format::{CustomFormat, Grouping, ToFormattedString};
 
-use super::options::{IndentStyle, NumberStyle, SizeStyle, SortDirection, ViewOptions};
+use super::options::{IndentStyle, NumberStyle, RoundingMode, SizeStyle, SortDirection, ViewOptions};
 
 #[derive(Debug, Clone)]
 pub struct FormatterContext {
@@ -16,8 +21,10 @@ impl FormatterContext {
 	#[must_use]
 	pub fn new(options: ViewOptions) -> Self {
 		let number = NumberFormatter::new(options.number_style);
-		let size = SizeFormatter::new(options.size_style, number.clone());
-		let percent = PercentFormatter::new(options.percent_precision);
+		let size = SizeFormatter::new(options.size_style, number.clone())
+			.with_raw_bytes(options.size_raw)
+			.with_precision(options.size_precision);
+		let percent = PercentFormatter::new(options.percent_precision, options.rounding);
 		Self { options, number, size, percent }
 	}
 
@@ -43,6 +50,30 @@ impl FormatterContext {
 			IndentStyle::Spaces(n) => " ".repeat(usize::from(n) * level),
 		}
 	}
+
+	/// Return the percentages of a set of counts that together make up a whole.
+	///
+	/// With `balanced_percentages` set, they are rounded to the display precision by the largest remainder method
+	/// so they sum to exactly 100; otherwise `percentages` is returned unchanged.
+	#[must_use]
+	pub fn shares<const N: usize>(&self, counts: [u64; N], percentages: [f64; N]) -> [f64; N] {
+		if self.options.balanced_percentages {
+			balanced_percentages(counts, self.options.percent_precision).unwrap_or(percentages)
+		} else {
+			percentages
+		}
+	}
+
+	/// Shorten a file path to its last `path_depth` components for display, marking the cut with a leading `…/`.
+	#[must_use]
+	pub fn file_path<'a>(&self, path: &'a str) -> Cow<'a, str> {
+		let Some(depth) = self.options.path_depth else {
+			return Cow::Borrowed(path);
+		};
+		path.rmatch_indices(['/', '\\'])
+			.nth(depth.saturating_sub(1))
+			.map_or(Cow::Borrowed(path), |(pos, _)| Cow::Owned(format!("\u{2026}/{}", &path[pos + 1..])))
+	}
 }
 
 #[derive(Debug, Clone)]
@@ -59,14 +90,15 @@ impl NumberFormatter {
 	/// which cannot happen with the separator strings used here.
 	#[must_use]
 	pub fn new(style: NumberStyle) -> Self {
-		let separator = match style {
+		let (separator, grouping) = match style {
 			NumberStyle::Plain => return Self::Plain,
-			NumberStyle::Comma => ",",
-			NumberStyle::Underscore => "_",
-			NumberStyle::Space => " ",
+			NumberStyle::Comma => (",", Grouping::Standard),
+			NumberStyle::Underscore => ("_", Grouping::Standard),
+			NumberStyle::Space => (" ", Grouping::Standard),
+			NumberStyle::Indian => (",", Grouping::Indian),
 		};
 		let format = CustomFormat::builder()
-			.grouping(Grouping::Standard)
+			.grouping(grouping)
 			.separator(separator)
 			.build()
 			.expect("static separator strings are always valid");
@@ -86,12 +118,28 @@ impl NumberFormatter {
 pub struct SizeFormatter {
 	style: SizeStyle,
 	number: NumberFormatter,
+	raw_bytes: bool,
+	precision: Option<u8>,
 }
 
 impl SizeFormatter {
 	#[must_use]
 	pub const fn new(style: SizeStyle, number: NumberFormatter) -> Self {
-		Self { style, number }
+		Self { style, number, raw_bytes: false, precision: None }
+	}
+
+	/// Always write sizes in bytes, without scaling to larger units.
+	#[must_use]
+	pub const fn with_raw_bytes(mut self, raw_bytes: bool) -> Self {
+		self.raw_bytes = raw_bytes;
+		self
+	}
+
+	/// Write scaled sizes with `precision` decimals. `None` picks 2, 1 or 0 decimals by magnitude.
+	#[must_use]
+	pub const fn with_precision(mut self, precision: Option<u8>) -> Self {
+		self.precision = precision;
+		self
 	}
 
 	#[must_use]
@@ -110,35 +158,132 @@ impl SizeFormatter {
 		};
 		let mut value = size as f64;
 		let mut unit_index = 0usize;
-		while value >= base && unit_index < units.len() - 1 {
+		while !self.raw_bytes && value >= base && unit_index < units.len() - 1 {
 			value /= base;
 			unit_index += 1;
 		}
-		match unit_index {
-			0 => format!("{} B", self.number.format(size)),
-			_ if value < 10.0 => format!("{value:.2} {}", units[unit_index]),
-			_ if value < 100.0 => format!("{value:.1} {}", units[unit_index]),
-			_ => format!("{value:.0} {}", units[unit_index]),
+		if unit_index == 0 {
+			return format!("{} B", self.number.format(size));
 		}
+		let precision = self.precision.map_or(
+			match value {
+				_ if value < 10.0 => 2,
+				_ if value < 100.0 => 1,
+				_ => 0,
+			},
+			usize::from,
+		);
+		format!("{value:.precision$} {}", units[unit_index])
 	}
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct PercentFormatter {
 	precision: u8,
+	rounding: RoundingMode,
 }
 
 impl PercentFormatter {
 	#[must_use]
-	pub const fn new(precision: u8) -> Self {
-		Self { precision }
+	pub const fn new(precision: u8, rounding: RoundingMode) -> Self {
+		Self { precision, rounding }
 	}
 
 	#[must_use]
 	pub fn format(self, value: f64) -> String {
 		let precision = usize::from(self.precision);
-		format!("{value:.precision$}")
+		match self.rounding {
+			RoundingMode::Nearest => format!("{value:.precision$}"),
+			RoundingMode::Up | RoundingMode::Down => round_decimal(value, precision, self.rounding == RoundingMode::Up)
+				.unwrap_or_else(|| format!("{value:.precision$}")),
+		}
+	}
+}
+
+/// Round `value` to `precision` decimal places using its shortest decimal representation, so values such as
+/// `1.005` (stored as `1.00499...`) round the way they read. Returns `None` for values that cannot be
+/// represented as a scaled integer (non-finite or absurdly large), leaving the caller to fall back.
+fn round_decimal(value: f64, precision: usize, half_up: bool) -> Option<String> {
+	if !value.is_finite() {
+		return None;
+	}
+	let repr = value.abs().to_string();
+	let (int_part, frac_part) = repr.split_once('.').unwrap_or((&repr, ""));
+	let kept = frac_part.get(..precision).unwrap_or(frac_part);
+	let mut units: u128 = format!("{int_part}{kept:0<precision$}").parse().ok()?;
+	if half_up && frac_part.as_bytes().get(precision).is_some_and(|digit| *digit >= b'5') {
+		units += 1;
+	}
+	let sign = if value.is_sign_negative() && units > 0 { "-" } else { "" };
+	if precision == 0 {
+		return Some(format!("{sign}{units}"));
+	}
+	let scale = 10u128.checked_pow(u32::try_from(precision).ok()?)?;
+	Some(format!("{sign}{}.{:0precision$}", units / scale, units % scale))
+}
+
+/// Split 100 between `counts` in steps of `10^-precision` by the largest remainder method: every share is rounded
+/// down, then the steps left over go to the shares with the largest remainders, earlier shares winning ties.
+///
+/// Returns `None` when the counts are all zero.
+#[expect(
+	clippy::cast_precision_loss,
+	reason = "display-only; the step counts convert exactly for every precision up to the CLI's maximum of 6"
+)]
+fn balanced_percentages<const N: usize>(counts: [u64; N], precision: u8) -> Option<[f64; N]> {
+	let total: u128 = counts.iter().map(|&count| u128::from(count)).sum();
+	if total == 0 {
+		return None;
 	}
+	let scale = 10u128.checked_pow(u32::from(precision))?;
+	let whole = 100 * scale;
+	let mut steps = counts.map(|count| u128::from(count) * whole / total);
+	let leftover = whole - steps.iter().sum::<u128>();
+	let mut order: Vec<usize> = (0..N).collect();
+	order.sort_by_key(|&idx| std::cmp::Reverse(u128::from(counts[idx]) * whole % total));
+	for &idx in order.iter().take(usize::try_from(leftover).ok()?) {
+		steps[idx] += 1;
+	}
+	Some(steps.map(|step| step as f64 / scale as f64))
+}
+
+/// Render `path` for display, dropping the Windows `\\?\` verbatim prefix so long and UNC paths read like ordinary ones.
+#[must_use]
+pub fn display_path(path: &Path) -> String {
+	let rendered = path.display().to_string();
+	if cfg!(windows) { strip_verbatim_prefix(&rendered).into_owned() } else { rendered }
+}
+
+fn strip_verbatim_prefix(path: &str) -> Cow<'_, str> {
+	path.strip_prefix(r"\\?\UNC\").map_or_else(
+		|| Cow::Borrowed(path.strip_prefix(r"\\?\").unwrap_or(path)),
+		|rest| Cow::Owned(format!(r"\\{rest}")),
+	)
+}
+
+/// Render `time` as an RFC 3339 timestamp in UTC with whole seconds, e.g. `2024-05-01T12:30:00Z`.
+///
+/// Times before the Unix epoch are clamped to it.
+#[must_use]
+pub fn rfc3339_utc(time: SystemTime) -> String {
+	let seconds = time.duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs());
+	let (days, day_seconds) = (seconds / 86_400, seconds % 86_400);
+	// Civil-from-days (Howard Hinnant), shifted so eras start on March 1st and leap days fall at the end of a year.
+	let days = days + 719_468;
+	let era = days / 146_097;
+	let day_of_era = days % 146_097;
+	let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+	let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+	let shifted_month = (5 * day_of_year + 2) / 153;
+	let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+	let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+	let year = era * 400 + year_of_era + u64::from(month <= 2);
+	format!(
+		"{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+		day_seconds / 3_600,
+		day_seconds / 60 % 60,
+		day_seconds % 60
+	)
 }
 
 /// Return `singular` when `count` equals 1, otherwise return `plural`.
@@ -159,8 +304,9 @@ pub fn apply_sort<T>(
 		let rhs = metric(b);
 		let mut ordering = match (lhs, rhs) {
 			(SortValue::Num(l), SortValue::Num(r)) => l.cmp(&r),
+			(SortValue::Float(l), SortValue::Float(r)) => l.total_cmp(&r),
 			(SortValue::Text(l), SortValue::Text(r)) => l.cmp(r),
-			(SortValue::Num(_), SortValue::Text(_)) | (SortValue::Text(_), SortValue::Num(_)) => Ordering::Equal,
+			_ => Ordering::Equal,
 		};
 		if ordering == Ordering::Equal {
 			ordering = tiebreaker(a, b);
@@ -172,15 +318,41 @@ pub fn apply_sort<T>(
 #[derive(Debug)]
 pub enum SortValue<'a> {
 	Num(u64),
+	/// A computed percentage or ratio, ordered with [`f64::total_cmp`].
+	Float(f64),
 	Text(&'a str),
 }
 
 #[cfg(test)]
 mod tests {
+	use std::time::Duration;
+
 	use rstest::rstest;
 
 	use super::*;
 
+	#[rstest]
+	#[case::epoch(0, "1970-01-01T00:00:00Z")]
+	#[case::leap_day(951_782_400, "2000-02-29T00:00:00Z")]
+	#[case::end_of_year(1_704_067_199, "2023-12-31T23:59:59Z")]
+	#[case::mid_day(1_714_566_600, "2024-05-01T12:30:00Z")]
+	fn rfc3339_utc_renders_civil_dates(#[case] seconds: u64, #[case] expected: &str) {
+		assert_eq!(rfc3339_utc(UNIX_EPOCH + Duration::from_secs(seconds)), expected);
+	}
+
+	#[rstest]
+	#[case::unset(None, "crates/codestats/src/analysis/stats.rs", "crates/codestats/src/analysis/stats.rs")]
+	#[case::trimmed(Some(3), "crates/codestats/src/analysis/stats.rs", "\u{2026}/src/analysis/stats.rs")]
+	#[case::file_name_only(Some(1), "crates/codestats/stats.rs", "\u{2026}/stats.rs")]
+	#[case::zero_keeps_file_name(Some(0), "src/stats.rs", "\u{2026}/stats.rs")]
+	#[case::shallow_enough(Some(3), "src/stats.rs", "src/stats.rs")]
+	#[case::exact_depth(Some(2), "src/stats.rs", "src/stats.rs")]
+	#[case::backslashes(Some(1), "src\\analysis\\stats.rs", "\u{2026}/stats.rs")]
+	fn test_file_path(#[case] path_depth: Option<usize>, #[case] path: &str, #[case] expected: &str) {
+		let ctx = FormatterContext::new(ViewOptions { path_depth, ..Default::default() });
+		assert_eq!(ctx.file_path(path), expected);
+	}
+
 	#[rstest]
 	#[case(NumberStyle::Plain, 0, "0")]
 	#[case(NumberStyle::Plain, 1_234_567, "1234567")]
@@ -190,6 +362,9 @@ mod tests {
 	#[case(NumberStyle::Comma, 1_234_567, "1,234,567")]
 	#[case(NumberStyle::Underscore, 1_234_567, "1_234_567")]
 	#[case(NumberStyle::Space, 1_234_567, "1 234 567")]
+	#[case(NumberStyle::Indian, 999, "999")]
+	#[case(NumberStyle::Indian, 100_000, "1,00,000")]
+	#[case(NumberStyle::Indian, 1_234_567_890, "1,23,45,67,890")]
 	fn test_number_formatter(#[case] style: NumberStyle, #[case] value: u64, #[case] expected: &str) {
 		let fmt = NumberFormatter::new(style);
 		assert_eq!(fmt.format(value), expected);
@@ -214,12 +389,55 @@ mod tests {
 		assert_eq!(fmt.format(bytes), expected);
 	}
 
+	#[rstest]
+	#[case::raw_small(true, None, 512, "512 B")]
+	#[case::raw_large(true, None, 5 * 1024 * 1024, "5,242,880 B")]
+	#[case::raw_ignores_precision(true, Some(3), 2048, "2,048 B")]
+	#[case::fixed_precision(false, Some(3), 1536, "1.500 KiB")]
+	#[case::zero_precision(false, Some(0), 10240, "10 KiB")]
+	#[case::bytes_have_no_decimals(false, Some(2), 1023, "1,023 B")]
+	fn test_size_formatter_raw_and_precision(
+		#[case] raw_bytes: bool,
+		#[case] precision: Option<u8>,
+		#[case] bytes: u64,
+		#[case] expected: &str,
+	) {
+		let num = NumberFormatter::new(NumberStyle::Comma);
+		let fmt = SizeFormatter::new(SizeStyle::Binary, num).with_raw_bytes(raw_bytes).with_precision(precision);
+		assert_eq!(fmt.format(bytes), expected);
+	}
+
 	#[rstest]
 	#[case(2, 50.0, "50.00")]
 	#[case(2, 33.333, "33.33")]
 	#[case(0, 99.9, "100")]
 	fn test_percent_formatter(#[case] precision: u8, #[case] value: f64, #[case] expected: &str) {
-		let fmt = PercentFormatter::new(precision);
+		let fmt = PercentFormatter::new(precision, RoundingMode::Nearest);
+		assert_eq!(fmt.format(value), expected);
+	}
+
+	#[rstest]
+	#[case::nearest_tie(RoundingMode::Nearest, 2, 12.345, "12.35")]
+	#[case::up_tie(RoundingMode::Up, 2, 12.345, "12.35")]
+	#[case::down_tie(RoundingMode::Down, 2, 12.345, "12.34")]
+	#[case::nearest_binary_tie(RoundingMode::Nearest, 2, 1.005, "1.00")]
+	#[case::up_binary_tie(RoundingMode::Up, 2, 1.005, "1.01")]
+	#[case::up_below_half(RoundingMode::Up, 2, 12.344, "12.34")]
+	#[case::down_near_next(RoundingMode::Down, 2, 12.349, "12.34")]
+	#[case::up_carry(RoundingMode::Up, 1, 99.95, "100.0")]
+	#[case::down_no_carry(RoundingMode::Down, 1, 99.99, "99.9")]
+	#[case::up_zero_precision(RoundingMode::Up, 0, 2.5, "3")]
+	#[case::down_zero_precision(RoundingMode::Down, 0, 2.9, "2")]
+	#[case::up_pads(RoundingMode::Up, 3, 50.0, "50.000")]
+	#[case::down_exact(RoundingMode::Down, 2, 0.0, "0.00")]
+	#[case::up_small(RoundingMode::Up, 2, 0.005, "0.01")]
+	fn test_percent_rounding(
+		#[case] rounding: RoundingMode,
+		#[case] precision: u8,
+		#[case] value: f64,
+		#[case] expected: &str,
+	) {
+		let fmt = PercentFormatter::new(precision, rounding);
 		assert_eq!(fmt.format(value), expected);
 	}
 
@@ -233,6 +451,15 @@ mod tests {
 		assert_eq!(pluralize(count, singular, plural), expected);
 	}
 
+	#[rstest]
+	#[case::drive(r"\\?\C:\src\main.rs", r"C:\src\main.rs")]
+	#[case::unc(r"\\?\UNC\server\share\main.rs", r"\\server\share\main.rs")]
+	#[case::plain_windows(r"C:\src\main.rs", r"C:\src\main.rs")]
+	#[case::unix("src/main.rs", "src/main.rs")]
+	fn test_strip_verbatim_prefix(#[case] input: &str, #[case] expected: &str) {
+		assert_eq!(strip_verbatim_prefix(input), expected);
+	}
+
 	#[test]
 	fn test_apply_sort_ascending() {
 		let mut items = vec![3u64, 1, 4, 1, 5];
@@ -254,6 +481,13 @@ mod tests {
 		assert_eq!(items, vec![("a", 1), ("b", 1), ("c", 2)]);
 	}
 
+	#[test]
+	fn test_apply_sort_float() {
+		let mut items = vec![2.5, f64::INFINITY, 0.0, 1.5];
+		apply_sort(&mut items, SortDirection::Asc, |x| SortValue::Float(*x), |_, _| Ordering::Equal);
+		assert_eq!(items, vec![0.0, 1.5, 2.5, f64::INFINITY]);
+	}
+
 	#[test]
 	fn test_apply_sort_text() {
 		let mut items = vec!["banana", "apple", "cherry"];
@@ -261,6 +495,19 @@ mod tests {
 		assert_eq!(items, vec!["apple", "banana", "cherry"]);
 	}
 
+	#[rstest]
+	#[case::thirds([1, 1, 1], 1, Some([33.4, 33.3, 33.3]))]
+	#[case::largest_remainder([2, 1, 4], 0, Some([29.0, 14.0, 57.0]))]
+	#[case::exact([1, 3, 0], 2, Some([25.0, 75.0, 0.0]))]
+	#[case::empty([0, 0, 0], 1, None)]
+	fn test_balanced_percentages(#[case] counts: [u64; 3], #[case] precision: u8, #[case] expected: Option<[f64; 3]>) {
+		let shares = balanced_percentages(counts, precision);
+		assert_eq!(shares, expected);
+		if let Some(shares) = shares {
+			assert!((shares.iter().sum::<f64>() - 100.0).abs() < 1e-9);
+		}
+	}
+
 	#[rstest]
 	#[case(IndentStyle::Tab, 0, "")]
 	#[case(IndentStyle::Tab, 1, "\t")]
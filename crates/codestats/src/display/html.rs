@@ -1,18 +1,19 @@
-use std::{io::Write, path::Path};
+use std::io::Write;
 
 use anyhow::Result;
 use askama::Template;
 
-use super::{FormatterContext, OutputFormatter, ReportData, Verbosity, ViewOptions};
+use super::{FormatterContext, OutputFormatter, ReportData, ReportRoot, Verbosity, ViewOptions};
 use crate::{
 	analysis::AnalysisResults,
-	display::report::{LanguageRecord, Summary},
+	display::report::{LanguageRecord, ReportMeta, Summary},
 };
 
 #[derive(Template)]
 #[template(path = "report.html", escape = "html")]
 struct ReportTemplate<'a> {
 	title: &'a str,
+	meta: &'a ReportMeta,
 	summary: &'a Summary,
 	totals: String,
 	languages: &'a [LanguageRecord<'a>],
@@ -30,11 +31,11 @@ impl OutputFormatter for HtmlFormatter {
 	fn write_output(
 		&self,
 		results: &AnalysisResults,
-		path: &Path,
+		root: ReportRoot<'_>,
 		view_options: ViewOptions,
 		writer: &mut dyn Write,
 	) -> Result<()> {
-		let (ctx, report) = self.prepare_report(results, path, view_options);
+		let (ctx, report) = self.prepare_report(results, root, view_options);
 		Self::write_document(&report, view_options.verbosity, &ctx, writer)
 	}
 }
@@ -50,6 +51,7 @@ impl HtmlFormatter {
 		let totals = parts.join(", ");
 		let template = ReportTemplate {
 			title: &report.analysis_path,
+			meta: &report.meta,
 			summary: &report.summary,
 			totals,
 			languages: &report.languages,
@@ -1,18 +1,149 @@
-use std::{borrow::Cow, io::Write, path::Path};
+use std::{borrow::Cow, fmt::Display, io::Write, str::FromStr};
 
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 
-use super::{FormatterContext, OutputFormatter, ReportData, Verbosity, ViewOptions, apply_sort};
+use super::{FormatterContext, OutputFormatter, ReportData, ReportRoot, Verbosity, ViewOptions};
 use crate::{
 	analysis::{AnalysisResults, stats::percentage},
 	display::{
-		formatting::{SortValue, pluralize},
-		options::LanguageSortKey,
-		report::{DirFileRecord, DirRecord, LanguageRecord, Summary},
+		formatting::pluralize,
+		report::{DirFileRecord, DirLanguageRecord, DirRecord, LanguageRecord, SplitTotals, Summary, select_languages},
 	},
 };
 
-pub struct HumanFormatter;
+#[derive(Default)]
+pub struct HumanFormatter {
+	summary_format: Option<SummaryFormat>,
+}
+
+impl HumanFormatter {
+	/// Create a formatter that writes the first overview line from `format` instead of the built-in wording.
+	#[must_use]
+	pub const fn with_summary_format(format: SummaryFormat) -> Self {
+		Self { summary_format: Some(format) }
+	}
+}
+
+/// A value that can be placed in a [`SummaryFormat`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SummaryField {
+	Files,
+	Lines,
+	Code,
+	Size,
+	Path,
+}
+
+impl SummaryField {
+	const ALL: [(&'static str, Self); 5] = [
+		("files", Self::Files),
+		("lines", Self::Lines),
+		("code", Self::Code),
+		("size", Self::Size),
+		("path", Self::Path),
+	];
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SummaryPart {
+	Text(String),
+	Field(SummaryField),
+}
+
+/// A template for the human overview line, such as `{path}: {code} code lines in {files} files`.
+///
+/// Placeholders are `{files}`, `{lines}`, `{code}`, `{size}` and `{path}`; `{{` and `}}` write literal braces. Numbers
+/// and sizes follow the configured number style and size units. Unknown or unclosed placeholders fail to parse.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct SummaryFormat {
+	source: String,
+	parts: Vec<SummaryPart>,
+}
+
+impl SummaryFormat {
+	fn render(&self, report: &ReportData, ctx: &FormatterContext) -> String {
+		let summary = &report.summary;
+		let mut line = String::new();
+		for part in &self.parts {
+			match part {
+				SummaryPart::Text(text) => line.push_str(text),
+				SummaryPart::Field(SummaryField::Files) => line.push_str(&ctx.number(summary.total_files)),
+				SummaryPart::Field(SummaryField::Lines) => line.push_str(&ctx.number(summary.total_lines)),
+				SummaryPart::Field(SummaryField::Code) => line.push_str(&ctx.number(summary.total_code_lines)),
+				SummaryPart::Field(SummaryField::Size) => line.push_str(&summary.total_size_human),
+				SummaryPart::Field(SummaryField::Path) => line.push_str(&report.analysis_path),
+			}
+		}
+		line
+	}
+}
+
+impl FromStr for SummaryFormat {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut parts = Vec::new();
+		let mut text = String::new();
+		let mut chars = s.chars();
+		while let Some(c) = chars.next() {
+			match c {
+				'{' if chars.as_str().starts_with('{') => {
+					chars.next();
+					text.push('{');
+				}
+				'}' if chars.as_str().starts_with('}') => {
+					chars.next();
+					text.push('}');
+				}
+				'{' => {
+					let rest = chars.as_str();
+					let end = rest.find('}').ok_or_else(|| format!("unclosed placeholder in summary format '{s}'"))?;
+					let name = &rest[..end];
+					let &(_, field) = SummaryField::ALL.iter().find(|(known, _)| *known == name).ok_or_else(|| {
+						format!(
+							"unknown placeholder '{{{name}}}' in summary format (expected files, lines, code, size or path)"
+						)
+					})?;
+					if !text.is_empty() {
+						parts.push(SummaryPart::Text(std::mem::take(&mut text)));
+					}
+					parts.push(SummaryPart::Field(field));
+					chars = rest[end + 1..].chars();
+				}
+				'}' => {
+					return Err(format!("unmatched '}}' in summary format '{s}' (write '}}}}' for a literal brace)"));
+				}
+				c => text.push(c),
+			}
+		}
+		if !text.is_empty() {
+			parts.push(SummaryPart::Text(text));
+		}
+		Ok(Self { source: s.to_owned(), parts })
+	}
+}
+
+impl TryFrom<String> for SummaryFormat {
+	type Error = String;
+
+	fn try_from(value: String) -> Result<Self, Self::Error> {
+		value.parse()
+	}
+}
+
+impl From<SummaryFormat> for String {
+	fn from(format: SummaryFormat) -> Self {
+		format.source
+	}
+}
+
+impl std::fmt::Display for SummaryFormat {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(&self.source)
+	}
+}
 
 fn join_with_commas_and(parts: &[String]) -> Option<Cow<'_, str>> {
 	match parts {
@@ -29,18 +160,96 @@ fn join_with_commas_and(parts: &[String]) -> Option<Cow<'_, str>> {
 	}
 }
 
+const fn symlink_marker(is_symlinked: bool) -> &'static str {
+	if is_symlinked { " (via symlink)" } else { "" }
+}
+
+/// Wrap `text` in an ANSI SGR sequence when color is enabled.
+fn paint(text: impl Display, sgr: &str, enabled: bool) -> String {
+	if enabled { format!("\x1b[{sgr}m{text}\x1b[0m") } else { text.to_string() }
+}
+
+fn header(ctx: &FormatterContext, text: impl Display) -> String {
+	paint(text, "1", ctx.options.color)
+}
+
+/// Format a percentage, colored green from 50%, yellow from 10%, and dimmed below that.
+fn percent(ctx: &FormatterContext, value: f64) -> String {
+	let sgr = if value >= 50.0 {
+		"32"
+	} else if value >= 10.0 {
+		"33"
+	} else {
+		"2"
+	};
+	paint(ctx.percent(value), sgr, ctx.options.color)
+}
+
+/// Render `pct` percent of `width` columns as block characters, drawing the remainder with an eighth block.
+#[expect(
+	clippy::cast_precision_loss,
+	clippy::cast_possible_truncation,
+	clippy::cast_sign_loss,
+	reason = "bar lengths are bounded by the terminal width, and the percentage is clamped to 0-100 first"
+)]
+fn bar(pct: f64, width: usize) -> String {
+	const EIGHTHS: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+	let eighths = (pct.clamp(0.0, 100.0) / 100.0 * width as f64 * 8.0).round() as usize;
+	let mut rendered = "█".repeat(eighths / 8);
+	if !eighths.is_multiple_of(8) {
+		rendered.push(EIGHTHS[eighths % 8]);
+	}
+	rendered
+}
+
+/// Bin file sizes into eight buckets on a logarithmic scale from the smallest to the largest size and draw each
+/// bucket as a block scaled to the fullest one, leaving empty buckets blank. Identical sizes draw a single block.
+#[expect(
+	clippy::cast_precision_loss,
+	clippy::cast_possible_truncation,
+	clippy::cast_sign_loss,
+	reason = "bucket indices and block levels are clamped to the eight buckets and levels first"
+)]
+fn sparkline(sizes: &[u64]) -> String {
+	const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+	let (Some(&min), Some(&max)) = (sizes.iter().min(), sizes.iter().max()) else {
+		return String::new();
+	};
+	if min == max {
+		return BLOCKS[7].to_string();
+	}
+	let scale = |size: u64| (size as f64).ln_1p();
+	let (low, span) = (scale(min), scale(max) - scale(min));
+	let mut buckets = [0_usize; 8];
+	for &size in sizes {
+		let bucket = ((scale(size) - low) / span * 8.0) as usize;
+		buckets[bucket.min(7)] += 1;
+	}
+	let fullest = buckets.iter().copied().max().unwrap_or(1) as f64;
+	buckets
+		.iter()
+		.map(
+			|&count| {
+				if count == 0 { ' ' } else { BLOCKS[((count as f64 / fullest * 8.0).ceil() as usize).clamp(1, 8) - 1] }
+			},
+		)
+		.collect()
+}
+
 impl OutputFormatter for HumanFormatter {
 	fn write_output(
 		&self,
 		results: &AnalysisResults,
-		path: &Path,
+		root: ReportRoot<'_>,
 		view_options: ViewOptions,
 		writer: &mut dyn Write,
 	) -> Result<()> {
-		let (ctx, report) = self.prepare_report(results, path, view_options);
-		Self::write_overview(&report, &ctx, writer)?;
+		let (ctx, report) = self.prepare_report(results, root, view_options);
+		if view_options.show_summary {
+			self.write_overview(&report, &ctx, writer)?;
+		}
 		if view_options.verbosity == Verbosity::Summary {
-			Self::write_language_summary(results, &report.summary, &ctx, view_options, writer)?;
+			Self::write_language_summary(results, &report.summary, &ctx, writer)?;
 			return Ok(());
 		}
 		if view_options.by_dir {
@@ -56,24 +265,36 @@ impl OutputFormatter for HumanFormatter {
 			return Ok(());
 		}
 		Self::write_language_breakdown(&report, &ctx, view_options.verbosity == Verbosity::Verbose, writer)?;
+		if view_options.show_indentation {
+			Self::write_indentation_breakdown(&report, &ctx, writer)?;
+		}
+		if view_options.show_comment_styles {
+			Self::write_comment_style_breakdown(&report, &ctx, writer)?;
+		}
 		Ok(())
 	}
 }
 
 impl HumanFormatter {
-	fn write_overview(report: &ReportData, ctx: &FormatterContext, writer: &mut dyn Write) -> Result<()> {
+	fn write_overview(&self, report: &ReportData, ctx: &FormatterContext, writer: &mut dyn Write) -> Result<()> {
 		let summary = &report.summary;
-		let total_size_human = &summary.total_size_human;
-		writeln!(
-			writer,
-			"Codestats for {}: {} {}, {} total {}, {} total size.",
-			report.analysis_path,
-			ctx.number(summary.total_files),
-			pluralize(summary.total_files, "file", "files"),
-			ctx.number(summary.total_lines),
-			pluralize(summary.total_lines, "line", "lines"),
-			total_size_human
-		)?;
+		if let Some(format) = &self.summary_format {
+			writeln!(writer, "{}", format.render(report, ctx))?;
+		} else {
+			writeln!(
+				writer,
+				"Codestats for {}: {} {}, {} total {}, {} total size.",
+				report.analysis_path,
+				ctx.number(summary.total_files),
+				pluralize(summary.total_files, "file", "files"),
+				ctx.number(summary.total_lines),
+				pluralize(summary.total_lines, "line", "lines"),
+				summary.total_size_human
+			)?;
+		}
+		if let Some(normalized) = &summary.normalized_size_human {
+			writeln!(writer, "Normalized size (LF line endings): {normalized}.")?;
+		}
 		let line_breakdown_parts = summary.line_breakdown_parts(true, ctx);
 		if let Some(breakdown) = join_with_commas_and(&line_breakdown_parts) {
 			writeln!(writer, "Line breakdown: {breakdown}.")?;
@@ -82,6 +303,76 @@ impl HumanFormatter {
 		if let Some(percentages) = join_with_commas_and(&percentage_parts) {
 			writeln!(writer, "Percentages: {percentages}.")?;
 		}
+		if let (Some(by_lines), Some(by_bytes)) = (&summary.largest_file_by_lines, &summary.largest_file_by_bytes) {
+			let lines = format!("{} {}", ctx.number(by_lines.lines), pluralize(by_lines.lines, "line", "lines"));
+			if by_lines.path == by_bytes.path {
+				writeln!(writer, "Largest file: {} ({lines}, {}).", by_lines.path, by_bytes.size_human)?;
+			} else {
+				writeln!(
+					writer,
+					"Largest file: {} ({lines}); by size: {} ({}).",
+					by_lines.path, by_bytes.path, by_bytes.size_human
+				)?;
+			}
+		}
+		if summary.generated_files > 0 {
+			writeln!(
+				writer,
+				"Excluded {} generated {}.",
+				ctx.number(summary.generated_files),
+				pluralize(summary.generated_files, "file", "files")
+			)?;
+		}
+		if summary.minified_files > 0 {
+			writeln!(
+				writer,
+				"Minified: {} {} with a line over 1 MiB, read only up to that length.",
+				ctx.number(summary.minified_files),
+				pluralize(summary.minified_files, "file", "files")
+			)?;
+		}
+		if ctx.options.show_encodings {
+			writeln!(
+				writer,
+				"Encodings: {} {} with a byte-order mark.",
+				ctx.number(summary.bom_files),
+				pluralize(summary.bom_files, "file", "files")
+			)?;
+		}
+		if let Some(assets) = &summary.assets {
+			writeln!(
+				writer,
+				"Binary assets: {} {}, {}.",
+				ctx.number(assets.files),
+				pluralize(assets.files, "file", "files"),
+				assets.size_human
+			)?;
+		}
+		let describe = |totals: &SplitTotals| {
+			format!(
+				"{} {}, {} {} ({} code)",
+				ctx.number(totals.files),
+				pluralize(totals.files, "file", "files"),
+				ctx.number(totals.lines),
+				pluralize(totals.lines, "line", "lines"),
+				ctx.number(totals.code_lines)
+			)
+		};
+		if let Some(tests) = &summary.tests {
+			writeln!(writer, "Tests: {}. Non-tests: {}.", describe(&tests.test), describe(&tests.non_test))?;
+		}
+		if let Some(categories) = &summary.categories {
+			let parts: Vec<_> = categories
+				.iter()
+				.map(|category| format!("{}% {}", ctx.percent(category.percentage), category.name))
+				.collect();
+			if let Some(breakdown) = join_with_commas_and(&parts) {
+				writeln!(writer, "Categories: {breakdown}.")?;
+			}
+		}
+		if let Some(vendored) = &summary.vendored {
+			writeln!(writer, "Vendored: {}, {}.", describe(vendored), ctx.size(vendored.size))?;
+		}
 		Ok(())
 	}
 
@@ -89,44 +380,22 @@ impl HumanFormatter {
 		results: &AnalysisResults,
 		summary: &Summary,
 		ctx: &FormatterContext,
-		view_options: ViewOptions,
 		writer: &mut dyn Write,
 	) -> Result<()> {
-		let mut langs: Vec<_> = results.languages().collect();
+		let (langs, _) = select_languages(results, ctx);
 		if langs.is_empty() {
 			return Ok(());
 		}
-		let sort_key = view_options.language_sort_key;
-		apply_sort(
-			&mut langs,
-			view_options.sort_direction,
-			|(lang, stats)| match sort_key {
-				LanguageSortKey::Lines => SortValue::Num(stats.lines()),
-				LanguageSortKey::Code => SortValue::Num(stats.code_lines()),
-				LanguageSortKey::Comments => SortValue::Num(stats.comment_lines()),
-				LanguageSortKey::Blanks => SortValue::Num(stats.blank_lines()),
-				LanguageSortKey::Files => SortValue::Num(stats.files()),
-				LanguageSortKey::Size => SortValue::Num(stats.size()),
-				LanguageSortKey::Name => SortValue::Text(lang.name),
-			},
-			|a, b| a.0.name.cmp(b.0.name),
-		);
-		if let Some(n) = view_options.top_languages {
-			langs.truncate(n);
-		}
-		if let Some(min) = view_options.min_lines {
-			langs.retain(|(_, stats)| stats.lines() >= min);
-		}
 		let parts: Vec<String> = langs
 			.iter()
-			.map(|(lang, stats)| {
+			.map(|(name, stats)| {
 				let pct = percentage(stats.files(), summary.total_files);
 				format!(
 					"{} {} {} ({}%)",
 					ctx.number(stats.files()),
-					lang.name,
+					name,
 					pluralize(stats.files(), "file", "files"),
-					ctx.percent(pct)
+					percent(ctx, pct)
 				)
 			})
 			.collect();
@@ -140,7 +409,7 @@ impl HumanFormatter {
 		verbose: bool,
 		writer: &mut dyn Write,
 	) -> Result<()> {
-		writeln!(writer, "Language breakdown:")?;
+		writeln!(writer, "{}", header(ctx, "Language breakdown:"))?;
 		for language in &report.languages {
 			Self::write_language_stats(language, &report.summary, ctx, verbose, writer)?;
 		}
@@ -163,10 +432,18 @@ impl HumanFormatter {
 		let line_pct = percentage(language.lines, summary.total_lines);
 		let size_pct = percentage(language.size, summary.total_size);
 		let size_human = &language.size_human;
-		let file_pct_str = ctx.percent(file_pct);
-		let line_pct_str = ctx.percent(line_pct);
-		let size_pct_str = ctx.percent(size_pct);
-		writeln!(writer, "{}:", language.name)?;
+		let file_pct_str = percent(ctx, file_pct);
+		let line_pct_str = percent(ctx, line_pct);
+		let size_pct_str = percent(ctx, size_pct);
+		let name = header(ctx, format_args!("{}:", language.name));
+		if let Some(width) = ctx.options.bar_width {
+			// Leave room for "<name>: " before the bar and " <pct>%" after it, measured without color codes.
+			let used = language.name.chars().count() + ctx.percent(line_pct).chars().count() + 4;
+			let bar = bar(line_pct, width.saturating_sub(used));
+			writeln!(writer, "{name} {bar} {line_pct_str}%")?;
+		} else {
+			writeln!(writer, "{name}")?;
+		}
 		writeln!(
 			writer,
 			"{i1}Files: {} {} ({}% of total).",
@@ -182,7 +459,31 @@ impl HumanFormatter {
 			line_pct_str
 		)?;
 		writeln!(writer, "{i1}Average lines per file: {:.1}.", language.avg_lines_per_file)?;
+		if verbose {
+			writeln!(writer, "{i1}Bytes per line: {:.1}.", language.bytes_per_line)?;
+		}
+		writeln!(
+			writer,
+			"{i1}Longest line: {} {}.",
+			language.format_max_line_length(ctx),
+			pluralize(language.max_line_length, "column", "columns")
+		)?;
 		writeln!(writer, "{i1}Size: {size_human} ({size_pct_str}% of total).")?;
+		if let Some(files) = language.files_detail.as_deref().filter(|_| ctx.options.sparkline) {
+			let sizes: Vec<u64> = files.iter().map(|file| file.size).collect();
+			if let (Some(&min), Some(&max)) = (sizes.iter().min(), sizes.iter().max()) {
+				writeln!(
+					writer,
+					"{i1}Size distribution: {} ({} to {}).",
+					sparkline(&sizes),
+					ctx.size(min),
+					ctx.size(max)
+				)?;
+			}
+		}
+		if let Some(normalized) = &language.normalized_size_human {
+			writeln!(writer, "{i1}Normalized size: {normalized}.")?;
+		}
 		writeln!(writer, "{i1}Line breakdown:")?;
 		for line_type in language.line_types() {
 			writeln!(
@@ -190,17 +491,56 @@ impl HumanFormatter {
 				"{i2}{}: {} lines ({}%).",
 				line_type.title_label(),
 				ctx.number(line_type.count),
-				ctx.percent(line_type.percentage)
+				percent(ctx, line_type.percentage)
 			)?;
 		}
 		if verbose {
-			Self::write_file_breakdown(language, summary, ctx, writer)?;
+			Self::write_file_breakdown(language, ctx, writer)?;
+		}
+		Ok(())
+	}
+
+	fn write_indentation_breakdown(report: &ReportData, ctx: &FormatterContext, writer: &mut dyn Write) -> Result<()> {
+		let i1 = ctx.indent(1);
+		writeln!(writer, "{}", header(ctx, "Indentation:"))?;
+		for language in &report.languages {
+			writeln!(
+				writer,
+				"{i1}{}: {} tab-indented {}, {} space-indented {} (dominant: {}).",
+				language.name,
+				language.format_tab_lines(ctx),
+				pluralize(language.tab_lines, "line", "lines"),
+				language.format_space_lines(ctx),
+				pluralize(language.space_lines, "line", "lines"),
+				language.indentation
+			)?;
+		}
+		Ok(())
+	}
+
+	fn write_comment_style_breakdown(
+		report: &ReportData,
+		ctx: &FormatterContext,
+		writer: &mut dyn Write,
+	) -> Result<()> {
+		let i1 = ctx.indent(1);
+		writeln!(writer, "{}", header(ctx, "Comment styles:"))?;
+		for language in &report.languages {
+			writeln!(
+				writer,
+				"{i1}{}: {} line-comment {}, {} block-comment {}.",
+				language.name,
+				language.format_line_comment_lines(ctx),
+				pluralize(language.line_comment_lines, "line", "lines"),
+				language.format_block_comment_lines(ctx),
+				pluralize(language.block_comment_lines, "line", "lines"),
+			)?;
 		}
 		Ok(())
 	}
 
 	fn write_dir_breakdown(report: &ReportData, ctx: &FormatterContext, writer: &mut dyn Write) -> Result<()> {
-		writeln!(writer, "Directory breakdown:")?;
+		writeln!(writer, "{}", header(ctx, "Directory breakdown:"))?;
 		for dir in &report.directories {
 			Self::write_dir_stats(dir, &report.summary, ctx, writer)?;
 		}
@@ -222,22 +562,22 @@ impl HumanFormatter {
 		let line_pct = percentage(dir.lines, summary.total_lines);
 		let size_pct = percentage(dir.size, summary.total_size);
 		let size_human = &dir.size_human;
-		writeln!(writer, "{}:", dir.path)?;
+		writeln!(writer, "{}", header(ctx, format_args!("{}:", dir.path)))?;
 		writeln!(
 			writer,
 			"{i1}Files: {} {} ({}% of total).",
 			ctx.number(dir.files),
 			pluralize(dir.files, "file", "files"),
-			ctx.percent(file_pct)
+			percent(ctx, file_pct)
 		)?;
 		writeln!(
 			writer,
 			"{i1}Lines: {} {} ({}% of total).",
 			ctx.number(dir.lines),
 			pluralize(dir.lines, "line", "lines"),
-			ctx.percent(line_pct)
+			percent(ctx, line_pct)
 		)?;
-		writeln!(writer, "{i1}Size: {size_human} ({}% of total).", ctx.percent(size_pct))?;
+		writeln!(writer, "{i1}Size: {size_human} ({}% of total).", percent(ctx, size_pct))?;
 		writeln!(writer, "{i1}Line breakdown:")?;
 		for line_type in dir.line_types() {
 			writeln!(
@@ -245,15 +585,42 @@ impl HumanFormatter {
 				"{i2}{}: {} lines ({}%).",
 				line_type.title_label(),
 				ctx.number(line_type.count),
-				ctx.percent(line_type.percentage)
+				percent(ctx, line_type.percentage)
 			)?;
 		}
+		if let Some(languages) = &dir.languages {
+			Self::write_dir_language_breakdown(languages, dir, ctx, writer)?;
+		}
 		if let Some(files) = &dir.files_detail {
 			Self::write_dir_file_breakdown(files, summary, ctx, writer)?;
 		}
 		Ok(())
 	}
 
+	fn write_dir_language_breakdown(
+		languages: &[DirLanguageRecord],
+		dir: &DirRecord,
+		ctx: &FormatterContext,
+		writer: &mut dyn Write,
+	) -> Result<()> {
+		let i1 = ctx.indent(1);
+		let i2 = ctx.indent(2);
+		writeln!(writer, "{i1}Language breakdown:")?;
+		for language in languages {
+			writeln!(
+				writer,
+				"{i2}{}: {} {}, {} lines ({}% of directory lines), {} code.",
+				language.name,
+				ctx.number(language.files),
+				pluralize(language.files, "file", "files"),
+				ctx.number(language.lines),
+				percent(ctx, percentage(language.lines, dir.lines)),
+				ctx.number(language.code_lines)
+			)?;
+		}
+		Ok(())
+	}
+
 	fn write_dir_file_breakdown(
 		files: &[DirFileRecord],
 		summary: &Summary,
@@ -267,22 +634,18 @@ impl HumanFormatter {
 			let file_pct = percentage(file.total_lines, summary.total_lines);
 			writeln!(
 				writer,
-				"{i2}{}: {} lines, {} ({}% of total lines).",
-				file.path,
+				"{i2}{}{}: {} lines, {} ({}% of total lines).",
+				ctx.file_path(&file.path),
+				symlink_marker(file.is_symlinked),
 				ctx.number(file.total_lines),
 				file.size_human,
-				ctx.percent(file_pct)
+				percent(ctx, file_pct)
 			)?;
 		}
 		Ok(())
 	}
 
-	fn write_file_breakdown(
-		language: &LanguageRecord,
-		summary: &Summary,
-		ctx: &FormatterContext,
-		writer: &mut dyn Write,
-	) -> Result<()> {
+	fn write_file_breakdown(language: &LanguageRecord, ctx: &FormatterContext, writer: &mut dyn Write) -> Result<()> {
 		let i1 = ctx.indent(1);
 		let i2 = ctx.indent(2);
 		writeln!(writer, "{i1}File breakdown:")?;
@@ -290,13 +653,13 @@ impl HumanFormatter {
 			return Ok(());
 		};
 		for file_stat in files {
-			let file_pct = percentage(file_stat.total_lines, summary.total_lines);
-			let file_pct_str = ctx.percent(file_pct);
+			let file_pct_str = percent(ctx, file_stat.pct_of_total_lines);
 			let size_human = &file_stat.size_human;
 			writeln!(
 				writer,
-				"{i2}{}: {} lines, {} ({}% of total lines).",
-				file_stat.path,
+				"{i2}{}{}: {} lines, {} ({}% of total lines).",
+				ctx.file_path(file_stat.path),
+				symlink_marker(file_stat.is_symlinked),
 				ctx.number(file_stat.total_lines),
 				size_human,
 				file_pct_str
@@ -310,10 +673,18 @@ impl HumanFormatter {
 mod tests {
 	use std::path::Path;
 
-	use super::{HumanFormatter, join_with_commas_and};
+	use rstest::rstest;
+
+	use super::{HumanFormatter, SummaryFormat, bar, join_with_commas_and, sparkline};
 	use crate::{
-		analysis::{AnalysisResults, stats::FileContribution},
-		display::{OutputFormatter, ViewOptions, options::IndentStyle},
+		analysis::{
+			AnalysisResults,
+			stats::{FileContribution, FileStats},
+		},
+		display::{
+			OutputFormatter, Verbosity, ViewOptions,
+			options::{IndentStyle, NumberStyle},
+		},
 	};
 
 	#[test]
@@ -328,6 +699,30 @@ mod tests {
 		assert_eq!(join_with_commas_and(&triple).as_deref(), Some("alpha, beta, and gamma"));
 	}
 
+	#[test]
+	fn summary_format_replaces_the_overview_line() {
+		let mut results = AnalysisResults::default();
+		let lang = crate::langs::LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		results.add_file_stats(lang, FileContribution::new(1200, 1000, 150, 50, 0, 2048), None);
+		let formatter =
+			HumanFormatter::with_summary_format("{{{path}}} {files}/{lines}/{code} {size}".parse().unwrap());
+		let options = ViewOptions { number_style: NumberStyle::Comma, ..Default::default() };
+		let mut buf = Vec::new();
+		formatter.write_output(&results, Path::new("repo").into(), options, &mut buf).unwrap();
+		let output = String::from_utf8(buf).unwrap();
+		assert!(output.starts_with("{repo} 1/1,200/1,000 2.00 KiB\n"), "got:\n{output}");
+		assert!(!output.contains("Codestats for"));
+	}
+
+	#[rstest]
+	#[case::unknown("{files} {comments}", "unknown placeholder '{comments}'")]
+	#[case::unclosed("{files", "unclosed placeholder")]
+	#[case::stray_close("files}", "unmatched '}'")]
+	fn summary_format_rejects_invalid_templates(#[case] template: &str, #[case] expected: &str) {
+		let err = template.parse::<SummaryFormat>().unwrap_err();
+		assert!(err.contains(expected), "{err}");
+	}
+
 	#[test]
 	fn human_output_uses_configured_indent() {
 		let mut results = AnalysisResults::default();
@@ -335,11 +730,199 @@ mod tests {
 		let contribution = FileContribution::new(12, 10, 0, 2, 0, 100);
 		results.add_file_stats(lang, contribution, None);
 		let options = ViewOptions { indent_style: IndentStyle::Spaces(2), ..Default::default() };
-		let formatter = HumanFormatter;
+		let formatter = HumanFormatter::default();
 		let mut buf = Vec::new();
-		formatter.write_output(&results, Path::new("."), options, &mut buf).unwrap();
+		formatter.write_output(&results, Path::new(".").into(), options, &mut buf).unwrap();
 		let output = String::from_utf8(buf).unwrap();
 		assert!(output.contains("  Files:"), "expected 2-space indent for Files, got:\n{output}");
 		assert!(!output.contains("\tFiles:"), "should not contain tab-indented Files");
 	}
+
+	#[test]
+	fn human_output_shows_indentation_section() {
+		let mut results = AnalysisResults::default();
+		let lang = crate::langs::LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		let contribution = FileContribution::new(12, 10, 0, 2, 0, 100).with_indentation(7, 1);
+		results.add_file_stats(lang, contribution, None);
+		let formatter = HumanFormatter::default();
+		let mut buf = Vec::new();
+		formatter.write_output(&results, Path::new(".").into(), ViewOptions::default(), &mut buf).unwrap();
+		let output = String::from_utf8(buf).unwrap();
+		assert!(!output.contains("Indentation:"), "indentation section should be opt-in, got:\n{output}");
+		let options = ViewOptions { show_indentation: true, ..Default::default() };
+		let mut buf = Vec::new();
+		formatter.write_output(&results, Path::new(".").into(), options, &mut buf).unwrap();
+		let output = String::from_utf8(buf).unwrap();
+		assert!(
+			output.contains("\tRust: 7 tab-indented lines, 1 space-indented line (dominant: tabs)."),
+			"expected indentation section, got:\n{output}"
+		);
+	}
+
+	#[test]
+	fn human_output_shows_comment_style_section() {
+		let mut results = AnalysisResults::default();
+		let lang = crate::langs::LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		let contribution = FileContribution::new(12, 6, 6, 0, 0, 100).with_comment_styles(5, 1);
+		results.add_file_stats(lang, contribution, None);
+		let formatter = HumanFormatter::default();
+		let mut buf = Vec::new();
+		formatter.write_output(&results, Path::new(".").into(), ViewOptions::default(), &mut buf).unwrap();
+		let output = String::from_utf8(buf).unwrap();
+		assert!(!output.contains("Comment styles:"), "comment style section should be opt-in, got:\n{output}");
+		let options = ViewOptions { show_comment_styles: true, ..Default::default() };
+		let mut buf = Vec::new();
+		formatter.write_output(&results, Path::new(".").into(), options, &mut buf).unwrap();
+		let output = String::from_utf8(buf).unwrap();
+		assert!(
+			output.contains("\tRust: 5 line-comment lines, 1 block-comment line."),
+			"expected comment style section, got:\n{output}"
+		);
+	}
+
+	#[rstest]
+	#[case::empty(0.0, 10, "")]
+	#[case::half(50.0, 10, "█████")]
+	#[case::full(100.0, 4, "████")]
+	#[case::partial(12.5, 3, "▍")]
+	#[case::clamped(150.0, 2, "██")]
+	#[case::no_room(40.0, 0, "")]
+	fn bar_scales_to_width(#[case] pct: f64, #[case] width: usize, #[case] expected: &str) {
+		assert_eq!(bar(pct, width), expected);
+	}
+
+	#[rstest]
+	#[case::empty(&[], "")]
+	#[case::uniform(&[100, 100, 100], "█")]
+	#[case::one_giant_file(&[10, 10, 10, 10, 100_000], "█      ▂")]
+	#[case::spread(&[0, 0, 9, 99, 999, 999, 999, 999], "▄ ▂  ▂ █")]
+	fn sparkline_bins_sizes_logarithmically(#[case] sizes: &[u64], #[case] expected: &str) {
+		assert_eq!(sparkline(sizes), expected);
+	}
+
+	#[test]
+	fn human_output_draws_size_sparkline_only_when_verbose() {
+		let mut results = AnalysisResults::default();
+		let rust = crate::langs::LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		for (path, size) in [("a.rs", 100), ("b.rs", 100), ("c.rs", 102_400)] {
+			results.add_file_stats(
+				rust,
+				FileContribution::new(1, 1, 0, 0, 0, size),
+				Some(FileStats::new(path.into(), 1, 1, 0, 0, 0, size)),
+			);
+		}
+		let render = |options| {
+			let mut buf = Vec::new();
+			HumanFormatter::default().write_output(&results, Path::new(".").into(), options, &mut buf).unwrap();
+			String::from_utf8(buf).unwrap()
+		};
+		assert!(!render(ViewOptions { sparkline: true, ..Default::default() }).contains("Size distribution"));
+		let verbose = ViewOptions { verbosity: Verbosity::Verbose, ..Default::default() };
+		assert!(!render(verbose).contains("Size distribution"));
+		let output = render(ViewOptions { sparkline: true, ..verbose });
+		assert!(output.contains("\tSize distribution: █      ▄ (100 B to 100 KiB).\n"), "got:\n{output}");
+	}
+
+	#[test]
+	fn human_output_draws_bars_only_with_a_width() {
+		let mut results = AnalysisResults::default();
+		let rust = crate::langs::LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		let python = crate::langs::LANGUAGES.iter().find(|l| l.name == "Python").unwrap();
+		results.add_file_stats(rust, FileContribution::new(30, 30, 0, 0, 0, 100), None);
+		results.add_file_stats(python, FileContribution::new(10, 10, 0, 0, 0, 100), None);
+		let mut buf = Vec::new();
+		HumanFormatter::default()
+			.write_output(&results, Path::new(".").into(), ViewOptions::default(), &mut buf)
+			.unwrap();
+		let output = String::from_utf8(buf).unwrap();
+		assert!(output.contains("\nRust:\n"), "got:\n{output}");
+		let options = ViewOptions { bar_width: Some(30), ..Default::default() };
+		let mut buf = Vec::new();
+		HumanFormatter::default().write_output(&results, Path::new(".").into(), options, &mut buf).unwrap();
+		let output = String::from_utf8(buf).unwrap();
+		assert!(output.contains("\nRust: █████████████▌ 75.0%\n"), "got:\n{output}");
+		assert!(output.contains("\nPython: ████ 25.0%\n"), "got:\n{output}");
+	}
+
+	#[test]
+	fn human_output_colors_headers_and_percentages_only_when_enabled() {
+		let mut results = AnalysisResults::default();
+		let rust = crate::langs::LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		results.add_file_stats(rust, FileContribution::new(20, 18, 0, 2, 0, 100), None);
+		let mut buf = Vec::new();
+		HumanFormatter::default()
+			.write_output(&results, Path::new(".").into(), ViewOptions::default(), &mut buf)
+			.unwrap();
+		assert!(!String::from_utf8(buf).unwrap().contains('\x1b'));
+		let options = ViewOptions { color: true, ..Default::default() };
+		let mut buf = Vec::new();
+		HumanFormatter::default().write_output(&results, Path::new(".").into(), options, &mut buf).unwrap();
+		let output = String::from_utf8(buf).unwrap();
+		assert!(output.contains("\x1b[1mLanguage breakdown:\x1b[0m"), "got:\n{output}");
+		assert!(output.contains("\x1b[1mRust:\x1b[0m"), "got:\n{output}");
+		assert!(output.contains("(\x1b[32m100.0\x1b[0m% of total)"), "got:\n{output}");
+		assert!(output.contains("Blanks: 2 lines (\x1b[33m10.0\x1b[0m%)."), "got:\n{output}");
+	}
+
+	#[test]
+	fn human_output_names_the_largest_file() {
+		let rust = crate::langs::LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		let mut results = AnalysisResults::default();
+		results.add_file_stats(rust, FileContribution::new(30, 30, 0, 0, 0, 900), None);
+		results.track_largest_file("src/lib.rs", 30, 900);
+		let render = |results: &AnalysisResults| {
+			let mut buf = Vec::new();
+			HumanFormatter::default()
+				.write_output(results, Path::new(".").into(), ViewOptions::default(), &mut buf)
+				.unwrap();
+			String::from_utf8(buf).unwrap()
+		};
+		let output = render(&results);
+		assert!(output.contains("\nLargest file: src/lib.rs (30 lines, 900 B).\n"), "got:\n{output}");
+		results.track_largest_file("assets/data.rs", 2, 4000);
+		let output = render(&results);
+		assert!(
+			output.contains("\nLargest file: src/lib.rs (30 lines); by size: assets/data.rs (3.91 KiB).\n"),
+			"got:\n{output}"
+		);
+	}
+
+	#[test]
+	fn human_output_shows_assets_only_when_included() {
+		let mut results = AnalysisResults::default();
+		results.add_asset(2048);
+		results.add_asset(1024);
+		let render = |options| {
+			let mut buf = Vec::new();
+			HumanFormatter::default().write_output(&results, Path::new(".").into(), options, &mut buf).unwrap();
+			String::from_utf8(buf).unwrap()
+		};
+		assert!(!render(ViewOptions::default()).contains("Binary assets"));
+		let output = render(ViewOptions { include_assets: true, ..Default::default() });
+		assert!(output.contains("Binary assets: 2 files, 3.00 KiB."), "got:\n{output}");
+	}
+
+	#[test]
+	fn human_output_shows_bom_files_when_requested() {
+		let mut results = AnalysisResults::default();
+		results.add_bom_file();
+		let options = ViewOptions { show_encodings: true, ..Default::default() };
+		let mut buf = Vec::new();
+		HumanFormatter::default().write_output(&results, Path::new(".").into(), options, &mut buf).unwrap();
+		let output = String::from_utf8(buf).unwrap();
+		assert!(output.contains("Encodings: 1 file with a byte-order mark."), "got:\n{output}");
+	}
+
+	#[test]
+	fn human_output_leaves_out_the_overview_without_summary() {
+		let mut results = AnalysisResults::default();
+		let lang = crate::langs::LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		results.add_file_stats(lang, FileContribution::new(12, 10, 0, 2, 0, 100), None);
+		let options = ViewOptions { show_summary: false, ..Default::default() };
+		let mut buf = Vec::new();
+		HumanFormatter::default().write_output(&results, Path::new(".").into(), options, &mut buf).unwrap();
+		let output = String::from_utf8(buf).unwrap();
+		assert!(output.starts_with("Language breakdown:"), "got:\n{output}");
+		assert!(!output.contains("Codestats for"), "got:\n{output}");
+	}
 }
@@ -1,21 +1,26 @@
-use std::{io::Write, path::Path};
+use std::io::Write;
 
 use anyhow::Result;
 use serde::Serialize;
 use serde_json::ser::{PrettyFormatter, Serializer};
 
-use super::{OutputFormatter, ViewOptions};
-use crate::{analysis::AnalysisResults, display::options::IndentStyle};
+use super::{OutputFormatter, ReportRoot, ViewOptions};
+use crate::{
+	analysis::AnalysisResults,
+	display::{formatting::FormatterContext, options::IndentStyle, report::StreamedReport},
+};
 
+/// Serialize the report straight to `writer`, building per-file records only as they are written.
 fn write_json(
-	formatter: &impl OutputFormatter,
 	results: &AnalysisResults,
-	path: &Path,
+	root: ReportRoot<'_>,
 	view_options: ViewOptions,
 	writer: &mut dyn Write,
 	pretty: bool,
+	config: Option<&serde_json::Value>,
 ) -> Result<()> {
-	let (_ctx, report) = formatter.prepare_report(results, path, view_options);
+	let ctx = FormatterContext::new(view_options);
+	let report = StreamedReport::streamed(results, root, &ctx).with_config(config);
 	if pretty {
 		let indent_bytes: Vec<u8> = match view_options.indent_style {
 			IndentStyle::Tab => b"\t".to_vec(),
@@ -31,31 +36,53 @@ fn write_json(
 	Ok(())
 }
 
-pub struct JsonFormatter;
+#[derive(Default)]
+pub struct JsonFormatter {
+	config: Option<serde_json::Value>,
+}
+
+impl JsonFormatter {
+	/// Embed `config`, the settings the report was produced with, as a top-level `config` object.
+	#[must_use]
+	pub const fn with_config(config: serde_json::Value) -> Self {
+		Self { config: Some(config) }
+	}
+}
 
 impl OutputFormatter for JsonFormatter {
 	fn write_output(
 		&self,
 		results: &AnalysisResults,
-		path: &Path,
+		root: ReportRoot<'_>,
 		view_options: ViewOptions,
 		writer: &mut dyn Write,
 	) -> Result<()> {
-		write_json(self, results, path, view_options, writer, true)
+		write_json(results, root, view_options, writer, true, self.config.as_ref())
 	}
 }
 
-pub struct JsonCompactFormatter;
+#[derive(Default)]
+pub struct JsonCompactFormatter {
+	config: Option<serde_json::Value>,
+}
+
+impl JsonCompactFormatter {
+	/// Embed `config`, the settings the report was produced with, as a top-level `config` object.
+	#[must_use]
+	pub const fn with_config(config: serde_json::Value) -> Self {
+		Self { config: Some(config) }
+	}
+}
 
 impl OutputFormatter for JsonCompactFormatter {
 	fn write_output(
 		&self,
 		results: &AnalysisResults,
-		path: &Path,
+		root: ReportRoot<'_>,
 		view_options: ViewOptions,
 		writer: &mut dyn Write,
 	) -> Result<()> {
-		write_json(self, results, path, view_options, writer, false)
+		write_json(results, root, view_options, writer, false, self.config.as_ref())
 	}
 }
 
@@ -65,17 +92,20 @@ mod tests {
 
 	use super::*;
 	use crate::{
-		analysis::AnalysisResults,
-		display::{ViewOptions, options::IndentStyle},
+		analysis::{AnalysisResults, FileStats, stats::FileContribution},
+		display::{
+			Verbosity, ViewOptions,
+			options::{IndentStyle, LanguageSortKey},
+		},
 	};
 
 	#[test]
 	fn json_pretty_uses_configured_indent() {
 		let results = AnalysisResults::default();
 		let options = ViewOptions { indent_style: IndentStyle::Spaces(4), ..Default::default() };
-		let formatter = JsonFormatter;
+		let formatter = JsonFormatter::default();
 		let mut buf = Vec::new();
-		formatter.write_output(&results, Path::new("."), options, &mut buf).unwrap();
+		formatter.write_output(&results, Path::new(".").into(), options, &mut buf).unwrap();
 		let output = String::from_utf8(buf).unwrap();
 		assert!(output.contains("    \""), "expected 4-space indent in JSON, got:\n{output}");
 	}
@@ -84,20 +114,82 @@ mod tests {
 	fn json_pretty_uses_tab_indent() {
 		let results = AnalysisResults::default();
 		let options = ViewOptions::default(); // default is Tab
-		let formatter = JsonFormatter;
+		let formatter = JsonFormatter::default();
 		let mut buf = Vec::new();
-		formatter.write_output(&results, Path::new("."), options, &mut buf).unwrap();
+		formatter.write_output(&results, Path::new(".").into(), options, &mut buf).unwrap();
 		let output = String::from_utf8(buf).unwrap();
 		assert!(output.contains("\t\""), "expected tab indent in JSON, got:\n{output}");
 	}
 
+	#[test]
+	fn json_files_detail_order_is_independent_of_insertion_order() {
+		let rust = crate::langs::LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		// Equal sizes and line counts everywhere, plus a duplicated path that differs only in its breakdown.
+		let files = [
+			FileStats::new("b.rs".to_string(), 10, 8, 1, 1, 0, 100),
+			FileStats::new("a.rs".to_string(), 10, 8, 1, 1, 0, 100),
+			FileStats::new("a.rs".to_string(), 10, 7, 2, 1, 0, 100),
+			FileStats::new("c.rs".to_string(), 10, 8, 1, 1, 0, 100),
+		];
+		let render = |order: &[usize], sort_key: LanguageSortKey| {
+			let mut results = AnalysisResults::default();
+			for &idx in order {
+				let file = files[idx].clone();
+				let contribution = FileContribution::new(10, file.code_lines(), file.comment_lines(), 1, 0, 100);
+				results.add_file_stats(rust, contribution, Some(file));
+			}
+			let options = ViewOptions {
+				verbosity: Verbosity::Verbose,
+				language_sort_key: sort_key,
+				timestamp: false,
+				..Default::default()
+			};
+			let mut buf = Vec::new();
+			JsonCompactFormatter::default().write_output(&results, Path::new(".").into(), options, &mut buf).unwrap();
+			String::from_utf8(buf).unwrap()
+		};
+		for sort_key in [LanguageSortKey::Lines, LanguageSortKey::Size, LanguageSortKey::Files, LanguageSortKey::Name] {
+			let expected = render(&[0, 1, 2, 3], sort_key);
+			assert_eq!(render(&[3, 2, 1, 0], sort_key), expected, "unstable order for {sort_key:?}");
+			assert_eq!(render(&[2, 0, 3, 1], sort_key), expected, "unstable order for {sort_key:?}");
+		}
+	}
+
+	#[test]
+	fn streamed_json_matches_serialized_report_data() {
+		let rust = crate::langs::LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		let python = crate::langs::LANGUAGES.iter().find(|l| l.name == "Python").unwrap();
+		let mut results = AnalysisResults::default();
+		for (lang, path, lines) in [(rust, "src/a.rs", 10), (rust, "src/b/c.rs", 4), (python, "tools/x.py", 7)] {
+			let file = FileStats::new(path.to_string(), lines, lines - 1, 0, 1, 0, lines * 10);
+			results.add_file_stats(lang, FileContribution::new(lines, lines - 1, 0, 1, 0, lines * 10), Some(file));
+			results.track_largest_file(path, lines, lines * 10);
+		}
+		for verbosity in [Verbosity::Summary, Verbosity::Regular, Verbosity::Verbose] {
+			let options =
+				ViewOptions { verbosity, by_dir: true, top_languages: Some(1), timestamp: false, ..Default::default() };
+			let ctx = FormatterContext::new(options);
+			let report = crate::display::ReportData::from_results(&results, Path::new(".").into(), verbosity, &ctx);
+			let mut expected = Vec::new();
+			let mut ser = Serializer::with_formatter(&mut expected, PrettyFormatter::with_indent(b"\t"));
+			report.serialize(&mut ser).unwrap();
+			expected.push(b'\n');
+			let mut buf = Vec::new();
+			JsonFormatter::default().write_output(&results, Path::new(".").into(), options, &mut buf).unwrap();
+			assert_eq!(String::from_utf8(buf).unwrap(), String::from_utf8(expected).unwrap(), "{verbosity:?}");
+			let mut buf = Vec::new();
+			JsonCompactFormatter::default().write_output(&results, Path::new(".").into(), options, &mut buf).unwrap();
+			assert_eq!(String::from_utf8(buf).unwrap(), serde_json::to_string(&report).unwrap() + "\n");
+		}
+	}
+
 	#[test]
 	fn json_compact_ignores_indent() {
 		let results = AnalysisResults::default();
 		let options = ViewOptions { indent_style: IndentStyle::Spaces(4), ..Default::default() };
-		let formatter = JsonCompactFormatter;
+		let formatter = JsonCompactFormatter::default();
 		let mut buf = Vec::new();
-		formatter.write_output(&results, Path::new("."), options, &mut buf).unwrap();
+		formatter.write_output(&results, Path::new(".").into(), options, &mut buf).unwrap();
 		let output = String::from_utf8(buf).unwrap();
 		assert!(
 			!output.contains('\n') || output.ends_with('\n') && output.matches('\n').count() == 1,
@@ -2,12 +2,12 @@
 // The askama `#[filter_fn]` macro generates code that triggers these lints.
 #![allow(clippy::inline_always, clippy::unused_self)]
 
-use std::{io::Write, path::Path};
+use std::io::Write;
 
 use anyhow::Result;
 use askama::{Result as AskamaResult, Template, Values};
 
-use super::{FormatterContext, OutputFormatter, ReportData, Verbosity, ViewOptions};
+use super::{FormatterContext, OutputFormatter, ReportData, ReportRoot, Verbosity, ViewOptions};
 use crate::{
 	analysis::AnalysisResults,
 	display::report::{LanguageRecord, Summary},
@@ -33,6 +33,7 @@ struct MarkdownTemplate<'a> {
 	line_breakdown: Vec<String>,
 	totals: Vec<String>,
 	languages: &'a [LanguageRecord<'a>],
+	show_summary: bool,
 	show_files: bool,
 }
 
@@ -42,11 +43,11 @@ impl OutputFormatter for MarkdownFormatter {
 	fn write_output(
 		&self,
 		results: &AnalysisResults,
-		path: &Path,
+		root: ReportRoot<'_>,
 		view_options: ViewOptions,
 		writer: &mut dyn Write,
 	) -> Result<()> {
-		let (ctx, report) = self.prepare_report(results, path, view_options);
+		let (ctx, report) = self.prepare_report(results, root, view_options);
 		Self::write_markdown(&report, view_options.verbosity, &ctx, writer)
 	}
 }
@@ -68,6 +69,7 @@ impl MarkdownFormatter {
 			line_breakdown,
 			totals,
 			languages: &report.languages,
+			show_summary: ctx.options.show_summary,
 			show_files: verbosity == Verbosity::Verbose,
 		};
 		let rendered = template.render()?;
@@ -7,21 +7,17 @@ pub enum NumberStyle {
 	Comma,
 	Underscore,
 	Space,
+	/// Commas in the Indian lakh/crore grouping, e.g. `12,34,567`.
+	Indian,
 }
 
-impl std::str::FromStr for NumberStyle {
-	type Err = String;
-
-	fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-		match s {
-			"plain" => Ok(Self::Plain),
-			"comma" => Ok(Self::Comma),
-			"underscore" => Ok(Self::Underscore),
-			"space" => Ok(Self::Space),
-			_ => Err(format!("invalid number style '{s}'")),
-		}
-	}
-}
+impl_names!(NumberStyle, "number style", {
+	Plain => "plain",
+	Comma => "comma",
+	Underscore => "underscore",
+	Space => "space",
+	Indian => "indian",
+});
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
@@ -30,21 +26,33 @@ pub enum SizeStyle {
 	Decimal,
 }
 
-impl std::str::FromStr for SizeStyle {
-	type Err = String;
+impl_names!(SizeStyle, "size style", {
+	Binary => "binary",
+	Decimal => "decimal",
+});
 
-	fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-		match s {
-			"binary" => Ok(Self::Binary),
-			"decimal" => Ok(Self::Decimal),
-			_ => Err(format!("invalid size style '{s}'")),
-		}
-	}
+/// How percentages are rounded to the configured precision.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RoundingMode {
+	/// Rust's float formatting, which rounds ties to even on the binary value.
+	#[default]
+	Nearest,
+	/// Round ties away from zero on the decimal value, so `1.005` becomes `1.01`.
+	Up,
+	/// Truncate extra digits, so `12.349` becomes `12.34`.
+	Down,
 }
 
+impl_names!(RoundingMode, "rounding mode", {
+	Nearest => "nearest",
+	Up => "up",
+	Down => "down",
+});
+
 /// Field used when ordering languages (and optionally files).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
-#[serde(rename_all = "lowercase")]
+#[serde(rename_all = "kebab-case")]
 pub enum LanguageSortKey {
 	Lines,
 	Code,
@@ -53,24 +61,26 @@ pub enum LanguageSortKey {
 	Files,
 	Size,
 	Name,
+	/// Share of lines that are comments.
+	CommentPct,
+	/// Share of lines that are code.
+	CodePct,
+	/// Code lines per comment line; code without any comments sorts as infinitely many.
+	Ratio,
 }
 
-impl std::str::FromStr for LanguageSortKey {
-	type Err = String;
-
-	fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-		match s {
-			"lines" => Ok(Self::Lines),
-			"code" => Ok(Self::Code),
-			"comments" => Ok(Self::Comments),
-			"blanks" => Ok(Self::Blanks),
-			"files" => Ok(Self::Files),
-			"size" => Ok(Self::Size),
-			"name" => Ok(Self::Name),
-			_ => Err(format!("invalid sort key '{s}'")),
-		}
-	}
-}
+impl_names!(LanguageSortKey, "sort key", {
+	Lines => "lines",
+	Code => "code",
+	Comments => "comments",
+	Blanks => "blanks",
+	Files => "files",
+	Size => "size",
+	Name => "name",
+	CommentPct => "comment-pct",
+	CodePct => "code-pct",
+	Ratio => "ratio",
+});
 
 /// Direction for applying a sort key.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
@@ -80,18 +90,40 @@ pub enum SortDirection {
 	Desc,
 }
 
-impl std::str::FromStr for SortDirection {
-	type Err = String;
+impl_names!(SortDirection, "sort direction", {
+	Asc => "asc",
+	Desc => "desc",
+});
 
-	fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-		match s {
-			"asc" => Ok(Self::Asc),
-			"desc" => Ok(Self::Desc),
-			_ => Err(format!("invalid sort direction '{s}'")),
+/// When human output is colored.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorChoice {
+	/// Color only when writing to a terminal and `NO_COLOR` is unset.
+	#[default]
+	Auto,
+	Always,
+	Never,
+}
+
+impl ColorChoice {
+	/// Decide whether to color output, given whether it goes to a terminal and whether `NO_COLOR` is set.
+	#[must_use]
+	pub const fn enabled(self, is_terminal: bool, no_color: bool) -> bool {
+		match self {
+			Self::Auto => is_terminal && !no_color,
+			Self::Always => true,
+			Self::Never => false,
 		}
 	}
 }
 
+impl_names!(ColorChoice, "color choice", {
+	Auto => "auto",
+	Always => "always",
+	Never => "never",
+});
+
 /// Indentation style for output formatting.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum IndentStyle {
@@ -148,18 +180,66 @@ pub enum Verbosity {
 	Verbose,
 }
 
+impl_names!(Verbosity, "verbosity", {
+	Summary => "summary",
+	Regular => "regular",
+	Verbose => "verbose",
+});
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[expect(clippy::struct_excessive_bools, reason = "each bool toggles an independent optional report section")]
 pub struct ViewOptions {
 	pub verbosity: Verbosity,
 	pub number_style: NumberStyle,
 	pub size_style: SizeStyle,
+	/// Write sizes in bytes instead of scaling them to KiB, MiB, ...
+	pub size_raw: bool,
+	/// Decimals in scaled sizes; `None` picks 2, 1 or 0 by magnitude.
+	pub size_precision: Option<u8>,
 	pub percent_precision: u8,
+	pub rounding: RoundingMode,
+	/// Adjust each set of line-type percentages so the displayed values sum to exactly 100.
+	pub balanced_percentages: bool,
 	pub language_sort_key: LanguageSortKey,
 	pub sort_direction: SortDirection,
+	/// Sort key for per-file details; `None` sorts files like their languages.
+	pub file_sort_key: Option<LanguageSortKey>,
+	/// Sort direction for per-file details; `None` uses `sort_direction`.
+	pub file_sort_direction: Option<SortDirection>,
 	pub indent_style: IndentStyle,
 	pub top_languages: Option<usize>,
 	pub min_lines: Option<u64>,
 	pub by_dir: bool,
+	/// Number of leading path components used to bucket files in the directory breakdown.
+	pub dir_depth: usize,
+	/// Trailing path components kept when listing files in human output; `None` shows full paths.
+	pub path_depth: Option<usize>,
+	/// Print the summary overview; when off, human, Markdown, CSV and TSV output start at the language breakdown.
+	pub show_summary: bool,
+	/// List languages and alias groups with no files, such as languages asked for with `--lang` that were not found.
+	pub show_empty: bool,
+	pub show_indentation: bool,
+	/// Report line-comment vs block-comment lines for each language.
+	pub show_comment_styles: bool,
+	pub show_encodings: bool,
+	/// Report test and non-test totals separately in the summary.
+	pub split_tests: bool,
+	/// Report per-category totals in the summary and each file's category in per-file details.
+	pub split_categories: bool,
+	/// Report the vendored bucket in the summary.
+	pub collapse_vendored: bool,
+	/// Report the binary assets bucket in the summary.
+	pub include_assets: bool,
+	/// Report sizes with `\r\n` line endings counted as `\n` alongside the on-disk sizes.
+	pub normalized_size: bool,
+	/// Terminal columns available for per-language bars in human output; `None` leaves the bars out.
+	pub bar_width: Option<usize>,
+	/// Draw a sparkline of each language's file-size distribution in verbose human output.
+	pub sparkline: bool,
+	/// Style human output with ANSI colors; other formats ignore it.
+	pub color: bool,
+	/// Record when the report was generated in JSON, HTML and template output.
+	pub timestamp: bool,
 }
 
 impl Default for ViewOptions {
@@ -168,22 +248,87 @@ impl Default for ViewOptions {
 			verbosity: Verbosity::default(),
 			number_style: NumberStyle::Plain,
 			size_style: SizeStyle::Binary,
+			size_raw: false,
+			size_precision: None,
 			percent_precision: 1,
+			rounding: RoundingMode::Nearest,
+			balanced_percentages: false,
 			language_sort_key: LanguageSortKey::Lines,
 			sort_direction: SortDirection::Desc,
+			file_sort_key: None,
+			file_sort_direction: None,
 			indent_style: IndentStyle::Tab,
 			top_languages: None,
 			min_lines: None,
 			by_dir: false,
+			dir_depth: 1,
+			path_depth: None,
+			show_summary: true,
+			show_empty: false,
+			show_indentation: false,
+			show_comment_styles: false,
+			show_encodings: false,
+			split_tests: false,
+			split_categories: false,
+			collapse_vendored: false,
+			include_assets: false,
+			normalized_size: false,
+			bar_width: None,
+			sparkline: false,
+			color: false,
+			timestamp: true,
 		}
 	}
 }
 
+impl ViewOptions {
+	/// Key per-file details are sorted by: the file sort key when set, otherwise the language sort key.
+	#[must_use]
+	pub fn file_sort_key(&self) -> LanguageSortKey {
+		self.file_sort_key.unwrap_or(self.language_sort_key)
+	}
+
+	/// Direction per-file details are sorted in: the file sort direction when set, otherwise the language one.
+	#[must_use]
+	pub fn file_sort_direction(&self) -> SortDirection {
+		self.file_sort_direction.unwrap_or(self.sort_direction)
+	}
+}
+
 #[cfg(test)]
 mod tests {
+	use std::{fmt::Debug, str::FromStr};
+
 	use rstest::rstest;
 
 	use super::*;
+	use crate::display::OutputFormat;
+
+	/// Every variant must parse back from its displayed name, and serialize to that same name.
+	fn assert_names_round_trip<T>(all: &[T])
+	where
+		T: Copy + Debug + PartialEq + std::fmt::Display + FromStr<Err = String> + Serialize,
+	{
+		for &variant in all {
+			let name = variant.to_string();
+			assert_eq!(name.parse::<T>(), Ok(variant), "{name} does not parse back");
+			assert_eq!(serde_json::to_value(variant).unwrap(), name.as_str(), "serde disagrees with {name}");
+		}
+	}
+
+	#[test]
+	fn enum_names_round_trip_through_display_from_str_and_serde() {
+		assert_names_round_trip(OutputFormat::ALL);
+		assert_names_round_trip(NumberStyle::ALL);
+		assert_names_round_trip(SizeStyle::ALL);
+		assert_names_round_trip(RoundingMode::ALL);
+		assert_names_round_trip(LanguageSortKey::ALL);
+		assert_names_round_trip(SortDirection::ALL);
+		assert_names_round_trip(ColorChoice::ALL);
+		assert_names_round_trip(Verbosity::ALL);
+		assert_names_round_trip(&[IndentStyle::Tab, IndentStyle::Spaces(1), IndentStyle::Spaces(8)]);
+		assert!("JSON".parse::<OutputFormat>().is_err());
+	}
 
 	#[test]
 	fn view_options_default() {
@@ -191,6 +336,7 @@ mod tests {
 		assert_eq!(opts.number_style, NumberStyle::Plain);
 		assert_eq!(opts.size_style, SizeStyle::Binary);
 		assert_eq!(opts.percent_precision, 1);
+		assert_eq!(opts.rounding, RoundingMode::Nearest);
 		assert_eq!(opts.language_sort_key, LanguageSortKey::Lines);
 		assert_eq!(opts.sort_direction, SortDirection::Desc);
 		assert_eq!(opts.indent_style, IndentStyle::Tab);
@@ -201,6 +347,7 @@ mod tests {
 	#[case::comma(NumberStyle::Comma, "\"comma\"")]
 	#[case::underscore(NumberStyle::Underscore, "\"underscore\"")]
 	#[case::space(NumberStyle::Space, "\"space\"")]
+	#[case::indian(NumberStyle::Indian, "\"indian\"")]
 	fn number_style_serde_roundtrip(#[case] variant: NumberStyle, #[case] expected_json: &str) {
 		let json = serde_json::to_string(&variant).unwrap();
 		assert_eq!(json, expected_json);
@@ -208,6 +355,21 @@ mod tests {
 		assert_eq!(deserialized, variant);
 	}
 
+	#[rstest]
+	#[case::auto_terminal(ColorChoice::Auto, true, false, true)]
+	#[case::auto_piped(ColorChoice::Auto, false, false, false)]
+	#[case::auto_no_color(ColorChoice::Auto, true, true, false)]
+	#[case::always_overrides_no_color(ColorChoice::Always, false, true, true)]
+	#[case::never(ColorChoice::Never, true, false, false)]
+	fn color_choice_enabled(
+		#[case] choice: ColorChoice,
+		#[case] is_terminal: bool,
+		#[case] no_color: bool,
+		#[case] expected: bool,
+	) {
+		assert_eq!(choice.enabled(is_terminal, no_color), expected);
+	}
+
 	#[rstest]
 	#[case::binary(SizeStyle::Binary, "\"binary\"")]
 	#[case::decimal(SizeStyle::Decimal, "\"decimal\"")]
@@ -226,6 +388,9 @@ mod tests {
 	#[case::files(LanguageSortKey::Files, "\"files\"")]
 	#[case::size(LanguageSortKey::Size, "\"size\"")]
 	#[case::name(LanguageSortKey::Name, "\"name\"")]
+	#[case::comment_pct(LanguageSortKey::CommentPct, "\"comment-pct\"")]
+	#[case::code_pct(LanguageSortKey::CodePct, "\"code-pct\"")]
+	#[case::ratio(LanguageSortKey::Ratio, "\"ratio\"")]
 	fn language_sort_key_serde_roundtrip(#[case] variant: LanguageSortKey, #[case] expected_json: &str) {
 		let json = serde_json::to_string(&variant).unwrap();
 		assert_eq!(json, expected_json);
@@ -248,6 +413,7 @@ mod tests {
 	#[case::comma("\"comma\"", NumberStyle::Comma)]
 	#[case::underscore("\"underscore\"", NumberStyle::Underscore)]
 	#[case::space("\"space\"", NumberStyle::Space)]
+	#[case::indian("\"indian\"", NumberStyle::Indian)]
 	fn number_style_from_lowercase(#[case] input: &str, #[case] expected: NumberStyle) {
 		let result: NumberStyle = serde_json::from_str(input).unwrap();
 		assert_eq!(result, expected);
@@ -269,6 +435,9 @@ mod tests {
 	#[case::files("\"files\"", LanguageSortKey::Files)]
 	#[case::size("\"size\"", LanguageSortKey::Size)]
 	#[case::name("\"name\"", LanguageSortKey::Name)]
+	#[case::comment_pct("\"comment-pct\"", LanguageSortKey::CommentPct)]
+	#[case::code_pct("\"code-pct\"", LanguageSortKey::CodePct)]
+	#[case::ratio("\"ratio\"", LanguageSortKey::Ratio)]
 	fn language_sort_key_from_lowercase(#[case] input: &str, #[case] expected: LanguageSortKey) {
 		let result: LanguageSortKey = serde_json::from_str(input).unwrap();
 		assert_eq!(result, expected);
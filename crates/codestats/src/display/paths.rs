@@ -0,0 +1,120 @@
+use std::io::Write;
+
+use anyhow::Result;
+
+use super::{OutputFormatter, ReportRoot, ViewOptions};
+use crate::analysis::AnalysisResults;
+
+/// Lists the path of every analyzed file, relative to the analysis root and sorted, one record per file.
+///
+/// Paths are written as the bytes found on disk, so on Unix a file name that is not valid UTF-8 comes out unchanged
+/// rather than with replacement characters.
+///
+/// [`Self::with_languages`] prefixes each path with its language and a tab, as `<language>\t<path>`. The language is
+/// the name shown in other reports, so a display alias group replaces its member languages.
+///
+/// Records end with a newline by default; [`Self::null_terminated`] ends them with NUL instead, for tools that split
+/// records on NUL and must cope with file names containing newlines. Files are listed whatever the verbosity, but
+/// only when the analysis collected per-file details.
+pub struct PathsFormatter {
+	terminator: u8,
+	languages: bool,
+}
+
+impl Default for PathsFormatter {
+	fn default() -> Self {
+		Self { terminator: b'\n', languages: false }
+	}
+}
+
+impl PathsFormatter {
+	/// Create a formatter that ends each record with a NUL byte instead of a newline.
+	#[must_use]
+	pub const fn null_terminated() -> Self {
+		Self { terminator: b'\0', languages: false }
+	}
+
+	/// Prefix each path with the file's language and a tab.
+	#[must_use]
+	pub const fn with_languages(mut self) -> Self {
+		self.languages = true;
+		self
+	}
+}
+
+impl OutputFormatter for PathsFormatter {
+	fn write_output(
+		&self,
+		results: &AnalysisResults,
+		_root: ReportRoot<'_>,
+		_view_options: ViewOptions,
+		writer: &mut dyn Write,
+	) -> Result<()> {
+		let mut files: Vec<(&str, &[u8], &str)> = results
+			.reported_languages()
+			.flat_map(|(name, stats)| stats.files_list().iter().map(move |file| (file.path(), file.path_bytes(), name)))
+			.collect();
+		files.sort_unstable();
+		for (_, path, language) in files {
+			if self.languages {
+				write!(writer, "{language}\t")?;
+			}
+			writer.write_all(path)?;
+			writer.write_all(&[self.terminator])?;
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::path::Path;
+
+	use super::PathsFormatter;
+	use crate::{
+		analysis::{
+			AnalysisResults,
+			stats::{FileContribution, FileStats},
+		},
+		display::{OutputFormatter, ViewOptions},
+	};
+
+	fn results() -> AnalysisResults {
+		let mut results = AnalysisResults::default();
+		for (name, path) in [("Rust", "src/main.rs"), ("Python", "odd\nname.py"), ("Rust", "build.rs")] {
+			let lang = crate::langs::LANGUAGES.iter().find(|l| l.name == name).unwrap();
+			let file = FileStats::new(path.to_owned(), 1, 1, 0, 0, 0, 10);
+			results.add_file_stats(lang, FileContribution::new(1, 1, 0, 0, 0, 10), Some(file));
+		}
+		results
+	}
+
+	#[test]
+	fn paths_are_sorted_and_newline_terminated_by_default() {
+		let mut buf = Vec::new();
+		PathsFormatter::default()
+			.write_output(&results(), Path::new(".").into(), ViewOptions::default(), &mut buf)
+			.unwrap();
+		assert_eq!(buf, b"build.rs\nodd\nname.py\nsrc/main.rs\n");
+	}
+
+	#[test]
+	fn languages_prefix_each_path() {
+		let mut buf = Vec::new();
+		PathsFormatter::default()
+			.with_languages()
+			.write_output(&results(), Path::new(".").into(), ViewOptions::default(), &mut buf)
+			.unwrap();
+		assert_eq!(buf, b"Rust\tbuild.rs\nPython\todd\nname.py\nRust\tsrc/main.rs\n");
+	}
+
+	#[test]
+	fn null_terminated_paths_keep_newlines_in_names_unambiguous() {
+		let mut buf = Vec::new();
+		PathsFormatter::null_terminated()
+			.write_output(&results(), Path::new(".").into(), ViewOptions::default(), &mut buf)
+			.unwrap();
+		let records: Vec<&[u8]> = buf.split_inclusive(|&b| b == 0).collect();
+		assert_eq!(records, [&b"build.rs\0"[..], b"odd\nname.py\0", b"src/main.rs\0"]);
+	}
+}
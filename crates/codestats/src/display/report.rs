@@ -1,17 +1,19 @@
 use std::{
 	collections::HashMap,
 	path::{Component, Path},
+	time::SystemTime,
 };
 
-use serde::Serialize;
+use serde::{Serialize, Serializer, ser::SerializeSeq as _};
 
 use crate::{
-	analysis::{AnalysisResults, FileStats, LanguageStats, LineType, stats::percentage},
+	analysis::{AnalysisResults, FileStats, LanguageStats, LargestFile, LineType, SubsetTotals, stats::percentage},
 	display::{
 		apply_sort,
-		formatting::{FormatterContext, SortValue, pluralize as pluralize_fn},
+		formatting::{FormatterContext, SortValue, display_path, pluralize as pluralize_fn, rfc3339_utc},
 		options::{LanguageSortKey, Verbosity},
 	},
+	langs::DetectionConfidence,
 };
 
 macro_rules! impl_formatters {
@@ -29,48 +31,196 @@ macro_rules! impl_formatters {
 	};
 }
 
+/// Provenance of a report, so saved copies say what produced them and when.
 #[derive(Debug, Serialize)]
-pub struct ReportData<'a> {
+pub struct ReportMeta {
+	pub tool_version: &'static str,
+	/// RFC 3339 time the report was rendered, in UTC; left out when timestamps are turned off for reproducible output.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub generated_at: Option<String>,
+}
+
+impl ReportMeta {
+	fn new(ctx: &FormatterContext) -> Self {
+		Self {
+			tool_version: env!("CARGO_PKG_VERSION"),
+			generated_at: ctx.options.timestamp.then(|| rfc3339_utc(SystemTime::now())),
+		}
+	}
+}
+
+/// The analyzed path a report describes, and the label shown for it instead when there is one.
+///
+/// Directory breakdowns always bucket files by the real path; the label only replaces the `analysis_path` shown in the
+/// report, so reports from different checkouts can be compared without leaking machine-specific paths.
+#[derive(Debug, Clone, Copy)]
+pub struct ReportRoot<'a> {
+	path: &'a Path,
+	label: Option<&'a str>,
+}
+
+impl<'a> ReportRoot<'a> {
+	#[must_use]
+	pub const fn new(path: &'a Path) -> Self {
+		Self { path, label: None }
+	}
+
+	/// Show `label` as the analysis path when one is given.
+	#[must_use]
+	pub const fn with_label(mut self, label: Option<&'a str>) -> Self {
+		self.label = label;
+		self
+	}
+}
+
+impl<'a> From<&'a Path> for ReportRoot<'a> {
+	fn from(path: &'a Path) -> Self {
+		Self::new(path)
+	}
+}
+
+/// Everything a report shows, with its languages held in `L`.
+///
+/// Formatters that walk the languages more than once use the default, fully built [`LanguageRecord`]s. JSON output uses
+/// [`StreamedReport`] instead, which serializes to exactly the same text.
+#[derive(Debug, Serialize)]
+pub struct ReportData<'a, L = Vec<LanguageRecord<'a>>> {
 	pub analysis_path: String,
+	pub meta: ReportMeta,
 	pub summary: Summary,
-	pub languages: Vec<LanguageRecord<'a>>,
+	pub languages: L,
 	#[serde(skip_serializing_if = "Vec::is_empty")]
 	pub directories: Vec<DirRecord>,
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub languages_hidden: Option<usize>,
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub dirs_hidden: Option<usize>,
+	/// Settings the report was produced with, present only when embedded with `--embed-config`.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub config: Option<&'a serde_json::Value>,
 }
 
+/// A [`ReportData`] whose per-file language records are built only while they are serialized.
+///
+/// This never holds more than one language's [`FileRecord`]s at a time, which keeps verbose reports over very large
+/// trees from doubling peak memory. Directory records are still built up front.
+pub type StreamedReport<'a> = ReportData<'a, StreamedLanguages<'a>>;
+
 impl<'a> ReportData<'a> {
 	#[must_use]
 	pub fn from_results(
 		results: &'a AnalysisResults,
-		path: &Path,
+		root: ReportRoot<'_>,
+		verbosity: Verbosity,
+		ctx: &FormatterContext,
+	) -> Self {
+		let total_lines = results.total_lines();
+		Self::build(results, root, verbosity, ctx, |selected| {
+			selected
+				.into_iter()
+				.map(|(name, stats)| {
+					let files_detail =
+						(verbosity == Verbosity::Verbose).then(|| file_records(stats, total_lines, ctx).collect());
+					LanguageRecord::from_stats(name, stats, files_detail, ctx)
+				})
+				.collect()
+		})
+	}
+}
+
+impl<'a> StreamedReport<'a> {
+	#[must_use]
+	pub fn streamed(results: &'a AnalysisResults, root: ReportRoot<'_>, ctx: &'a FormatterContext) -> Self {
+		let verbosity = ctx.options.verbosity;
+		Self::build(results, root, verbosity, ctx, |languages| StreamedLanguages {
+			languages,
+			total_lines: results.total_lines(),
+			verbose: verbosity == Verbosity::Verbose,
+			ctx,
+		})
+	}
+}
+
+impl<'a, L> ReportData<'a, L> {
+	/// Build a report whose languages are the selected ones turned into `L` by `languages`.
+	fn build(
+		results: &'a AnalysisResults,
+		root: ReportRoot<'_>,
 		verbosity: Verbosity,
 		ctx: &FormatterContext,
+		languages: impl FnOnce(Vec<(&'a str, &'a LanguageStats)>) -> L,
 	) -> Self {
 		let summary = Summary::from_results(results, ctx);
-		let (languages, languages_hidden) = if verbosity > Verbosity::Summary {
-			LanguageRecord::from_results(results, verbosity, ctx)
-		} else {
-			(Vec::default(), 0)
-		};
+		let (selected, languages_hidden) =
+			if verbosity > Verbosity::Summary { select_languages(results, ctx) } else { (Vec::new(), 0) };
 		let (directories, dirs_hidden) = if ctx.options.by_dir && verbosity > Verbosity::Summary {
-			DirRecord::from_results(results, path, ctx)
+			DirRecord::from_results(results, root.path, ctx)
 		} else {
 			(Vec::new(), 0)
 		};
-		let languages_hidden = (languages_hidden > 0).then_some(languages_hidden);
-		let dirs_hidden = (dirs_hidden > 0).then_some(dirs_hidden);
 		Self {
-			analysis_path: path.display().to_string(),
+			analysis_path: root.label.map_or_else(|| display_path(root.path), str::to_owned),
+			meta: ReportMeta::new(ctx),
 			summary,
-			languages,
+			languages: languages(selected),
 			directories,
-			languages_hidden,
-			dirs_hidden,
+			languages_hidden: (languages_hidden > 0).then_some(languages_hidden),
+			dirs_hidden: (dirs_hidden > 0).then_some(dirs_hidden),
+			config: None,
+		}
+	}
+
+	/// Embed `config`, the settings the report was produced with.
+	#[must_use]
+	pub const fn with_config(mut self, config: Option<&'a serde_json::Value>) -> Self {
+		self.config = config;
+		self
+	}
+}
+
+/// Languages of a [`StreamedReport`], serialized as a sequence of [`LanguageRecord`]s.
+pub struct StreamedLanguages<'a> {
+	languages: Vec<(&'a str, &'a LanguageStats)>,
+	total_lines: u64,
+	verbose: bool,
+	ctx: &'a FormatterContext,
+}
+
+impl Serialize for StreamedLanguages<'_> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		let mut seq = serializer.serialize_seq(Some(self.languages.len()))?;
+		for &(name, stats) in &self.languages {
+			seq.serialize_element(&StreamedLanguage {
+				record: LanguageRecord::from_stats(name, stats, None, self.ctx),
+				files_detail: self.verbose.then_some(StreamedFiles {
+					stats,
+					total_lines: self.total_lines,
+					ctx: self.ctx,
+				}),
+			})?;
 		}
+		seq.end()
+	}
+}
+
+/// A [`LanguageRecord`] whose `files_detail` is filled in lazily, serialized with the same field order.
+#[derive(Serialize)]
+struct StreamedLanguage<'a> {
+	#[serde(flatten)]
+	record: LanguageRecord<'a>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	files_detail: Option<StreamedFiles<'a>>,
+}
+
+struct StreamedFiles<'a> {
+	stats: &'a LanguageStats,
+	total_lines: u64,
+	ctx: &'a FormatterContext,
+}
+
+impl Serialize for StreamedFiles<'_> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_seq(file_records(self.stats, self.total_lines, self.ctx))
 	}
 }
 
@@ -82,12 +232,134 @@ pub struct Summary {
 	pub total_comment_lines: u64,
 	pub total_blank_lines: u64,
 	pub total_shebang_lines: u64,
+	pub total_mixed_lines: u64,
+	/// Source lines of code: every line holding code, including mixed lines when they are split out.
+	pub total_sloc: u64,
 	pub total_size: u64,
 	pub total_size_human: String,
+	/// Average size in bytes of a line, or 0 when there are no lines.
+	pub bytes_per_line: f64,
+	/// Total size with `\r\n` line endings counted as `\n`, present when normalized sizes are requested.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub normalized_size: Option<u64>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub normalized_size_human: Option<String>,
 	pub code_percentage: f64,
 	pub comment_percentage: f64,
 	pub blank_percentage: f64,
 	pub shebang_percentage: f64,
+	pub mixed_percentage: f64,
+	pub generated_files: u64,
+	pub bom_files: u64,
+	/// Files with a line over 1 MiB, whose lines were only read up to that length.
+	pub minified_files: u64,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub tests: Option<TestSplit>,
+	/// Totals per file category, most lines first, present when categories are split.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub categories: Option<Vec<CategoryRecord>>,
+	/// Files collapsed as vendored, present when vendored collapsing is enabled. They are not part of the totals.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub vendored: Option<SplitTotals>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub assets: Option<AssetTotals>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub largest_file_by_lines: Option<LargestFileRecord>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub largest_file_by_bytes: Option<LargestFileRecord>,
+}
+
+/// Binary files counted by size only, present when assets are included.
+#[derive(Debug, Serialize)]
+pub struct AssetTotals {
+	pub files: u64,
+	pub size: u64,
+	pub size_human: String,
+}
+
+/// The single largest file for one measure, as shown in the summary.
+#[derive(Debug, Serialize)]
+pub struct LargestFileRecord {
+	pub path: String,
+	pub lines: u64,
+	pub size: u64,
+	pub size_human: String,
+}
+
+impl LargestFileRecord {
+	fn from_file(file: &LargestFile, ctx: &FormatterContext) -> Self {
+		Self { path: file.path().to_owned(), lines: file.lines(), size: file.size(), size_human: ctx.size(file.size()) }
+	}
+}
+
+/// Summary totals split between test and non-test files, present when test splitting is enabled.
+#[derive(Debug, Serialize)]
+pub struct TestSplit {
+	pub test: SplitTotals,
+	pub non_test: SplitTotals,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SplitTotals {
+	pub files: u64,
+	pub lines: u64,
+	pub code_lines: u64,
+	pub comment_lines: u64,
+	pub blank_lines: u64,
+	pub size: u64,
+}
+
+impl SplitTotals {
+	const fn from_totals(totals: &SubsetTotals) -> Self {
+		Self {
+			files: totals.files(),
+			lines: totals.lines(),
+			code_lines: totals.code_lines(),
+			comment_lines: totals.comment_lines(),
+			blank_lines: totals.blank_lines(),
+			size: totals.size(),
+		}
+	}
+}
+
+impl TestSplit {
+	const fn from_results(results: &AnalysisResults) -> Self {
+		let test = SplitTotals::from_totals(results.test_totals());
+		let non_test = SplitTotals {
+			files: results.total_files().saturating_sub(test.files),
+			lines: results.total_lines().saturating_sub(test.lines),
+			code_lines: results.total_code_lines().saturating_sub(test.code_lines),
+			comment_lines: results.total_comment_lines().saturating_sub(test.comment_lines),
+			blank_lines: results.total_blank_lines().saturating_sub(test.blank_lines),
+			size: results.total_size().saturating_sub(test.size),
+		};
+		Self { test, non_test }
+	}
+}
+
+/// Totals for one file category, such as tests or docs.
+#[derive(Debug, Serialize)]
+pub struct CategoryRecord {
+	pub name: String,
+	#[serde(flatten)]
+	pub totals: SplitTotals,
+	/// Share of all lines held by files in this category, as a percentage.
+	pub percentage: f64,
+}
+
+impl CategoryRecord {
+	fn from_results(results: &AnalysisResults) -> Vec<Self> {
+		let mut records: Vec<_> = results
+			.category_totals()
+			.map(|(name, totals)| Self {
+				name: name.to_owned(),
+				totals: SplitTotals::from_totals(totals),
+				percentage: percentage(totals.lines(), results.total_lines()),
+			})
+			.collect();
+		records.sort_by(|a, b| b.totals.lines.cmp(&a.totals.lines).then_with(|| a.name.cmp(&b.name)));
+		records
+	}
 }
 
 #[derive(Debug)]
@@ -125,10 +397,12 @@ struct LineTypeSeries {
 	comment: u64,
 	blank: u64,
 	shebang: u64,
+	mixed: u64,
 	code_pct: f64,
 	comment_pct: f64,
 	blank_pct: f64,
 	shebang_pct: f64,
+	mixed_pct: f64,
 }
 
 fn iter_line_types(series: LineTypeSeries) -> impl Iterator<Item = LineTypeStats> {
@@ -137,6 +411,7 @@ fn iter_line_types(series: LineTypeSeries) -> impl Iterator<Item = LineTypeStats
 		LineTypeStats { kind: LineType::Comment, count: series.comment, percentage: series.comment_pct },
 		LineTypeStats { kind: LineType::Blank, count: series.blank, percentage: series.blank_pct },
 		LineTypeStats { kind: LineType::Shebang, count: series.shebang, percentage: series.shebang_pct },
+		LineTypeStats { kind: LineType::Mixed, count: series.mixed, percentage: series.mixed_pct },
 	]
 	.into_iter()
 	.filter(|info| info.count > 0)
@@ -145,6 +420,22 @@ fn iter_line_types(series: LineTypeSeries) -> impl Iterator<Item = LineTypeStats
 impl Summary {
 	#[must_use]
 	fn from_results(results: &AnalysisResults, ctx: &FormatterContext) -> Self {
+		let [code_percentage, comment_percentage, blank_percentage, shebang_percentage, mixed_percentage] = ctx.shares(
+			[
+				results.total_code_lines(),
+				results.total_comment_lines(),
+				results.total_blank_lines(),
+				results.total_shebang_lines(),
+				results.total_mixed_lines(),
+			],
+			[
+				results.code_percentage(),
+				results.comment_percentage(),
+				results.blank_percentage(),
+				results.shebang_percentage(),
+				results.mixed_percentage(),
+			],
+		);
 		Self {
 			total_files: results.total_files(),
 			total_lines: results.total_lines(),
@@ -152,12 +443,31 @@ impl Summary {
 			total_comment_lines: results.total_comment_lines(),
 			total_blank_lines: results.total_blank_lines(),
 			total_shebang_lines: results.total_shebang_lines(),
+			total_mixed_lines: results.total_mixed_lines(),
+			total_sloc: results.total_sloc(),
 			total_size: results.total_size(),
 			total_size_human: ctx.size(results.total_size()),
-			code_percentage: results.code_percentage(),
-			comment_percentage: results.comment_percentage(),
-			blank_percentage: results.blank_percentage(),
-			shebang_percentage: results.shebang_percentage(),
+			bytes_per_line: results.bytes_per_line(),
+			normalized_size: ctx.options.normalized_size.then(|| results.total_normalized_size()),
+			normalized_size_human: ctx.options.normalized_size.then(|| ctx.size(results.total_normalized_size())),
+			code_percentage,
+			comment_percentage,
+			blank_percentage,
+			shebang_percentage,
+			mixed_percentage,
+			generated_files: results.generated_files(),
+			bom_files: results.bom_files(),
+			minified_files: results.minified_files(),
+			tests: ctx.options.split_tests.then(|| TestSplit::from_results(results)),
+			categories: ctx.options.split_categories.then(|| CategoryRecord::from_results(results)),
+			vendored: ctx.options.collapse_vendored.then(|| SplitTotals::from_totals(results.vendored_totals())),
+			assets: ctx.options.include_assets.then(|| AssetTotals {
+				files: results.asset_files(),
+				size: results.asset_size(),
+				size_human: ctx.size(results.asset_size()),
+			}),
+			largest_file_by_lines: results.largest_file_by_lines().map(|file| LargestFileRecord::from_file(file, ctx)),
+			largest_file_by_bytes: results.largest_file_by_size().map(|file| LargestFileRecord::from_file(file, ctx)),
 		}
 	}
 
@@ -167,10 +477,12 @@ impl Summary {
 			comment: self.total_comment_lines,
 			blank: self.total_blank_lines,
 			shebang: self.total_shebang_lines,
+			mixed: self.total_mixed_lines,
 			code_pct: self.code_percentage,
 			comment_pct: self.comment_percentage,
 			blank_pct: self.blank_percentage,
 			shebang_pct: self.shebang_percentage,
+			mixed_pct: self.mixed_percentage,
 		})
 	}
 
@@ -197,6 +509,13 @@ impl Summary {
 
 	pub fn metrics(&self) -> impl Iterator<Item = SummaryMetric<'_>> {
 		let size_human = self.total_size_human.as_str();
+		// Mixed lines only exist when split out of code, so omit the row otherwise.
+		let mixed = (self.total_mixed_lines > 0).then_some(SummaryMetric {
+			label: "Mixed Lines",
+			value: self.total_mixed_lines,
+			percentage: Some(self.mixed_percentage),
+			human_readable: None,
+		});
 		[
 			SummaryMetric { label: "Total Files", value: self.total_files, percentage: None, human_readable: None },
 			SummaryMetric { label: "Total Lines", value: self.total_lines, percentage: None, human_readable: None },
@@ -224,14 +543,30 @@ impl Summary {
 				percentage: Some(self.shebang_percentage),
 				human_readable: None,
 			},
-			SummaryMetric {
-				label: "Total Size",
-				value: self.total_size,
-				percentage: None,
-				human_readable: Some(size_human),
-			},
 		]
 		.into_iter()
+		.chain(mixed)
+		.chain([SummaryMetric {
+			label: "Total Size",
+			value: self.total_size,
+			percentage: None,
+			human_readable: Some(size_human),
+		}])
+		.chain(self.tests.iter().flat_map(|tests| self.test_metrics(tests)))
+	}
+
+	fn test_metrics<'s>(&self, tests: &'s TestSplit) -> impl Iterator<Item = SummaryMetric<'s>> {
+		let code_pct = |code| Some(percentage(code, self.total_code_lines));
+		[
+			("Test Files", tests.test.files, None),
+			("Test Lines", tests.test.lines, None),
+			("Test Code Lines", tests.test.code_lines, code_pct(tests.test.code_lines)),
+			("Non-Test Files", tests.non_test.files, None),
+			("Non-Test Lines", tests.non_test.lines, None),
+			("Non-Test Code Lines", tests.non_test.code_lines, code_pct(tests.non_test.code_lines)),
+		]
+		.into_iter()
+		.map(|(label, value, percentage)| SummaryMetric { label, value, percentage, human_readable: None })
 	}
 }
 
@@ -242,16 +577,29 @@ fn sort_key_for_file_record(file: &FileStats, key: LanguageSortKey) -> SortValue
 		LanguageSortKey::Comments => SortValue::Num(file.comment_lines()),
 		LanguageSortKey::Blanks => SortValue::Num(file.blank_lines()),
 		LanguageSortKey::Size => SortValue::Num(file.size()),
+		LanguageSortKey::CommentPct => SortValue::Float(percentage(file.comment_lines(), file.total_lines())),
+		LanguageSortKey::CodePct => SortValue::Float(percentage(file.code_lines(), file.total_lines())),
+		LanguageSortKey::Ratio => SortValue::Float(code_comment_ratio(file.code_lines(), file.comment_lines())),
 		// "Files" is a language-level metric, so fall back to a stable per-file key.
 		LanguageSortKey::Files | LanguageSortKey::Name => SortValue::Text(file.path()),
 	}
 }
 
-const fn sort_key_for_language_record<'a>(
-	name: &'a str,
-	stats: &'a LanguageStats,
-	key: LanguageSortKey,
-) -> SortValue<'a> {
+fn sort_key_for_dir_file_record(file: &DirFileRecord, key: LanguageSortKey) -> SortValue<'_> {
+	match key {
+		LanguageSortKey::Lines => SortValue::Num(file.total_lines),
+		LanguageSortKey::Code => SortValue::Num(file.code_lines),
+		LanguageSortKey::Comments => SortValue::Num(file.comment_lines),
+		LanguageSortKey::Blanks => SortValue::Num(file.blank_lines),
+		LanguageSortKey::Size => SortValue::Num(file.size),
+		LanguageSortKey::CommentPct => SortValue::Float(percentage(file.comment_lines, file.total_lines)),
+		LanguageSortKey::CodePct => SortValue::Float(percentage(file.code_lines, file.total_lines)),
+		LanguageSortKey::Ratio => SortValue::Float(code_comment_ratio(file.code_lines, file.comment_lines)),
+		LanguageSortKey::Files | LanguageSortKey::Name => SortValue::Text(file.path.as_str()),
+	}
+}
+
+fn sort_key_for_language_record<'a>(name: &'a str, stats: &'a LanguageStats, key: LanguageSortKey) -> SortValue<'a> {
 	match key {
 		LanguageSortKey::Lines => SortValue::Num(stats.lines()),
 		LanguageSortKey::Code => SortValue::Num(stats.code_lines()),
@@ -260,7 +608,83 @@ const fn sort_key_for_language_record<'a>(
 		LanguageSortKey::Files => SortValue::Num(stats.files()),
 		LanguageSortKey::Size => SortValue::Num(stats.size()),
 		LanguageSortKey::Name => SortValue::Text(name),
+		LanguageSortKey::CommentPct => SortValue::Float(stats.comment_percentage()),
+		LanguageSortKey::CodePct => SortValue::Float(stats.code_percentage()),
+		LanguageSortKey::Ratio => SortValue::Float(code_comment_ratio(stats.code_lines(), stats.comment_lines())),
+	}
+}
+
+/// Code lines per comment line. Code without comments is infinitely far from documented, and nothing at all is 0.
+#[expect(clippy::cast_precision_loss, reason = "the ratio only orders records; precision loss is harmless")]
+fn code_comment_ratio(code: u64, comment: u64) -> f64 {
+	match (code, comment) {
+		(0, 0) => 0.0,
+		(_, 0) => f64::INFINITY,
+		_ => code as f64 / comment as f64,
+	}
+}
+
+/// Languages to report, sorted and filtered by the view options, along with how many were left out.
+///
+/// This is the one place that decides whether languages and groups without files are listed, so every format agrees.
+/// Entries left out for being empty do not count as hidden.
+pub fn select_languages<'a>(
+	results: &'a AnalysisResults,
+	ctx: &FormatterContext,
+) -> (Vec<(&'a str, &'a LanguageStats)>, usize) {
+	let mut stats_vec: Vec<_> =
+		results.reported_languages().filter(|(_, stats)| ctx.options.show_empty || !stats.is_empty()).collect();
+	let total = stats_vec.len();
+	let sort_key = ctx.options.language_sort_key;
+	apply_sort(
+		&mut stats_vec,
+		ctx.options.sort_direction,
+		|(name, stats)| sort_key_for_language_record(name, stats, sort_key),
+		|a, b| a.0.cmp(b.0),
+	);
+	if let Some(n) = ctx.options.top_languages {
+		stats_vec.truncate(n);
 	}
+	if let Some(min) = ctx.options.min_lines {
+		stats_vec.retain(|(_, stats)| stats.lines() >= min);
+	}
+	let hidden = total.saturating_sub(stats_vec.len());
+	(stats_vec, hidden)
+}
+
+/// Per-file records for one language in display order, built one at a time as the iterator advances.
+fn file_records<'a>(
+	stats: &'a LanguageStats,
+	total_lines: u64,
+	ctx: &FormatterContext,
+) -> impl ExactSizeIterator<Item = FileRecord<'a>> {
+	let mut files: Vec<_> = stats.files_list().iter().collect();
+	let sort_key = ctx.options.file_sort_key();
+	apply_sort(
+		&mut files,
+		ctx.options.file_sort_direction(),
+		|file| sort_key_for_file_record(file, sort_key),
+		Ord::cmp,
+	);
+	files.into_iter().map(move |file| FileRecord {
+		path: file.path(),
+		total_lines: file.total_lines(),
+		code_lines: file.code_lines(),
+		comment_lines: file.comment_lines(),
+		blank_lines: file.blank_lines(),
+		shebang_lines: file.shebang_lines(),
+		mixed_lines: file.mixed_lines(),
+		sloc: file.sloc(),
+		size: file.size(),
+		size_human: ctx.size(file.size()),
+		max_line_length: file.max_line_length(),
+		is_symlinked: file.is_symlinked(),
+		category: file.category(),
+		hash: file.hash().map(|hash| format!("{hash:016x}")),
+		detection_confidence: file.detection().map(ConfidenceRecord::from),
+		pct_of_language_lines: percentage(file.total_lines(), stats.lines()),
+		pct_of_total_lines: percentage(file.total_lines(), total_lines),
+	})
 }
 
 #[derive(Debug, Serialize)]
@@ -272,69 +696,57 @@ pub struct LanguageRecord<'a> {
 	pub comment_lines: u64,
 	pub blank_lines: u64,
 	pub shebang_lines: u64,
+	pub mixed_lines: u64,
+	/// Source lines of code: every line holding code, including mixed lines when they are split out.
+	pub sloc: u64,
 	pub size: u64,
 	pub size_human: String,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub normalized_size: Option<u64>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub normalized_size_human: Option<String>,
 	pub avg_lines_per_file: f64,
+	/// Average size in bytes of a line, or 0 when the language has no lines.
+	pub bytes_per_line: f64,
+	pub max_line_length: u64,
 	pub code_percentage: f64,
 	pub comment_percentage: f64,
 	pub blank_percentage: f64,
 	pub shebang_percentage: f64,
+	pub mixed_percentage: f64,
+	pub tab_lines: u64,
+	pub space_lines: u64,
+	pub indentation: &'static str,
+	pub line_comment_lines: u64,
+	pub block_comment_lines: u64,
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub files_detail: Option<Vec<FileRecord<'a>>>,
 }
 
 impl<'a> LanguageRecord<'a> {
 	#[must_use]
-	fn from_results(results: &'a AnalysisResults, verbosity: Verbosity, ctx: &FormatterContext) -> (Vec<Self>, usize) {
-		let mut stats_vec: Vec<_> = results.languages().map(|(lang, stats)| (lang.name, stats)).collect();
-		let total = stats_vec.len();
-		let sort_key = ctx.options.language_sort_key;
-		apply_sort(
-			&mut stats_vec,
-			ctx.options.sort_direction,
-			|(name, stats)| sort_key_for_language_record(name, stats, sort_key),
-			|a, b| a.0.cmp(b.0),
+	fn from_stats(
+		name: &'a str,
+		stats: &'a LanguageStats,
+		files_detail: Option<Vec<FileRecord<'a>>>,
+		ctx: &FormatterContext,
+	) -> Self {
+		let [code_percentage, comment_percentage, blank_percentage, shebang_percentage, mixed_percentage] = ctx.shares(
+			[
+				stats.code_lines(),
+				stats.comment_lines(),
+				stats.blank_lines(),
+				stats.shebang_lines(),
+				stats.mixed_lines(),
+			],
+			[
+				stats.code_percentage(),
+				stats.comment_percentage(),
+				stats.blank_percentage(),
+				stats.shebang_percentage(),
+				stats.mixed_percentage(),
+			],
 		);
-		if let Some(n) = ctx.options.top_languages {
-			stats_vec.truncate(n);
-		}
-		if let Some(min) = ctx.options.min_lines {
-			stats_vec.retain(|(_, stats)| stats.lines() >= min);
-		}
-		let hidden = total.saturating_sub(stats_vec.len());
-		let records =
-			stats_vec.into_iter().map(|(name, stats)| Self::from_stats(name, stats, verbosity, ctx)).collect();
-		(records, hidden)
-	}
-
-	#[must_use]
-	fn from_stats(name: &'a str, stats: &'a LanguageStats, verbosity: Verbosity, ctx: &FormatterContext) -> Self {
-		let files_detail = (verbosity == Verbosity::Verbose).then(|| {
-			let mut files: Vec<_> = stats.files_list().iter().collect();
-			let sort_key = ctx.options.language_sort_key;
-			apply_sort(
-				&mut files,
-				ctx.options.sort_direction,
-				|file| sort_key_for_file_record(file, sort_key),
-				|a, b| a.path().cmp(b.path()),
-			);
-			files
-				.into_iter()
-				.map(|file| {
-					let size_human = ctx.size(file.size());
-					FileRecord {
-						path: file.path(),
-						total_lines: file.total_lines(),
-						code_lines: file.code_lines(),
-						comment_lines: file.comment_lines(),
-						blank_lines: file.blank_lines(),
-						shebang_lines: file.shebang_lines(),
-						size: file.size(),
-						size_human,
-					}
-				})
-				.collect()
-		});
 		Self {
 			name,
 			files: stats.files(),
@@ -343,13 +755,25 @@ impl<'a> LanguageRecord<'a> {
 			comment_lines: stats.comment_lines(),
 			blank_lines: stats.blank_lines(),
 			shebang_lines: stats.shebang_lines(),
+			mixed_lines: stats.mixed_lines(),
+			sloc: stats.sloc(),
 			size: stats.size(),
 			size_human: ctx.size(stats.size()),
+			normalized_size: ctx.options.normalized_size.then(|| stats.normalized_size()),
+			normalized_size_human: ctx.options.normalized_size.then(|| ctx.size(stats.normalized_size())),
 			avg_lines_per_file: stats.average_lines_per_file(),
-			code_percentage: stats.code_percentage(),
-			comment_percentage: stats.comment_percentage(),
-			blank_percentage: stats.blank_percentage(),
-			shebang_percentage: stats.shebang_percentage(),
+			bytes_per_line: stats.bytes_per_line(),
+			max_line_length: stats.max_line_length(),
+			code_percentage,
+			comment_percentage,
+			blank_percentage,
+			shebang_percentage,
+			mixed_percentage,
+			tab_lines: stats.tab_lines(),
+			space_lines: stats.space_lines(),
+			indentation: stats.dominant_indentation().label(),
+			line_comment_lines: stats.line_comment_lines(),
+			block_comment_lines: stats.block_comment_lines(),
 			files_detail,
 		}
 	}
@@ -360,10 +784,12 @@ impl<'a> LanguageRecord<'a> {
 			comment: self.comment_lines,
 			blank: self.blank_lines,
 			shebang: self.shebang_lines,
+			mixed: self.mixed_lines,
 			code_pct: self.code_percentage,
 			comment_pct: self.comment_percentage,
 			blank_pct: self.blank_percentage,
 			shebang_pct: self.shebang_percentage,
+			mixed_pct: self.mixed_percentage,
 		})
 	}
 }
@@ -375,6 +801,12 @@ impl_formatters!(LanguageRecord<'_> {
 	format_comment_lines => comment_lines : number,
 	format_blank_lines => blank_lines : number,
 	format_shebang_lines => shebang_lines : number,
+	format_tab_lines => tab_lines : number,
+	format_space_lines => space_lines : number,
+	format_line_comment_lines => line_comment_lines : number,
+	format_block_comment_lines => block_comment_lines : number,
+	format_sloc => sloc : number,
+	format_max_line_length => max_line_length : number,
 	format_size => size : number,
 	format_code_percentage => code_percentage : percent,
 	format_comment_percentage => comment_percentage : percent,
@@ -390,8 +822,44 @@ pub struct FileRecord<'a> {
 	pub comment_lines: u64,
 	pub blank_lines: u64,
 	pub shebang_lines: u64,
+	pub mixed_lines: u64,
+	/// Source lines of code: every line holding code, including mixed lines when they are split out.
+	pub sloc: u64,
 	pub size: u64,
 	pub size_human: String,
+	pub max_line_length: u64,
+	pub is_symlinked: bool,
+	/// Category assigned by the category rules, present when categories are split.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub category: Option<&'a str>,
+	/// Content hash as 16 hex digits, present when hashes are requested.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub hash: Option<String>,
+	/// What decided the file's language.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub detection_confidence: Option<ConfidenceRecord>,
+	/// Share of the language's lines held by this file, as a percentage.
+	pub pct_of_language_lines: f64,
+	/// Share of all analyzed lines held by this file, as a percentage.
+	pub pct_of_total_lines: f64,
+}
+
+/// What decided a file's language: the winning content score, or `"filename"`, `"shebang"` or `"override"` when no
+/// scoring was needed.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum ConfidenceRecord {
+	Score(i32),
+	Source(String),
+}
+
+impl From<DetectionConfidence> for ConfidenceRecord {
+	fn from(confidence: DetectionConfidence) -> Self {
+		match confidence {
+			DetectionConfidence::Score(score) => Self::Score(score),
+			other => Self::Source(other.to_string()),
+		}
+	}
 }
 
 impl_formatters!(FileRecord<'_> {
@@ -400,10 +868,12 @@ impl_formatters!(FileRecord<'_> {
 	format_comment_lines => comment_lines : number,
 	format_blank_lines => blank_lines : number,
 	format_shebang_lines => shebang_lines : number,
+	format_sloc => sloc : number,
 	format_size => size : number,
 });
 
-#[derive(Debug, Serialize)]
+/// Ordering compares the path first and then every other field, so sorting by it is total.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
 pub struct DirFileRecord {
 	pub path: String,
 	pub total_lines: u64,
@@ -411,30 +881,107 @@ pub struct DirFileRecord {
 	pub comment_lines: u64,
 	pub blank_lines: u64,
 	pub shebang_lines: u64,
+	pub mixed_lines: u64,
 	pub size: u64,
 	pub size_human: String,
+	pub max_line_length: u64,
+	pub is_symlinked: bool,
 }
 
-fn dir_key(file_path_str: &str, root: &Path) -> String {
+impl DirFileRecord {
+	fn from_stats(file: &FileStats, ctx: &FormatterContext) -> Self {
+		Self {
+			path: file.path().to_owned(),
+			total_lines: file.total_lines(),
+			code_lines: file.code_lines(),
+			comment_lines: file.comment_lines(),
+			blank_lines: file.blank_lines(),
+			shebang_lines: file.shebang_lines(),
+			mixed_lines: file.mixed_lines(),
+			size: file.size(),
+			size_human: ctx.size(file.size()),
+			max_line_length: file.max_line_length(),
+			is_symlinked: file.is_symlinked(),
+		}
+	}
+}
+
+/// Bucket a file by up to `depth` leading directory components of its path relative to `root`.
+fn dir_key(file_path_str: &str, root: &Path, depth: usize) -> String {
 	let path = Path::new(file_path_str);
 	let relative = path.strip_prefix(root).unwrap_or(path);
-	let mut components = relative.components();
-	match (components.next(), components.next()) {
-		(Some(Component::Normal(first)), Some(_)) => first.to_string_lossy().into_owned(),
-		_ => "(root)".to_owned(),
-	}
+	let Some(parent) = relative.parent() else {
+		return "(root)".to_owned();
+	};
+	let dirs: Vec<_> = parent
+		.components()
+		.filter_map(|component| match component {
+			Component::Normal(name) => Some(name.to_string_lossy()),
+			_ => None,
+		})
+		.take(depth.max(1))
+		.collect();
+	if dirs.is_empty() { "(root)".to_owned() } else { dirs.join("/") }
 }
 
 #[derive(Debug, Default)]
-struct DirAccumulator {
+struct DirAccumulator<'a> {
 	files: u64,
 	lines: u64,
 	code_lines: u64,
 	comment_lines: u64,
 	blank_lines: u64,
 	shebang_lines: u64,
+	mixed_lines: u64,
 	size: u64,
 	file_stats: Vec<DirFileRecord>,
+	languages: HashMap<&'a str, DirLanguageRecord>,
+}
+
+/// Per-language totals within a single directory bucket, reported in verbose mode.
+#[derive(Debug, Default, Serialize)]
+pub struct DirLanguageRecord {
+	pub name: String,
+	pub files: u64,
+	pub lines: u64,
+	pub code_lines: u64,
+	pub comment_lines: u64,
+	pub blank_lines: u64,
+	pub size: u64,
+}
+
+impl DirLanguageRecord {
+	const fn add_file(&mut self, file: &FileStats) {
+		self.files = self.files.saturating_add(1);
+		self.lines = self.lines.saturating_add(file.total_lines());
+		self.code_lines = self.code_lines.saturating_add(file.code_lines());
+		self.comment_lines = self.comment_lines.saturating_add(file.comment_lines());
+		self.blank_lines = self.blank_lines.saturating_add(file.blank_lines());
+		self.size = self.size.saturating_add(file.size());
+	}
+
+	fn sorted(languages: HashMap<&str, Self>, ctx: &FormatterContext) -> Vec<Self> {
+		let mut languages: Vec<_> = languages.into_values().collect();
+		let sort_key = ctx.options.language_sort_key;
+		apply_sort(
+			&mut languages,
+			ctx.options.sort_direction,
+			|lang| match sort_key {
+				LanguageSortKey::Lines => SortValue::Num(lang.lines),
+				LanguageSortKey::Code => SortValue::Num(lang.code_lines),
+				LanguageSortKey::Comments => SortValue::Num(lang.comment_lines),
+				LanguageSortKey::Blanks => SortValue::Num(lang.blank_lines),
+				LanguageSortKey::Files => SortValue::Num(lang.files),
+				LanguageSortKey::Size => SortValue::Num(lang.size),
+				LanguageSortKey::Name => SortValue::Text(lang.name.as_str()),
+				LanguageSortKey::CommentPct => SortValue::Float(percentage(lang.comment_lines, lang.lines)),
+				LanguageSortKey::CodePct => SortValue::Float(percentage(lang.code_lines, lang.lines)),
+				LanguageSortKey::Ratio => SortValue::Float(code_comment_ratio(lang.code_lines, lang.comment_lines)),
+			},
+			|a, b| a.name.cmp(&b.name),
+		);
+		languages
+	}
 }
 
 #[derive(Debug, Serialize)]
@@ -446,12 +993,16 @@ pub struct DirRecord {
 	pub comment_lines: u64,
 	pub blank_lines: u64,
 	pub shebang_lines: u64,
+	pub mixed_lines: u64,
 	pub size: u64,
 	pub size_human: String,
 	pub code_percentage: f64,
 	pub comment_percentage: f64,
 	pub blank_percentage: f64,
 	pub shebang_percentage: f64,
+	pub mixed_percentage: f64,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub languages: Option<Vec<DirLanguageRecord>>,
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub files_detail: Option<Vec<DirFileRecord>>,
 }
@@ -460,9 +1011,9 @@ impl DirRecord {
 	fn from_results(results: &AnalysisResults, root: &Path, ctx: &FormatterContext) -> (Vec<Self>, usize) {
 		let verbose = ctx.options.verbosity == Verbosity::Verbose;
 		let mut map: HashMap<String, DirAccumulator> = HashMap::new();
-		for (_, stats) in results.languages() {
+		for (name, stats) in results.reported_languages() {
 			for file in stats.files_list() {
-				let key = dir_key(file.path(), root);
+				let key = dir_key(file.path(), root, ctx.options.dir_depth);
 				let acc = map.entry(key).or_default();
 				acc.files = acc.files.saturating_add(1);
 				acc.lines = acc.lines.saturating_add(file.total_lines());
@@ -470,18 +1021,14 @@ impl DirRecord {
 				acc.comment_lines = acc.comment_lines.saturating_add(file.comment_lines());
 				acc.blank_lines = acc.blank_lines.saturating_add(file.blank_lines());
 				acc.shebang_lines = acc.shebang_lines.saturating_add(file.shebang_lines());
+				acc.mixed_lines = acc.mixed_lines.saturating_add(file.mixed_lines());
 				acc.size = acc.size.saturating_add(file.size());
 				if verbose {
-					acc.file_stats.push(DirFileRecord {
-						path: file.path().to_owned(),
-						total_lines: file.total_lines(),
-						code_lines: file.code_lines(),
-						comment_lines: file.comment_lines(),
-						blank_lines: file.blank_lines(),
-						shebang_lines: file.shebang_lines(),
-						size: file.size(),
-						size_human: ctx.size(file.size()),
-					});
+					acc.languages
+						.entry(name)
+						.or_insert_with(|| DirLanguageRecord { name: name.to_owned(), ..DirLanguageRecord::default() })
+						.add_file(file);
+					acc.file_stats.push(DirFileRecord::from_stats(file, ctx));
 				}
 			}
 		}
@@ -490,29 +1037,28 @@ impl DirRecord {
 		let mut records: Vec<_> = map
 			.into_iter()
 			.map(|(path, acc)| {
+				let languages = verbose.then(|| DirLanguageRecord::sorted(acc.languages, ctx));
 				let files_detail = verbose.then(|| {
 					let mut files = acc.file_stats;
+					let file_sort_key = ctx.options.file_sort_key();
 					apply_sort(
 						&mut files,
-						ctx.options.sort_direction,
-						|file| match sort_key {
-							LanguageSortKey::Lines => SortValue::Num(file.total_lines),
-							LanguageSortKey::Code => SortValue::Num(file.code_lines),
-							LanguageSortKey::Comments => SortValue::Num(file.comment_lines),
-							LanguageSortKey::Blanks => SortValue::Num(file.blank_lines),
-							LanguageSortKey::Size => SortValue::Num(file.size),
-							LanguageSortKey::Files | LanguageSortKey::Name => SortValue::Text(file.path.as_str()),
-						},
-						|a, b| a.path.cmp(&b.path),
+						ctx.options.file_sort_direction(),
+						|file| sort_key_for_dir_file_record(file, file_sort_key),
+						Ord::cmp,
 					);
 					files
 				});
+				let counts = [acc.code_lines, acc.comment_lines, acc.blank_lines, acc.shebang_lines, acc.mixed_lines];
+				let [code_percentage, comment_percentage, blank_percentage, shebang_percentage, mixed_percentage] =
+					ctx.shares(counts, counts.map(|count| percentage(count, acc.lines)));
 				Self {
 					size_human: ctx.size(acc.size),
-					code_percentage: percentage(acc.code_lines, acc.lines),
-					comment_percentage: percentage(acc.comment_lines, acc.lines),
-					blank_percentage: percentage(acc.blank_lines, acc.lines),
-					shebang_percentage: percentage(acc.shebang_lines, acc.lines),
+					code_percentage,
+					comment_percentage,
+					blank_percentage,
+					shebang_percentage,
+					mixed_percentage,
 					path,
 					files: acc.files,
 					lines: acc.lines,
@@ -520,7 +1066,9 @@ impl DirRecord {
 					comment_lines: acc.comment_lines,
 					blank_lines: acc.blank_lines,
 					shebang_lines: acc.shebang_lines,
+					mixed_lines: acc.mixed_lines,
 					size: acc.size,
+					languages,
 					files_detail,
 				}
 			})
@@ -535,6 +1083,9 @@ impl DirRecord {
 				LanguageSortKey::Blanks => SortValue::Num(r.blank_lines),
 				LanguageSortKey::Size => SortValue::Num(r.size),
 				LanguageSortKey::Name => SortValue::Text(r.path.as_str()),
+				LanguageSortKey::CommentPct => SortValue::Float(percentage(r.comment_lines, r.lines)),
+				LanguageSortKey::CodePct => SortValue::Float(percentage(r.code_lines, r.lines)),
+				LanguageSortKey::Ratio => SortValue::Float(code_comment_ratio(r.code_lines, r.comment_lines)),
 			},
 			|a, b| a.path.cmp(&b.path),
 		);
@@ -554,18 +1105,25 @@ impl DirRecord {
 			comment: self.comment_lines,
 			blank: self.blank_lines,
 			shebang: self.shebang_lines,
+			mixed: self.mixed_lines,
 			code_pct: self.code_percentage,
 			comment_pct: self.comment_percentage,
 			blank_pct: self.blank_percentage,
 			shebang_pct: self.shebang_percentage,
+			mixed_pct: self.mixed_percentage,
 		})
 	}
 }
 
 #[cfg(test)]
 mod tests {
+	use rstest::rstest;
+
 	use super::*;
-	use crate::display::{formatting::FormatterContext, options::ViewOptions};
+	use crate::display::{
+		formatting::FormatterContext,
+		options::{SortDirection, ViewOptions},
+	};
 
 	#[test]
 	fn summary_line_breakdown_parts_skip_zero() {
@@ -576,12 +1134,27 @@ mod tests {
 			total_comment_lines: 2,
 			total_blank_lines: 3,
 			total_shebang_lines: 0,
+			total_mixed_lines: 0,
+			total_sloc: 5,
 			total_size: 0,
 			total_size_human: "0 B".to_string(),
+			bytes_per_line: 0.0,
+			normalized_size: None,
+			normalized_size_human: None,
 			code_percentage: 50.0,
 			comment_percentage: 20.0,
 			blank_percentage: 30.0,
 			shebang_percentage: 0.0,
+			mixed_percentage: 0.0,
+			generated_files: 0,
+			bom_files: 0,
+			minified_files: 0,
+			tests: None,
+			categories: None,
+			vendored: None,
+			assets: None,
+			largest_file_by_lines: None,
+			largest_file_by_bytes: None,
 		};
 		let ctx = FormatterContext::new(ViewOptions::default());
 		let parts = summary.line_breakdown_parts(true, &ctx);
@@ -597,12 +1170,27 @@ mod tests {
 			total_comment_lines: 2,
 			total_blank_lines: 3,
 			total_shebang_lines: 0,
+			total_mixed_lines: 0,
+			total_sloc: 5,
 			total_size: 0,
 			total_size_human: "0 B".to_string(),
+			bytes_per_line: 0.0,
+			normalized_size: None,
+			normalized_size_human: None,
 			code_percentage: 50.0,
 			comment_percentage: 20.0,
 			blank_percentage: 30.0,
 			shebang_percentage: 0.0,
+			mixed_percentage: 0.0,
+			generated_files: 0,
+			bom_files: 0,
+			minified_files: 0,
+			tests: None,
+			categories: None,
+			vendored: None,
+			assets: None,
+			largest_file_by_lines: None,
+			largest_file_by_bytes: None,
 		};
 		let ctx = FormatterContext::new(ViewOptions::default());
 		let parts = summary.percentage_parts(&ctx);
@@ -618,12 +1206,27 @@ mod tests {
 			total_comment_lines: 20,
 			total_blank_lines: 15,
 			total_shebang_lines: 5,
+			total_mixed_lines: 0,
+			total_sloc: 60,
 			total_size: 4096,
 			total_size_human: "4.0 KiB".to_string(),
+			bytes_per_line: 0.0,
+			normalized_size: None,
+			normalized_size_human: None,
 			code_percentage: 60.0,
 			comment_percentage: 20.0,
 			blank_percentage: 15.0,
 			shebang_percentage: 5.0,
+			mixed_percentage: 0.0,
+			generated_files: 0,
+			bom_files: 0,
+			minified_files: 0,
+			tests: None,
+			categories: None,
+			vendored: None,
+			assets: None,
+			largest_file_by_lines: None,
+			largest_file_by_bytes: None,
 		};
 		let metrics: Vec<_> = summary.metrics().collect();
 		assert_eq!(metrics.len(), 7);
@@ -653,6 +1256,19 @@ mod tests {
 		assert!(metrics[6].percentage.is_none());
 	}
 
+	#[test]
+	fn summary_metrics_include_mixed_lines_when_present() {
+		let mut results = AnalysisResults::default();
+		let lang = crate::langs::LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		let contribution = crate::analysis::stats::FileContribution::new(10, 6, 2, 0, 0, 100).with_mixed_lines(2);
+		results.add_file_stats(lang, contribution, None);
+		let ctx = FormatterContext::new(ViewOptions::default());
+		let summary = Summary::from_results(&results, &ctx);
+		let labels: Vec<&str> = summary.metrics().map(|m| m.label).collect();
+		assert_eq!(labels[6..], ["Mixed Lines", "Total Size"]);
+		assert_eq!(summary.line_breakdown_parts(false, &ctx), vec!["6 code", "2 comments", "2 mixed"]);
+	}
+
 	#[test]
 	fn summary_line_breakdown_pluralize() {
 		let summary = Summary {
@@ -662,12 +1278,27 @@ mod tests {
 			total_comment_lines: 1,
 			total_blank_lines: 1,
 			total_shebang_lines: 1,
+			total_mixed_lines: 0,
+			total_sloc: 1,
 			total_size: 0,
 			total_size_human: "0 B".to_string(),
+			bytes_per_line: 0.0,
+			normalized_size: None,
+			normalized_size_human: None,
 			code_percentage: 25.0,
 			comment_percentage: 25.0,
 			blank_percentage: 25.0,
 			shebang_percentage: 25.0,
+			mixed_percentage: 0.0,
+			generated_files: 0,
+			bom_files: 0,
+			minified_files: 0,
+			tests: None,
+			categories: None,
+			vendored: None,
+			assets: None,
+			largest_file_by_lines: None,
+			largest_file_by_bytes: None,
 		};
 		let ctx = FormatterContext::new(ViewOptions::default());
 		let parts = summary.line_breakdown_parts(true, &ctx);
@@ -684,12 +1315,27 @@ mod tests {
 			total_comment_lines: 15,
 			total_blank_lines: 10,
 			total_shebang_lines: 5,
+			total_mixed_lines: 0,
+			total_sloc: 10,
 			total_size: 0,
 			total_size_human: "0 B".to_string(),
+			bytes_per_line: 0.0,
+			normalized_size: None,
+			normalized_size_human: None,
 			code_percentage: 25.0,
 			comment_percentage: 37.5,
 			blank_percentage: 25.0,
 			shebang_percentage: 12.5,
+			mixed_percentage: 0.0,
+			generated_files: 0,
+			bom_files: 0,
+			minified_files: 0,
+			tests: None,
+			categories: None,
+			vendored: None,
+			assets: None,
+			largest_file_by_lines: None,
+			largest_file_by_bytes: None,
 		};
 		let ctx = FormatterContext::new(ViewOptions::default());
 		let parts = summary.line_breakdown_parts(false, &ctx);
@@ -720,6 +1366,38 @@ mod tests {
 		assert_eq!(shebang.title_label(), "Shebangs");
 	}
 
+	#[rstest]
+	#[case::root_file("main.rs", 1, "(root)")]
+	#[case::top_level("src/main.rs", 1, "src")]
+	#[case::truncated("services/a/src/lib.rs", 2, "services/a")]
+	#[case::shallower_than_depth("libs/lib.rs", 3, "libs")]
+	#[case::zero_depth_acts_as_one("services/a/lib.rs", 0, "services")]
+	fn dir_key_groups_by_leading_components(#[case] path: &str, #[case] depth: usize, #[case] expected: &str) {
+		assert_eq!(dir_key(path, Path::new("."), depth), expected);
+	}
+
+	#[test]
+	fn dir_records_include_language_breakdown_when_verbose() {
+		let mut results = AnalysisResults::default();
+		let rust = crate::langs::LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		let python = crate::langs::LANGUAGES.iter().find(|l| l.name == "Python").unwrap();
+		let contribution = crate::analysis::stats::FileContribution::new(10, 10, 0, 0, 0, 100);
+		let file = |path: &str| Some(FileStats::new(path.to_owned(), 10, 10, 0, 0, 0, 100));
+		results.add_file_stats(rust, contribution, file("services/a/src/lib.rs"));
+		results.add_file_stats(rust, contribution, file("services/a/src/main.rs"));
+		results.add_file_stats(python, contribution, file("services/b/app.py"));
+		let options =
+			ViewOptions { by_dir: true, dir_depth: 2, verbosity: Verbosity::Verbose, ..ViewOptions::default() };
+		let ctx = FormatterContext::new(options);
+		let (records, hidden) = DirRecord::from_results(&results, Path::new("."), &ctx);
+		assert_eq!(hidden, 0);
+		let paths: Vec<_> = records.iter().map(|r| r.path.as_str()).collect();
+		assert_eq!(paths, ["services/a", "services/b"]);
+		let languages = records[0].languages.as_ref().expect("verbose includes languages");
+		assert_eq!(languages.len(), 1);
+		assert_eq!((languages[0].name.as_str(), languages[0].files, languages[0].lines), ("Rust", 2, 20));
+	}
+
 	#[test]
 	fn report_data_summary_verbosity_skips_languages() {
 		let mut results = AnalysisResults::default();
@@ -727,7 +1405,7 @@ mod tests {
 		results.add_file_stats(lang, crate::analysis::stats::FileContribution::new(10, 10, 0, 0, 0, 100), None);
 
 		let ctx = FormatterContext::new(ViewOptions::default());
-		let report = ReportData::from_results(&results, Path::new("."), Verbosity::Summary, &ctx);
+		let report = ReportData::from_results(&results, Path::new(".").into(), Verbosity::Summary, &ctx);
 
 		assert_eq!(report.summary.total_files, 1);
 		assert!(report.languages.is_empty());
@@ -740,11 +1418,87 @@ mod tests {
 		results.add_file_stats(lang, crate::analysis::stats::FileContribution::new(10, 10, 0, 0, 0, 100), None);
 
 		let ctx = FormatterContext::new(ViewOptions::default());
-		let report = ReportData::from_results(&results, Path::new("."), Verbosity::Regular, &ctx);
+		let report = ReportData::from_results(&results, Path::new(".").into(), Verbosity::Regular, &ctx);
 
 		assert_eq!(report.summary.total_files, 1);
 		assert_eq!(report.languages.len(), 1);
 		assert_eq!(report.languages[0].name, "Rust");
 		assert!(report.languages[0].files_detail.is_none());
 	}
+
+	#[test]
+	fn report_data_verbose_files_carry_line_shares() {
+		let mut results = AnalysisResults::default();
+		let rust = crate::langs::LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		let python = crate::langs::LANGUAGES.iter().find(|l| l.name == "Python").unwrap();
+		let file = |path: &str, lines| {
+			let contribution = crate::analysis::stats::FileContribution::new(lines, lines, 0, 0, 0, 100);
+			(contribution, Some(FileStats::new(path.to_owned(), lines, lines, 0, 0, 0, 100)))
+		};
+		let (contribution, stats) = file("a.rs", 30);
+		results.add_file_stats(rust, contribution, stats);
+		let (contribution, stats) = file("b.rs", 10);
+		results.add_file_stats(rust, contribution, stats);
+		let (contribution, stats) = file("c.py", 60);
+		results.add_file_stats(python, contribution, stats);
+
+		let ctx = FormatterContext::new(ViewOptions::default());
+		let report = ReportData::from_results(&results, Path::new(".").into(), Verbosity::Verbose, &ctx);
+
+		let rust_record = report.languages.iter().find(|l| l.name == "Rust").unwrap();
+		let files = rust_record.files_detail.as_ref().expect("verbose includes files");
+		let a = files.iter().find(|f| f.path == "a.rs").unwrap();
+		assert!((a.pct_of_language_lines - 75.0).abs() < f64::EPSILON);
+		assert!((a.pct_of_total_lines - 30.0).abs() < f64::EPSILON);
+	}
+
+	#[rstest]
+	#[case::ratio_desc(LanguageSortKey::Ratio, SortDirection::Desc, ["Go", "Rust", "Python"])]
+	#[case::comment_pct_asc(LanguageSortKey::CommentPct, SortDirection::Asc, ["Go", "Rust", "Python"])]
+	#[case::code_pct_desc(LanguageSortKey::CodePct, SortDirection::Desc, ["Go", "Rust", "Python"])]
+	fn select_languages_sorts_by_computed_shares(
+		#[case] language_sort_key: LanguageSortKey,
+		#[case] sort_direction: SortDirection,
+		#[case] expected: [&str; 3],
+	) {
+		let mut results = AnalysisResults::default();
+		for (name, code, comment) in [("Rust", 80, 20), ("Python", 50, 50), ("Go", 90, 0)] {
+			let lang = crate::langs::LANGUAGES.iter().find(|l| l.name == name).unwrap();
+			let contribution = crate::analysis::stats::FileContribution::new(code + comment, code, comment, 0, 0, 100);
+			results.add_file_stats(lang, contribution, None);
+		}
+		let ctx = FormatterContext::new(ViewOptions { language_sort_key, sort_direction, ..Default::default() });
+		let (selected, _) = select_languages(&results, &ctx);
+		let names: Vec<_> = selected.iter().map(|(name, _)| *name).collect();
+		assert_eq!(names, expected);
+	}
+
+	#[rstest]
+	#[case::inherits_language_sort(None, None, ["Rust", "Python"], ["a.rs", "c.rs", "b.rs"])]
+	#[case::name_asc(Some(LanguageSortKey::Name), Some(SortDirection::Asc), ["Rust", "Python"], ["a.rs", "b.rs", "c.rs"])]
+	#[case::direction_only(None, Some(SortDirection::Asc), ["Rust", "Python"], ["b.rs", "c.rs", "a.rs"])]
+	fn file_sort_is_independent_of_language_sort(
+		#[case] file_sort_key: Option<LanguageSortKey>,
+		#[case] file_sort_direction: Option<SortDirection>,
+		#[case] languages: [&str; 2],
+		#[case] files: [&str; 3],
+	) {
+		let mut results = AnalysisResults::default();
+		let rust = crate::langs::LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		let python = crate::langs::LANGUAGES.iter().find(|l| l.name == "Python").unwrap();
+		for (lang, path, lines) in [(rust, "a.rs", 30), (rust, "b.rs", 10), (rust, "c.rs", 20), (python, "d.py", 5)] {
+			let contribution = crate::analysis::stats::FileContribution::new(lines, lines, 0, 0, 0, 100);
+			results.add_file_stats(
+				lang,
+				contribution,
+				Some(FileStats::new(path.to_owned(), lines, lines, 0, 0, 0, 100)),
+			);
+		}
+		let ctx = FormatterContext::new(ViewOptions { file_sort_key, file_sort_direction, ..Default::default() });
+		let report = ReportData::from_results(&results, Path::new(".").into(), Verbosity::Verbose, &ctx);
+		let names: Vec<_> = report.languages.iter().map(|lang| lang.name).collect();
+		assert_eq!(names, languages);
+		let paths: Vec<_> = report.languages[0].files_detail.as_ref().unwrap().iter().map(|file| file.path).collect();
+		assert_eq!(paths, files);
+	}
 }
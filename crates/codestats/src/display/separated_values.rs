@@ -1,21 +1,38 @@
-use std::{borrow::Cow, io::Write, path::Path};
+use std::{
+	borrow::Cow,
+	io::Write,
+	sync::{Mutex, PoisonError},
+};
 
 use anyhow::Result;
 
-use super::{FormatterContext, OutputFormatter, ReportData, Verbosity, ViewOptions};
-use crate::{analysis::AnalysisResults, display::report::LanguageRecord};
+use super::{FormatterContext, OutputFormatter, ReportData, ReportRoot, Verbosity, ViewOptions};
+use crate::{
+	analysis::{AnalysisResults, FileSink, FileStats},
+	display::report::LanguageRecord,
+	langs::Language,
+};
+
+/// Columns of a per-file row, after the language column when rows are streamed.
+const FILE_COLUMNS: [&str; 8] =
+	["file_path", "total_lines", "code_lines", "comment_lines", "blank_lines", "shebang_lines", "size", "size_human"];
 
 /// Trait for field escaping strategies in separated value formats.
 pub trait FieldEscaper {
-	fn escape(field: &str) -> Cow<'_, str>;
+	/// Delimiter used when the formatter is created with [`Default`].
+	const DEFAULT_DELIMITER: char;
+
+	fn escape(field: &str, delimiter: char) -> Cow<'_, str>;
 }
 
 /// CSV-style escaping: wrap fields in quotes if they contain delimiter/quotes/newlines, and escape internal quotes by doubling them.
 pub struct CsvEscaper;
 
 impl FieldEscaper for CsvEscaper {
-	fn escape(field: &str) -> Cow<'_, str> {
-		let needs_quotes = field.contains([',', '"', '\n', '\r']);
+	const DEFAULT_DELIMITER: char = ',';
+
+	fn escape(field: &str, delimiter: char) -> Cow<'_, str> {
+		let needs_quotes = field.contains([delimiter, '"', '\n', '\r']);
 		if !needs_quotes {
 			return Cow::Borrowed(field);
 		}
@@ -28,7 +45,9 @@ impl FieldEscaper for CsvEscaper {
 pub struct TsvEscaper;
 
 impl FieldEscaper for TsvEscaper {
-	fn escape(field: &str) -> Cow<'_, str> {
+	const DEFAULT_DELIMITER: char = '\t';
+
+	fn escape(field: &str, _delimiter: char) -> Cow<'_, str> {
 		if !field.contains(['\\', '\t', '\n', '\r']) {
 			return Cow::Borrowed(field);
 		}
@@ -46,71 +65,84 @@ impl FieldEscaper for TsvEscaper {
 	}
 }
 
-pub struct SeparatedValuesFormatter<const DELIMITER: u8, E: FieldEscaper> {
+pub struct SeparatedValuesFormatter<E: FieldEscaper> {
+	delimiter: char,
 	_escaper: std::marker::PhantomData<E>,
 }
 
-impl<const DELIMITER: u8, E: FieldEscaper> Default for SeparatedValuesFormatter<DELIMITER, E> {
+impl<E: FieldEscaper> Default for SeparatedValuesFormatter<E> {
 	fn default() -> Self {
-		Self { _escaper: std::marker::PhantomData }
+		Self::with_delimiter(E::DEFAULT_DELIMITER)
 	}
 }
 
-impl<const DELIMITER: u8, E: FieldEscaper> OutputFormatter for SeparatedValuesFormatter<DELIMITER, E> {
+impl<E: FieldEscaper> SeparatedValuesFormatter<E> {
+	/// Create a formatter that separates fields with `delimiter` instead of the format's default.
+	#[must_use]
+	pub const fn with_delimiter(delimiter: char) -> Self {
+		Self { delimiter, _escaper: std::marker::PhantomData }
+	}
+}
+
+impl<E: FieldEscaper> OutputFormatter for SeparatedValuesFormatter<E> {
 	fn write_output(
 		&self,
 		results: &AnalysisResults,
-		path: &Path,
+		root: ReportRoot<'_>,
 		view_options: ViewOptions,
 		writer: &mut dyn Write,
 	) -> Result<()> {
-		let (ctx, report) = self.prepare_report(results, path, view_options);
+		let (ctx, report) = self.prepare_report(results, root, view_options);
 		match view_options.verbosity {
-			Verbosity::Summary => Self::write_summary_section(&report, &ctx, writer),
-			Verbosity::Regular => Self::write_simple(&report.languages, &ctx, writer),
-			Verbosity::Verbose => Self::write_verbose(&report, &ctx, writer),
+			Verbosity::Summary => self.write_summary_section(&report, &ctx, writer),
+			Verbosity::Regular => self.write_simple(&report.languages, &ctx, writer),
+			Verbosity::Verbose => self.write_verbose(&report, &ctx, writer),
 		}
 	}
 }
 
-impl<const DELIMITER: u8, E: FieldEscaper> SeparatedValuesFormatter<DELIMITER, E> {
-	fn write_verbose(report: &ReportData, ctx: &FormatterContext, writer: &mut dyn Write) -> Result<()> {
-		Self::write_summary_section(report, ctx, writer)?;
-		writer.write_all(b"\n")?;
-		Self::write_language_section(&report.languages, ctx, writer)?;
+impl<E: FieldEscaper> SeparatedValuesFormatter<E> {
+	fn write_verbose(&self, report: &ReportData, ctx: &FormatterContext, writer: &mut dyn Write) -> Result<()> {
+		if ctx.options.show_summary {
+			self.write_summary_section(report, ctx, writer)?;
+			writer.write_all(b"\n")?;
+		}
+		self.write_language_section(&report.languages, ctx, writer)?;
 		writer.write_all(b"\n")?;
-		Self::write_files_sections(&report.languages, ctx, writer)?;
+		self.write_files_sections(&report.languages, ctx, writer)?;
 		Ok(())
 	}
 
-	fn write_summary_section(report: &ReportData, ctx: &FormatterContext, output: &mut dyn Write) -> Result<()> {
+	fn write_summary_section(&self, report: &ReportData, ctx: &FormatterContext, output: &mut dyn Write) -> Result<()> {
 		output.write_all(b"Summary:\n")?;
-		Self::write_record(output, &["metric", "value", "percentage", "human_readable"])?;
-		Self::write_record(output, &["Analysis Path", report.analysis_path.as_str(), "", ""])?;
+		self.write_record(output, &["metric", "value", "percentage", "human_readable"])?;
+		self.write_record(output, &["Analysis Path", report.analysis_path.as_str(), "", ""])?;
 		for metric in report.summary.metrics() {
 			let value = ctx.number(metric.value);
 			let pct = metric.percentage.map(|pct| ctx.percent(pct)).unwrap_or_default();
 			let human = metric.human_readable.unwrap_or("");
-			Self::write_record(output, &[metric.label, value.as_str(), pct.as_str(), human])?;
+			self.write_record(output, &[metric.label, value.as_str(), pct.as_str(), human])?;
 		}
 		Ok(())
 	}
 
 	fn write_language_section(
+		&self,
 		languages: &[LanguageRecord],
 		ctx: &FormatterContext,
 		output: &mut dyn Write,
 	) -> Result<()> {
 		output.write_all(b"Language breakdown:\n")?;
-		Self::write_language_header(output)?;
+		self.write_language_header(output)?;
 		for lang in languages {
-			Self::write_language_row(lang, ctx, output)?;
+			self.write_language_row(lang, ctx, output)?;
 		}
 		output.write_all(b"\n")?;
 		Ok(())
 	}
 
 	fn write_files_sections(
+		&self,
 		languages: &[LanguageRecord],
 		ctx: &FormatterContext,
 		output: &mut dyn Write,
@@ -120,21 +152,9 @@ impl<const DELIMITER: u8, E: FieldEscaper> SeparatedValuesFormatter<DELIMITER, E
 				continue;
 			};
 			writeln!(output, "{} files:", language.name)?;
-			Self::write_record(
-				output,
-				&[
-					"file_path",
-					"total_lines",
-					"code_lines",
-					"comment_lines",
-					"blank_lines",
-					"shebang_lines",
-					"size",
-					"size_human",
-				],
-			)?;
+			self.write_record(output, &FILE_COLUMNS)?;
 			for file_stat in files {
-				Self::write_record(
+				self.write_record(
 					output,
 					&[
 						file_stat.path,
@@ -153,16 +173,16 @@ impl<const DELIMITER: u8, E: FieldEscaper> SeparatedValuesFormatter<DELIMITER, E
 		Ok(())
 	}
 
-	fn write_simple(languages: &[LanguageRecord], ctx: &FormatterContext, output: &mut dyn Write) -> Result<()> {
-		Self::write_language_header(output)?;
+	fn write_simple(&self, languages: &[LanguageRecord], ctx: &FormatterContext, output: &mut dyn Write) -> Result<()> {
+		self.write_language_header(output)?;
 		for lang in languages {
-			Self::write_language_row(lang, ctx, output)?;
+			self.write_language_row(lang, ctx, output)?;
 		}
 		Ok(())
 	}
 
-	fn write_language_header(output: &mut dyn Write) -> Result<()> {
-		Self::write_record(
+	fn write_language_header(&self, output: &mut dyn Write) -> Result<()> {
+		self.write_record(
 			output,
 			&[
 				"language",
@@ -184,8 +204,8 @@ impl<const DELIMITER: u8, E: FieldEscaper> SeparatedValuesFormatter<DELIMITER, E
 		Ok(())
 	}
 
-	fn write_language_row(lang: &LanguageRecord, ctx: &FormatterContext, output: &mut dyn Write) -> Result<()> {
-		Self::write_record(
+	fn write_language_row(&self, lang: &LanguageRecord, ctx: &FormatterContext, output: &mut dyn Write) -> Result<()> {
+		self.write_record(
 			output,
 			&[
 				lang.name,
@@ -207,22 +227,146 @@ impl<const DELIMITER: u8, E: FieldEscaper> SeparatedValuesFormatter<DELIMITER, E
 		Ok(())
 	}
 
-	fn write_record(output: &mut dyn Write, fields: &[&str]) -> Result<()> {
-		for (idx, field) in fields.iter().enumerate() {
-			if idx > 0 {
-				output.write_all(&[DELIMITER])?;
-			}
-			Self::write_field(output, field)?;
+	fn write_record(&self, output: &mut dyn Write, fields: &[&str]) -> Result<()> {
+		write_record(output, fields, self.delimiter, E::escape)
+	}
+
+	/// Start a [`FileRowStream`] on `writer` in this format, writing its header row right away.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the header row cannot be written.
+	pub fn stream_files(&self, mut writer: Box<dyn Write + Send>, view_options: ViewOptions) -> Result<FileRowStream> {
+		let mut header = vec!["language"];
+		header.extend(FILE_COLUMNS);
+		write_record(&mut writer, &header, self.delimiter, E::escape)?;
+		Ok(FileRowStream {
+			delimiter: self.delimiter,
+			escape: E::escape,
+			ctx: FormatterContext::new(view_options),
+			output: Mutex::new(StreamOutput { writer, error: None }),
+		})
+	}
+}
+
+fn write_record(
+	output: &mut dyn Write,
+	fields: &[&str],
+	delimiter: char,
+	escape: fn(&str, char) -> Cow<'_, str>,
+) -> Result<()> {
+	for (idx, field) in fields.iter().enumerate() {
+		if idx > 0 {
+			output.write_all(delimiter.encode_utf8(&mut [0; 4]).as_bytes())?;
 		}
-		output.write_all(b"\n")?;
-		Ok(())
+		output.write_all(escape(field, delimiter).as_bytes())?;
 	}
+	output.write_all(b"\n")?;
+	Ok(())
+}
 
-	fn write_field(output: &mut dyn Write, field: &str) -> Result<()> {
-		output.write_all(E::escape(field).as_bytes())?;
-		Ok(())
+/// Writes one CSV or TSV row per file as the analyzer hands files over, so verbose exports of huge trees never hold
+/// every file in memory.
+///
+/// Rows come in the order files finish, under the detected language's name: sorting, `--top` and display aliases
+/// only apply to a report built from complete results. [`FileSink::accept`] cannot fail, so the first write error is
+/// kept, later rows are dropped, and [`FileRowStream::finish`] returns it.
+pub struct FileRowStream {
+	delimiter: char,
+	escape: fn(&str, char) -> Cow<'_, str>,
+	ctx: FormatterContext,
+	output: Mutex<StreamOutput>,
+}
+
+struct StreamOutput {
+	writer: Box<dyn Write + Send>,
+	error: Option<anyhow::Error>,
+}
+
+impl FileRowStream {
+	/// Flush the rows written so far.
+	///
+	/// # Errors
+	///
+	/// Returns the first error hit while writing a row, or an error if flushing fails.
+	pub fn finish(&self) -> Result<()> {
+		let mut output = self.output.lock().unwrap_or_else(PoisonError::into_inner);
+		let error = output.error.take();
+		let flushed = output.writer.flush();
+		drop(output);
+		error.map_or_else(|| Ok(flushed?), Err)
 	}
 }
 
-pub type CsvFormatter = SeparatedValuesFormatter<b',', CsvEscaper>;
-pub type TsvFormatter = SeparatedValuesFormatter<b'\t', TsvEscaper>;
+impl FileSink for FileRowStream {
+	fn accept(&self, language: &'static Language, file: FileStats) {
+		let ctx = &self.ctx;
+		let row = [
+			language.name,
+			file.path(),
+			&ctx.number(file.total_lines()),
+			&ctx.number(file.code_lines()),
+			&ctx.number(file.comment_lines()),
+			&ctx.number(file.blank_lines()),
+			&ctx.number(file.shebang_lines()),
+			&ctx.number(file.size()),
+			&ctx.size(file.size()),
+		];
+		let mut output = self.output.lock().unwrap_or_else(PoisonError::into_inner);
+		if output.error.is_none()
+			&& let Err(err) = write_record(&mut output.writer, &row, self.delimiter, self.escape)
+		{
+			output.error = Some(err);
+		}
+	}
+}
+
+pub type CsvFormatter = SeparatedValuesFormatter<CsvEscaper>;
+pub type TsvFormatter = SeparatedValuesFormatter<TsvEscaper>;
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn csv_escaping_follows_custom_delimiter() {
+		assert_eq!(CsvEscaper::escape("1,5", ';'), "1,5");
+		assert_eq!(CsvEscaper::escape("a;b", ';'), "\"a;b\"");
+		assert_eq!(CsvEscaper::escape("a;b", ','), "a;b");
+	}
+
+	#[test]
+	fn file_row_stream_writes_header_then_one_row_per_file() {
+		let rust = crate::langs::LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		let buf = std::sync::Arc::new(Mutex::new(Vec::new()));
+		let stream =
+			CsvFormatter::default().stream_files(Box::new(SharedBuf(buf.clone())), ViewOptions::default()).unwrap();
+		stream.accept(rust, FileStats::new("src/a,b.rs".into(), 3, 2, 1, 0, 0, 40));
+		stream.finish().unwrap();
+		let written = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+		assert_eq!(
+			written,
+			"language,file_path,total_lines,code_lines,comment_lines,blank_lines,shebang_lines,size,size_human\n\
+			 Rust,\"src/a,b.rs\",3,2,1,0,0,40,40 B\n"
+		);
+	}
+
+	struct SharedBuf(std::sync::Arc<Mutex<Vec<u8>>>);
+
+	impl Write for SharedBuf {
+		fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+			self.0.lock().unwrap().write(bytes)
+		}
+
+		fn flush(&mut self) -> std::io::Result<()> {
+			Ok(())
+		}
+	}
+
+	#[test]
+	fn csv_formatter_writes_custom_delimiter() {
+		let mut buf = Vec::new();
+		CsvFormatter::with_delimiter(';').write_record(&mut buf, &["a", "b;c", "d"]).unwrap();
+		assert_eq!(String::from_utf8(buf).unwrap(), "a;\"b;c\";d\n");
+	}
+}
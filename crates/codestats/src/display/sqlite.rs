@@ -0,0 +1,220 @@
+use std::io::Write;
+
+use anyhow::Result;
+use rusqlite::{Connection, MAIN_DB, Statement, params};
+
+use super::{OutputFormatter, ReportData, ReportRoot, Verbosity, ViewOptions};
+use crate::analysis::AnalysisResults;
+
+const SUMMARY_COLUMNS: &[(&str, &str)] = &[
+	("analysis_path", "TEXT NOT NULL"),
+	("total_files", "INTEGER NOT NULL"),
+	("total_lines", "INTEGER NOT NULL"),
+	("total_code_lines", "INTEGER NOT NULL"),
+	("total_comment_lines", "INTEGER NOT NULL"),
+	("total_blank_lines", "INTEGER NOT NULL"),
+	("total_shebang_lines", "INTEGER NOT NULL"),
+	("total_mixed_lines", "INTEGER NOT NULL"),
+	("total_sloc", "INTEGER NOT NULL"),
+	("total_size", "INTEGER NOT NULL"),
+	("normalized_size", "INTEGER"),
+	("code_percentage", "REAL NOT NULL"),
+	("comment_percentage", "REAL NOT NULL"),
+	("blank_percentage", "REAL NOT NULL"),
+	("shebang_percentage", "REAL NOT NULL"),
+	("mixed_percentage", "REAL NOT NULL"),
+	("generated_files", "INTEGER NOT NULL"),
+	("bom_files", "INTEGER NOT NULL"),
+	("minified_files", "INTEGER NOT NULL"),
+];
+
+const LANGUAGE_COLUMNS: &[(&str, &str)] = &[
+	("name", "TEXT PRIMARY KEY"),
+	("files", "INTEGER NOT NULL"),
+	("lines", "INTEGER NOT NULL"),
+	("code_lines", "INTEGER NOT NULL"),
+	("comment_lines", "INTEGER NOT NULL"),
+	("blank_lines", "INTEGER NOT NULL"),
+	("shebang_lines", "INTEGER NOT NULL"),
+	("mixed_lines", "INTEGER NOT NULL"),
+	("sloc", "INTEGER NOT NULL"),
+	("size", "INTEGER NOT NULL"),
+	("normalized_size", "INTEGER"),
+	("avg_lines_per_file", "REAL NOT NULL"),
+	("max_line_length", "INTEGER NOT NULL"),
+	("code_percentage", "REAL NOT NULL"),
+	("comment_percentage", "REAL NOT NULL"),
+	("blank_percentage", "REAL NOT NULL"),
+	("shebang_percentage", "REAL NOT NULL"),
+	("mixed_percentage", "REAL NOT NULL"),
+	("tab_lines", "INTEGER NOT NULL"),
+	("space_lines", "INTEGER NOT NULL"),
+	("indentation", "TEXT NOT NULL"),
+	("line_comment_lines", "INTEGER NOT NULL"),
+	("block_comment_lines", "INTEGER NOT NULL"),
+];
+
+const FILE_COLUMNS: &[(&str, &str)] = &[
+	("language", "TEXT NOT NULL REFERENCES languages(name)"),
+	("path", "TEXT NOT NULL"),
+	("total_lines", "INTEGER NOT NULL"),
+	("code_lines", "INTEGER NOT NULL"),
+	("comment_lines", "INTEGER NOT NULL"),
+	("blank_lines", "INTEGER NOT NULL"),
+	("shebang_lines", "INTEGER NOT NULL"),
+	("mixed_lines", "INTEGER NOT NULL"),
+	("sloc", "INTEGER NOT NULL"),
+	("size", "INTEGER NOT NULL"),
+	("max_line_length", "INTEGER NOT NULL"),
+	("is_symlinked", "INTEGER NOT NULL"),
+	("pct_of_language_lines", "REAL NOT NULL"),
+	("pct_of_total_lines", "REAL NOT NULL"),
+];
+
+/// Writes the report as a `SQLite` database with `summary`, `languages` and `files` tables.
+///
+/// The database is built in memory and written out as the bytes of a database file, so the writer should be a file
+/// rather than a terminal. Files are always included, whatever the verbosity; sizes are raw byte counts, since SQL can
+/// format its own.
+pub struct SqliteFormatter;
+
+impl OutputFormatter for SqliteFormatter {
+	fn write_output(
+		&self,
+		results: &AnalysisResults,
+		root: ReportRoot<'_>,
+		view_options: ViewOptions,
+		writer: &mut dyn Write,
+	) -> Result<()> {
+		let view_options = ViewOptions { verbosity: Verbosity::Verbose, ..view_options };
+		let (_, report) = self.prepare_report(results, root, view_options);
+		let mut conn = Connection::open_in_memory()?;
+		let tx = conn.transaction()?;
+		insert_summary(&report, &mut create_table(&tx, "summary", SUMMARY_COLUMNS)?)?;
+		insert_languages(
+			&report,
+			&mut create_table(&tx, "languages", LANGUAGE_COLUMNS)?,
+			&mut create_table(&tx, "files", FILE_COLUMNS)?,
+		)?;
+		tx.commit()?;
+		writer.write_all(&conn.serialize(MAIN_DB)?)?;
+		Ok(())
+	}
+}
+
+/// Create `table` with `columns` and prepare an insert of one row into it.
+fn create_table<'conn>(conn: &'conn Connection, table: &str, columns: &[(&str, &str)]) -> Result<Statement<'conn>> {
+	let definitions: Vec<String> = columns.iter().map(|(name, kind)| format!("{name} {kind}")).collect();
+	conn.execute(&format!("CREATE TABLE {table} ({})", definitions.join(", ")), [])?;
+	let names: Vec<&str> = columns.iter().map(|&(name, _)| name).collect();
+	let placeholders = vec!["?"; columns.len()].join(", ");
+	Ok(conn.prepare(&format!("INSERT INTO {table} ({}) VALUES ({placeholders})", names.join(", ")))?)
+}
+
+fn insert_summary(report: &ReportData, insert: &mut Statement) -> Result<()> {
+	let summary = &report.summary;
+	insert.execute(params![
+		report.analysis_path,
+		summary.total_files,
+		summary.total_lines,
+		summary.total_code_lines,
+		summary.total_comment_lines,
+		summary.total_blank_lines,
+		summary.total_shebang_lines,
+		summary.total_mixed_lines,
+		summary.total_sloc,
+		summary.total_size,
+		summary.normalized_size,
+		summary.code_percentage,
+		summary.comment_percentage,
+		summary.blank_percentage,
+		summary.shebang_percentage,
+		summary.mixed_percentage,
+		summary.generated_files,
+		summary.bom_files,
+		summary.minified_files,
+	])?;
+	Ok(())
+}
+
+fn insert_languages(report: &ReportData, languages: &mut Statement, files: &mut Statement) -> Result<()> {
+	for lang in &report.languages {
+		languages.execute(params![
+			lang.name,
+			lang.files,
+			lang.lines,
+			lang.code_lines,
+			lang.comment_lines,
+			lang.blank_lines,
+			lang.shebang_lines,
+			lang.mixed_lines,
+			lang.sloc,
+			lang.size,
+			lang.normalized_size,
+			lang.avg_lines_per_file,
+			lang.max_line_length,
+			lang.code_percentage,
+			lang.comment_percentage,
+			lang.blank_percentage,
+			lang.shebang_percentage,
+			lang.mixed_percentage,
+			lang.tab_lines,
+			lang.space_lines,
+			lang.indentation,
+			lang.line_comment_lines,
+			lang.block_comment_lines,
+		])?;
+		for file in lang.files_detail.iter().flatten() {
+			files.execute(params![
+				lang.name,
+				file.path,
+				file.total_lines,
+				file.code_lines,
+				file.comment_lines,
+				file.blank_lines,
+				file.shebang_lines,
+				file.mixed_lines,
+				file.sloc,
+				file.size,
+				file.max_line_length,
+				file.is_symlinked,
+				file.pct_of_language_lines,
+				file.pct_of_total_lines,
+			])?;
+		}
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use std::path::Path;
+
+	use super::*;
+	use crate::analysis::{FileStats, stats::FileContribution};
+
+	#[test]
+	fn tables_hold_summary_languages_and_files() {
+		let mut results = AnalysisResults::default();
+		let rust = crate::langs::LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		for (path, lines, code) in [("src/main.rs", 10, 6), ("src/lib.rs", 4, 4)] {
+			let contribution = FileContribution::new(lines, code, lines - code, 0, 0, 100);
+			let file = FileStats::new(path.to_owned(), lines, code, lines - code, 0, 0, 100);
+			results.add_file_stats(rust, contribution, Some(file));
+		}
+		let mut bytes = Vec::new();
+		SqliteFormatter.write_output(&results, Path::new(".").into(), ViewOptions::default(), &mut bytes).unwrap();
+		let mut conn = Connection::open_in_memory().unwrap();
+		conn.deserialize_read_exact(MAIN_DB, bytes.as_slice(), bytes.len(), true).unwrap();
+		let (files, code): (u64, u64) = conn
+			.query_row("SELECT total_files, total_code_lines FROM summary", [], |row| Ok((row.get(0)?, row.get(1)?)))
+			.unwrap();
+		assert_eq!((files, code), (2, 10));
+		let sloc: u64 = conn.query_row("SELECT sloc FROM languages WHERE name = 'Rust'", [], |row| row.get(0)).unwrap();
+		assert_eq!(sloc, 10);
+		let largest: String = conn
+			.query_row("SELECT path FROM files WHERE language = 'Rust' ORDER BY total_lines DESC", [], |row| row.get(0))
+			.unwrap();
+		assert_eq!(largest, "src/main.rs");
+	}
+}
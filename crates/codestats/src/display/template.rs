@@ -0,0 +1,140 @@
+use std::{collections::HashMap, fs, io::Write, path::Path};
+
+use anyhow::{Context as _, Result};
+use tera::{Context, Tera, Value};
+
+use super::{FormatterContext, OutputFormatter, ReportRoot, Verbosity, ViewOptions};
+use crate::analysis::AnalysisResults;
+
+const TEMPLATE_NAME: &str = "custom";
+
+/// Renders reports through a user-supplied Tera template loaded at runtime.
+///
+/// The template sees the same fields as the built-in Markdown report (`title`, `summary`, `languages`,
+/// `directories`, `show_files`, `line_breakdown`, `totals`) plus the `fmt_number`, `fmt_percent`, and
+/// `fmt_float` filters.
+#[derive(Debug, Clone)]
+pub struct TemplateFormatter {
+	// Box to keep `Formatter` variant sizes balanced (clippy::large_enum_variant).
+	tera: Box<Tera>,
+}
+
+impl TemplateFormatter {
+	/// Parse a template from its source text.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the template has invalid syntax.
+	pub fn new(source: &str) -> Result<Self> {
+		let mut tera = Tera::default();
+		tera.add_raw_template(TEMPLATE_NAME, source)
+			.map_err(|err| flatten_error(&err))
+			.context("Failed to parse template")?;
+		Ok(Self { tera: Box::new(tera) })
+	}
+
+	/// Load and parse a template file.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the file cannot be read or the template has invalid syntax.
+	pub fn from_file(path: &Path) -> Result<Self> {
+		let source =
+			fs::read_to_string(path).with_context(|| format!("Failed to read template file `{}`", path.display()))?;
+		Self::new(&source).with_context(|| format!("Invalid template file `{}`", path.display()))
+	}
+}
+
+impl OutputFormatter for TemplateFormatter {
+	fn write_output(
+		&self,
+		results: &AnalysisResults,
+		root: ReportRoot<'_>,
+		view_options: ViewOptions,
+		writer: &mut dyn Write,
+	) -> Result<()> {
+		let (ctx, report) = self.prepare_report(results, root, view_options);
+		let mut context = Context::new();
+		context.insert("title", &report.analysis_path);
+		context.insert("meta", &report.meta);
+		context.insert("summary", &report.summary);
+		context.insert("languages", &report.languages);
+		context.insert("directories", &report.directories);
+		context.insert("languages_hidden", &report.languages_hidden);
+		context.insert("dirs_hidden", &report.dirs_hidden);
+		context.insert("show_files", &(view_options.verbosity == Verbosity::Verbose));
+		context.insert("line_breakdown", &report.summary.line_breakdown_parts(false, &ctx));
+		context.insert("totals", &report.summary.percentage_parts(&ctx));
+		let mut tera = Tera::clone(&self.tera);
+		register_filters(&mut tera, &ctx);
+		tera.render_to(TEMPLATE_NAME, &context, writer)
+			.map_err(|err| flatten_error(&err))
+			.context("Failed to render template")?;
+		Ok(())
+	}
+}
+
+fn register_filters(tera: &mut Tera, ctx: &FormatterContext) {
+	let number_ctx = ctx.clone();
+	tera.register_filter("fmt_number", move |value: &Value, _: &HashMap<String, Value>| {
+		let number = value.as_u64().ok_or_else(|| tera::Error::msg("fmt_number expects a non-negative integer"))?;
+		Ok(Value::String(number_ctx.number(number)))
+	});
+	let percent_ctx = ctx.clone();
+	tera.register_filter("fmt_percent", move |value: &Value, _: &HashMap<String, Value>| {
+		let percent = value.as_f64().ok_or_else(|| tera::Error::msg("fmt_percent expects a number"))?;
+		Ok(Value::String(percent_ctx.percent(percent)))
+	});
+	tera.register_filter("fmt_float", |value: &Value, args: &HashMap<String, Value>| {
+		let float = value.as_f64().ok_or_else(|| tera::Error::msg("fmt_float expects a number"))?;
+		let precision = args.get("precision").and_then(Value::as_u64).map_or(1, |p| usize::try_from(p).unwrap_or(1));
+		Ok(Value::String(format!("{float:.precision$}")))
+	});
+}
+
+/// Tera nests the useful message (line, column, offending filter) in the error's source chain.
+fn flatten_error(err: &tera::Error) -> anyhow::Error {
+	let mut message = err.to_string();
+	let mut source = std::error::Error::source(err);
+	while let Some(cause) = source {
+		message.push_str(": ");
+		message.push_str(&cause.to_string());
+		source = cause.source();
+	}
+	anyhow::anyhow!(message)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::analysis::stats::FileContribution;
+
+	fn render(source: &str, results: &AnalysisResults) -> String {
+		let formatter = TemplateFormatter::new(source).expect("template parses");
+		let mut output = Vec::new();
+		formatter.write_output(results, Path::new("proj").into(), ViewOptions::default(), &mut output).expect("render");
+		String::from_utf8(output).expect("utf-8 output")
+	}
+
+	#[test]
+	fn renders_summary_and_languages() {
+		let mut results = AnalysisResults::default();
+		let lang = crate::langs::LANGUAGES.iter().find(|l| l.name == "Rust").unwrap();
+		results.add_file_stats(lang, FileContribution::new(1200, 1000, 100, 100, 0, 4096), None);
+		let source = "{{ title }}: {{ summary.total_lines | fmt_number }}\n\
+			{% for lang in languages %}{{ lang.name }} {{ lang.code_percentage | fmt_percent }}%{% endfor %}";
+		assert_eq!(render(source, &results), "proj: 1200\nRust 83.3%");
+	}
+
+	#[test]
+	fn fmt_float_accepts_precision() {
+		let results = AnalysisResults::default();
+		assert_eq!(render("{{ 2.25 | fmt_float(precision=1) }}", &results), "2.2");
+	}
+
+	#[test]
+	fn invalid_template_is_rejected() {
+		let err = TemplateFormatter::new("{% for %}").expect_err("broken syntax");
+		assert!(err.to_string().contains("parse"), "{err:#}");
+	}
+}
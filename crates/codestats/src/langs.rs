@@ -11,6 +11,7 @@
 //! ## Language Definitions
 //!
 //! Language metadata is loaded from `languages.json5` at build time and compiled into static data structures. See [`LANGUAGES`] for the complete list.
+//! The same validation is available at runtime through [`parse_languages`], for checking a candidate file without rebuilding.
 
 use std::io::Write;
 
@@ -18,17 +19,58 @@ use anyhow::Result;
 
 mod data;
 mod detection;
-
-pub use data::{LANGUAGES, Language};
-pub use detection::{detect_language_info, scoring};
+mod schema;
+
+pub use data::{LANGUAGES, LANGUAGES_HASH, Language};
+#[cfg(feature = "archive")]
+pub(crate) use detection::language_from_name;
+pub(crate) use detection::matches_shebang;
+pub use detection::{
+	DEFAULT_DETECTION_THRESHOLD, DetectionConfidence, KeywordHits, detect_language_info, detect_language_with_score,
+	detect_language_with_threshold, explain_detection, keyword_hits, scoring,
+};
+pub use schema::{LanguageConfig, PARADIGMS, parse_languages};
 
 use crate::display::formatting::pluralize;
 
-/// Write a list of all supported programming languages to a writer.
+/// Find the supported language name closest to `input`, for "did you mean" suggestions.
+///
+/// Names are compared case-insensitively by edit distance. Only names within roughly a third of the input's length
+/// are suggested, so unrelated input yields `None`; ties go to the language listed first.
+#[must_use]
+pub fn closest_name(input: &str) -> Option<&'static str> {
+	let input = input.to_lowercase();
+	let max_distance = input.chars().count() / 3 + 1;
+	LANGUAGES
+		.iter()
+		.map(|lang| (edit_distance(&input, &lang.name.to_lowercase()), lang.name))
+		.filter(|&(distance, _)| distance <= max_distance)
+		.min_by_key(|&(distance, _)| distance)
+		.map(|(_, name)| name)
+}
+
+/// Levenshtein distance between `a` and `b`, counted in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+	let b: Vec<char> = b.chars().collect();
+	let mut row: Vec<usize> = (0..=b.len()).collect();
+	for (i, ca) in a.chars().enumerate() {
+		let mut diagonal = row[0];
+		row[0] = i + 1;
+		for (j, &cb) in b.iter().enumerate() {
+			let substitution = diagonal + usize::from(ca != cb);
+			diagonal = row[j + 1];
+			row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+		}
+	}
+	row[b.len()]
+}
+
+/// Write a list of all supported programming languages to a writer, wrapped to `width` columns, or with one name per
+/// line and no separators when `width` is 0.
 /// # Errors
 ///
 /// Returns an error if writing to the provided writer fails.
-pub fn print_all_languages(writer: &mut dyn Write, terminal_width: usize) -> Result<()> {
+pub fn print_all_languages(writer: &mut dyn Write, width: usize) -> Result<()> {
 	let lang_count = u64::try_from(LANGUAGES.len()).unwrap_or(u64::MAX);
 	writeln!(
 		writer,
@@ -36,6 +78,12 @@ pub fn print_all_languages(writer: &mut dyn Write, terminal_width: usize) -> Res
 		pluralize(lang_count, "language", "languages"),
 		LANGUAGES.len()
 	)?;
+	if width == 0 {
+		for lang in LANGUAGES {
+			writeln!(writer, "{}", lang.name)?;
+		}
+		return Ok(());
+	}
 	let mut lines: Vec<String> = Vec::new();
 	let mut current_line = String::new();
 	let mut current_width = 0usize;
@@ -44,7 +92,7 @@ pub fn print_all_languages(writer: &mut dyn Write, terminal_width: usize) -> Res
 		let separator = if is_last { "." } else { ", " };
 		let item = format!("{}{}", lang.name, separator);
 		let item_width = item.chars().count();
-		let would_exceed = !current_line.is_empty() && current_width + item_width > terminal_width;
+		let would_exceed = !current_line.is_empty() && current_width + item_width > width;
 		if would_exceed {
 			lines.push(current_line);
 			current_line = item;
@@ -63,9 +111,47 @@ pub fn print_all_languages(writer: &mut dyn Write, terminal_width: usize) -> Res
 	Ok(())
 }
 
+/// Write every language's [`Language::index`] and name as a JSON array of `{"index", "name"}` objects.
+///
+/// Indices follow the alphabetical order of `languages.json5`, which the build enforces, so an index only changes when
+/// a language is added or removed before it.
+/// # Errors
+///
+/// Returns an error if writing to the provided writer fails.
+pub fn print_language_index(writer: &mut dyn Write) -> Result<()> {
+	let entries: Vec<_> =
+		LANGUAGES.iter().map(|lang| serde_json::json!({ "index": lang.index, "name": lang.name })).collect();
+	serde_json::to_writer_pretty(&mut *writer, &entries)?;
+	writeln!(writer)?;
+	Ok(())
+}
+
 #[cfg(test)]
 mod tests {
-	use super::detection::patterns::get_candidates;
+	use rstest::rstest;
+
+	use super::{LANGUAGES, closest_name, detection::patterns::get_candidates, print_language_index};
+
+	#[rstest]
+	#[case::dropped_letter("Rst", Some("Rust"))]
+	#[case::case_only("PYTHON", Some("Python"))]
+	#[case::transposed("Pyhton", Some("Python"))]
+	#[case::unrelated("qqqqqqqq", None)]
+	fn closest_name_suggests_near_matches(#[case] input: &str, #[case] expected: Option<&str>) {
+		assert_eq!(closest_name(input), expected);
+	}
+
+	#[test]
+	fn language_index_lists_every_language_at_its_index() {
+		let mut buf = Vec::new();
+		print_language_index(&mut buf).unwrap();
+		let entries: Vec<serde_json::Value> = serde_json::from_slice(&buf).unwrap();
+		assert_eq!(entries.len(), LANGUAGES.len());
+		for (position, (entry, lang)) in entries.iter().zip(LANGUAGES).enumerate() {
+			assert_eq!(entry["index"], position);
+			assert_eq!(entry["name"], lang.name);
+		}
+	}
 
 	#[test]
 	fn get_candidates_uses_literal_map() {
@@ -129,6 +215,16 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn print_all_languages_zero_width_lists_one_name_per_line() {
+		let mut buf = Vec::new();
+		print_all_languages(&mut buf, 0).unwrap();
+		let output = String::from_utf8(buf).unwrap();
+		let names: Vec<_> = output.lines().skip(1).collect();
+		assert_eq!(names.len(), super::data::LANGUAGES.len());
+		assert!(names.contains(&"Rust"), "bare names expected, got: {names:?}");
+	}
+
 	#[test]
 	fn print_all_languages_large_width() {
 		let small_width = 40;
@@ -1,7 +1,9 @@
 pub(super) mod patterns;
 pub mod scoring;
 
-use std::borrow::Cow;
+use std::{borrow::Cow, fmt};
+
+use serde::{Deserialize, Serialize};
 
 use self::patterns::get_candidates;
 use super::data::{LANGUAGES, Language};
@@ -10,6 +12,10 @@ use super::data::{LANGUAGES, Language};
 const COMMENT_MATCH_SCORE: i32 = 50;
 /// Score awarded for each keyword match when disambiguating languages.
 const KEYWORD_MATCH_SCORE: i32 = 10;
+/// Lowest score that lets content scoring pick a language, so any positive score wins.
+pub const DEFAULT_DETECTION_THRESHOLD: u32 = 1;
+/// Language of `*.sh` files, which defers to the shebang to pick a specific shell dialect when there is one.
+const GENERIC_SHELL: &str = "Shell";
 
 /// Calculate a language match score based on comment styles and keywords found in content.
 ///
@@ -38,14 +44,10 @@ fn score_language(lang: &Language, content: &str, tokens: &[&str]) -> i32 {
 	}
 	let mut matched_chars: usize = 0;
 	for keyword in lang.keywords {
-		// If keyword contains special characters, use substring matching to handle cases like "@interface" in Objective-C, which wouldn't match via tokenization since @ is a delimiter.
-		let count = if keyword.chars().any(|c| !c.is_ascii_alphanumeric() && c != '_') {
-			let occurrences = content.matches(keyword).count();
-			matched_chars = matched_chars.saturating_add(occurrences.saturating_mul(keyword.len()));
-			occurrences
-		} else {
-			tokens.iter().filter(|token| token.eq_ignore_ascii_case(keyword)).count()
-		};
+		let count = keyword_occurrences(keyword, content, tokens);
+		if is_symbolic(keyword) {
+			matched_chars = matched_chars.saturating_add(count.saturating_mul(keyword.len()));
+		}
 		let clamped_count = count.min(usize::try_from(i32::MAX / KEYWORD_MATCH_SCORE).unwrap_or(usize::MAX));
 		// We now know that this is safe because we've clamped the value.
 		#[expect(
@@ -72,6 +74,22 @@ fn score_language(lang: &Language, content: &str, tokens: &[&str]) -> i32 {
 	score
 }
 
+/// How often `keyword` occurs in `content`, whose tokens are `tokens`.
+///
+/// Keywords with special characters are counted as substrings, to handle cases like "@interface" in Objective-C,
+/// which wouldn't match via tokenization since @ is a delimiter. Other keywords count matching tokens, ignoring case.
+fn keyword_occurrences(keyword: &str, content: &str, tokens: &[&str]) -> usize {
+	if is_symbolic(keyword) {
+		content.matches(keyword).count()
+	} else {
+		tokens.iter().filter(|token| token.eq_ignore_ascii_case(keyword)).count()
+	}
+}
+
+fn is_symbolic(keyword: &str) -> bool {
+	keyword.chars().any(|c| !c.is_ascii_alphanumeric() && c != '_')
+}
+
 fn is_symbol_only_language(lang: &Language) -> bool {
 	!lang.keywords.is_empty()
 		&& lang.keywords.iter().all(|kw| kw.chars().all(|c| !c.is_ascii_alphanumeric() && c != '_'))
@@ -80,14 +98,15 @@ fn is_symbol_only_language(lang: &Language) -> bool {
 }
 
 #[inline]
-fn disambiguate<'a>(candidates: &[&'a Language], content: &str) -> Option<&'a Language> {
+fn candidate_scores<'a>(candidates: &[&'a Language], content: &str) -> impl Iterator<Item = (&'a Language, i32)> {
 	let tokens: Vec<_> = tokenize(content).collect();
-	candidates
-		.iter()
-		.map(|lang| (*lang, score_language(lang, content, &tokens)))
+	candidates.iter().map(move |lang| (*lang, score_language(lang, content, &tokens)))
+}
+
+fn disambiguate<'a>(candidates: &[&'a Language], content: &str, threshold: u32) -> Option<(&'a Language, i32)> {
+	candidate_scores(candidates, content)
 		.max_by_key(|(_, score)| *score)
-		.filter(|(_, score)| *score > 0)
-		.map(|(lang, _)| lang)
+		.filter(|(_, score)| *score > 0 && i64::from(*score) >= i64::from(threshold))
 }
 
 #[inline]
@@ -101,6 +120,33 @@ fn normalize_shebang(line: &str) -> std::borrow::Cow<'_, str> {
 	line.strip_prefix("#! ").map_or(Cow::Borrowed(line), |rest| Cow::Owned(format!("#!{rest}")))
 }
 
+/// The program a shebang line runs, by base name: `bash` for `#!/bin/bash`, `#!/usr/local/bin/bash` and
+/// `#!/usr/bin/env -S bash -e` alike.
+fn shebang_interpreter(shebang: &str) -> Option<&str> {
+	let mut words = shebang.strip_prefix("#!")?.split_whitespace();
+	let program = words.next()?.rsplit('/').next()?;
+	if program == "env" {
+		// Skip env's own options and variable assignments to reach the program it runs.
+		words.find(|word| !word.starts_with('-') && !word.contains('=')).and_then(|word| word.rsplit('/').next())
+	} else {
+		Some(program)
+	}
+}
+
+/// Whether the shebang line `line` runs one of the interpreters in `lang.shebangs`.
+///
+/// A line matches a shebang that runs the same interpreter, so `#!/usr/bin/env fish` matches Fish's `#!/bin/fish`,
+/// or one it extends with anything but more letters, which catches versioned interpreters such as `python3.12`
+/// without mistaking `shellcheck` for `sh`.
+pub fn matches_shebang(lang: &Language, line: &str) -> bool {
+	let normalized = normalize_shebang(line);
+	let interpreter = shebang_interpreter(&normalized);
+	lang.shebangs.iter().any(|shebang| {
+		(interpreter.is_some() && shebang_interpreter(shebang) == interpreter)
+			|| normalized.strip_prefix(shebang).is_some_and(|rest| !rest.starts_with(char::is_alphabetic))
+	})
+}
+
 #[inline]
 fn detect_from_shebang(content: &str) -> Option<&'static Language> {
 	let first_line = content.lines().next()?;
@@ -108,24 +154,134 @@ fn detect_from_shebang(content: &str) -> Option<&'static Language> {
 	if !trimmed.starts_with("#!") {
 		return None;
 	}
-	let normalized = normalize_shebang(trimmed);
-	LANGUAGES
-		.iter()
-		.find(|lang| !lang.shebangs.is_empty() && lang.shebangs.iter().any(|shebang| normalized.starts_with(shebang)))
+	LANGUAGES.iter().find(|lang| matches_shebang(lang, trimmed))
+}
+
+/// What decided a file's language, as reported by [`detect_language_with_score`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DetectionConfidence {
+	/// The file name matched a single language.
+	Filename,
+	/// The shebang line named the interpreter.
+	Shebang,
+	/// A language was assigned ahead of detection, by `.editorconfig`.
+	Override,
+	/// Content scoring picked among several candidates, with this winning score.
+	Score(i32),
+}
+
+impl fmt::Display for DetectionConfidence {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Filename => f.write_str("filename"),
+			Self::Shebang => f.write_str("shebang"),
+			Self::Override => f.write_str("override"),
+			Self::Score(score) => write!(f, "{score}"),
+		}
+	}
 }
 
 #[must_use]
 pub fn detect_language_info(filename: &str, content: Option<&str>) -> Option<&'static Language> {
+	detect_language_with_threshold(filename, content, DEFAULT_DETECTION_THRESHOLD)
+}
+
+/// Detect a language like [`detect_language_info`], but only accept a content-scored candidate whose score reaches
+/// `threshold`.
+///
+/// Each comment style match is worth 50 and each keyword occurrence 10, so a threshold of 20 ignores files that
+/// share a single keyword with a candidate. Below the threshold the file is left unrecognized rather than guessed.
+#[must_use]
+pub fn detect_language_with_threshold(
+	filename: &str,
+	content: Option<&str>,
+	threshold: u32,
+) -> Option<&'static Language> {
+	detect_language_with_score(filename, content, threshold).map(|(language, _)| language)
+}
+
+/// Detect a language like [`detect_language_with_threshold`], and also report what decided it: the file name, the
+/// shebang, or the winning score when content scoring chose among several candidates.
+#[must_use]
+pub fn detect_language_with_score(
+	filename: &str,
+	content: Option<&str>,
+	threshold: u32,
+) -> Option<(&'static Language, DetectionConfidence)> {
+	let shebang = |content: &str| detect_from_shebang(content).map(|lang| (lang, DetectionConfidence::Shebang));
 	let candidates = get_candidates(filename);
 	match candidates.len() {
-		0 => content.and_then(detect_from_shebang),
-		1 => Some(candidates[0]),
+		0 => content.and_then(shebang),
+		1 if candidates[0].name == GENERIC_SHELL => {
+			content.and_then(shebang).or(Some((candidates[0], DetectionConfidence::Filename)))
+		}
+		1 => Some((candidates[0], DetectionConfidence::Filename)),
 		_ => content.and_then(|file_content| {
-			detect_from_shebang(file_content).or_else(|| disambiguate(&candidates, file_content))
+			shebang(file_content).or_else(|| {
+				disambiguate(&candidates, file_content, threshold)
+					.map(|(lang, score)| (lang, DetectionConfidence::Score(score)))
+			})
 		}),
 	}
 }
 
+/// The language of `filename` when its name alone decides it, so that no content could detect another.
+#[cfg(feature = "archive")]
+#[must_use]
+pub fn language_from_name(filename: &str) -> Option<&'static Language> {
+	match get_candidates(filename).as_slice() {
+		[language] if language.name != GENERIC_SHELL => Some(language),
+		_ => None,
+	}
+}
+
+/// Score every candidate language for `filename` when detection has to choose between them by content.
+///
+/// Returns `None` unless the name matches more than one language and `content` has no recognized shebang, which
+/// are the only cases where [`detect_language_info`] falls back to scoring. The highest positive score wins, with
+/// ties going to the last of the tied candidates.
+#[must_use]
+pub fn explain_detection(filename: &str, content: &str) -> Option<Vec<(&'static Language, i32)>> {
+	let candidates = get_candidates(filename);
+	if candidates.len() < 2 || detect_from_shebang(content).is_some() {
+		return None;
+	}
+	Some(candidate_scores(&candidates, content).collect())
+}
+
+/// Keyword matches behind one candidate language's detection score, as reported by [`keyword_hits`].
+#[derive(Debug)]
+pub struct KeywordHits {
+	pub language: &'static Language,
+	/// The candidate's full score, including comment style matches.
+	pub score: i32,
+	/// Each keyword found in the content with its number of occurrences, in `languages.json5` order.
+	pub keywords: Vec<(&'static str, usize)>,
+}
+
+/// Score every language `filename` could be and list which of its keywords occur in `content`, and how often.
+///
+/// Unlike [`explain_detection`], this covers every candidate even when the name alone or a shebang decides, since it
+/// is meant for tuning keyword lists in `languages.json5` rather than explaining one detection.
+#[must_use]
+pub fn keyword_hits(filename: &str, content: &str) -> Vec<KeywordHits> {
+	let tokens: Vec<_> = tokenize(content).collect();
+	get_candidates(filename)
+		.into_iter()
+		.map(|language| KeywordHits {
+			language,
+			score: score_language(language, content, &tokens),
+			keywords: language
+				.keywords
+				.iter()
+				.map(|keyword| (*keyword, keyword_occurrences(keyword, content, &tokens)))
+				.filter(|&(_, count)| count > 0)
+				.collect(),
+		})
+		.collect()
+}
+
 #[cfg(test)]
 mod tests {
 	use rstest::rstest;
@@ -139,8 +295,14 @@ mod tests {
 		line_comments: &["//"],
 		block_comments: &[],
 		nested_blocks: false,
+		string_delimiters: &[],
+		raw_string_delimiters: &[],
+		require_boundary: false,
+		docstrings: false,
+		line_comment_must_start_line: false,
 		shebangs: &[],
 		keywords: &["alpha", "beta"],
+		paradigm: None,
 	};
 
 	const TEST_LANGUAGE_BETA: Language = Language {
@@ -150,8 +312,14 @@ mod tests {
 		line_comments: &["#"],
 		block_comments: &[],
 		nested_blocks: false,
+		string_delimiters: &[],
+		raw_string_delimiters: &[],
+		require_boundary: false,
+		docstrings: false,
+		line_comment_must_start_line: false,
 		shebangs: &[],
 		keywords: &["winner"],
+		paradigm: None,
 	};
 
 	#[test]
@@ -167,10 +335,45 @@ mod tests {
 		let candidates = vec![&TEST_LANGUAGE_ALPHA, &TEST_LANGUAGE_BETA];
 		let alpha_content = "alpha only";
 		let beta_content = "# winner winner";
-		let chosen_alpha = disambiguate(&candidates, alpha_content).unwrap();
-		assert_eq!(chosen_alpha.name, "Alpha");
-		let chosen_beta = disambiguate(&candidates, beta_content).unwrap();
-		assert_eq!(chosen_beta.name, "Beta");
+		let (chosen_alpha, alpha_score) =
+			disambiguate(&candidates, alpha_content, DEFAULT_DETECTION_THRESHOLD).unwrap();
+		assert_eq!((chosen_alpha.name, alpha_score), ("Alpha", 10));
+		let (chosen_beta, beta_score) = disambiguate(&candidates, beta_content, DEFAULT_DETECTION_THRESHOLD).unwrap();
+		assert_eq!((chosen_beta.name, beta_score), ("Beta", 50 + 2 * 10));
+	}
+
+	#[rstest]
+	#[case::single_keyword_below("alpha", 20, None)]
+	#[case::two_keywords_reach("alpha beta", 20, Some("Alpha"))]
+	#[case::comment_reaches("# note", 50, Some("Beta"))]
+	#[case::default_accepts_one_keyword("alpha", DEFAULT_DETECTION_THRESHOLD, Some("Alpha"))]
+	fn disambiguate_respects_threshold(#[case] content: &str, #[case] threshold: u32, #[case] expected: Option<&str>) {
+		let candidates = vec![&TEST_LANGUAGE_ALPHA, &TEST_LANGUAGE_BETA];
+		let chosen = disambiguate(&candidates, content, threshold);
+		assert_eq!(chosen.map(|(lang, _)| lang.name), expected);
+	}
+
+	#[test]
+	fn explain_detection_reports_scores_only_when_scoring_decides() {
+		let content = "@interface Foo : NSObject\n@end\n";
+		let scores = explain_detection("example.m", content).unwrap();
+		assert!(scores.len() > 1);
+		let (best, _) = scores.iter().max_by_key(|(_, score)| *score).unwrap();
+		assert_eq!(best.name, "Objective-C");
+		assert!(explain_detection("main.rs", "fn main() {}").is_none());
+		assert!(explain_detection("example.m", "#!/usr/bin/env python\n").is_none());
+	}
+
+	#[test]
+	fn keyword_hits_counts_each_matched_keyword() {
+		let content = "@interface Foo : NSObject\n@end\n@interface Bar : NSObject\n@end\n";
+		let hits = keyword_hits("example.m", content);
+		assert!(hits.len() > 1);
+		let objc = hits.iter().find(|hits| hits.language.name == "Objective-C").unwrap();
+		assert_eq!(objc.keywords, [("@interface", 2), ("@end", 2), ("NSObject", 2)]);
+		assert_eq!(objc.score, 60);
+		assert!(keyword_hits("main.rs", "fn main() {}")[0].keywords.is_empty());
+		assert!(keyword_hits("notes.unknown-extension", "@interface").is_empty());
 	}
 
 	#[test]
@@ -180,6 +383,22 @@ mod tests {
 		assert_eq!(language.name, "Objective-C");
 	}
 
+	#[rstest]
+	#[case::filename("main.rs", "fn main() {}\n", "Rust", DetectionConfidence::Filename)]
+	#[case::shebang("deploy", "#!/usr/bin/env python3\nprint(1)\n", "Python", DetectionConfidence::Shebang)]
+	#[case::generic_shell("run.sh", "echo hi\n", "Shell", DetectionConfidence::Filename)]
+	#[case::scored("example.m", "@interface Foo : NSObject\n@end\n", "Objective-C", DetectionConfidence::Score(30))]
+	fn detect_language_with_score_reports_what_decided(
+		#[case] filename: &str,
+		#[case] content: &str,
+		#[case] expected: &str,
+		#[case] confidence: DetectionConfidence,
+	) {
+		let (language, found) =
+			detect_language_with_score(filename, Some(content), DEFAULT_DETECTION_THRESHOLD).unwrap();
+		assert_eq!((language.name, found), (expected, confidence));
+	}
+
 	#[test]
 	fn detect_language_info_skips_when_no_signal() {
 		let language = detect_language_info("ambiguous.m", Some("plain text without hints"));
@@ -228,6 +447,12 @@ mod tests {
 	#[rstest]
 	#[case::python("#!/usr/bin/env python3\nprint('hello')", Some("Python"))]
 	#[case::bash("#!/bin/bash\necho hello", Some("Bash"))]
+	#[case::env_fish("#!/usr/bin/env fish\necho hello", Some("Fish"))]
+	#[case::env_zsh("#!/usr/bin/env zsh\necho hello", Some("ZSH"))]
+	#[case::local_bash("#!/usr/local/bin/bash -e\necho hello", Some("Bash"))]
+	#[case::posix_sh("#!/bin/sh\necho hello", Some("Shell"))]
+	#[case::not_sh("#!/usr/bin/env shellcheck\n", None)]
+	#[case::versioned("#!/usr/bin/env python3.12\nprint('hello')", Some("Python"))]
 	#[case::no_shebang("print('hello')\n# not a shebang", None)]
 	#[case::empty("", None)]
 	fn test_detect_from_shebang(#[case] content: &str, #[case] expected_name: Option<&str>) {
@@ -235,6 +460,24 @@ mod tests {
 		assert_eq!(result.map(|l| l.name), expected_name);
 	}
 
+	#[rstest]
+	#[case::plain("#!/bin/bash", Some("bash"))]
+	#[case::env("#!/usr/bin/env fish", Some("fish"))]
+	#[case::env_options("#!/usr/bin/env -S LANG=C zsh -f", Some("zsh"))]
+	#[case::bare_env("#!/usr/bin/env", None)]
+	fn test_shebang_interpreter(#[case] shebang: &str, #[case] expected: Option<&str>) {
+		assert_eq!(shebang_interpreter(shebang), expected);
+	}
+
+	#[rstest]
+	#[case::fish("deploy.sh", "#!/usr/bin/env fish\necho hi\n", "Fish")]
+	#[case::bash("deploy.sh", "#!/bin/bash\necho hi\n", "Bash")]
+	#[case::no_shebang("deploy.sh", "echo hi\n", "Shell")]
+	#[case::no_extension("deploy", "#!/usr/bin/env zsh\necho hi\n", "ZSH")]
+	fn shell_dialect_follows_the_shebang(#[case] filename: &str, #[case] content: &str, #[case] expected: &str) {
+		assert_eq!(detect_language_info(filename, Some(content)).map(|lang| lang.name), Some(expected));
+	}
+
 	#[test]
 	fn is_symbol_only_language_detects_brainfuck() {
 		// Find Brainfuck in LANGUAGES
@@ -258,6 +501,16 @@ mod tests {
 		assert_eq!(lang.unwrap().name, "Rust");
 	}
 
+	#[rstest]
+	#[case::compound_beats_tail("main.tf.json", "Terraform")]
+	#[case::exact_name_beats_extension("package-lock.json", "JSON")]
+	#[case::declaration_file("types.d.ts", "TypeScript")]
+	#[case::test_infix("component.test.tsx", "TypeScript")]
+	#[case::template_suffix("schema.sql.j2", "Jinja2")]
+	fn most_specific_pattern_wins(#[case] filename: &str, #[case] expected: &str) {
+		assert_eq!(detect_language_info(filename, None).map(|lang| lang.name), Some(expected));
+	}
+
 	#[test]
 	fn unknown_extension_with_shebang() {
 		let content = "#!/usr/bin/env node\nconsole.log('hello');";
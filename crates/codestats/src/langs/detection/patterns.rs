@@ -7,11 +7,26 @@ use super::super::data::{LANGUAGES, Language};
 pub(super) struct LanguageGlobs {
 	pub(super) set: GlobSet,
 	pub(super) pattern_lang_indexes: Vec<usize>,
+	/// How specific each pattern is; see [`specificity`].
+	pub(super) pattern_specificity: Vec<usize>,
+}
+
+/// Rank a file pattern so that the most specific patterns matching a name win.
+///
+/// An exact file name such as `CMakeLists.txt` beats any wildcard. Among `*.ext` patterns, a longer compound suffix
+/// beats its shorter tails, so `main.tf.json` is Terraform (`*.tf.json`) rather than JSON (`*.json`). Other wildcard
+/// patterns rank like a single extension.
+fn specificity(pattern: &str) -> usize {
+	if !pattern.contains(['*', '?', '[', '{']) {
+		return usize::MAX;
+	}
+	pattern.strip_prefix("*.").filter(|suffix| !suffix.contains('*')).map_or(1, |suffix| suffix.split('.').count())
 }
 
 pub(super) static LANGUAGE_GLOBSET: LazyLock<LanguageGlobs> = LazyLock::new(|| {
 	let mut builder = GlobSetBuilder::new();
 	let mut pattern_lang_indexes = Vec::new();
+	let mut pattern_specificity = Vec::new();
 	for lang in LANGUAGES {
 		for pattern in lang.file_patterns {
 			let mut glob_builder = GlobBuilder::new(pattern);
@@ -21,11 +36,12 @@ pub(super) static LANGUAGE_GLOBSET: LazyLock<LanguageGlobs> = LazyLock::new(|| {
 				.unwrap_or_else(|e| panic!("Invalid glob pattern '{pattern}' for language {}: {e}", lang.name));
 			// Record the language index so glob match indices can map back to LANGUAGES.
 			pattern_lang_indexes.push(lang.index);
+			pattern_specificity.push(specificity(pattern));
 			builder.add(glob);
 		}
 	}
 	let set = builder.build().expect("GlobSetBuilder should never fail with valid patterns from languages.json5");
-	LanguageGlobs { set, pattern_lang_indexes }
+	LanguageGlobs { set, pattern_lang_indexes, pattern_specificity }
 });
 
 // Thread-local buffer for tracking which languages have been seen during candidate gathering.
@@ -34,6 +50,7 @@ thread_local! {
 	static SEEN_BUFFER: RefCell<Vec<bool>> = RefCell::new(vec![false; LANGUAGES.len()]);
 }
 
+/// Languages whose most specific matching patterns match `filename`, in `LANGUAGES` order.
 #[inline]
 pub fn get_candidates(filename: &str) -> Vec<&'static Language> {
 	let globs = &*LANGUAGE_GLOBSET;
@@ -41,7 +58,11 @@ pub fn get_candidates(filename: &str) -> Vec<&'static Language> {
 		let mut seen = seen_cell.borrow_mut();
 		seen.fill(false);
 		let mut candidates = Vec::new();
-		for match_idx in globs.set.matches(filename) {
+		let matches = globs.set.matches(filename);
+		let Some(best) = matches.iter().map(|&match_idx| globs.pattern_specificity[match_idx]).max() else {
+			return candidates;
+		};
+		for match_idx in matches.into_iter().filter(|&match_idx| globs.pattern_specificity[match_idx] == best) {
 			let lang_idx = globs.pattern_lang_indexes[match_idx];
 			if !seen[lang_idx] {
 				seen[lang_idx] = true;
@@ -1,6 +1,6 @@
 use std::sync::LazyLock;
 
-use aho_corasick::{AhoCorasick, AhoCorasickBuilder, MatchKind};
+use aho_corasick::{AhoCorasick, AhoCorasickBuilder, Match, MatchKind};
 
 use super::super::data::{LANGUAGES, Language};
 
@@ -8,25 +8,95 @@ use super::super::data::{LANGUAGES, Language};
 pub struct LanguageMatchers {
 	pub(crate) line_comments: Option<AhoCorasick>,
 	pub(crate) block_comments: Option<BlockCommentMatchers>,
+	pub(crate) strings: Option<StringMatchers>,
+}
+
+/// Finds string literals so comment markers inside them can be skipped.
+#[derive(Debug)]
+pub struct StringMatchers {
+	start_automaton: AhoCorasick,
+	/// End delimiter of each start pattern, whether a backslash escapes it, and whether the string may span lines.
+	ends: Vec<(&'static str, bool, bool)>,
+}
+
+impl StringMatchers {
+	/// Find the first string literal in `line` at or after byte `from`, as its start and the offset just past its end.
+	///
+	/// A string that does not end on the line runs to the end of the line. A `'` inside a number, as in C++'s `1'000`,
+	/// is a digit separator rather than the start of a literal.
+	#[inline]
+	pub(crate) fn find(&self, line: &str, from: usize) -> Option<(usize, usize)> {
+		self.find_literal(line, from).map(|(start, end, _)| (start, end))
+	}
+
+	/// Find where the string opened by start pattern `pattern` on an earlier line ends in `line`, as the offset just
+	/// past its end delimiter, or `None` if it runs on past the end of `line`.
+	#[inline]
+	pub(crate) fn close(&self, line: &str, pattern: usize) -> Option<usize> {
+		self.find_end(line, 0, pattern)
+	}
+
+	/// The start pattern of a string in `line` that is still open at its end and continues on the next line.
+	///
+	/// Only raw strings and backquoted template literals continue; any other string ends with its line.
+	pub(crate) fn open_at_end(&self, line: &str) -> Option<usize> {
+		let mut from = 0;
+		let mut open = None;
+		while let Some((_, end, unclosed)) = self.find_literal(line, from) {
+			open = unclosed.filter(|&pattern| self.ends[pattern].2);
+			from = end;
+		}
+		open
+	}
+
+	/// Like [`Self::find`], also returning the start pattern of a string left unclosed at the end of the line.
+	fn find_literal(&self, line: &str, from: usize) -> Option<(usize, usize, Option<usize>)> {
+		let bytes = line.as_bytes();
+		let m = self.start_automaton.find_iter(&line[from..]).find(|m| !is_digit_separator(bytes, from + m.start()))?;
+		let (start, pattern) = (from + m.start(), m.pattern().as_usize());
+		let found = self.find_end(line, from + m.end(), pattern);
+		Some(found.map_or((start, line.len(), Some(pattern)), |end| (start, end, None)))
+	}
+
+	/// Find the end delimiter of start pattern `pattern` in a string body starting at byte `body` of `line`, as the
+	/// offset just past it.
+	fn find_end(&self, line: &str, body: usize, pattern: usize) -> Option<usize> {
+		let (end, escapes, _) = self.ends[pattern];
+		let bytes = line.as_bytes();
+		let mut search = body;
+		while let Some(pos) = line[search..].find(end) {
+			let at = search + pos;
+			let backslashes = bytes[body..at].iter().rev().take_while(|&&b| b == b'\\').count();
+			if escapes && backslashes % 2 == 1 {
+				search = at + end.chars().next().map_or(1, char::len_utf8);
+				continue;
+			}
+			return Some(at + end.len());
+		}
+		None
+	}
 }
 
 #[derive(Debug)]
 pub struct BlockCommentMatchers {
 	start_automaton: AhoCorasick,
 	end_automaton: AhoCorasick,
+	/// End markers, kept to recognize a start marker that overlaps one (e.g. `(*)`) when `require_boundary` is set.
+	end_markers: &'static [(&'static str, &'static str)],
+	require_boundary: bool,
 }
 
 impl BlockCommentMatchers {
 	#[inline]
 	pub(crate) fn find_block_start(&self, line: &str) -> Option<(usize, usize)> {
-		self.start_automaton.find(line).map(|m| (m.start(), m.len()))
+		self.find_start(line).map(|m| (m.start(), m.len()))
 	}
 
 	#[inline]
 	pub(crate) fn find_block_end_or_nested_start(&self, line: &str, nested: bool) -> Option<(usize, usize, bool)> {
 		if nested {
-			let start_match = self.start_automaton.find(line);
-			let end_match = self.end_automaton.find(line);
+			let start_match = self.find_start(line);
+			let end_match = self.find_end(line);
 			match (start_match, end_match) {
 				(Some(s), Some(e)) if s.start() < e.start() => Some((s.start(), s.len(), true)),
 				(Some(s), None) => Some((s.start(), s.len(), true)),
@@ -34,11 +104,53 @@ impl BlockCommentMatchers {
 				(None, None) => None,
 			}
 		} else {
-			self.end_automaton.find(line).map(|m| (m.start(), m.len(), false))
+			self.find_end(line).map(|m| (m.start(), m.len(), false))
+		}
+	}
+
+	/// Find the first start marker that is not glued to a preceding word or to an end marker.
+	#[inline]
+	fn find_start(&self, line: &str) -> Option<Match> {
+		if !self.require_boundary {
+			return self.start_automaton.find(line);
 		}
+		let bytes = line.as_bytes();
+		self.start_automaton.find_iter(line).find(|m| {
+			let after_word = m.start() > 0 && is_word_byte(bytes[m.start() - 1]);
+			let overlaps_end =
+				self.end_markers.iter().any(|(_, end)| bytes[m.start() + 1..].starts_with(end.as_bytes()));
+			!after_word && !overlaps_end
+		})
+	}
+
+	/// Find the first end marker that is not glued to a following word.
+	#[inline]
+	fn find_end(&self, line: &str) -> Option<Match> {
+		if !self.require_boundary {
+			return self.end_automaton.find(line);
+		}
+		let bytes = line.as_bytes();
+		self.end_automaton.find_iter(line).find(|m| !bytes.get(m.end()).is_some_and(|&b| is_word_byte(b)))
 	}
 }
 
+#[inline]
+const fn is_word_byte(b: u8) -> bool {
+	b.is_ascii_alphanumeric() || b == b'_'
+}
+
+/// Whether the byte at `pos` is a `'` ending a word that starts with a digit, such as `1'000` or `0xFF'FF`.
+///
+/// Char literal prefixes (`u8'x'`, `L'x'`) and string prefixes (`r'x'`) start with a letter, so they still open a
+/// literal.
+fn is_digit_separator(bytes: &[u8], pos: usize) -> bool {
+	if bytes[pos] != b'\'' {
+		return false;
+	}
+	let word_start = bytes[..pos].iter().rposition(|&b| !is_word_byte(b)).map_or(0, |idx| idx + 1);
+	word_start < pos && bytes[word_start].is_ascii_digit()
+}
+
 static LANGUAGE_MATCHERS: LazyLock<Vec<LanguageMatchers>> =
 	LazyLock::new(|| LANGUAGES.iter().map(build_language_matchers).collect());
 
@@ -76,7 +188,29 @@ fn build_language_matchers(lang: &Language) -> LanguageMatchers {
 			.match_kind(MatchKind::LeftmostFirst)
 			.build(end_patterns)
 			.expect("AhoCorasick should never fail to build with valid block comment end patterns");
-		Some(BlockCommentMatchers { start_automaton, end_automaton })
+		Some(BlockCommentMatchers {
+			start_automaton,
+			end_automaton,
+			end_markers: lang.block_comments,
+			require_boundary: lang.require_boundary,
+		})
+	};
+	// Raw strings and backquoted template literals may span lines; other strings end with their line.
+	let ends: Vec<_> = lang
+		.string_delimiters
+		.iter()
+		.map(|&(start, end)| (end, true, start == "`"))
+		.chain(lang.raw_string_delimiters.iter().map(|&(_, end)| (end, false, true)))
+		.collect();
+	let strings = if ends.is_empty() {
+		None
+	} else {
+		let starts = lang.string_delimiters.iter().chain(lang.raw_string_delimiters).map(|&(start, _)| start);
+		let start_automaton = AhoCorasickBuilder::new()
+			.match_kind(MatchKind::LeftmostLongest)
+			.build(starts)
+			.expect("AhoCorasick should never fail to build with valid string delimiters");
+		Some(StringMatchers { start_automaton, ends })
 	};
-	LanguageMatchers { line_comments, block_comments }
+	LanguageMatchers { line_comments, block_comments, strings }
 }
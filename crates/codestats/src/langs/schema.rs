@@ -0,0 +1,316 @@
+//! Schema and validation for `languages.json5`.
+//!
+//! This module is shared verbatim with `build.rs`, which uses it to generate [`LANGUAGES`](super::LANGUAGES), so it may
+//! only depend on crates that are also build dependencies.
+
+use std::collections::{HashMap, HashSet};
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Deserializer, de};
+
+fn validate_no_whitespace(s: &str, field: &str, idx: Option<usize>) -> Result<(), String> {
+	if s.trim() == s {
+		Ok(())
+	} else {
+		Err(idx.map_or_else(
+			|| format!("{field}: has leading/trailing whitespace"),
+			|i| format!("{field} {}: has leading/trailing whitespace", i + 1),
+		))
+	}
+}
+
+fn deserialize_vec_strings<'de, D>(
+	deserializer: D,
+	field: &'static str,
+	allow_empty_vec: bool,
+	validate: impl Fn(&str, usize) -> Result<(), String>,
+) -> Result<Vec<String>, D::Error>
+where
+	D: Deserializer<'de>,
+{
+	let values: Vec<String> = Vec::deserialize(deserializer)?;
+	if !allow_empty_vec && values.is_empty() {
+		return Err(de::Error::custom(format!("{field} cannot be empty")));
+	}
+	for (idx, value) in values.iter().enumerate() {
+		validate(value, idx).map_err(de::Error::custom)?;
+	}
+	Ok(values)
+}
+
+fn deserialize_file_patterns<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+	D: Deserializer<'de>,
+{
+	deserialize_vec_strings(deserializer, "file_patterns", false, |s, idx| {
+		if s.is_empty() {
+			Err(format!("pattern {}: cannot be empty", idx + 1))
+		} else {
+			validate_no_whitespace(s, "pattern", Some(idx))
+		}
+	})
+}
+
+fn deserialize_line_comments<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+	D: Deserializer<'de>,
+{
+	deserialize_vec_strings(deserializer, "line_comments", true, |s, idx| {
+		if s.is_empty() { Err(format!("line comment {}: cannot be empty", idx + 1)) } else { Ok(()) }
+	})
+}
+
+fn deserialize_block_comments<'de, D>(deserializer: D) -> Result<Vec<(String, String)>, D::Error>
+where
+	D: Deserializer<'de>,
+{
+	deserialize_delimiter_pairs(deserializer, "block comment")
+}
+
+fn deserialize_string_delimiters<'de, D>(deserializer: D) -> Result<Vec<(String, String)>, D::Error>
+where
+	D: Deserializer<'de>,
+{
+	deserialize_delimiter_pairs(deserializer, "string delimiter")
+}
+
+fn deserialize_delimiter_pairs<'de, D>(deserializer: D, what: &str) -> Result<Vec<(String, String)>, D::Error>
+where
+	D: Deserializer<'de>,
+{
+	let pairs: Vec<Vec<String>> = Vec::deserialize(deserializer)?;
+	let mut out: Vec<(String, String)> = Vec::with_capacity(pairs.len());
+	for (idx, pair) in pairs.into_iter().enumerate() {
+		let err = |msg| de::Error::custom(format!("{what} {}: {msg}", idx + 1));
+		if pair.len() != 2 {
+			return Err(err("must contain exactly start and end delimiters"));
+		}
+		let mut iter = pair.into_iter();
+		let start = iter.next().unwrap();
+		let end = iter.next().unwrap();
+		if start.is_empty() {
+			return Err(err("start cannot be empty"));
+		}
+		if end.is_empty() {
+			return Err(err("end cannot be empty"));
+		}
+		out.push((start, end));
+	}
+	Ok(out)
+}
+
+/// The paradigms a language may be tagged with, in the order `--by-paradigm` reports them.
+pub const PARADIGMS: &[&str] = &["systems", "general", "scripting", "markup", "config", "data"];
+
+fn deserialize_paradigm<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+	D: Deserializer<'de>,
+{
+	let value = String::deserialize(deserializer)?;
+	if PARADIGMS.contains(&value.as_str()) {
+		Ok(Some(value))
+	} else {
+		Err(de::Error::custom(format!("unknown paradigm '{value}' (expected one of {})", PARADIGMS.join(", "))))
+	}
+}
+
+/// A single language entry as written in `languages.json5`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[expect(clippy::struct_excessive_bools, reason = "each flag mirrors an optional key in languages.json5")]
+pub struct LanguageConfig {
+	#[serde(skip)]
+	pub name: String,
+	#[serde(deserialize_with = "deserialize_file_patterns")]
+	pub file_patterns: Vec<String>,
+	#[serde(default, deserialize_with = "deserialize_line_comments")]
+	pub line_comments: Vec<String>,
+	#[serde(default, deserialize_with = "deserialize_block_comments")]
+	pub block_comments: Vec<(String, String)>,
+	#[serde(default)]
+	pub nested_blocks: bool,
+	/// Start and end of string literals in which a backslash escapes the next character, like `"a \" b"`.
+	///
+	/// Comment markers inside a string on the same line are not comments.
+	#[serde(default, deserialize_with = "deserialize_string_delimiters")]
+	pub string_delimiters: Vec<(String, String)>,
+	/// Start and end of string literals without escapes, like Rust's `r#"..."#` or Go's backquoted strings.
+	#[serde(default, deserialize_with = "deserialize_string_delimiters")]
+	pub raw_string_delimiters: Vec<(String, String)>,
+	/// Ignore block comment markers glued to surrounding code, such as `x(*` or the `(*)` operator.
+	#[serde(default)]
+	pub require_boundary: bool,
+	/// The block comments are triple-quoted strings, counted as docstrings only when nothing precedes them on the line.
+	#[serde(default)]
+	pub docstrings: bool,
+	/// Line comment markers only start a comment at the beginning of a line, like `C` in fixed-form Fortran.
+	#[serde(default)]
+	pub line_comment_must_start_line: bool,
+	#[serde(default)]
+	pub shebangs: Vec<String>,
+	#[serde(default)]
+	pub keywords: Vec<String>,
+	/// Broad family the language belongs to, one of [`PARADIGMS`].
+	#[serde(default, deserialize_with = "deserialize_paradigm")]
+	pub paradigm: Option<String>,
+}
+
+fn normalize_languages(entries: IndexMap<String, LanguageConfig>) -> Result<Vec<LanguageConfig>, String> {
+	let mut errors = Vec::new();
+	let mut prev_name: Option<String> = None;
+	let mut languages = Vec::with_capacity(entries.len());
+	for (index, (name, mut config)) in entries.into_iter().enumerate() {
+		if name.trim().is_empty() {
+			errors.push(format!("Language at position {}: name cannot be empty", index + 1));
+			continue;
+		}
+		if let Some(prev) = &prev_name
+			&& name.to_lowercase() < prev.to_lowercase()
+		{
+			// Enforce a stable ordering so generated indices remain consistent across edits.
+			errors.push(format!("Language '{name}' is not in alphabetical order (should come before '{prev}')"));
+		}
+		prev_name = Some(name.clone());
+		config.name = name;
+		languages.push(config);
+	}
+	if errors.is_empty() { Ok(languages) } else { Err(errors.join("\n")) }
+}
+
+struct PatternInfo {
+	names: Vec<String>,
+	all_have_keywords: bool,
+}
+
+struct LanguageValidator {
+	errors: Vec<String>,
+	seen_names: HashSet<String>,
+	seen_patterns: HashMap<String, PatternInfo>,
+}
+
+impl LanguageValidator {
+	fn new() -> Self {
+		Self { errors: Vec::new(), seen_names: HashSet::new(), seen_patterns: HashMap::new() }
+	}
+
+	fn validate_all(&mut self, languages: &[LanguageConfig]) {
+		for lang in languages {
+			if !self.seen_names.insert(lang.name.clone()) {
+				self.errors.push(format!("Duplicate language name '{}'", lang.name));
+			}
+			if lang.name.trim() != lang.name {
+				self.errors.push(format!("Language '{}': name has leading/trailing whitespace", lang.name));
+			}
+			for pattern in &lang.file_patterns {
+				let info = self
+					.seen_patterns
+					.entry(pattern.clone())
+					.or_insert(PatternInfo { names: Vec::new(), all_have_keywords: true });
+				info.names.push(lang.name.clone());
+				info.all_have_keywords &= !lang.keywords.is_empty();
+			}
+		}
+		for (pattern, info) in &self.seen_patterns {
+			if info.names.len() > 1 && !info.all_have_keywords {
+				// Shared patterns need keyword disambiguation to avoid random selection.
+				self.errors.push(format!(
+					"Duplicate pattern '{}' in [{}] - all must have 'keywords' for disambiguation",
+					pattern,
+					info.names.join(", ")
+				));
+			}
+		}
+	}
+
+	fn into_result(self) -> Result<(), String> {
+		if self.errors.is_empty() {
+			Ok(())
+		} else {
+			Err(format!("Language validation failed with {} error(s):\n{}", self.errors.len(), self.errors.join("\n")))
+		}
+	}
+}
+
+fn validate_languages(languages: &[LanguageConfig]) -> Result<(), String> {
+	let mut validator = LanguageValidator::new();
+	validator.validate_all(languages);
+	validator.into_result()
+}
+
+/// Parse and validate the contents of a `languages.json5` file.
+///
+/// Languages must be in alphabetical order, have unique names, and any file pattern shared between languages must be
+/// disambiguated by `keywords` on every language that uses it.
+///
+/// # Errors
+///
+/// Returns a description of every problem found if the content is not valid JSON5, does not match the schema, or
+/// fails validation.
+pub fn parse_languages(content: &str) -> Result<Vec<LanguageConfig>, String> {
+	let entries: IndexMap<String, LanguageConfig> = json5::from_str(content).map_err(|e| e.to_string())?;
+	let languages = normalize_languages(entries)?;
+	validate_languages(&languages)?;
+	Ok(languages)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn bundled_languages_file_is_valid() {
+		let languages = parse_languages(include_str!("../../languages.json5")).unwrap();
+		assert_eq!(languages.len(), super::super::LANGUAGES.len());
+	}
+
+	#[test]
+	fn accepts_minimal_language() {
+		let languages = parse_languages(r#"{ Foo: { file_patterns: ["*.foo"], line_comments: ["//"] } }"#).unwrap();
+		assert_eq!(languages[0].name, "Foo");
+		assert_eq!(languages[0].line_comments, ["//"]);
+	}
+
+	#[test]
+	fn rejects_unknown_fields() {
+		let err = parse_languages(r#"{ Foo: { file_patterns: ["*.foo"], comments: ["//"] } }"#).unwrap_err();
+		assert!(err.contains("comments"), "{err}");
+	}
+
+	#[test]
+	fn rejects_out_of_order_languages() {
+		let err =
+			parse_languages(r#"{ Zed: { file_patterns: ["*.z"] }, Alpha: { file_patterns: ["*.a"] } }"#).unwrap_err();
+		assert!(err.contains("'Alpha' is not in alphabetical order"), "{err}");
+	}
+
+	#[test]
+	fn rejects_shared_patterns_without_keywords() {
+		let content = r#"{
+			A: { file_patterns: ["*.x"], keywords: ["alpha"] },
+			B: { file_patterns: ["*.x"] },
+		}"#;
+		let err = parse_languages(content).unwrap_err();
+		assert!(err.contains("Duplicate pattern '*.x' in [A, B]"), "{err}");
+	}
+
+	#[test]
+	fn rejects_malformed_block_comments() {
+		let err = parse_languages(r#"{ Foo: { file_patterns: ["*.foo"], block_comments: [["/*"]] } }"#).unwrap_err();
+		assert!(err.contains("block comment 1: must contain exactly start and end delimiters"), "{err}");
+	}
+
+	#[test]
+	fn rejects_malformed_string_delimiters() {
+		let err =
+			parse_languages(r#"{ Foo: { file_patterns: ["*.foo"], string_delimiters: [["\"", ""]] } }"#).unwrap_err();
+		assert!(err.contains("string delimiter 1: end cannot be empty"), "{err}");
+	}
+
+	#[test]
+	fn rejects_unknown_paradigm() {
+		let err = parse_languages(r#"{ Foo: { file_patterns: ["*.foo"], paradigm: "esoteric" } }"#).unwrap_err();
+		assert!(err.contains("unknown paradigm 'esoteric'"), "{err}");
+		let languages = parse_languages(r#"{ Foo: { file_patterns: ["*.foo"], paradigm: "data" } }"#).unwrap();
+		assert_eq!(languages[0].paradigm.as_deref(), Some("data"));
+	}
+}
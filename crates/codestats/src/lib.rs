@@ -1,6 +1,44 @@
 #![warn(clippy::all, clippy::cargo, clippy::nursery, clippy::pedantic, clippy::perf)]
 #![deny(warnings)]
 
+/// Implement `Display`, `FromStr`, and an `ALL` list for a unit-only enum from one table of names.
+///
+/// Both directions come from the same table, so the displayed and parsed spellings of a variant cannot drift apart.
+/// The names should match the enum's serde representation.
+macro_rules! impl_names {
+	($type:ty, $what:literal, { $($(#[$attr:meta])* $variant:ident => $name:literal),* $(,)? }) => {
+		impl $type {
+			/// Every variant, in declaration order.
+			pub const ALL: &[Self] = &[$($(#[$attr])* Self::$variant),*];
+
+			/// The name used on the command line and in config files.
+			#[must_use]
+			pub const fn as_str(self) -> &'static str {
+				match self {
+					$($(#[$attr])* Self::$variant => $name,)*
+				}
+			}
+		}
+
+		impl std::fmt::Display for $type {
+			fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+				f.write_str(self.as_str())
+			}
+		}
+
+		impl std::str::FromStr for $type {
+			type Err = String;
+
+			fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+				match s {
+					$($(#[$attr])* $name => Ok(Self::$variant),)*
+					_ => Err(format!(concat!("invalid ", $what, " '{}'"), s)),
+				}
+			}
+		}
+	};
+}
+
 pub mod analysis;
 pub mod config;
 pub mod display;
@@ -71,7 +71,7 @@ async fn analyze_dir(dir: &Path) -> Result<Response, AppError> {
 		let results = analyzer.analyze()?;
 		let formatter = get_formatter(OutputFormat::JsonCompact);
 		let mut buf = Vec::new();
-		formatter.write_output(&results, &dir, ViewOptions::default(), &mut buf)?;
+		formatter.write_output(&results, dir.as_path().into(), ViewOptions::default(), &mut buf)?;
 		Ok(buf)
 	})
 	.await